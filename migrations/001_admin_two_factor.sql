@@ -0,0 +1,5 @@
+-- Admin two-factor authentication (TOTP). Adds the columns schema.sql
+-- declares on `admins`; the recovery code table comes from schema.sql.
+ALTER TABLE admins
+    ADD COLUMN totp_secret_encrypted VARCHAR(255) NULL AFTER password_hash,
+    ADD COLUMN totp_enabled_at TIMESTAMP NULL AFTER totp_secret_encrypted;
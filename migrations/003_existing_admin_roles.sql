@@ -0,0 +1,8 @@
+-- Role-based access control. Admins created before roles existed have none
+-- and would be refused everything, so they become super-admins. Run after
+-- schema.sql, which creates and seeds the role tables.
+INSERT IGNORE INTO admin_roles (admin_id, role_id)
+SELECT a.id, r.id
+FROM admins a
+JOIN roles r ON r.name = 'super-admin'
+WHERE NOT EXISTS (SELECT 1 FROM admin_roles ar WHERE ar.admin_id = a.id);
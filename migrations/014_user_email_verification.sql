@@ -0,0 +1,5 @@
+-- Email verification for users. Existing users start unverified; admins can
+-- send them a link from the user detail page.
+ALTER TABLE users
+    ADD COLUMN email_verified_at TIMESTAMP NULL AFTER email,
+    ADD INDEX idx_email_verified_at (email_verified_at);
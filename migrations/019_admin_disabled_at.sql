@@ -0,0 +1,3 @@
+-- Disabling admin accounts. Existing admins stay enabled.
+ALTER TABLE admins
+    ADD COLUMN disabled_at TIMESTAMP NULL AFTER totp_enabled_at;
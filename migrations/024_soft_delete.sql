@@ -0,0 +1,12 @@
+-- Trash for users, countries and states. Existing rows stay live.
+ALTER TABLE countries
+    ADD COLUMN deleted_at TIMESTAMP NULL,
+    ADD INDEX idx_countries_deleted_at (deleted_at);
+
+ALTER TABLE states
+    ADD COLUMN deleted_at TIMESTAMP NULL,
+    ADD INDEX idx_states_deleted_at (deleted_at);
+
+ALTER TABLE users
+    ADD COLUMN deleted_at TIMESTAMP NULL,
+    ADD INDEX idx_users_deleted_at (deleted_at);
@@ -0,0 +1,6 @@
+-- User lifecycle statuses. Existing users were already able to sign in, so
+-- they become active; only accounts created from now on start as pending.
+-- The user_status_history table comes from schema.sql.
+ALTER TABLE users
+    ADD COLUMN status VARCHAR(16) NOT NULL DEFAULT 'active' AFTER created_at,
+    ADD INDEX idx_users_status (status);
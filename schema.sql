@@ -1,7 +1,10 @@
 -- Countries for address dropdown
 CREATE TABLE IF NOT EXISTS countries (
     id INT AUTO_INCREMENT PRIMARY KEY,
-    name VARCHAR(100) NOT NULL UNIQUE
+    name VARCHAR(100) NOT NULL UNIQUE,
+    -- Set when moved to the trash; purged for good after TRASH_RETENTION_DAYS
+    deleted_at TIMESTAMP NULL,
+    INDEX idx_countries_deleted_at (deleted_at)
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
 
 -- States for address dropdown
@@ -9,7 +12,9 @@ CREATE TABLE IF NOT EXISTS states (
     id INT AUTO_INCREMENT PRIMARY KEY,
     country_id INT NOT NULL,
     name VARCHAR(100) NOT NULL,
-    INDEX idx_states_country_id (country_id)
+    deleted_at TIMESTAMP NULL,
+    INDEX idx_states_country_id (country_id),
+    INDEX idx_states_deleted_at (deleted_at)
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
 
 -- Users table for authentication
@@ -17,15 +22,23 @@ CREATE TABLE IF NOT EXISTS users (
     id INT AUTO_INCREMENT PRIMARY KEY,
     username VARCHAR(50) NOT NULL UNIQUE,
     email VARCHAR(100) NOT NULL UNIQUE,
+    email_verified_at TIMESTAMP NULL,
     password_hash VARCHAR(255) NOT NULL,
     address VARCHAR(255) NULL,
     country_id INT NULL,
     state_id INT NULL,
     created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    -- pending, active, suspended, locked or closed (models::UserStatus).
+    -- create_user inserts pending; the default covers users that predate statuses.
+    status VARCHAR(16) NOT NULL DEFAULT 'active',
+    deleted_at TIMESTAMP NULL,
     INDEX idx_username (username),
     INDEX idx_email (email),
+    INDEX idx_email_verified_at (email_verified_at),
     INDEX idx_country_id (country_id),
-    INDEX idx_state_id (state_id)
+    INDEX idx_state_id (state_id),
+    INDEX idx_users_status (status),
+    INDEX idx_users_deleted_at (deleted_at)
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
 
 -- Admins table for admin authentication
@@ -34,7 +47,250 @@ CREATE TABLE IF NOT EXISTS admins (
     username VARCHAR(50) NOT NULL UNIQUE,
     email VARCHAR(100) NOT NULL UNIQUE,
     password_hash VARCHAR(255) NOT NULL,
+    totp_secret_encrypted VARCHAR(255) NULL,
+    totp_enabled_at TIMESTAMP NULL,
+    disabled_at TIMESTAMP NULL,
     created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
     INDEX idx_admin_username (username),
     INDEX idx_admin_email (email)
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+-- One-time recovery codes for admin two-factor authentication (SHA-256 hashed)
+CREATE TABLE IF NOT EXISTS admin_recovery_codes (
+    id INT AUTO_INCREMENT PRIMARY KEY,
+    admin_id INT NOT NULL,
+    code_hash CHAR(64) NOT NULL,
+    used_at TIMESTAMP NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    INDEX idx_recovery_admin_id (admin_id),
+    CONSTRAINT fk_recovery_admin FOREIGN KEY (admin_id) REFERENCES admins(id) ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+-- Single-use admin password reset tokens (SHA-256 hashed)
+CREATE TABLE IF NOT EXISTS admin_password_resets (
+    id INT AUTO_INCREMENT PRIMARY KEY,
+    admin_id INT NOT NULL,
+    token_hash CHAR(64) NOT NULL UNIQUE,
+    expires_at TIMESTAMP NOT NULL,
+    used_at TIMESTAMP NULL,
+    requested_ip VARCHAR(45) NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    INDEX idx_password_resets_admin_id (admin_id),
+    CONSTRAINT fk_password_resets_admin FOREIGN KEY (admin_id) REFERENCES admins(id) ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+-- Long-lived "remember me" logins for admins. The cookie holds
+-- selector:validator; only the SHA-256 of the validator is stored, and it is
+-- replaced every time the token restores a session.
+CREATE TABLE IF NOT EXISTS admin_remember_tokens (
+    id INT AUTO_INCREMENT PRIMARY KEY,
+    admin_id INT NOT NULL,
+    selector CHAR(24) NOT NULL UNIQUE,
+    validator_hash CHAR(64) NOT NULL,
+    previous_validator_hash CHAR(64) NULL,
+    rotated_at TIMESTAMP NULL,
+    -- Admin session index entry the token last created (services::admin_sessions)
+    session_ref VARCHAR(64) NULL,
+    expires_at TIMESTAMP NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    INDEX idx_admin_remember_tokens_admin_id (admin_id),
+    CONSTRAINT fk_admin_remember_tokens_admin FOREIGN KEY (admin_id) REFERENCES admins(id) ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+-- Per-admin access overrides (services::access_policy). A NULL column
+-- means no restriction of that kind; ip_allowlist replaces ADMIN_IP_ALLOWLIST.
+CREATE TABLE IF NOT EXISTS admin_access_policies (
+    admin_id INT PRIMARY KEY,
+    -- Comma-separated CIDR ranges
+    ip_allowlist TEXT NULL,
+    -- Comma-separated weekdays (mon..sun) in the admin's offset
+    allowed_days VARCHAR(32) NULL,
+    allowed_from TIME NULL,
+    allowed_until TIME NULL,
+    utc_offset_minutes SMALLINT NOT NULL DEFAULT 0,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    CONSTRAINT fk_admin_access_policies_admin FOREIGN KEY (admin_id) REFERENCES admins(id) ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+-- Requests to the admin area refused by an access policy
+CREATE TABLE IF NOT EXISTS admin_access_denials (
+    id BIGINT AUTO_INCREMENT PRIMARY KEY,
+    admin_id INT NULL,
+    ip VARCHAR(45) NOT NULL,
+    path VARCHAR(255) NOT NULL,
+    reason VARCHAR(32) NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    INDEX idx_admin_access_denials_created_at (created_at),
+    CONSTRAINT fk_admin_access_denials_admin FOREIGN KEY (admin_id) REFERENCES admins(id) ON DELETE SET NULL
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+-- Every admin sign-in attempt, successful or not
+CREATE TABLE IF NOT EXISTS admin_login_events (
+    id BIGINT AUTO_INCREMENT PRIMARY KEY,
+    -- NULL for unknown usernames
+    admin_id INT NULL,
+    username VARCHAR(100) NOT NULL,
+    -- password, two_factor, passkey, sso or remember_me
+    method VARCHAR(20) NOT NULL,
+    success BOOLEAN NOT NULL,
+    -- Why a failed attempt was refused
+    reason VARCHAR(32) NULL,
+    ip VARCHAR(45) NOT NULL,
+    user_agent VARCHAR(255) NOT NULL DEFAULT '',
+    -- ISO 3166 code from GEOIP_DATABASE, when configured
+    country_code CHAR(2) NULL,
+    -- Successful sign-in from an address or country the admin had not used before
+    new_ip BOOLEAN NOT NULL DEFAULT FALSE,
+    new_country BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    INDEX idx_admin_login_events_admin (admin_id, created_at),
+    INDEX idx_admin_login_events_created_at (created_at),
+    CONSTRAINT fk_admin_login_events_admin FOREIGN KEY (admin_id) REFERENCES admins(id) ON DELETE SET NULL
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+-- WebAuthn credentials (passkeys) an admin can sign in with
+CREATE TABLE IF NOT EXISTS admin_passkeys (
+    id INT AUTO_INCREMENT PRIMARY KEY,
+    admin_id INT NOT NULL,
+    name VARCHAR(100) NOT NULL,
+    credential_id VARBINARY(1023) NOT NULL,
+    -- SHA2(credential_id, 256), for lookup; ids can be up to 1023 bytes
+    credential_hash CHAR(64) NOT NULL UNIQUE,
+    -- COSE_Key of the credential
+    public_key BLOB NOT NULL,
+    algorithm INT NOT NULL,
+    sign_count INT UNSIGNED NOT NULL DEFAULT 0,
+    last_used_at TIMESTAMP NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    INDEX idx_admin_passkeys_admin_id (admin_id),
+    CONSTRAINT fk_admin_passkeys_admin FOREIGN KEY (admin_id) REFERENCES admins(id) ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+-- Bearer tokens for /api; only the SHA-256 of each token is stored
+CREATE TABLE IF NOT EXISTS api_tokens (
+    id INT AUTO_INCREMENT PRIMARY KEY,
+    admin_id INT NOT NULL,
+    name VARCHAR(100) NOT NULL,
+    token_prefix VARCHAR(16) NOT NULL,
+    token_hash CHAR(64) NOT NULL UNIQUE,
+    scopes VARCHAR(500) NOT NULL,
+    expires_at TIMESTAMP NOT NULL,
+    last_used_at TIMESTAMP NULL,
+    revoked_at TIMESTAMP NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    INDEX idx_api_tokens_admin_id (admin_id),
+    CONSTRAINT fk_api_tokens_admin FOREIGN KEY (admin_id) REFERENCES admins(id) ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+-- Durable record of admin mutations; admin_username is kept if the admin is deleted
+CREATE TABLE IF NOT EXISTS audit_log (
+    id BIGINT AUTO_INCREMENT PRIMARY KEY,
+    admin_id INT NULL,
+    admin_username VARCHAR(50) NOT NULL,
+    action VARCHAR(50) NOT NULL,
+    entity_type VARCHAR(50) NOT NULL,
+    entity_id VARCHAR(64) NULL,
+    before_json JSON NULL,
+    after_json JSON NULL,
+    ip VARCHAR(45) NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    INDEX idx_audit_log_created_at (created_at),
+    INDEX idx_audit_log_admin (admin_id, created_at),
+    INDEX idx_audit_log_entity (entity_type, entity_id),
+    CONSTRAINT fk_audit_log_admin FOREIGN KEY (admin_id) REFERENCES admins(id) ON DELETE SET NULL
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+-- Every user status change with its reason; admin_id is NULL for automatic
+-- changes such as email verification
+CREATE TABLE IF NOT EXISTS user_status_history (
+    id BIGINT AUTO_INCREMENT PRIMARY KEY,
+    user_id INT NOT NULL,
+    from_status VARCHAR(16) NOT NULL,
+    to_status VARCHAR(16) NOT NULL,
+    reason VARCHAR(255) NOT NULL,
+    admin_id INT NULL,
+    admin_username VARCHAR(50) NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    INDEX idx_user_status_history_user (user_id, created_at),
+    CONSTRAINT fk_user_status_history_user FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    CONSTRAINT fk_user_status_history_admin FOREIGN KEY (admin_id) REFERENCES admins(id) ON DELETE SET NULL
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+-- Previous password hashes per account, used to block reuse (pruned to PASSWORD_HISTORY rows)
+CREATE TABLE IF NOT EXISTS password_history (
+    id BIGINT AUTO_INCREMENT PRIMARY KEY,
+    account_type ENUM('admin', 'user') NOT NULL,
+    account_id INT NOT NULL,
+    password_hash VARCHAR(255) NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    INDEX idx_password_history_account (account_type, account_id, id)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+-- Role-based access control: admins get roles, roles get permissions
+CREATE TABLE IF NOT EXISTS roles (
+    id INT AUTO_INCREMENT PRIMARY KEY,
+    name VARCHAR(50) NOT NULL UNIQUE,
+    description VARCHAR(255) NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+CREATE TABLE IF NOT EXISTS permissions (
+    id INT AUTO_INCREMENT PRIMARY KEY,
+    name VARCHAR(100) NOT NULL UNIQUE,
+    description VARCHAR(255) NULL
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+CREATE TABLE IF NOT EXISTS role_permissions (
+    role_id INT NOT NULL,
+    permission_id INT NOT NULL,
+    PRIMARY KEY (role_id, permission_id),
+    CONSTRAINT fk_role_permissions_role FOREIGN KEY (role_id) REFERENCES roles(id) ON DELETE CASCADE,
+    CONSTRAINT fk_role_permissions_permission FOREIGN KEY (permission_id) REFERENCES permissions(id) ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+CREATE TABLE IF NOT EXISTS admin_roles (
+    admin_id INT NOT NULL,
+    role_id INT NOT NULL,
+    PRIMARY KEY (admin_id, role_id),
+    CONSTRAINT fk_admin_roles_admin FOREIGN KEY (admin_id) REFERENCES admins(id) ON DELETE CASCADE,
+    CONSTRAINT fk_admin_roles_role FOREIGN KEY (role_id) REFERENCES roles(id) ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+-- Permission names must match src/controllers/permissions.rs
+INSERT IGNORE INTO permissions (name, description) VALUES
+    ('users.view', 'View users'),
+    ('users.create', 'Create users'),
+    ('users.edit', 'Edit users'),
+    ('users.delete', 'Delete users'),
+    ('users.export', 'Export users to PDF'),
+    ('users.impersonate', 'Sign in to the public site as a user'),
+    ('users.status', 'Suspend, lock, close and reactivate users'),
+    ('geo.view', 'View countries and states'),
+    ('geo.edit', 'Create and edit countries and states'),
+    ('geo.delete', 'Delete countries and states'),
+    ('audit.view', 'View and export the audit log'),
+    ('admins.view', 'View admin accounts'),
+    ('admins.manage', 'Create, edit, disable and delete admin accounts');
+
+INSERT IGNORE INTO roles (name, description) VALUES
+    ('super-admin', 'Full access'),
+    ('user-manager', 'Manage users'),
+    ('geo-editor', 'Manage countries and states'),
+    ('read-only', 'View users and geo data');
+
+-- super-admin holds every permission; re-run after adding permissions
+INSERT IGNORE INTO role_permissions (role_id, permission_id)
+SELECT r.id, p.id FROM roles r CROSS JOIN permissions p WHERE r.name = 'super-admin';
+
+-- Listed explicitly so new users.* permissions (impersonation, status
+-- changes) are never granted to user managers by accident
+INSERT IGNORE INTO role_permissions (role_id, permission_id)
+SELECT r.id, p.id FROM roles r JOIN permissions p
+    ON p.name IN ('users.view', 'users.create', 'users.edit', 'users.delete', 'users.export')
+WHERE r.name = 'user-manager';
+
+INSERT IGNORE INTO role_permissions (role_id, permission_id)
+SELECT r.id, p.id FROM roles r JOIN permissions p ON p.name LIKE 'geo.%' WHERE r.name = 'geo-editor';
+
+INSERT IGNORE INTO role_permissions (role_id, permission_id)
+SELECT r.id, p.id FROM roles r JOIN permissions p ON p.name IN ('users.view', 'geo.view') WHERE r.name = 'read-only';
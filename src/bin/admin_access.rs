@@ -0,0 +1,209 @@
+use axum_askama_admin_template::models::AdminAccessPolicy;
+use axum_askama_admin_template::repository;
+use axum_askama_admin_template::services::access_policy;
+use sqlx::mysql::MySqlPoolOptions;
+use std::env;
+
+#[tokio::main]
+async fn main() {
+    // Load environment variables
+    dotenvy::dotenv().ok();
+
+    let args: Vec<String> = env::args().collect();
+
+    // Simple argument parsing
+    let mut username = String::new();
+    let mut ips: Option<String> = None;
+    let mut days: Option<String> = None;
+    let mut hours: Option<String> = None;
+    let mut utc_offset: Option<String> = None;
+    let mut clear = false;
+
+    let mut i = 1;
+    while i < args.len() {
+        let target = match args[i].as_str() {
+            "--username" | "-u" => None,
+            "--ips" => Some(&mut ips),
+            "--days" => Some(&mut days),
+            "--hours" => Some(&mut hours),
+            "--utc-offset" => Some(&mut utc_offset),
+            "--clear" => {
+                clear = true;
+                i += 1;
+                continue;
+            }
+            "--help" | "-h" => {
+                print_usage();
+                return;
+            }
+            _ => {
+                eprintln!("Error: Unknown argument: {}", args[i]);
+                print_usage();
+                return;
+            }
+        };
+        let Some(value) = args.get(i + 1).cloned() else {
+            eprintln!("Error: {} requires a value", args[i]);
+            print_usage();
+            return;
+        };
+        match target {
+            Some(target) => *target = Some(value),
+            None => username = value,
+        }
+        i += 2;
+    }
+
+    if username.is_empty() {
+        eprintln!("Error: --username is required");
+        print_usage();
+        return;
+    }
+
+    let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set in .env file");
+    let pool = MySqlPoolOptions::new()
+        .max_connections(1)
+        .connect(&database_url)
+        .await
+        .expect("Failed to create database pool");
+
+    let admin = match repository::find_admin_by_username(&pool, &username).await {
+        Ok(Some(admin)) => admin,
+        Ok(None) => {
+            eprintln!("Error: No admin named {}", username);
+            return;
+        }
+        Err(e) => {
+            eprintln!("Error: Failed to look up admin {}: {:?}", username, e);
+            return;
+        }
+    };
+
+    if clear {
+        match repository::delete_admin_access_policy(&pool, admin.id).await {
+            Ok(true) => println!("✓ Removed the access policy of {}", username),
+            Ok(false) => println!("{} has no access policy", username),
+            Err(e) => eprintln!("✗ Failed to remove access policy: {:?}", e),
+        }
+        pool.close().await;
+        return;
+    }
+
+    let current = match repository::find_admin_access_policy(&pool, admin.id).await {
+        Ok(policy) => policy,
+        Err(e) => {
+            eprintln!("Error: Failed to load access policy: {:?}", e);
+            return;
+        }
+    };
+    if ips.is_none() && days.is_none() && hours.is_none() && utc_offset.is_none() {
+        match current {
+            Some(policy) => print_policy(&username, &policy),
+            None => println!("{} has no access policy (ADMIN_IP_ALLOWLIST applies)", username),
+        }
+        pool.close().await;
+        return;
+    }
+
+    // Options not given keep their current value; "any" removes a restriction
+    let mut policy = current.unwrap_or(AdminAccessPolicy { admin_id: admin.id, ..Default::default() });
+    if let Err(e) = apply_changes(&mut policy, ips, days, hours, utc_offset) {
+        eprintln!("Error: {}", e);
+        return;
+    }
+    match repository::upsert_admin_access_policy(&pool, &policy).await {
+        Ok(()) => {
+            println!("✓ Updated the access policy of {}", username);
+            print_policy(&username, &policy);
+        }
+        Err(e) => eprintln!("✗ Failed to save access policy: {:?}", e),
+    }
+
+    pool.close().await;
+}
+
+fn is_any(value: &str) -> bool {
+    value.trim().eq_ignore_ascii_case("any")
+}
+
+fn apply_changes(
+    policy: &mut AdminAccessPolicy,
+    ips: Option<String>,
+    days: Option<String>,
+    hours: Option<String>,
+    utc_offset: Option<String>,
+) -> Result<(), String> {
+    if let Some(ips) = ips {
+        policy.ip_allowlist = if is_any(&ips) {
+            None
+        } else {
+            let ranges = access_policy::parse_cidrs(&ips)?;
+            if ranges.is_empty() {
+                return Err("--ips needs at least one range, or \"any\"".to_string());
+            }
+            Some(ranges.iter().map(ToString::to_string).collect::<Vec<_>>().join(","))
+        };
+    }
+    if let Some(days) = days {
+        policy.allowed_days = if is_any(&days) {
+            None
+        } else {
+            Some(access_policy::format_days(&access_policy::parse_days(&days)?))
+        };
+    }
+    if let Some(hours) = hours {
+        (policy.allowed_from, policy.allowed_until) = if is_any(&hours) {
+            (None, None)
+        } else {
+            let (from, until) = access_policy::parse_hours(&hours)?;
+            (Some(from), Some(until))
+        };
+    }
+    if let Some(utc_offset) = utc_offset {
+        policy.utc_offset_minutes = access_policy::parse_utc_offset(&utc_offset)?;
+    }
+    Ok(())
+}
+
+fn print_policy(username: &str, policy: &AdminAccessPolicy) {
+    let hours = match (policy.allowed_from, policy.allowed_until) {
+        (Some(from), Some(until)) => format!(
+            "{:02}:{:02}-{:02}:{:02}",
+            from.hour(),
+            from.minute(),
+            until.hour(),
+            until.minute()
+        ),
+        _ => "any".to_string(),
+    };
+    let offset = policy.utc_offset_minutes;
+    println!("Access policy of {}:", username);
+    println!("  IP ranges:  {}", policy.ip_allowlist.as_deref().unwrap_or("ADMIN_IP_ALLOWLIST"));
+    println!("  Days:       {}", policy.allowed_days.as_deref().unwrap_or("any"));
+    println!("  Hours:      {}", hours);
+    println!(
+        "  UTC offset: {}{:02}:{:02}",
+        if offset < 0 { '-' } else { '+' },
+        offset.abs() / 60,
+        offset.abs() % 60
+    );
+}
+
+fn print_usage() {
+    println!("Usage: cargo run --bin admin_access -- --username <username> [options]");
+    println!();
+    println!("Shows or changes an admin's access policy. Without options the current");
+    println!("policy is printed; options not given keep their value.");
+    println!();
+    println!("Options:");
+    println!("  --username, -u <username>  Admin username");
+    println!("  --ips <ranges|any>         Comma-separated CIDR ranges replacing ADMIN_IP_ALLOWLIST");
+    println!("  --days <days|any>          Allowed weekdays, e.g. mon-fri or mon,wed,fri");
+    println!("  --hours <HH:MM-HH:MM|any>  Allowed hours; a later start than end spans midnight");
+    println!("  --utc-offset <offset>      Offset the days and hours are in, e.g. +07:00 (default +00:00)");
+    println!("  --clear                    Remove the admin's policy");
+    println!("  --help, -h                 Show this help message");
+    println!();
+    println!("Example:");
+    println!("  cargo run --bin admin_access -- -u admin --ips 10.0.0.0/8 --days mon-fri --hours 08:00-18:00 --utc-offset +07:00");
+}
@@ -0,0 +1,125 @@
+use axum_askama_admin_template::repository;
+use axum_askama_admin_template::services::admin_sessions;
+use sqlx::mysql::MySqlPoolOptions;
+use std::env;
+use tower_sessions_redis_store::fred::interfaces::ClientLike;
+use tower_sessions_redis_store::fred::prelude::{RedisConfig, RedisPool};
+
+#[tokio::main]
+async fn main() {
+    // Load environment variables
+    dotenvy::dotenv().ok();
+
+    let args: Vec<String> = env::args().collect();
+
+    // Simple argument parsing
+    let mut username = String::new();
+    let mut revoke_all = false;
+
+    let mut i = 1;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--username" | "-u" => {
+                if i + 1 < args.len() {
+                    username = args[i + 1].clone();
+                    i += 2;
+                } else {
+                    eprintln!("Error: --username requires a value");
+                    print_usage();
+                    return;
+                }
+            }
+            "--revoke-all" => {
+                revoke_all = true;
+                i += 1;
+            }
+            "--help" | "-h" => {
+                print_usage();
+                return;
+            }
+            _ => {
+                eprintln!("Error: Unknown argument: {}", args[i]);
+                print_usage();
+                return;
+            }
+        }
+    }
+
+    if username.is_empty() {
+        eprintln!("Error: --username is required");
+        print_usage();
+        return;
+    }
+
+    let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set in .env file");
+    let pool = MySqlPoolOptions::new()
+        .max_connections(1)
+        .connect(&database_url)
+        .await
+        .expect("Failed to create database pool");
+
+    let admin = match repository::find_admin_by_username(&pool, &username).await {
+        Ok(Some(admin)) => admin,
+        Ok(None) => {
+            eprintln!("Error: No admin named {}", username);
+            return;
+        }
+        Err(e) => {
+            eprintln!("Error: Failed to look up admin {}: {:?}", username, e);
+            return;
+        }
+    };
+
+    let redis_url = env::var("REDIS_URL").expect("REDIS_URL must be set in .env file");
+    let redis_config = RedisConfig::from_url(redis_url.as_str()).expect("Invalid REDIS_URL");
+    let redis_pool = RedisPool::new(redis_config, None, None, None, 1)
+        .expect("Failed to create Redis pool");
+    let _redis_conn = redis_pool.connect();
+    redis_pool
+        .wait_for_connect()
+        .await
+        .expect("Failed to connect to Redis");
+
+    if revoke_all {
+        match admin_sessions::revoke_all(&redis_pool, admin.id).await {
+            Ok(count) => println!("✓ Revoked {} session(s) for {}", count, username),
+            Err(e) => eprintln!("✗ Failed to revoke sessions for {}: {}", username, e),
+        }
+        match repository::delete_remember_tokens_by_admin(&pool, admin.id).await {
+            Ok(count) => println!("✓ Deleted {} remember-me token(s) for {}", count, username),
+            Err(e) => eprintln!("✗ Failed to delete remember-me tokens for {}: {:?}", username, e),
+        }
+    } else {
+        let sessions = admin_sessions::list(&redis_pool, admin.id).await;
+        if sessions.is_empty() {
+            println!("No active sessions for {}", username);
+        }
+        for record in sessions {
+            println!(
+                "{}  {}  signed in {}  last seen {}  {}",
+                record.session_ref,
+                record.ip,
+                record.created_at_display(),
+                record.last_seen_display(),
+                record.user_agent
+            );
+        }
+    }
+
+    let _ = redis_pool.quit().await;
+    pool.close().await;
+}
+
+fn print_usage() {
+    println!("Usage: cargo run --bin admin_sessions -- --username <username> [--revoke-all]");
+    println!();
+    println!("Lists an admin's active sessions, or signs them out everywhere.");
+    println!();
+    println!("Options:");
+    println!("  --username, -u <username>  Admin username");
+    println!("  --revoke-all               Revoke every session and remember-me token of the admin");
+    println!("  --help, -h                 Show this help message");
+    println!();
+    println!("Example:");
+    println!("  cargo run --bin admin_sessions -- -u admin --revoke-all");
+}
@@ -1,3 +1,6 @@
+use axum_askama_admin_template::models::PasswordOwner;
+use axum_askama_admin_template::services::password_hash::PasswordHashConfig;
+use axum_askama_admin_template::services::password_policy::PasswordPolicy;
 use sqlx::mysql::MySqlPoolOptions;
 use std::env;
 
@@ -12,6 +15,7 @@ async fn main() {
     let mut username = String::new();
     let mut email = String::new();
     let mut password = String::new();
+    let mut role = "super-admin".to_string();
 
     let mut i = 1;
     while i < args.len() {
@@ -46,6 +50,16 @@ async fn main() {
                     return;
                 }
             }
+            "--role" | "-r" => {
+                if i + 1 < args.len() {
+                    role = args[i + 1].clone();
+                    i += 2;
+                } else {
+                    eprintln!("Error: --role requires a value");
+                    print_usage();
+                    return;
+                }
+            }
             "--help" | "-h" => {
                 print_usage();
                 return;
@@ -65,12 +79,30 @@ async fn main() {
         return;
     }
 
+    // Same rules as passwords set through the web UI
+    let policy = match PasswordPolicy::from_env() {
+        Ok(policy) => policy,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return;
+        }
+    };
+    let violations = policy.violations(&password);
+    if !violations.is_empty() {
+        eprintln!("Error: Password does not meet the password policy:");
+        for violation in violations {
+            eprintln!("  - {}", violation);
+        }
+        return;
+    }
+
     let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set in .env file");
 
     println!("Creating admin user...");
     println!("  Username: {}", username);
     println!("  Email: {}", email);
     println!("  Password: {}", "*".repeat(password.len()));
+    println!("  Role: {}", role);
 
     let pool = MySqlPoolOptions::new()
         .max_connections(5)
@@ -78,9 +110,9 @@ async fn main() {
         .await
         .expect("Failed to create database pool");
 
-    // Hash the password using bcrypt
-    let password_hash = bcrypt::hash(&password, bcrypt::DEFAULT_COST)
-        .expect("Failed to hash password");
+    // Hash the password with the configured algorithm (PASSWORD_HASH_ALGORITHM)
+    let hasher = PasswordHashConfig::from_env().expect("Invalid password hashing configuration");
+    let password_hash = hasher.hash(&password).await.expect("Failed to hash password");
 
     // Insert the admin user
     let result = sqlx::query(
@@ -94,6 +126,26 @@ async fn main() {
 
     match result {
         Ok(query_result) => {
+            let owner = PasswordOwner::Admin(query_result.last_insert_id() as i32);
+            policy.remember(&pool, owner, &password_hash).await;
+
+            // Grant the requested role (roles are seeded by schema.sql)
+            let role_result = sqlx::query(
+                "INSERT INTO admin_roles (admin_id, role_id) SELECT ?, id FROM roles WHERE name = ?"
+            )
+            .bind(query_result.last_insert_id())
+            .bind(&role)
+            .execute(&pool)
+            .await;
+
+            match role_result {
+                Ok(r) if r.rows_affected() == 0 => {
+                    eprintln!("\n✗ Role '{}' does not exist; the admin has no permissions", role);
+                }
+                Ok(_) => {}
+                Err(e) => eprintln!("\n✗ Failed to assign role '{}': {:?}", role, e),
+            }
+
             let host = env::var("APP_HOST").unwrap_or_else(|_| "127.0.0.1".to_string());
             let port = env::var("APP_PORT").unwrap_or_else(|_| "3000".to_string());
             println!("\n✓ Admin user created successfully!");
@@ -120,9 +172,10 @@ fn print_usage() {
     println!("  --username, -u <username>  Admin username");
     println!("  --email, -e <email>        Admin email");
     println!("  --password, -p <password>  Admin password");
+    println!("  --role, -r <role>          Role to grant (default: super-admin)");
     println!("  --help, -h                 Show this help message");
     println!();
     println!("Example:");
-    println!("  cargo run --bin create_admin -- -u admin -e admin@example.com -p admin123");
+    println!("  cargo run --bin create_admin -- -u admin -e admin@example.com -p 'Change-Me-2024'");
 }
 
@@ -0,0 +1,295 @@
+// Minimal OpenID Connect provider for trying admin single sign-on locally.
+// Not for production: anyone can sign in as any email address.
+//
+//   cargo run --bin mock_oidc -- --addr 127.0.0.1:9000
+//
+// then start the app with OIDC_ISSUER=http://127.0.0.1:9000 and
+// OIDC_CLIENT_ID=admin-console (any client id and secret are accepted).
+use std::collections::HashMap;
+use std::env;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use axum::{
+    Form, Json, Router,
+    extract::{Query, State},
+    http::StatusCode,
+    response::{Html, IntoResponse, Redirect, Response},
+    routing::{get, post},
+};
+use axum_askama_admin_template::services::oidc::code_challenge;
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use rand::RngCore;
+use rsa::pkcs1v15::SigningKey;
+use rsa::signature::{SignatureEncoding, Signer};
+use rsa::traits::PublicKeyParts;
+use rsa::{RsaPrivateKey, RsaPublicKey};
+use serde::Deserialize;
+use serde_json::json;
+use sha2::Sha256;
+use time::OffsetDateTime;
+
+const KEY_ID: &str = "mock-key-1";
+const TOKEN_TTL_SECONDS: i64 = 300;
+
+struct IssuedCode {
+    client_id: String,
+    redirect_uri: String,
+    nonce: Option<String>,
+    code_challenge: Option<String>,
+    email: String,
+    name: String,
+    groups: Vec<String>,
+}
+
+#[derive(Clone)]
+struct MockProvider {
+    issuer: String,
+    signing_key: SigningKey<Sha256>,
+    public_key: RsaPublicKey,
+    codes: Arc<Mutex<HashMap<String, IssuedCode>>>,
+}
+
+#[derive(Deserialize)]
+struct AuthorizeParams {
+    client_id: String,
+    redirect_uri: String,
+    state: Option<String>,
+    nonce: Option<String>,
+    code_challenge: Option<String>,
+    #[serde(default)]
+    email: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    groups: String,
+}
+
+#[derive(Deserialize)]
+struct TokenRequest {
+    grant_type: String,
+    code: String,
+    redirect_uri: String,
+    code_verifier: Option<String>,
+}
+
+fn random_token() -> String {
+    let mut bytes = [0u8; 24];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn token_error(error: &str) -> Response {
+    (StatusCode::BAD_REQUEST, Json(json!({ "error": error }))).into_response()
+}
+
+async fn discovery(State(provider): State<MockProvider>) -> impl IntoResponse {
+    Json(json!({
+        "issuer": provider.issuer,
+        "authorization_endpoint": format!("{}/authorize", provider.issuer),
+        "token_endpoint": format!("{}/token", provider.issuer),
+        "jwks_uri": format!("{}/jwks", provider.issuer),
+        "response_types_supported": ["code"],
+        "subject_types_supported": ["public"],
+        "id_token_signing_alg_values_supported": ["RS256"],
+        "code_challenge_methods_supported": ["S256"],
+        "scopes_supported": ["openid", "email", "profile"],
+    }))
+}
+
+async fn jwks(State(provider): State<MockProvider>) -> impl IntoResponse {
+    Json(json!({
+        "keys": [{
+            "kty": "RSA",
+            "use": "sig",
+            "alg": "RS256",
+            "kid": KEY_ID,
+            "n": URL_SAFE_NO_PAD.encode(provider.public_key.n().to_bytes_be()),
+            "e": URL_SAFE_NO_PAD.encode(provider.public_key.e().to_bytes_be()),
+        }]
+    }))
+}
+
+// Sign-in form standing in for the provider's login page (GET)
+async fn authorize_page(Query(params): Query<AuthorizeParams>) -> impl IntoResponse {
+    let hidden = [
+        ("client_id", params.client_id),
+        ("redirect_uri", params.redirect_uri),
+        ("state", params.state.unwrap_or_default()),
+        ("nonce", params.nonce.unwrap_or_default()),
+        ("code_challenge", params.code_challenge.unwrap_or_default()),
+    ]
+    .iter()
+    .map(|(name, value)| {
+        format!(r#"<input type="hidden" name="{}" value="{}">"#, name, escape(value))
+    })
+    .collect::<String>();
+
+    Html(format!(
+        r#"<!DOCTYPE html>
+<html><head><title>Mock identity provider</title></head>
+<body>
+<h1>Mock identity provider</h1>
+<form method="post" action="/authorize">
+{}
+<p><label>Email <input type="email" name="email" value="admin@example.com" required></label></p>
+<p><label>Name <input type="text" name="name" value="Example Admin"></label></p>
+<p><label>Groups (comma separated) <input type="text" name="groups" value="admins"></label></p>
+<p><button type="submit">Sign in</button></p>
+</form>
+</body></html>"#,
+        hidden
+    ))
+}
+
+// Issues a code and sends the browser back to the client (POST)
+async fn authorize_submit(
+    State(provider): State<MockProvider>,
+    Form(params): Form<AuthorizeParams>,
+) -> impl IntoResponse {
+    let Ok(mut redirect) = reqwest::Url::parse(&params.redirect_uri) else {
+        return (StatusCode::BAD_REQUEST, "Invalid redirect_uri").into_response();
+    };
+    let code = random_token();
+    let issued = IssuedCode {
+        client_id: params.client_id,
+        redirect_uri: params.redirect_uri.clone(),
+        nonce: params.nonce.filter(|n| !n.is_empty()),
+        code_challenge: params.code_challenge.filter(|c| !c.is_empty()),
+        email: params.email.trim().to_string(),
+        name: params.name.trim().to_string(),
+        groups: params
+            .groups
+            .split(',')
+            .map(|g| g.trim().to_string())
+            .filter(|g| !g.is_empty())
+            .collect(),
+    };
+    provider.codes.lock().unwrap().insert(code.clone(), issued);
+
+    redirect.query_pairs_mut().append_pair("code", &code);
+    if let Some(state) = params.state.filter(|s| !s.is_empty()) {
+        redirect.query_pairs_mut().append_pair("state", &state);
+    }
+    Redirect::to(redirect.as_str()).into_response()
+}
+
+// Exchanges a code for an ID token (POST)
+async fn token(
+    State(provider): State<MockProvider>,
+    Form(request): Form<TokenRequest>,
+) -> impl IntoResponse {
+    if request.grant_type != "authorization_code" {
+        return token_error("unsupported_grant_type");
+    }
+    let Some(issued) = provider.codes.lock().unwrap().remove(&request.code) else {
+        return token_error("invalid_grant");
+    };
+    if issued.redirect_uri != request.redirect_uri {
+        return token_error("invalid_grant");
+    }
+    if let Some(challenge) = &issued.code_challenge {
+        let verifier = request.code_verifier.unwrap_or_default();
+        if &code_challenge(&verifier) != challenge {
+            return token_error("invalid_grant");
+        }
+    }
+
+    let now = OffsetDateTime::now_utc().unix_timestamp();
+    let header = json!({ "alg": "RS256", "typ": "JWT", "kid": KEY_ID });
+    let claims = json!({
+        "iss": provider.issuer,
+        "sub": format!("mock|{}", issued.email.to_lowercase()),
+        "aud": issued.client_id,
+        "iat": now,
+        "exp": now + TOKEN_TTL_SECONDS,
+        "nonce": issued.nonce,
+        "email": issued.email,
+        "email_verified": true,
+        "name": issued.name,
+        "preferred_username": issued.email.split('@').next().unwrap_or_default(),
+        "groups": issued.groups,
+    });
+    let signing_input = format!(
+        "{}.{}",
+        URL_SAFE_NO_PAD.encode(header.to_string()),
+        URL_SAFE_NO_PAD.encode(claims.to_string())
+    );
+    let signature = provider.signing_key.sign(signing_input.as_bytes()).to_vec();
+    let id_token = format!("{}.{}", signing_input, URL_SAFE_NO_PAD.encode(signature));
+
+    Json(json!({
+        "access_token": random_token(),
+        "token_type": "Bearer",
+        "expires_in": TOKEN_TTL_SECONDS,
+        "id_token": id_token,
+    }))
+    .into_response()
+}
+
+#[tokio::main]
+async fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut addr = env::var("MOCK_OIDC_ADDR").unwrap_or_else(|_| "127.0.0.1:9000".to_string());
+
+    let mut i = 1;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--addr" | "-a" => {
+                if i + 1 < args.len() {
+                    addr = args[i + 1].clone();
+                    i += 2;
+                } else {
+                    eprintln!("Error: --addr requires a value");
+                    print_usage();
+                    return;
+                }
+            }
+            "--help" | "-h" => {
+                print_usage();
+                return;
+            }
+            other => {
+                eprintln!("Error: unknown argument {}", other);
+                print_usage();
+                return;
+            }
+        }
+    }
+
+    let addr: SocketAddr = addr.parse().expect("Invalid --addr");
+    println!("Generating RSA signing key...");
+    let private_key =
+        RsaPrivateKey::new(&mut rand::thread_rng(), 2048).expect("Failed to generate RSA key");
+    let provider = MockProvider {
+        issuer: format!("http://{}", addr),
+        public_key: private_key.to_public_key(),
+        signing_key: SigningKey::<Sha256>::new(private_key),
+        codes: Arc::new(Mutex::new(HashMap::new())),
+    };
+    println!("Mock OIDC provider listening; set OIDC_ISSUER={}", provider.issuer);
+
+    let app = Router::new()
+        .route("/.well-known/openid-configuration", get(discovery))
+        .route("/jwks", get(jwks))
+        .route("/authorize", get(authorize_page).post(authorize_submit))
+        .route("/token", post(token))
+        .with_state(provider);
+
+    let listener = tokio::net::TcpListener::bind(addr).await.expect("Failed to bind");
+    axum::serve(listener, app).await.expect("Server error");
+}
+
+fn print_usage() {
+    println!("\nUsage: cargo run --bin mock_oidc -- [--addr 127.0.0.1:9000]");
+    println!("\nOptions:");
+    println!("  --addr, -a <host:port>     Listen address, also used as the issuer (default: 127.0.0.1:9000)");
+}
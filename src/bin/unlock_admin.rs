@@ -0,0 +1,113 @@
+use axum_askama_admin_template::services::login_throttle::{self, LoginRealm};
+use std::env;
+use std::net::IpAddr;
+use tower_sessions_redis_store::fred::interfaces::ClientLike;
+use tower_sessions_redis_store::fred::prelude::{RedisConfig, RedisPool};
+
+#[tokio::main]
+async fn main() {
+    // Load environment variables
+    dotenvy::dotenv().ok();
+
+    let args: Vec<String> = env::args().collect();
+
+    // Simple argument parsing
+    let mut username = String::new();
+    let mut ip = String::new();
+
+    let mut i = 1;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--username" | "-u" => {
+                if i + 1 < args.len() {
+                    username = args[i + 1].clone();
+                    i += 2;
+                } else {
+                    eprintln!("Error: --username requires a value");
+                    print_usage();
+                    return;
+                }
+            }
+            "--ip" | "-i" => {
+                if i + 1 < args.len() {
+                    ip = args[i + 1].clone();
+                    i += 2;
+                } else {
+                    eprintln!("Error: --ip requires a value");
+                    print_usage();
+                    return;
+                }
+            }
+            "--help" | "-h" => {
+                print_usage();
+                return;
+            }
+            _ => {
+                eprintln!("Error: Unknown argument: {}", args[i]);
+                print_usage();
+                return;
+            }
+        }
+    }
+
+    if username.is_empty() && ip.is_empty() {
+        eprintln!("Error: --username or --ip is required");
+        print_usage();
+        return;
+    }
+
+    let ip_addr = if ip.is_empty() {
+        None
+    } else {
+        match ip.parse::<IpAddr>() {
+            Ok(addr) => Some(addr),
+            Err(_) => {
+                eprintln!("Error: Invalid IP address: {}", ip);
+                return;
+            }
+        }
+    };
+
+    let redis_url = env::var("REDIS_URL").expect("REDIS_URL must be set in .env file");
+    let redis_config = RedisConfig::from_url(redis_url.as_str()).expect("Invalid REDIS_URL");
+    let redis_pool = RedisPool::new(redis_config, None, None, None, 1)
+        .expect("Failed to create Redis pool");
+    let _redis_conn = redis_pool.connect();
+    redis_pool
+        .wait_for_connect()
+        .await
+        .expect("Failed to connect to Redis");
+
+    if !username.is_empty() {
+        let result = login_throttle::unlock_username(&redis_pool, LoginRealm::Admin, &username).await;
+        match result {
+            Ok(0) => println!("No failed logins recorded for user {}", username),
+            Ok(_) => println!("✓ Cleared login lockout for user {}", username),
+            Err(e) => eprintln!("✗ Failed to unlock user {}: {}", username, e),
+        }
+    }
+
+    if let Some(addr) = ip_addr {
+        match login_throttle::unlock_ip(&redis_pool, LoginRealm::Admin, addr).await {
+            Ok(0) => println!("No failed logins recorded for IP {}", addr),
+            Ok(_) => println!("✓ Cleared login lockout for IP {}", addr),
+            Err(e) => eprintln!("✗ Failed to unlock IP {}: {}", addr, e),
+        }
+    }
+
+    let _ = redis_pool.quit().await;
+}
+
+fn print_usage() {
+    println!("Usage: cargo run --bin unlock_admin -- [--username <username>] [--ip <address>]");
+    println!();
+    println!("Clears failed-login counters and lockouts recorded in Redis.");
+    println!();
+    println!("Options:");
+    println!("  --username, -u <username>  Admin username to unlock");
+    println!("  --ip, -i <address>         Client IP address to unlock");
+    println!("  --help, -h                 Show this help message");
+    println!();
+    println!("Example:");
+    println!("  cargo run --bin unlock_admin -- -u admin");
+}
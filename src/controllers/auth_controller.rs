@@ -1,16 +1,39 @@
 use axum::{
-    async_trait,
+    Json, async_trait,
     extract::FromRequestParts,
-    http::{StatusCode, request::Parts},
+    http::{StatusCode, header, request::Parts},
     response::{IntoResponse, Redirect, Response},
 };
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use std::marker::PhantomData;
+use time::OffsetDateTime;
 use tower_sessions::Session;
 
+use crate::controllers::permissions::Permission;
+use crate::repository;
+use crate::services::{admin_sessions, api_tokens};
+use crate::state::AppState;
+use crate::views::templates::AdminErrorTemplate;
+
 const USER_ID_KEY: &str = "user_id";
 const USERNAME_KEY: &str = "username";
 const ADMIN_ID_KEY: &str = "admin_id";
 const ADMIN_USERNAME_KEY: &str = "admin_username";
+const ADMIN_SESSION_REF_KEY: &str = "admin_session_ref";
+// Unix time the admin last proved it was them (password, second factor, passkey or SSO)
+const ADMIN_REAUTH_AT_KEY: &str = "admin_reauth_at";
+const PENDING_2FA_ADMIN_ID_KEY: &str = "pending_2fa_admin_id";
+const PENDING_2FA_USERNAME_KEY: &str = "pending_2fa_username";
+const PENDING_2FA_STARTED_AT_KEY: &str = "pending_2fa_started_at";
+const PENDING_2FA_ATTEMPTS_KEY: &str = "pending_2fa_attempts";
+const PENDING_2FA_REMEMBER_KEY: &str = "pending_2fa_remember_me";
+const IMPERSONATION_KEY: &str = "impersonation";
+
+// How long a password-verified admin has to complete the second factor
+const PENDING_2FA_TTL_SECONDS: i64 = 300;
+pub const PENDING_2FA_MAX_ATTEMPTS: i32 = 5;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AuthUser {
@@ -36,27 +59,111 @@ impl AuthUser {
     }
 
     pub async fn from_session(session: &Session) -> Option<Self> {
+        // An impersonated user session ends with the admin session that started it
+        if let Some(impersonation) = Impersonation::from_session(session).await {
+            let admin_id = session.get::<i32>(ADMIN_ID_KEY).await.ok().flatten();
+            if admin_id != Some(impersonation.admin_id) {
+                let _ = Impersonation::end(session).await;
+                return None;
+            }
+        }
         let id = session.get::<i32>(USER_ID_KEY).await.ok()??;
         let username = session.get::<String>(USERNAME_KEY).await.ok()??;
         Some(Self { id, username })
     }
+
+    // The session's user, as long as the account still exists and may sign
+    // in. Users deleted, suspended, locked or closed since they signed in are
+    // logged out here, so handlers taking `AuthUser` need not check again.
+    async fn from_session_checked(
+        session: &Session,
+        state: &AppState,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let Some(user) = Self::from_session(session).await else {
+            return Ok(None);
+        };
+        match repository::find_user_status(&state.db, user.id).await? {
+            Some(status) if status.sign_in_refusal().is_none() => Ok(Some(user)),
+            status => {
+                tracing::info!(
+                    "Signed out user {}: account is {}",
+                    user.username,
+                    status.map_or("deleted", |status| status.as_str())
+                );
+                let _ = Self::logout(session).await;
+                Ok(None)
+            }
+        }
+    }
+}
+
+// Set while an admin browses the public site as a user. The admin's own
+// session keys stay in place next to the user's, so ending impersonation
+// only removes the user half.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Impersonation {
+    pub admin_id: i32,
+    pub admin_username: String,
+    pub user_id: i32,
+    pub username: String,
+    pub started_at: i64,
+}
+
+impl Impersonation {
+    pub async fn begin(
+        session: &Session,
+        admin: &AdminUser,
+        user: &AuthUser,
+    ) -> Result<Self, tower_sessions::session::Error> {
+        let impersonation = Self {
+            admin_id: admin.id,
+            admin_username: admin.username.clone(),
+            user_id: user.id,
+            username: user.username.clone(),
+            started_at: OffsetDateTime::now_utc().unix_timestamp(),
+        };
+        user.login(session).await?;
+        session.insert(IMPERSONATION_KEY, impersonation.clone()).await?;
+        Ok(impersonation)
+    }
+
+    pub async fn from_session(session: &Session) -> Option<Self> {
+        session.get::<Self>(IMPERSONATION_KEY).await.ok()?
+    }
+
+    // Logs out the impersonated user; returns what was ended, if anything
+    pub async fn end(session: &Session) -> Result<Option<Self>, tower_sessions::session::Error> {
+        let impersonation = session.remove::<Self>(IMPERSONATION_KEY).await?;
+        if impersonation.is_some() {
+            AuthUser::logout(session).await?;
+        }
+        Ok(impersonation)
+    }
+
+    pub fn started_at_display(&self) -> String {
+        OffsetDateTime::from_unix_timestamp(self.started_at)
+            .map(|dt| crate::utils::format_datetime(&dt))
+            .unwrap_or_default()
+    }
 }
 
 #[async_trait]
-impl<S> FromRequestParts<S> for AuthUser
-where
-    S: Send + Sync,
-{
+impl FromRequestParts<AppState> for AuthUser {
     type Rejection = Response;
 
-    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
         let session = Session::from_request_parts(parts, state)
             .await
             .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
 
-        Self::from_session(&session)
-            .await
-            .ok_or_else(|| Redirect::to("/").into_response())
+        match Self::from_session_checked(&session, state).await {
+            Ok(Some(user)) => Ok(user),
+            Ok(None) => Err(Redirect::to("/").into_response()),
+            Err(e) => {
+                tracing::error!("Failed to check user session: {:?}", e);
+                Err(StatusCode::INTERNAL_SERVER_ERROR.into_response())
+            }
+        }
     }
 }
 
@@ -65,19 +172,21 @@ where
 pub struct OptionalAuthUser(pub Option<AuthUser>);
 
 #[async_trait]
-impl<S> FromRequestParts<S> for OptionalAuthUser
-where
-    S: Send + Sync,
-{
+impl FromRequestParts<AppState> for OptionalAuthUser {
     type Rejection = Response;
 
-    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
         let session = Session::from_request_parts(parts, state)
             .await
             .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
 
-        let user = AuthUser::from_session(&session).await;
-        Ok(OptionalAuthUser(user))
+        match AuthUser::from_session_checked(&session, state).await {
+            Ok(user) => Ok(OptionalAuthUser(user)),
+            Err(e) => {
+                tracing::error!("Failed to check user session: {:?}", e);
+                Err(StatusCode::INTERNAL_SERVER_ERROR.into_response())
+            }
+        }
     }
 }
 
@@ -85,56 +194,338 @@ where
 pub struct AdminUser {
     pub id: i32,
     pub username: String,
+    // Loaded from the admin's roles by the extractor on every request,
+    // so role changes apply without logging out
+    #[serde(skip)]
+    pub permissions: HashSet<String>,
+    // Handle of this login in the admin session index, set by the extractor
+    #[serde(skip)]
+    pub session_ref: Option<String>,
 }
 
 impl AdminUser {
     pub fn new(id: i32, username: String) -> Self {
-        Self { id, username }
+        Self { id, username, permissions: HashSet::new(), session_ref: None }
     }
 
-    pub async fn login(&self, session: &Session) -> Result<(), tower_sessions::session::Error> {
+    // Used by handlers and templates (`admin.can("users.delete")`)
+    pub fn can(&self, permission: &str) -> bool {
+        self.permissions.contains(permission)
+    }
+
+    // Records the login in the admin session index so it can be listed and
+    // revoked; returns the index handle (session_ref)
+    pub async fn login(
+        &self,
+        session: &Session,
+        state: &AppState,
+        ip: &str,
+        user_agent: &str,
+    ) -> Result<String, tower_sessions::session::Error> {
+        let session_ref = admin_sessions::register(
+            &state.redis,
+            self.id,
+            ip,
+            user_agent,
+            state.session_timeout_seconds,
+        )
+        .await
+        .map_err(|e| {
+            tower_sessions::session::Error::Store(tower_sessions::session_store::Error::Backend(e))
+        })?;
+
         session.insert(ADMIN_ID_KEY, self.id).await?;
         session
             .insert(ADMIN_USERNAME_KEY, self.username.clone())
             .await?;
-        Ok(())
+        session.insert(ADMIN_SESSION_REF_KEY, session_ref.clone()).await?;
+        Ok(session_ref)
+    }
+
+    // Keeps the session in step when the signed-in admin renames themselves
+    pub async fn set_username(
+        session: &Session,
+        username: &str,
+    ) -> Result<(), tower_sessions::session::Error> {
+        session.insert(ADMIN_USERNAME_KEY, username.to_string()).await
+    }
+
+    // Starts the sudo-mode window; not called for remember-me restores
+    pub async fn mark_reauthenticated(session: &Session) -> Result<(), tower_sessions::session::Error> {
+        session
+            .insert(ADMIN_REAUTH_AT_KEY, OffsetDateTime::now_utc().unix_timestamp())
+            .await
+    }
+
+    pub async fn reauthenticated_within(session: &Session, seconds: i64) -> bool {
+        match session.get::<i64>(ADMIN_REAUTH_AT_KEY).await {
+            Ok(Some(at)) => OffsetDateTime::now_utc().unix_timestamp() - at <= seconds,
+            _ => false,
+        }
     }
 
     pub async fn logout(session: &Session) -> Result<(), tower_sessions::session::Error> {
         session.remove::<i32>(ADMIN_ID_KEY).await?;
         session.remove::<String>(ADMIN_USERNAME_KEY).await?;
+        session.remove::<String>(ADMIN_SESSION_REF_KEY).await?;
+        session.remove::<i64>(ADMIN_REAUTH_AT_KEY).await?;
         Ok(())
     }
 
     pub async fn from_session(session: &Session) -> Option<Self> {
+        // A session that has only passed the password step is not an admin session
+        if let Ok(Some(_)) = session.get::<i32>(PENDING_2FA_ADMIN_ID_KEY).await {
+            return None;
+        }
         let id = session.get::<i32>(ADMIN_ID_KEY).await.ok()??;
         let username = session.get::<String>(ADMIN_USERNAME_KEY).await.ok()??;
-        Some(Self { id, username })
+        Some(Self::new(id, username))
+    }
+}
+
+// Templates render `current_admin` directly as the username
+impl fmt::Display for AdminUser {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.username)
+    }
+}
+
+// Admin that passed the password check but still owes a TOTP or recovery code
+#[derive(Debug, Clone)]
+pub struct PendingTwoFactor {
+    pub admin_id: i32,
+    pub username: String,
+    pub attempts: i32,
+    // "remember me" was ticked on the password step
+    pub remember_me: bool,
+}
+
+impl PendingTwoFactor {
+    pub async fn begin(
+        session: &Session,
+        admin_id: i32,
+        username: &str,
+        remember_me: bool,
+    ) -> Result<(), tower_sessions::session::Error> {
+        AdminUser::logout(session).await?;
+        session.insert(PENDING_2FA_ADMIN_ID_KEY, admin_id).await?;
+        session.insert(PENDING_2FA_USERNAME_KEY, username.to_string()).await?;
+        session
+            .insert(PENDING_2FA_STARTED_AT_KEY, OffsetDateTime::now_utc().unix_timestamp())
+            .await?;
+        session.insert(PENDING_2FA_ATTEMPTS_KEY, 0).await?;
+        session.insert(PENDING_2FA_REMEMBER_KEY, remember_me).await?;
+        Ok(())
+    }
+
+    // Returns None when there is no pending login or it has expired
+    pub async fn from_session(session: &Session) -> Option<Self> {
+        let admin_id = session.get::<i32>(PENDING_2FA_ADMIN_ID_KEY).await.ok()??;
+        let username = session.get::<String>(PENDING_2FA_USERNAME_KEY).await.ok()??;
+        let started_at = session.get::<i64>(PENDING_2FA_STARTED_AT_KEY).await.ok()??;
+        let attempts = session
+            .get::<i32>(PENDING_2FA_ATTEMPTS_KEY)
+            .await
+            .ok()
+            .flatten()
+            .unwrap_or(0);
+        let remember_me = session
+            .get::<bool>(PENDING_2FA_REMEMBER_KEY)
+            .await
+            .ok()
+            .flatten()
+            .unwrap_or(false);
+
+        if OffsetDateTime::now_utc().unix_timestamp() - started_at > PENDING_2FA_TTL_SECONDS {
+            let _ = Self::clear(session).await;
+            return None;
+        }
+
+        Some(Self { admin_id, username, attempts, remember_me })
+    }
+
+    pub async fn record_failure(&self, session: &Session) -> Result<i32, tower_sessions::session::Error> {
+        let attempts = self.attempts + 1;
+        session.insert(PENDING_2FA_ATTEMPTS_KEY, attempts).await?;
+        Ok(attempts)
+    }
+
+    pub async fn clear(session: &Session) -> Result<(), tower_sessions::session::Error> {
+        session.remove::<i32>(PENDING_2FA_ADMIN_ID_KEY).await?;
+        session.remove::<String>(PENDING_2FA_USERNAME_KEY).await?;
+        session.remove::<i64>(PENDING_2FA_STARTED_AT_KEY).await?;
+        session.remove::<i32>(PENDING_2FA_ATTEMPTS_KEY).await?;
+        session.remove::<bool>(PENDING_2FA_REMEMBER_KEY).await?;
+        Ok(())
     }
 }
 
 #[async_trait]
-impl<S> FromRequestParts<S> for AdminUser
-where
-    S: Send + Sync,
-{
+impl FromRequestParts<AppState> for AdminUser {
     type Rejection = Response;
 
-    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
         let session = Session::from_request_parts(parts, state)
             .await
             .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
 
-        Self::from_session(&session).await.ok_or_else(|| {
-            let login_path = std::env::var("APP_BASE_PATH")
-                .map(|bp| format!("{}/login", bp))
-                .unwrap_or_else(|_| "/admin/login".to_string());
-            Redirect::to(&login_path).into_response()
-        })
+        let login_redirect = || Redirect::to(&format!("{}/login", state.base_path)).into_response();
+        let mut admin = Self::from_session(&session).await.ok_or_else(login_redirect)?;
+
+        // Sessions revoked from the sessions page, by "log out everywhere" or by a
+        // password change no longer have an index record and are dropped
+        let session_ref = session
+            .get::<String>(ADMIN_SESSION_REF_KEY)
+            .await
+            .ok()
+            .flatten();
+        let session_id = session.id().map(|id| id.to_string());
+        let active = match &session_ref {
+            Some(session_ref) => {
+                admin_sessions::touch(
+                    &state.redis,
+                    session_ref,
+                    admin.id,
+                    session_id,
+                    state.session_timeout_seconds,
+                )
+                .await
+            }
+            None => false,
+        };
+        if !active {
+            tracing::info!("Rejected revoked session for admin {}", admin.username);
+            let _ = Self::logout(&session).await;
+            return Err(login_redirect());
+        }
+        admin.session_ref = session_ref;
+
+        admin.permissions = repository::find_admin_permissions(&state.db, admin.id)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to load permissions for admin {}: {:?}", admin.id, e);
+                StatusCode::INTERNAL_SERVER_ERROR.into_response()
+            })?;
+
+        Ok(admin)
+    }
+}
+
+// Admin that holds permission `P`, e.g. `RequirePermission<UsersDelete>`.
+// Not logged in redirects to the login page; missing the permission renders a 403.
+#[derive(Debug, Clone)]
+pub struct RequirePermission<P: Permission>(pub AdminUser, pub PhantomData<P>);
+
+#[async_trait]
+impl<P: Permission> FromRequestParts<AppState> for RequirePermission<P> {
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let admin = AdminUser::from_request_parts(parts, state).await?;
+        if admin.can(P::NAME) {
+            return Ok(Self(admin, PhantomData));
+        }
+
+        tracing::warn!("Admin {} denied permission {}", admin.username, P::NAME);
+        let template = AdminErrorTemplate {
+            error_code: 403,
+            error_message: format!("You do not have permission to {}.", P::ACTION),
+            current_admin: Some(admin),
+            base_path: state.base_path.clone(),
+        };
+        Err((StatusCode::FORBIDDEN, template).into_response())
+    }
+}
+
+// Caller of an /api route authenticated with `Authorization: Bearer <token>`.
+// `admin.permissions` holds the token's scopes that its owner still has.
+#[derive(Debug, Clone)]
+pub struct BearerToken {
+    pub token_id: i32,
+    pub admin: AdminUser,
+}
+
+fn api_error(status: StatusCode, message: &str) -> Response {
+    let body = Json(serde_json::json!({ "error": message }));
+    if status == StatusCode::UNAUTHORIZED {
+        (status, [(header::WWW_AUTHENTICATE, "Bearer")], body).into_response()
+    } else {
+        (status, body).into_response()
+    }
+}
+
+#[async_trait]
+impl FromRequestParts<AppState> for BearerToken {
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let token = api_tokens::bearer_token(&parts.headers)
+            .ok_or_else(|| api_error(StatusCode::UNAUTHORIZED, "Missing bearer token"))?;
+
+        let invalid = || api_error(StatusCode::UNAUTHORIZED, "Invalid or expired token");
+        if !api_tokens::looks_like_token(token) {
+            return Err(invalid());
+        }
+
+        let owner = repository::find_active_api_token(&state.db, &api_tokens::hash_token(token))
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to look up API token: {:?}", e);
+                api_error(StatusCode::INTERNAL_SERVER_ERROR, "Internal server error")
+            })?
+            .ok_or_else(invalid)?;
+
+        // Scopes never exceed what the owner's roles currently grant
+        let granted = repository::find_admin_permissions(&state.db, owner.admin_id)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to load permissions for admin {}: {:?}", owner.admin_id, e);
+                api_error(StatusCode::INTERNAL_SERVER_ERROR, "Internal server error")
+            })?;
+        let mut admin = AdminUser::new(owner.admin_id, owner.username);
+        admin.permissions = owner
+            .scopes
+            .split_whitespace()
+            .filter(|scope| granted.contains(*scope))
+            .map(str::to_string)
+            .collect();
+
+        if let Err(e) = repository::touch_api_token(&state.db, owner.token_id).await {
+            tracing::error!("Failed to record API token use: {:?}", e);
+        }
+
+        Ok(Self { token_id: owner.token_id, admin })
+    }
+}
+
+// /api caller holding permission `P`: a bearer token with that scope, or,
+// when no Authorization header is sent, a logged-in admin session.
+#[derive(Debug, Clone)]
+pub struct ApiAccess<P: Permission>(pub AdminUser, pub PhantomData<P>);
+
+#[async_trait]
+impl<P: Permission> FromRequestParts<AppState> for ApiAccess<P> {
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        if !parts.headers.contains_key(header::AUTHORIZATION) {
+            let RequirePermission(admin, _) =
+                RequirePermission::<P>::from_request_parts(parts, state).await?;
+            return Ok(Self(admin, PhantomData));
+        }
+
+        let bearer = BearerToken::from_request_parts(parts, state).await?;
+        if !bearer.admin.can(P::NAME) {
+            tracing::warn!("API token {} denied scope {}", bearer.token_id, P::NAME);
+            let message = format!("Token does not have the {} scope", P::NAME);
+            return Err(api_error(StatusCode::FORBIDDEN, &message));
+        }
+        Ok(Self(bearer.admin, PhantomData))
     }
 }
 
-// Optional admin user - doesn't redirect if not logged in
+// Optional admin user - doesn't redirect if not logged in.
+// Permissions are not loaded; use `AdminUser` when they matter.
 #[derive(Debug, Clone)]
 pub struct OptionalAdminUser(pub Option<AdminUser>);
 
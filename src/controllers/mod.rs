@@ -1,2 +1,4 @@
 pub mod auth_controller;
 pub mod page_controller;
+pub mod permissions;
+pub mod request_info;
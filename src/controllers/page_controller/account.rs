@@ -0,0 +1,533 @@
+use axum::{
+    Form,
+    extract::{Extension, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Json, Redirect},
+};
+use tower_sessions::Session;
+use validator::Validate;
+
+use crate::controllers::auth_controller::{AuthUser, OptionalAuthUser};
+use crate::controllers::request_info::ClientIp;
+use crate::middleware::csrf;
+use crate::models::{self, PasswordOwner};
+use crate::repository;
+use crate::services::login_throttle::{self, LoginRealm};
+use crate::services::{password_hash, password_policy};
+use crate::state::AppState;
+use crate::views::templates::{
+    ErrorTemplate, UserLoginTemplate, UserProfileTemplate, UserRegisterTemplate,
+};
+
+use super::email_verification::send_verification_email;
+use super::shared::{
+    ensure_csrf_token, get_countries_cached, get_states_cached, impersonation_banner,
+    ChangePasswordForm, LoginForm, ProfileForm, RegisterForm, StatesQuery,
+};
+
+// User login page (GET)
+pub async fn user_login_page(
+    OptionalAuthUser(user): OptionalAuthUser,
+    State(state): State<AppState>,
+    Extension(session): Extension<Session>,
+) -> impl IntoResponse {
+    if user.is_some() {
+        return Redirect::to("/profile").into_response();
+    }
+
+    UserLoginTemplate {
+        error: None,
+        lockout: None,
+        username: None,
+        csrf_token: ensure_csrf_token(&session).await,
+        base_path: state.base_path.clone(),
+    }
+    .into_response()
+}
+
+// User login submission (POST)
+pub async fn user_login_submit(
+    State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    Extension(session): Extension<Session>,
+    Form(credentials): Form<LoginForm>,
+) -> impl IntoResponse {
+    let error = if credentials.validate().is_err() {
+        Some("Invalid username or password".to_string())
+    } else {
+        None
+    };
+    if error.is_some() {
+        return UserLoginTemplate {
+            error,
+            lockout: None,
+            username: Some(credentials.username),
+            csrf_token: ensure_csrf_token(&session).await,
+            base_path: state.base_path.clone(),
+        }
+        .into_response();
+    }
+
+    let throttle =
+        login_throttle::check(&state.redis, LoginRealm::User, &credentials.username, client_ip)
+            .await;
+    if let Some(message) = throttle.message() {
+        tracing::warn!("User login throttled for {} from {}", credentials.username, client_ip);
+        return UserLoginTemplate {
+            error: None,
+            lockout: Some(message),
+            username: Some(credentials.username),
+            csrf_token: ensure_csrf_token(&session).await,
+            base_path: state.base_path.clone(),
+        }
+        .into_response();
+    }
+
+    let user = match repository::find_user_by_username(&state.db, &credentials.username).await {
+        Ok(user) => user,
+        Err(e) => {
+            tracing::error!("Database error during user login: {:?}", e);
+            return UserLoginTemplate {
+                error: Some("An error occurred. Please try again.".to_string()),
+                lockout: None,
+                username: Some(credentials.username),
+                csrf_token: ensure_csrf_token(&session).await,
+                base_path: state.base_path.clone(),
+            }
+            .into_response();
+        }
+    };
+
+    let verified = match &user {
+        Some(user) => password_hash::verify(&credentials.password, &user.password_hash).await,
+        None => {
+            state.password_hash.verify_unknown_account(&credentials.password).await;
+            false
+        }
+    };
+    let Some(user) = user.filter(|_| verified) else {
+        tracing::warn!("User login failed for {}", credentials.username);
+        let throttle = login_throttle::record_failure(
+            &state.redis,
+            &state.login_throttle,
+            LoginRealm::User,
+            &credentials.username,
+            client_ip,
+        )
+        .await;
+        return UserLoginTemplate {
+            error: Some("Invalid username or password".to_string()),
+            lockout: throttle.message(),
+            username: Some(credentials.username),
+            csrf_token: ensure_csrf_token(&session).await,
+            base_path: state.base_path.clone(),
+        }
+        .into_response();
+    };
+
+    login_throttle::record_success(&state.redis, LoginRealm::User, &user.username).await;
+    if let Some(refusal) = user.status().sign_in_refusal() {
+        tracing::warn!("User login refused for {}: account is {}", user.username, user.status);
+        return UserLoginTemplate {
+            error: Some(refusal.to_string()),
+            lockout: None,
+            username: Some(credentials.username),
+            csrf_token: ensure_csrf_token(&session).await,
+            base_path: state.base_path.clone(),
+        }
+        .into_response();
+    }
+    state
+        .password_hash
+        .upgrade_if_needed(
+            &state.db,
+            PasswordOwner::User(user.id),
+            &user.password_hash,
+            &credentials.password,
+        )
+        .await;
+    if let Err(e) = start_user_session(&session, &user).await {
+        tracing::error!("Failed to create user session: {:?}", e);
+        return internal_error(&state, &session).await;
+    }
+
+    tracing::info!("User login successful: {}", user.username);
+    Redirect::to("/").into_response()
+}
+
+async fn start_user_session(
+    session: &Session,
+    user: &models::User,
+) -> Result<(), tower_sessions::session::Error> {
+    session.cycle_id().await?;
+    csrf::rotate_token(session).await;
+    AuthUser::new(user.id, user.username.clone()).login(session).await?;
+    session
+        .insert("flash_success", format!("Welcome, {}!", user.username))
+        .await?;
+    Ok(())
+}
+
+async fn internal_error(state: &AppState, session: &Session) -> axum::response::Response {
+    let template = ErrorTemplate {
+        error_code: 500,
+        error_message: "Something went wrong. Please try again.".to_string(),
+        impersonation: impersonation_banner(session).await,
+        base_path: state.base_path.clone(),
+    };
+    (StatusCode::INTERNAL_SERVER_ERROR, template).into_response()
+}
+
+// Registration page (GET)
+pub async fn user_register_page(
+    OptionalAuthUser(user): OptionalAuthUser,
+    State(state): State<AppState>,
+    Extension(session): Extension<Session>,
+) -> impl IntoResponse {
+    if user.is_some() {
+        return Redirect::to("/profile").into_response();
+    }
+
+    UserRegisterTemplate {
+        error: None,
+        username: None,
+        email: None,
+        password_errors: Vec::new(),
+        csrf_token: ensure_csrf_token(&session).await,
+        base_path: state.base_path.clone(),
+    }
+    .into_response()
+}
+
+// Registration submission (POST)
+pub async fn user_register_submit(
+    State(state): State<AppState>,
+    Extension(session): Extension<Session>,
+    Form(form): Form<RegisterForm>,
+) -> impl IntoResponse {
+    let error = if let Err(errors) = form.validate() {
+        let fields = errors.field_errors();
+        let message = if fields.contains_key("email") {
+            "Please enter a valid email address"
+        } else {
+            "Please choose a username"
+        };
+        Some(message.to_string())
+    } else {
+        None
+    };
+    let password_errors = match state
+        .password_policy
+        .validate(&state.db, "password", &form.password, None)
+        .await
+    {
+        Ok(()) => Vec::new(),
+        Err(errors) => password_policy::field_messages(&errors, "password"),
+    };
+    if error.is_some() || !password_errors.is_empty() {
+        return UserRegisterTemplate {
+            error,
+            username: Some(form.username),
+            email: Some(form.email),
+            password_errors,
+            csrf_token: ensure_csrf_token(&session).await,
+            base_path: state.base_path.clone(),
+        }
+        .into_response();
+    }
+
+    let username = form.username.trim();
+    let email = form.email.trim();
+    let password_hash = match state.password_hash.hash(&form.password).await {
+        Ok(hash) => hash,
+        Err(e) => {
+            tracing::error!("Password hashing failed: {:?}", e);
+            return internal_error(&state, &session).await;
+        }
+    };
+    if let Err(e) =
+        repository::create_user(&state.db, username, email, &password_hash, None, None, None).await
+    {
+        let msg = if format!("{}", e).contains("Duplicate entry") {
+            "Username or email already exists".to_string()
+        } else {
+            tracing::error!("Failed to register user: {:?}", e);
+            "Registration failed. Please try again.".to_string()
+        };
+        return UserRegisterTemplate {
+            error: Some(msg),
+            username: Some(form.username.clone()),
+            email: Some(form.email.clone()),
+            password_errors: Vec::new(),
+            csrf_token: ensure_csrf_token(&session).await,
+            base_path: state.base_path.clone(),
+        }
+        .into_response();
+    }
+
+    let user = match repository::find_user_by_username(&state.db, username).await {
+        Ok(Some(user)) => user,
+        _ => return Redirect::to("/login").into_response(),
+    };
+    state
+        .password_policy
+        .remember(&state.db, PasswordOwner::User(user.id), &password_hash)
+        .await;
+    if let Err(e) = send_verification_email(&state, &user).await {
+        tracing::error!("Failed to send verification email to user {}: {}", user.id, e);
+    }
+    if let Err(e) = start_user_session(&session, &user).await {
+        tracing::error!("Failed to create user session: {:?}", e);
+        return Redirect::to("/login").into_response();
+    }
+
+    tracing::info!("User registered: {}", user.username);
+    Redirect::to("/profile").into_response()
+}
+
+struct ProfileMessages {
+    error: Option<String>,
+    success: Option<String>,
+    password_error: Option<String>,
+    password_errors: Vec<String>,
+    password_success: Option<String>,
+}
+
+impl ProfileMessages {
+    fn none() -> Self {
+        Self {
+            error: None,
+            success: None,
+            password_error: None,
+            password_errors: Vec::new(),
+            password_success: None,
+        }
+    }
+}
+
+// Renders the profile page; `selection` overrides the stored address fields
+// so a rejected submission keeps what the user entered.
+async fn render_profile(
+    state: &AppState,
+    session: &Session,
+    user: &models::User,
+    selection: Option<&ProfileForm>,
+    messages: ProfileMessages,
+) -> axum::response::Response {
+    let countries = match get_countries_cached(state).await {
+        Ok(countries) => countries,
+        Err(_) => return internal_error(state, session).await,
+    };
+
+    let (address, country_id, state_id) = match selection {
+        Some(form) => (Some(form.address.clone()), form.country_id, form.state_id),
+        None => (
+            user.address.clone(),
+            user.country_id.unwrap_or(0),
+            user.state_id.unwrap_or(0),
+        ),
+    };
+    let states = if country_id > 0 {
+        get_states_cached(state, country_id).await.unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+
+    UserProfileTemplate {
+        current_user: user.username.clone(),
+        email: user.email.clone(),
+        address,
+        countries,
+        states,
+        selected_country_id: country_id,
+        selected_state_id: state_id,
+        error: messages.error,
+        success: messages.success,
+        password_error: messages.password_error,
+        password_success: messages.password_success,
+        password_errors: messages.password_errors,
+        impersonation: impersonation_banner(session).await,
+        csrf_token: ensure_csrf_token(session).await,
+        base_path: state.base_path.clone(),
+    }
+    .into_response()
+}
+
+async fn load_user(
+    state: &AppState,
+    session: &Session,
+    auth_user: &AuthUser,
+) -> Result<models::User, axum::response::Response> {
+    match repository::find_user_by_id(&state.db, auth_user.id).await {
+        Ok(Some(user)) => Ok(user),
+        Ok(None) => {
+            // `AuthUser` refuses removed and inactive accounts; this covers a
+            // deletion in between
+            let _ = AuthUser::logout(session).await;
+            Err(Redirect::to("/login").into_response())
+        }
+        Err(e) => {
+            tracing::error!("Failed to load user {}: {:?}", auth_user.id, e);
+            Err(internal_error(state, session).await)
+        }
+    }
+}
+
+// Profile page (GET)
+pub async fn user_profile_page(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Extension(session): Extension<Session>,
+) -> impl IntoResponse {
+    let user = match load_user(&state, &session, &auth_user).await {
+        Ok(user) => user,
+        Err(response) => return response,
+    };
+    render_profile(&state, &session, &user, None, ProfileMessages::none()).await
+}
+
+// Profile address update (POST)
+pub async fn user_profile_submit(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Extension(session): Extension<Session>,
+    Form(form): Form<ProfileForm>,
+) -> impl IntoResponse {
+    let user = match load_user(&state, &session, &auth_user).await {
+        Ok(user) => user,
+        Err(response) => return response,
+    };
+
+    let error = if form.validate().is_err() {
+        Some("Please enter an address and choose a country and state".to_string())
+    } else {
+        let states = get_states_cached(&state, form.country_id).await.unwrap_or_default();
+        if states.iter().any(|s| s.id == form.state_id) {
+            None
+        } else {
+            Some("The selected state does not belong to that country".to_string())
+        }
+    };
+    if error.is_some() {
+        let messages = ProfileMessages { error, ..ProfileMessages::none() };
+        return render_profile(&state, &session, &user, Some(&form), messages).await;
+    }
+
+    if let Err(e) = repository::update_user_profile(
+        &state.db,
+        user.id,
+        form.address.trim(),
+        form.country_id,
+        form.state_id,
+    )
+    .await
+    {
+        tracing::error!("Failed to update profile for user {}: {:?}", user.id, e);
+        let messages = ProfileMessages {
+            error: Some("Failed to update profile".to_string()),
+            ..ProfileMessages::none()
+        };
+        return render_profile(&state, &session, &user, Some(&form), messages).await;
+    }
+
+    let user = match load_user(&state, &session, &auth_user).await {
+        Ok(user) => user,
+        Err(response) => return response,
+    };
+    let messages = ProfileMessages {
+        success: Some("Profile updated".to_string()),
+        ..ProfileMessages::none()
+    };
+    render_profile(&state, &session, &user, None, messages).await
+}
+
+// Password change (POST)
+pub async fn user_password_submit(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    Extension(session): Extension<Session>,
+    Form(form): Form<ChangePasswordForm>,
+) -> impl IntoResponse {
+    let user = match load_user(&state, &session, &auth_user).await {
+        Ok(user) => user,
+        Err(response) => return response,
+    };
+
+    let owner = PasswordOwner::User(user.id);
+    let mut password_errors = Vec::new();
+    // Guessing the current password counts against the same limits as the
+    // login form, so a stolen session cannot be used to brute-force it
+    let throttle =
+        login_throttle::check(&state.redis, LoginRealm::User, &user.username, client_ip).await;
+    let password_error = if let Some(message) = throttle.message() {
+        tracing::warn!("Password change throttled for {} from {}", user.username, client_ip);
+        Some(message)
+    } else if !password_hash::verify(&form.current_password, &user.password_hash).await {
+        tracing::warn!("Password change with a wrong current password for {}", user.username);
+        let throttle = login_throttle::record_failure(
+            &state.redis,
+            &state.login_throttle,
+            LoginRealm::User,
+            &user.username,
+            client_ip,
+        )
+        .await;
+        Some(match throttle.message() {
+            Some(message) => format!("Current password is incorrect. {}", message),
+            None => "Current password is incorrect".to_string(),
+        })
+    } else if form.validate().is_err() {
+        Some("New passwords do not match".to_string())
+    } else if let Err(errors) = state
+        .password_policy
+        .validate(&state.db, "new_password", &form.new_password, Some(owner))
+        .await
+    {
+        password_errors = password_policy::field_messages(&errors, "new_password");
+        None
+    } else {
+        None
+    };
+    if password_error.is_some() || !password_errors.is_empty() {
+        let messages = ProfileMessages { password_error, password_errors, ..ProfileMessages::none() };
+        return render_profile(&state, &session, &user, None, messages).await;
+    }
+    login_throttle::record_success(&state.redis, LoginRealm::User, &user.username).await;
+
+    let password_hash = match state.password_hash.hash(&form.new_password).await {
+        Ok(hash) => hash,
+        Err(e) => {
+            tracing::error!("Password hashing failed: {:?}", e);
+            return internal_error(&state, &session).await;
+        }
+    };
+    if let Err(e) = repository::update_password(&state.db, user.id, &password_hash).await {
+        tracing::error!("Failed to change password for user {}: {:?}", user.id, e);
+        let messages = ProfileMessages {
+            password_error: Some("Failed to change password".to_string()),
+            ..ProfileMessages::none()
+        };
+        return render_profile(&state, &session, &user, None, messages).await;
+    }
+    state.password_policy.remember(&state.db, owner, &password_hash).await;
+
+    tracing::info!("User {} changed their password", user.username);
+    let messages = ProfileMessages {
+        password_success: Some("Password changed".to_string()),
+        ..ProfileMessages::none()
+    };
+    render_profile(&state, &session, &user, None, messages).await
+}
+
+// States for the profile's country dropdown (JSON)
+pub async fn user_states_api(
+    _auth_user: AuthUser,
+    State(state): State<AppState>,
+    Query(query): Query<StatesQuery>,
+) -> impl IntoResponse {
+    match get_states_cached(&state, query.country_id).await {
+        Ok(states) => Json(states).into_response(),
+        Err(code) => code.into_response(),
+    }
+}
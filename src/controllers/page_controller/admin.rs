@@ -4,15 +4,27 @@ use axum::{
     http::{HeaderMap, HeaderValue, StatusCode, header},
     response::{IntoResponse, Json, Redirect},
 };
+use axum_extra::extract::cookie::CookieJar;
 use printpdf::{BuiltinFont, Mm, PdfDocument};
 use std::io::BufWriter;
 use time::format_description::well_known::Rfc3339;
 use tower_sessions::Session;
 use validator::Validate;
 
-use crate::controllers::auth_controller::{AdminUser, OptionalAdminUser};
-use crate::models::{self, DatatableParams, DatatableResponse};
+use crate::controllers::auth_controller::{
+    AdminUser, ApiAccess, OptionalAdminUser, RequirePermission,
+};
+use crate::controllers::permissions::{
+    GeoDelete, GeoEdit, GeoView, Permission, UsersCreate, UsersDelete, UsersEdit, UsersExport,
+    UsersView,
+};
+use crate::controllers::request_info::ClientIp;
+use crate::models::{self, DatatableParams, DatatableResponse, PasswordOwner};
 use crate::repository;
+use crate::services::{admin_sessions, remember_me};
+use crate::services::audit::{self, AuditEvent};
+use crate::services::export_links::LinkError;
+use crate::services::password_policy;
 use crate::state::AppState;
 use crate::views::templates::{
     AdminErrorTemplate,
@@ -22,17 +34,21 @@ use crate::views::templates::{
     AdminDashboardTemplate, User, CountryOption, StateOption,
 };
 
+use super::email_verification::send_verification_email;
+use super::impersonation::end_impersonation;
 use super::shared::{
-    ensure_csrf_token, validate_csrf, map_country_options, get_countries_cached,
+    ensure_csrf_token, map_country_options, get_countries_cached,
     get_states_cached, invalidate_geo_cache, CountryForm, StateForm, CreateUserForm,
-    CsrfOnlyForm, UpdateUserForm, StatesQuery, PdfExportParams,
+    UpdateUserForm, StatesQuery, PdfExportParams, SignedPdfExportQuery,
 };
 
+// Sign-in attempts listed on the dashboard
+const RECENT_LOGINS_SHOWN: i64 = 10;
 
 
 // Countries list (admin)
 pub async fn admin_countries_list(
-    admin_user: AdminUser,
+    RequirePermission(admin_user, _): RequirePermission<GeoView>,
     State(state): State<AppState>,
     Extension(session): Extension<Session>,
 ) -> impl IntoResponse {
@@ -42,7 +58,7 @@ pub async fn admin_countries_list(
             let template = AdminErrorTemplate {
                 error_code: 500,
                 error_message: "Failed to load countries.".to_string(),
-                current_admin: Some(admin_user.username),
+                current_admin: Some(admin_user),
                 base_path: state.base_path.clone(),
             };
             return (StatusCode::INTERNAL_SERVER_ERROR, template).into_response();
@@ -51,7 +67,7 @@ pub async fn admin_countries_list(
 
     AdminCountriesListTemplate {
         page_title: "Countries".to_string(),
-        current_admin: Some(admin_user.username),
+        current_admin: Some(admin_user),
         csrf_token: ensure_csrf_token(&session).await,
         countries,
         base_path: state.base_path.clone(),
@@ -61,7 +77,7 @@ pub async fn admin_countries_list(
 
 // Country create page (GET)
 pub async fn admin_country_create_page(
-    admin_user: AdminUser,
+    RequirePermission(admin_user, _): RequirePermission<GeoEdit>,
     State(state): State<AppState>,
     Extension(session): Extension<Session>,
 ) -> impl IntoResponse {
@@ -73,7 +89,7 @@ pub async fn admin_country_create_page(
         name: None,
         error: None,
         success: None,
-        current_admin: Some(admin_user.username),
+        current_admin: Some(admin_user),
         csrf_token: ensure_csrf_token(&session).await,
         base_path: state.base_path.clone(),
     }
@@ -82,28 +98,13 @@ pub async fn admin_country_create_page(
 
 // Country create submission (POST)
 pub async fn admin_country_create_submit(
-    admin_user: AdminUser,
+    RequirePermission(admin_user, _): RequirePermission<GeoEdit>,
     State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
     Extension(session): Extension<Session>,
     Form(form): Form<CountryForm>,
 ) -> impl IntoResponse {
     let name = form.name.clone();
-    if !validate_csrf(&session, &form.csrf_token).await {
-        return AdminCountryFormTemplate {
-            form_title: "Create Country".to_string(),
-            form_action: format!("{}/countries", state.base_path),
-            submit_label: "Create Country".to_string(),
-            country_id: None,
-            name: Some(name.clone()),
-            error: Some("Invalid CSRF token".to_string()),
-            success: None,
-            current_admin: Some(admin_user.username),
-            csrf_token: ensure_csrf_token(&session).await,
-            base_path: state.base_path.clone(),
-        }
-        .into_response();
-    }
-
     if form.validate().is_err() {
         return AdminCountryFormTemplate {
             form_title: "Create Country".to_string(),
@@ -113,28 +114,33 @@ pub async fn admin_country_create_submit(
             name: Some(name.clone()),
             error: Some("Invalid country name".to_string()),
             success: None,
-            current_admin: Some(admin_user.username),
+            current_admin: Some(admin_user),
             csrf_token: ensure_csrf_token(&session).await,
             base_path: state.base_path.clone(),
         }
         .into_response();
     }
 
-    if let Err(_) = repository::create_country(&state.db, &form.name).await {
-        return AdminCountryFormTemplate {
-            form_title: "Create Country".to_string(),
-            form_action: format!("{}/countries", state.base_path),
-            submit_label: "Create Country".to_string(),
-            country_id: None,
-            name: Some(name.clone()),
-            error: Some("Failed to create country".to_string()),
-            success: None,
-            current_admin: Some(admin_user.username),
-            csrf_token: ensure_csrf_token(&session).await,
-            base_path: state.base_path.clone(),
+    let country_id = match repository::create_country(&state.db, &form.name).await {
+        Ok(country_id) => country_id,
+        Err(_) => {
+            return AdminCountryFormTemplate {
+                form_title: "Create Country".to_string(),
+                form_action: format!("{}/countries", state.base_path),
+                submit_label: "Create Country".to_string(),
+                country_id: None,
+                name: Some(name.clone()),
+                error: Some("Failed to create country".to_string()),
+                success: None,
+                current_admin: Some(admin_user),
+                csrf_token: ensure_csrf_token(&session).await,
+                base_path: state.base_path.clone(),
+            }
+            .into_response();
         }
-        .into_response();
-    }
+    };
+    let country = models::Country { id: country_id, name: form.name.clone() };
+    audit::record(&state.db, &admin_user, client_ip, AuditEvent::created(&country)).await;
 
     invalidate_geo_cache(&state).await;
     Redirect::to(&format!("{}/countries", state.base_path)).into_response()
@@ -142,7 +148,7 @@ pub async fn admin_country_create_submit(
 
 // Country edit page (GET)
 pub async fn admin_country_edit_page(
-    admin_user: AdminUser,
+    RequirePermission(admin_user, _): RequirePermission<GeoEdit>,
     State(state): State<AppState>,
     Path(id): Path<i32>,
     Extension(session): Extension<Session>,
@@ -153,7 +159,7 @@ pub async fn admin_country_edit_page(
             let template = AdminErrorTemplate {
                 error_code: 404,
                 error_message: "Country not found.".to_string(),
-                current_admin: Some(admin_user.username),
+                current_admin: Some(admin_user),
                 base_path: state.base_path.clone(),
             };
             return (StatusCode::NOT_FOUND, template).into_response();
@@ -162,7 +168,7 @@ pub async fn admin_country_edit_page(
             let template = AdminErrorTemplate {
                 error_code: 500,
                 error_message: "Failed to load country.".to_string(),
-                current_admin: Some(admin_user.username),
+                current_admin: Some(admin_user),
                 base_path: state.base_path.clone(),
             };
             return (StatusCode::INTERNAL_SERVER_ERROR, template).into_response();
@@ -177,7 +183,7 @@ pub async fn admin_country_edit_page(
         name: Some(country.name),
         error: None,
         success: None,
-        current_admin: Some(admin_user.username),
+        current_admin: Some(admin_user),
         csrf_token: ensure_csrf_token(&session).await,
         base_path: state.base_path.clone(),
     }
@@ -186,29 +192,14 @@ pub async fn admin_country_edit_page(
 
 // Country edit submission (POST)
 pub async fn admin_country_edit_submit(
-    admin_user: AdminUser,
+    RequirePermission(admin_user, _): RequirePermission<GeoEdit>,
     State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
     Path(id): Path<i32>,
     Extension(session): Extension<Session>,
     Form(form): Form<CountryForm>,
 ) -> impl IntoResponse {
     let name = form.name.clone();
-    if !validate_csrf(&session, &form.csrf_token).await {
-        return AdminCountryFormTemplate {
-            form_title: "Edit Country".to_string(),
-            form_action: format!("{}/countries/{}", state.base_path, id),
-            submit_label: "Save Changes".to_string(),
-            country_id: Some(id),
-            name: Some(name.clone()),
-            error: Some("Invalid CSRF token".to_string()),
-            success: None,
-            current_admin: Some(admin_user.username),
-            csrf_token: ensure_csrf_token(&session).await,
-            base_path: state.base_path.clone(),
-        }
-        .into_response();
-    }
-
     if form.validate().is_err() {
         return AdminCountryFormTemplate {
             form_title: "Edit Country".to_string(),
@@ -218,13 +209,14 @@ pub async fn admin_country_edit_submit(
             name: Some(name.clone()),
             error: Some("Invalid country name".to_string()),
             success: None,
-            current_admin: Some(admin_user.username),
+            current_admin: Some(admin_user),
             csrf_token: ensure_csrf_token(&session).await,
             base_path: state.base_path.clone(),
         }
         .into_response();
     }
 
+    let before = repository::get_country_by_id(&state.db, id).await.ok().flatten();
     if let Err(_) = repository::update_country(&state.db, id, &form.name).await {
         return AdminCountryFormTemplate {
             form_title: "Edit Country".to_string(),
@@ -234,41 +226,34 @@ pub async fn admin_country_edit_submit(
             name: Some(name.clone()),
             error: Some("Failed to update country".to_string()),
             success: None,
-            current_admin: Some(admin_user.username),
+            current_admin: Some(admin_user),
             csrf_token: ensure_csrf_token(&session).await,
             base_path: state.base_path.clone(),
         }
         .into_response();
     }
 
+    let country = models::Country { id, name: form.name.clone() };
+    let event = AuditEvent::updated(before.as_ref(), &country);
+    audit::record(&state.db, &admin_user, client_ip, event).await;
+
     invalidate_geo_cache(&state).await;
     Redirect::to(&format!("{}/countries", state.base_path)).into_response()
 }
 
 // Country delete (POST)
 pub async fn admin_country_delete(
-    admin_user: AdminUser,
+    RequirePermission(admin_user, _): RequirePermission<GeoDelete>,
     State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
     Path(id): Path<i32>,
-    Extension(session): Extension<Session>,
-    Form(form): Form<CsrfOnlyForm>,
 ) -> impl IntoResponse {
-    if !validate_csrf(&session, &form.csrf_token).await {
-        let template = AdminErrorTemplate {
-            error_code: 403,
-            error_message: "Invalid CSRF token".to_string(),
-            current_admin: Some(admin_user.username),
-            base_path: state.base_path.clone(),
-        };
-        return (StatusCode::FORBIDDEN, template).into_response();
-    }
-
     if let Ok(count) = repository::count_states_by_country_id(&state.db, id).await {
         if count > 0 {
             let template = AdminErrorTemplate {
                 error_code: 400,
                 error_message: "Cannot delete country with existing states.".to_string(),
-                current_admin: Some(admin_user.username),
+                current_admin: Some(admin_user),
                 base_path: state.base_path.clone(),
             };
             return (StatusCode::BAD_REQUEST, template).into_response();
@@ -280,23 +265,30 @@ pub async fn admin_country_delete(
             let template = AdminErrorTemplate {
                 error_code: 400,
                 error_message: "Cannot delete country assigned to users.".to_string(),
-                current_admin: Some(admin_user.username),
+                current_admin: Some(admin_user),
                 base_path: state.base_path.clone(),
             };
             return (StatusCode::BAD_REQUEST, template).into_response();
         }
     }
 
+    let before = repository::get_country_by_id(&state.db, id).await.ok().flatten();
     if let Err(_) = repository::delete_country(&state.db, id).await {
         let template = AdminErrorTemplate {
             error_code: 500,
             error_message: "Failed to delete country.".to_string(),
-            current_admin: Some(admin_user.username),
+            current_admin: Some(admin_user),
             base_path: state.base_path.clone(),
         };
         return (StatusCode::INTERNAL_SERVER_ERROR, template).into_response();
     }
 
+    let event = match &before {
+        Some(country) => AuditEvent::deleted(country),
+        None => AuditEvent::new("delete", "country", id),
+    };
+    audit::record(&state.db, &admin_user, client_ip, event).await;
+
     invalidate_geo_cache(&state).await;
     Redirect::to(&format!("{}/countries", state.base_path)).into_response()
 }
@@ -315,11 +307,26 @@ pub async fn admin_index(
 
 // Admin logout
 pub async fn admin_logout(
+    admin_user: Option<AdminUser>,
     Extension(session): Extension<Session>,
     State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    jar: CookieJar,
 ) -> impl IntoResponse {
+    end_impersonation(&state, &session, client_ip).await;
+    if let Some(AdminUser { id, session_ref: Some(session_ref), .. }) = &admin_user {
+        admin_sessions::remove(&state.redis, *id, session_ref).await;
+        let _ = repository::delete_remember_tokens_by_session(&state.db, *id, session_ref).await;
+    }
+    if let Some(cookie) = jar.get(remember_me::COOKIE_NAME) {
+        state.remember_me.forget(&state.db, cookie.value()).await;
+    }
     let _ = AdminUser::logout(&session).await;
-    Redirect::to(&format!("{}/login", state.base_path)).into_response()
+    (
+        jar.add(state.remember_me.removal_cookie()),
+        Redirect::to(&format!("{}/login", state.base_path)),
+    )
+        .into_response()
 }
 
 // Admin dashboard (GET) - requires authentication
@@ -327,15 +334,23 @@ pub async fn admin_dashboard(
     admin_user: AdminUser,
     State(state): State<AppState>,
 ) -> impl IntoResponse {
+    let recent_logins = repository::find_recent_admin_login_events(&state.db, admin_user.id, RECENT_LOGINS_SHOWN)
+        .await
+        .unwrap_or_else(|e| {
+            tracing::error!("Failed to load login history of admin {}: {:?}", admin_user.id, e);
+            Vec::new()
+        });
+
     AdminDashboardTemplate {
-        current_admin: Some(admin_user.username),
+        current_admin: Some(admin_user),
+        recent_logins,
         base_path: state.base_path.clone(),
     }
 }
 
 // States list (admin)
 pub async fn admin_states_list(
-    admin_user: AdminUser,
+    RequirePermission(admin_user, _): RequirePermission<GeoView>,
     State(state): State<AppState>,
     Extension(session): Extension<Session>,
 ) -> impl IntoResponse {
@@ -345,7 +360,7 @@ pub async fn admin_states_list(
             let template = AdminErrorTemplate {
                 error_code: 500,
                 error_message: "Failed to load states.".to_string(),
-                current_admin: Some(admin_user.username),
+                current_admin: Some(admin_user),
                 base_path: state.base_path.clone(),
             };
             return (StatusCode::INTERNAL_SERVER_ERROR, template).into_response();
@@ -364,7 +379,7 @@ pub async fn admin_states_list(
 
     AdminStatesListTemplate {
         page_title: "States".to_string(),
-        current_admin: Some(admin_user.username),
+        current_admin: Some(admin_user),
         csrf_token: ensure_csrf_token(&session).await,
         states,
         base_path: state.base_path.clone(),
@@ -374,7 +389,7 @@ pub async fn admin_states_list(
 
 // State create page (GET)
 pub async fn admin_state_create_page(
-    admin_user: AdminUser,
+    RequirePermission(admin_user, _): RequirePermission<GeoEdit>,
     State(state): State<AppState>,
     Extension(session): Extension<Session>,
 ) -> impl IntoResponse {
@@ -384,7 +399,7 @@ pub async fn admin_state_create_page(
             let template = AdminErrorTemplate {
                 error_code: code.as_u16(),
                 error_message: "Failed to load countries.".to_string(),
-                current_admin: Some(admin_user.username),
+                current_admin: Some(admin_user),
                 base_path: state.base_path.clone(),
             };
             return (code, template).into_response();
@@ -401,7 +416,7 @@ pub async fn admin_state_create_page(
         selected_country_id: 0,
         error: None,
         success: None,
-        current_admin: Some(admin_user.username),
+        current_admin: Some(admin_user),
         csrf_token: ensure_csrf_token(&session).await,
         base_path: state.base_path.clone(),
     }
@@ -410,8 +425,9 @@ pub async fn admin_state_create_page(
 
 // State create submission (POST)
 pub async fn admin_state_create_submit(
-    admin_user: AdminUser,
+    RequirePermission(admin_user, _): RequirePermission<GeoEdit>,
     State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
     Extension(session): Extension<Session>,
     Form(form): Form<StateForm>,
 ) -> impl IntoResponse {
@@ -421,31 +437,13 @@ pub async fn admin_state_create_submit(
             let template = AdminErrorTemplate {
                 error_code: code.as_u16(),
                 error_message: "Failed to load countries.".to_string(),
-                current_admin: Some(admin_user.username),
+                current_admin: Some(admin_user),
                 base_path: state.base_path.clone(),
             };
             return (code, template).into_response();
         }
     };
 
-    if !validate_csrf(&session, &form.csrf_token).await {
-        return AdminStateFormTemplate {
-            form_title: "Create State".to_string(),
-            form_action: format!("{}/states", state.base_path),
-            submit_label: "Create State".to_string(),
-            state_id: None,
-            name: Some(form.name.clone()),
-            countries,
-            selected_country_id: form.country_id,
-            error: Some("Invalid CSRF token".to_string()),
-            success: None,
-            current_admin: Some(admin_user.username),
-            csrf_token: ensure_csrf_token(&session).await,
-            base_path: state.base_path.clone(),
-        }
-        .into_response();
-    }
-
     if form.validate().is_err() {
         return AdminStateFormTemplate {
             form_title: "Create State".to_string(),
@@ -457,30 +455,39 @@ pub async fn admin_state_create_submit(
             selected_country_id: form.country_id,
             error: Some("Invalid state data".to_string()),
             success: None,
-            current_admin: Some(admin_user.username),
+            current_admin: Some(admin_user),
             csrf_token: ensure_csrf_token(&session).await,
             base_path: state.base_path.clone(),
         }
         .into_response();
     }
 
-    if let Err(_) = repository::create_state(&state.db, form.country_id, &form.name).await {
-        return AdminStateFormTemplate {
-            form_title: "Create State".to_string(),
-            form_action: format!("{}/states", state.base_path),
-            submit_label: "Create State".to_string(),
-            state_id: None,
-            name: Some(form.name.clone()),
-            countries,
-            selected_country_id: form.country_id,
-            error: Some("Failed to create state".to_string()),
-            success: None,
-            current_admin: Some(admin_user.username),
-            csrf_token: ensure_csrf_token(&session).await,
-            base_path: state.base_path.clone(),
+    let state_id = match repository::create_state(&state.db, form.country_id, &form.name).await {
+        Ok(state_id) => state_id,
+        Err(_) => {
+            return AdminStateFormTemplate {
+                form_title: "Create State".to_string(),
+                form_action: format!("{}/states", state.base_path),
+                submit_label: "Create State".to_string(),
+                state_id: None,
+                name: Some(form.name.clone()),
+                countries,
+                selected_country_id: form.country_id,
+                error: Some("Failed to create state".to_string()),
+                success: None,
+                current_admin: Some(admin_user),
+                csrf_token: ensure_csrf_token(&session).await,
+                base_path: state.base_path.clone(),
+            }
+            .into_response();
         }
-        .into_response();
-    }
+    };
+    let created = models::State {
+        id: state_id,
+        country_id: form.country_id,
+        name: form.name.clone(),
+    };
+    audit::record(&state.db, &admin_user, client_ip, AuditEvent::created(&created)).await;
 
     invalidate_geo_cache(&state).await;
     Redirect::to(&format!("{}/states", state.base_path)).into_response()
@@ -488,7 +495,7 @@ pub async fn admin_state_create_submit(
 
 // State edit page (GET)
 pub async fn admin_state_edit_page(
-    admin_user: AdminUser,
+    RequirePermission(admin_user, _): RequirePermission<GeoEdit>,
     State(state): State<AppState>,
     Path(id): Path<i32>,
     Extension(session): Extension<Session>,
@@ -499,7 +506,7 @@ pub async fn admin_state_edit_page(
             let template = AdminErrorTemplate {
                 error_code: 404,
                 error_message: "State not found.".to_string(),
-                current_admin: Some(admin_user.username),
+                current_admin: Some(admin_user),
                 base_path: state.base_path.clone(),
             };
             return (StatusCode::NOT_FOUND, template).into_response();
@@ -508,7 +515,7 @@ pub async fn admin_state_edit_page(
             let template = AdminErrorTemplate {
                 error_code: 500,
                 error_message: "Failed to load state.".to_string(),
-                current_admin: Some(admin_user.username),
+                current_admin: Some(admin_user),
                 base_path: state.base_path.clone(),
             };
             return (StatusCode::INTERNAL_SERVER_ERROR, template).into_response();
@@ -521,7 +528,7 @@ pub async fn admin_state_edit_page(
             let template = AdminErrorTemplate {
                 error_code: code.as_u16(),
                 error_message: "Failed to load countries.".to_string(),
-                current_admin: Some(admin_user.username),
+                current_admin: Some(admin_user),
                 base_path: state.base_path.clone(),
             };
             return (code, template).into_response();
@@ -538,7 +545,7 @@ pub async fn admin_state_edit_page(
         selected_country_id: state_row.country_id,
         error: None,
         success: None,
-        current_admin: Some(admin_user.username),
+        current_admin: Some(admin_user),
         csrf_token: ensure_csrf_token(&session).await,
         base_path: state.base_path.clone(),
     }
@@ -547,8 +554,9 @@ pub async fn admin_state_edit_page(
 
 // State edit submission (POST)
 pub async fn admin_state_edit_submit(
-    admin_user: AdminUser,
+    RequirePermission(admin_user, _): RequirePermission<GeoEdit>,
     State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
     Path(id): Path<i32>,
     Extension(session): Extension<Session>,
     Form(form): Form<StateForm>,
@@ -559,31 +567,13 @@ pub async fn admin_state_edit_submit(
             let template = AdminErrorTemplate {
                 error_code: code.as_u16(),
                 error_message: "Failed to load countries.".to_string(),
-                current_admin: Some(admin_user.username),
+                current_admin: Some(admin_user),
                 base_path: state.base_path.clone(),
             };
             return (code, template).into_response();
         }
     };
 
-    if !validate_csrf(&session, &form.csrf_token).await {
-        return AdminStateFormTemplate {
-            form_title: "Edit State".to_string(),
-            form_action: format!("{}/states/{}", state.base_path, id),
-            submit_label: "Save Changes".to_string(),
-            state_id: Some(id),
-            name: Some(form.name.clone()),
-            countries,
-            selected_country_id: form.country_id,
-            error: Some("Invalid CSRF token".to_string()),
-            success: None,
-            current_admin: Some(admin_user.username),
-            csrf_token: ensure_csrf_token(&session).await,
-            base_path: state.base_path.clone(),
-        }
-        .into_response();
-    }
-
     if form.validate().is_err() {
         return AdminStateFormTemplate {
             form_title: "Edit State".to_string(),
@@ -595,13 +585,14 @@ pub async fn admin_state_edit_submit(
             selected_country_id: form.country_id,
             error: Some("Invalid state data".to_string()),
             success: None,
-            current_admin: Some(admin_user.username),
+            current_admin: Some(admin_user),
             csrf_token: ensure_csrf_token(&session).await,
             base_path: state.base_path.clone(),
         }
         .into_response();
     }
 
+    let before = repository::get_state_by_id(&state.db, id).await.ok().flatten();
     if let Err(_) = repository::update_state(&state.db, id, form.country_id, &form.name).await {
         return AdminStateFormTemplate {
             form_title: "Edit State".to_string(),
@@ -613,57 +604,57 @@ pub async fn admin_state_edit_submit(
             selected_country_id: form.country_id,
             error: Some("Failed to update state".to_string()),
             success: None,
-            current_admin: Some(admin_user.username),
+            current_admin: Some(admin_user),
             csrf_token: ensure_csrf_token(&session).await,
             base_path: state.base_path.clone(),
         }
         .into_response();
     }
 
+    let updated = models::State { id, country_id: form.country_id, name: form.name.clone() };
+    let event = AuditEvent::updated(before.as_ref(), &updated);
+    audit::record(&state.db, &admin_user, client_ip, event).await;
+
     invalidate_geo_cache(&state).await;
     Redirect::to(&format!("{}/states", state.base_path)).into_response()
 }
 
 // State delete (POST)
 pub async fn admin_state_delete(
-    admin_user: AdminUser,
+    RequirePermission(admin_user, _): RequirePermission<GeoDelete>,
     State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
     Path(id): Path<i32>,
-    Extension(session): Extension<Session>,
-    Form(form): Form<CsrfOnlyForm>,
 ) -> impl IntoResponse {
-    if !validate_csrf(&session, &form.csrf_token).await {
-        let template = AdminErrorTemplate {
-            error_code: 403,
-            error_message: "Invalid CSRF token".to_string(),
-            current_admin: Some(admin_user.username),
-            base_path: state.base_path.clone(),
-        };
-        return (StatusCode::FORBIDDEN, template).into_response();
-    }
-
     if let Ok(count) = repository::count_users_by_state_id(&state.db, id).await {
         if count > 0 {
             let template = AdminErrorTemplate {
                 error_code: 400,
                 error_message: "Cannot delete state assigned to users.".to_string(),
-                current_admin: Some(admin_user.username),
+                current_admin: Some(admin_user),
                 base_path: state.base_path.clone(),
             };
             return (StatusCode::BAD_REQUEST, template).into_response();
         }
     }
 
+    let before = repository::get_state_by_id(&state.db, id).await.ok().flatten();
     if let Err(_) = repository::delete_state(&state.db, id).await {
         let template = AdminErrorTemplate {
             error_code: 500,
             error_message: "Failed to delete state.".to_string(),
-            current_admin: Some(admin_user.username),
+            current_admin: Some(admin_user),
             base_path: state.base_path.clone(),
         };
         return (StatusCode::INTERNAL_SERVER_ERROR, template).into_response();
     }
 
+    let event = match &before {
+        Some(deleted) => AuditEvent::deleted(deleted),
+        None => AuditEvent::new("delete", "state", id),
+    };
+    audit::record(&state.db, &admin_user, client_ip, event).await;
+
     invalidate_geo_cache(&state).await;
     Redirect::to(&format!("{}/states", state.base_path)).into_response()
 }
@@ -682,13 +673,16 @@ pub async fn admin_states_api(
 
 // Users list handler - requires authentication
 pub async fn users_list(
-    admin_user: AdminUser,
+    RequirePermission(admin_user, _): RequirePermission<UsersView>,
     State(state): State<AppState>,
+    Extension(session): Extension<Session>,
 ) -> impl IntoResponse {
     tracing::info!("Admin {} accessed users list", admin_user.username);
     let template = AdminUsersListTemplate {
         page_title: "All Users".to_string(),
-        current_admin: Some(admin_user.username),
+        current_admin: Some(admin_user),
+        csrf_token: ensure_csrf_token(&session).await,
+        export_link_minutes: state.export_links.ttl_minutes,
         base_path: state.base_path.clone(),
     };
 
@@ -696,7 +690,7 @@ pub async fn users_list(
 }
 
 pub async fn users_datatable_api(
-    admin_user: AdminUser,
+    ApiAccess(admin_user, _): ApiAccess<UsersView>,
     State(state): State<AppState>,
     Query(params): Query<DatatableParams>,
 ) -> impl IntoResponse {
@@ -706,6 +700,8 @@ pub async fn users_datatable_api(
     let offset = params.start.unwrap_or(0);
     let limit = params.length.unwrap_or(10);
     let search = params.search_value.filter(|s: &String| !s.is_empty());
+    let verified = models::parse_verified_filter(params.verified.as_deref());
+    let status = params.status.as_deref().and_then(models::UserStatus::parse);
 
     // Get total count
     let total_count = match repository::get_users_count(&state.db).await {
@@ -739,18 +735,30 @@ pub async fn users_datatable_api(
         offset,
         limit,
         search,
+        verified,
+        status,
         order_column: order_column.to_string(),
         order_direction: order_direction.to_string(),
     };
 
+    let filtered_count =
+        match repository::get_filtered_users_count(&state.db, &pagination_params.search, verified, status)
+            .await
+        {
+            Ok(count) => count,
+            Err(e) => {
+                tracing::error!("Failed to count filtered users: {:?}", e);
+                total_count
+            }
+        };
+
     // Get paginated users
     match repository::get_users_paginated(&state.db, &pagination_params).await {
         Ok(users) => {
-            let filtered_count = users.len() as i64;
             tracing::info!(
                 "Admin {} retrieved {} users",
                 admin_user.username,
-                filtered_count
+                users.len()
             );
             Json(DatatableResponse {
                 draw,
@@ -773,14 +781,98 @@ pub async fn users_datatable_api(
 
 // Users PDF export
 pub async fn admin_users_pdf(
-    _admin_user: AdminUser,
+    RequirePermission(_admin_user, _): RequirePermission<UsersExport>,
     State(state): State<AppState>,
     Query(params): Query<PdfExportParams>,
 ) -> impl IntoResponse {
+    users_pdf(&state, params).await
+}
+
+// Shareable link to an export (POST, JSON). The parameters are frozen into
+// an expiring signature, so the PDF opens without a session.
+pub async fn admin_users_pdf_link(
+    RequirePermission(admin_user, _): RequirePermission<UsersExport>,
+    State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    Form(params): Form<PdfExportParams>,
+) -> impl IntoResponse {
+    let query = state.export_links.sign(admin_user.id, params);
+    let Ok(query_string) = serde_urlencoded::to_string(&query) else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    let url = format!("{}{}/users/print/shared?{}", state.public_url, state.base_path, query_string);
+    let expires_at = time::OffsetDateTime::from_unix_timestamp(query.expires)
+        .map(|expires| crate::utils::format_datetime(&expires))
+        .unwrap_or_default();
+
+    tracing::info!("Admin {} created a shared users export link", admin_user.username);
+    let event = AuditEvent::new("share_export", "user", "pdf").with_after(serde_json::json!({
+        "search": query.search,
+        "verified": query.verified,
+        "status": query.status,
+        "order_column": query.order_column,
+        "order_direction": query.order_direction,
+        "expires_at": expires_at,
+    }));
+    audit::record(&state.db, &admin_user, client_ip, event).await;
+
+    Json(serde_json::json!({ "url": url, "expires_at": expires_at })).into_response()
+}
+
+// Shared users PDF export (GET), authorized by the link's signature only
+pub async fn admin_users_pdf_shared(
+    State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    Query(query): Query<SignedPdfExportQuery>,
+) -> impl IntoResponse {
+    let params = match state.export_links.verify(&query) {
+        Ok(params) => params,
+        Err(LinkError::Expired) => {
+            return shared_link_error(&state, StatusCode::GONE, "This export link has expired.");
+        }
+        Err(LinkError::BadSignature) => {
+            tracing::warn!("Shared export link with a bad signature from {}", client_ip);
+            return shared_link_error(&state, StatusCode::FORBIDDEN, "This export link is not valid.");
+        }
+    };
+
+    // Links stop working once their creator may no longer export users
+    let allowed = match repository::find_admin_by_id(&state.db, query.admin).await {
+        Ok(Some(admin)) if !admin.is_disabled() => repository::find_admin_permissions(&state.db, admin.id)
+            .await
+            .is_ok_and(|permissions| permissions.contains(UsersExport::NAME)),
+        Ok(_) => false,
+        Err(e) => {
+            tracing::error!("Failed to load admin {} for a shared export: {:?}", query.admin, e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    if !allowed {
+        return shared_link_error(&state, StatusCode::FORBIDDEN, "This export link is no longer valid.");
+    }
+
+    tracing::info!("Shared users export of admin {} opened from {}", query.admin, client_ip);
+    users_pdf(&state, params).await
+}
+
+fn shared_link_error(state: &AppState, code: StatusCode, message: &str) -> axum::response::Response {
+    let template = AdminErrorTemplate {
+        error_code: code.as_u16(),
+        error_message: message.to_string(),
+        current_admin: None,
+        base_path: state.base_path.clone(),
+    };
+    (code, template).into_response()
+}
+
+async fn users_pdf(state: &AppState, params: PdfExportParams) -> axum::response::Response {
     let order_column = params.order_column.as_deref().unwrap_or("id");
     let order_direction = params.order_direction.as_deref().unwrap_or("desc");
 
-    let users = match repository::get_users_for_export(&state.db, &params.search, order_column, order_direction).await {
+    let verified = models::parse_verified_filter(params.verified.as_deref());
+    let status = params.status.as_deref().and_then(models::UserStatus::parse);
+
+    let users = match repository::get_users_for_export(&state.db, &params.search, verified, status, order_column, order_direction).await {
         Ok(users) => users,
         Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
     };
@@ -835,7 +927,7 @@ pub async fn admin_users_pdf(
 
 // Admin user create page (GET)
 pub async fn user_create_page(
-    admin_user: AdminUser,
+    RequirePermission(admin_user, _): RequirePermission<UsersCreate>,
     State(state): State<AppState>,
     Extension(session): Extension<Session>,
 ) -> impl IntoResponse {
@@ -845,7 +937,7 @@ pub async fn user_create_page(
             let template = AdminErrorTemplate {
                 error_code: code.as_u16(),
                 error_message: "Failed to load countries.".to_string(),
-                current_admin: Some(admin_user.username),
+                current_admin: Some(admin_user),
                 base_path: state.base_path.clone(),
             };
             return (code, template).into_response();
@@ -857,7 +949,8 @@ pub async fn user_create_page(
         success: None,
         username: None,
         email: None,
-        current_admin: Some(admin_user.username),
+        current_admin: Some(admin_user),
+        password_errors: Vec::new(),
         csrf_token: ensure_csrf_token(&session).await,
         countries,
         states: Vec::new(),
@@ -871,8 +964,9 @@ pub async fn user_create_page(
 
 // Admin user create submission (POST)
 pub async fn user_create_submit(
-    admin_user: AdminUser,
+    RequirePermission(admin_user, _): RequirePermission<UsersCreate>,
     State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
     Extension(session): Extension<Session>,
     Form(form): Form<CreateUserForm>,
 ) -> impl IntoResponse {
@@ -882,7 +976,7 @@ pub async fn user_create_submit(
             let template = AdminErrorTemplate {
                 error_code: code.as_u16(),
                 error_message: "Failed to load countries.".to_string(),
-                current_admin: Some(admin_user.username),
+                current_admin: Some(admin_user),
                 base_path: state.base_path.clone(),
             };
             return (code, template).into_response();
@@ -893,13 +987,23 @@ pub async fn user_create_submit(
         Err(_) => Vec::new(),
     };
 
-    if !validate_csrf(&session, &form.csrf_token).await {
+    let error = form.validate().err().map(|_| "Invalid user data".to_string());
+    let password_errors = match state
+        .password_policy
+        .validate(&state.db, "password", &form.password, None)
+        .await
+    {
+        Ok(()) => Vec::new(),
+        Err(errors) => password_policy::field_messages(&errors, "password"),
+    };
+    if error.is_some() || !password_errors.is_empty() {
         return AdminCreateUserTemplate {
-            error: Some("Invalid CSRF token".to_string()),
+            error,
             success: None,
             username: Some(form.username.clone()),
             email: Some(form.email.clone()),
-            current_admin: Some(admin_user.username),
+            current_admin: Some(admin_user),
+            password_errors,
             csrf_token: ensure_csrf_token(&session).await,
             countries,
             states,
@@ -911,36 +1015,38 @@ pub async fn user_create_submit(
         .into_response();
     }
 
-    if form.validate().is_err() {
-        return AdminCreateUserTemplate {
-            error: Some("Invalid user data".to_string()),
-            success: None,
-            username: Some(form.username.clone()),
-            email: Some(form.email.clone()),
-            current_admin: Some(admin_user.username),
-            csrf_token: ensure_csrf_token(&session).await,
-            countries,
-            states,
-            selected_country_id: form.country_id,
-            selected_state_id: form.state_id,
-            address: Some(form.address.clone()),
-            base_path: state.base_path.clone(),
-        }
-        .into_response();
-    }
+    let result = match state.password_hash.hash(&form.password).await {
+        Ok(password_hash) => repository::create_user(
+            &state.db,
+            &form.username,
+            &form.email,
+            &password_hash,
+            Some(&form.address),
+            Some(form.country_id),
+            Some(form.state_id),
+        )
+        .await
+        .map(|user_id| (user_id, password_hash)),
+        Err(e) => Err(sqlx::Error::Protocol(format!("Password hashing failed: {}", e))),
+    };
 
-    match repository::create_user(
-        &state.db,
-        &form.username,
-        &form.email,
-        &form.password,
-        Some(&form.address),
-        Some(form.country_id),
-        Some(form.state_id),
-    )
-    .await
-    {
-        Ok(_) => Redirect::to(&format!("{}/users", state.base_path)).into_response(),
+    match result {
+        Ok((user_id, password_hash)) => {
+            let owner = PasswordOwner::User(user_id);
+            state.password_policy.remember(&state.db, owner, &password_hash).await;
+            let created = repository::find_user_by_id(&state.db, user_id).await.ok().flatten();
+            let event = match &created {
+                Some(user) => AuditEvent::created(user),
+                None => AuditEvent::new("create", "user", user_id),
+            };
+            audit::record(&state.db, &admin_user, client_ip, event).await;
+            if let Some(user) = &created
+                && let Err(e) = send_verification_email(&state, user).await
+            {
+                tracing::error!("Failed to send verification email to user {}: {}", user.id, e);
+            }
+            Redirect::to(&format!("{}/users", state.base_path)).into_response()
+        }
         Err(e) => {
             let msg = if format!("{}", e).contains("Duplicate entry") {
                 "Username or email already exists".to_string()
@@ -952,7 +1058,8 @@ pub async fn user_create_submit(
                 success: None,
                 username: Some(form.username.clone()),
                 email: Some(form.email.clone()),
-                current_admin: Some(admin_user.username),
+                current_admin: Some(admin_user),
+                password_errors: Vec::new(),
                 csrf_token: ensure_csrf_token(&session).await,
                 countries,
                 states,
@@ -968,18 +1075,30 @@ pub async fn user_create_submit(
 
 // Admin user detail (GET)
 pub async fn user_detail(
-    admin_user: AdminUser,
+    RequirePermission(admin_user, _): RequirePermission<UsersView>,
     State(state): State<AppState>,
     Path(id): Path<i32>,
     Extension(session): Extension<Session>,
 ) -> impl IntoResponse {
+    render_user_detail(&state, &session, admin_user, id, None, None).await
+}
+
+// Renders the user detail page, optionally with the outcome of an action
+pub(crate) async fn render_user_detail(
+    state: &AppState,
+    session: &Session,
+    admin_user: AdminUser,
+    id: i32,
+    error: Option<String>,
+    success: Option<String>,
+) -> axum::response::Response {
     let user = match repository::find_user_by_id(&state.db, id).await {
         Ok(Some(user)) => user,
         Ok(None) => {
             let template = AdminErrorTemplate {
                 error_code: 404,
                 error_message: "User not found.".to_string(),
-                current_admin: Some(admin_user.username),
+                current_admin: Some(admin_user),
                 base_path: state.base_path.clone(),
             };
             return (StatusCode::NOT_FOUND, template).into_response();
@@ -988,7 +1107,7 @@ pub async fn user_detail(
             let template = AdminErrorTemplate {
                 error_code: 500,
                 error_message: "Failed to load user.".to_string(),
-                current_admin: Some(admin_user.username),
+                current_admin: Some(admin_user),
                 base_path: state.base_path.clone(),
             };
             return (StatusCode::INTERNAL_SERVER_ERROR, template).into_response();
@@ -1015,19 +1134,34 @@ pub async fn user_detail(
         None
     };
 
+    let status = user.status();
+    let status_history = match repository::find_user_status_history(&state.db, user.id).await {
+        Ok(history) => history,
+        Err(e) => {
+            tracing::error!("Failed to load status history of user {}: {:?}", user.id, e);
+            Vec::new()
+        }
+    };
+
     let template_user = User {
         id: user.id as u32,
         name: user.username,
         email: user.email,
+        email_verified_at: user.email_verified_at.as_ref().map(crate::utils::format_datetime),
         address: user.address,
         country,
         state: state_name,
+        status,
     };
 
     AdminUserDetailTemplate {
         user: template_user,
-        current_admin: Some(admin_user.username),
-        csrf_token: ensure_csrf_token(&session).await,
+        status_transitions: status.transitions(),
+        status_history,
+        error,
+        success,
+        current_admin: Some(admin_user),
+        csrf_token: ensure_csrf_token(session).await,
         base_path: state.base_path.clone(),
     }
     .into_response()
@@ -1035,7 +1169,7 @@ pub async fn user_detail(
 
 // Admin user edit page (GET)
 pub async fn user_edit_page(
-    admin_user: AdminUser,
+    RequirePermission(admin_user, _): RequirePermission<UsersEdit>,
     State(state): State<AppState>,
     Path(id): Path<i32>,
     Extension(session): Extension<Session>,
@@ -1046,7 +1180,7 @@ pub async fn user_edit_page(
             let template = AdminErrorTemplate {
                 error_code: 404,
                 error_message: "User not found.".to_string(),
-                current_admin: Some(admin_user.username),
+                current_admin: Some(admin_user),
                 base_path: state.base_path.clone(),
             };
             return (StatusCode::NOT_FOUND, template).into_response();
@@ -1055,7 +1189,7 @@ pub async fn user_edit_page(
             let template = AdminErrorTemplate {
                 error_code: 500,
                 error_message: "Failed to load user.".to_string(),
-                current_admin: Some(admin_user.username),
+                current_admin: Some(admin_user),
                 base_path: state.base_path.clone(),
             };
             return (StatusCode::INTERNAL_SERVER_ERROR, template).into_response();
@@ -1068,7 +1202,7 @@ pub async fn user_edit_page(
             let template = AdminErrorTemplate {
                 error_code: code.as_u16(),
                 error_message: "Failed to load countries.".to_string(),
-                current_admin: Some(admin_user.username),
+                current_admin: Some(admin_user),
                 base_path: state.base_path.clone(),
             };
             return (code, template).into_response();
@@ -1088,7 +1222,8 @@ pub async fn user_edit_page(
         user_id: user.id,
         username: user.username,
         email: user.email,
-        current_admin: Some(admin_user.username),
+        current_admin: Some(admin_user),
+        password_errors: Vec::new(),
         csrf_token: ensure_csrf_token(&session).await,
         countries,
         states,
@@ -1102,8 +1237,9 @@ pub async fn user_edit_page(
 
 // Admin user edit submission (POST)
 pub async fn user_edit_submit(
-    admin_user: AdminUser,
+    RequirePermission(admin_user, _): RequirePermission<UsersEdit>,
     State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
     Path(id): Path<i32>,
     Extension(session): Extension<Session>,
     Form(form): Form<UpdateUserForm>,
@@ -1114,7 +1250,7 @@ pub async fn user_edit_submit(
             let template = AdminErrorTemplate {
                 error_code: code.as_u16(),
                 error_message: "Failed to load countries.".to_string(),
-                current_admin: Some(admin_user.username),
+                current_admin: Some(admin_user),
                 base_path: state.base_path.clone(),
             };
             return (code, template).into_response();
@@ -1125,33 +1261,28 @@ pub async fn user_edit_submit(
         Err(_) => Vec::new(),
     };
 
-    if !validate_csrf(&session, &form.csrf_token).await {
-        return AdminEditUserTemplate {
-            error: Some("Invalid CSRF token".to_string()),
-            success: None,
-            user_id: id,
-            username: form.username.clone(),
-            email: form.email.clone(),
-            current_admin: Some(admin_user.username),
-            csrf_token: ensure_csrf_token(&session).await,
-            countries,
-            states,
-            selected_country_id: form.country_id,
-            selected_state_id: form.state_id,
-            address: Some(form.address.clone()),
-            base_path: state.base_path.clone(),
+    let error = form.validate().err().map(|_| "Invalid user data".to_string());
+    let password_errors = if form.new_password.trim().is_empty() {
+        Vec::new()
+    } else {
+        match state
+            .password_policy
+            .validate(&state.db, "new_password", &form.new_password, Some(PasswordOwner::User(id)))
+            .await
+        {
+            Ok(()) => Vec::new(),
+            Err(errors) => password_policy::field_messages(&errors, "new_password"),
         }
-        .into_response();
-    }
-
-    if form.validate().is_err() {
+    };
+    if error.is_some() || !password_errors.is_empty() {
         return AdminEditUserTemplate {
-            error: Some("Invalid user data".to_string()),
+            error,
             success: None,
             user_id: id,
             username: form.username.clone(),
             email: form.email.clone(),
-            current_admin: Some(admin_user.username),
+            current_admin: Some(admin_user),
+            password_errors,
             csrf_token: ensure_csrf_token(&session).await,
             countries,
             states,
@@ -1163,6 +1294,7 @@ pub async fn user_edit_submit(
         .into_response();
     }
 
+    let before = repository::find_user_by_id(&state.db, id).await.ok().flatten();
     if let Err(_) = repository::update_user(
         &state.db,
         id,
@@ -1180,7 +1312,8 @@ pub async fn user_edit_submit(
             user_id: id,
             username: form.username.clone(),
             email: form.email.clone(),
-            current_admin: Some(admin_user.username),
+            current_admin: Some(admin_user),
+            password_errors: Vec::new(),
             csrf_token: ensure_csrf_token(&session).await,
             countries,
             states,
@@ -1192,15 +1325,38 @@ pub async fn user_edit_submit(
         .into_response();
     }
 
+    let after = repository::find_user_by_id(&state.db, id).await.ok().flatten();
+    let event = match &after {
+        Some(after) => AuditEvent::updated(before.as_ref(), after),
+        None => AuditEvent::new("update", "user", id),
+    };
+    audit::record(&state.db, &admin_user, client_ip, event).await;
+
+    // update_user cleared the verification; ask the new address to confirm
+    if let (Some(before), Some(after)) = (&before, &after)
+        && after.email_verified_at.is_none()
+        && !before.email.eq_ignore_ascii_case(&after.email)
+        && let Err(e) = send_verification_email(&state, after).await
+    {
+        tracing::error!("Failed to send verification email to user {}: {}", id, e);
+    }
+
     if !form.new_password.trim().is_empty() {
-        if let Err(_) = repository::update_password(&state.db, id, &form.new_password).await {
+        let result = match state.password_hash.hash(&form.new_password).await {
+            Ok(password_hash) => repository::update_password(&state.db, id, &password_hash)
+                .await
+                .map(|_| password_hash),
+            Err(e) => Err(sqlx::Error::Protocol(format!("Password hashing failed: {}", e))),
+        };
+        let Ok(password_hash) = result else {
             return AdminEditUserTemplate {
                 error: Some("Failed to update password".to_string()),
                 success: None,
                 user_id: id,
                 username: form.username.clone(),
                 email: form.email.clone(),
-                current_admin: Some(admin_user.username),
+                current_admin: Some(admin_user),
+                password_errors: Vec::new(),
                 csrf_token: ensure_csrf_token(&session).await,
                 countries,
                 states,
@@ -1210,7 +1366,11 @@ pub async fn user_edit_submit(
                 base_path: state.base_path.clone(),
             }
             .into_response();
-        }
+        };
+        let owner = PasswordOwner::User(id);
+        state.password_policy.remember(&state.db, owner, &password_hash).await;
+        let event = AuditEvent::new("password_change", "user", id);
+        audit::record(&state.db, &admin_user, client_ip, event).await;
     }
 
     Redirect::to(&format!("{}/users/{}", state.base_path, id)).into_response()
@@ -1218,31 +1378,27 @@ pub async fn user_edit_submit(
 
 // Admin user delete (POST)
 pub async fn user_delete(
-    admin_user: AdminUser,
+    RequirePermission(admin_user, _): RequirePermission<UsersDelete>,
     State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
     Path(id): Path<i32>,
-    Extension(session): Extension<Session>,
-    Form(form): Form<CsrfOnlyForm>,
 ) -> impl IntoResponse {
-    if !validate_csrf(&session, &form.csrf_token).await {
-        let template = AdminErrorTemplate {
-            error_code: 403,
-            error_message: "Invalid CSRF token".to_string(),
-            current_admin: Some(admin_user.username),
-            base_path: state.base_path.clone(),
-        };
-        return (StatusCode::FORBIDDEN, template).into_response();
-    }
-
+    let before = repository::find_user_by_id(&state.db, id).await.ok().flatten();
     if let Err(_) = repository::delete_user(&state.db, id).await {
         let template = AdminErrorTemplate {
             error_code: 500,
             error_message: "Failed to delete user.".to_string(),
-            current_admin: Some(admin_user.username),
+            current_admin: Some(admin_user),
             base_path: state.base_path.clone(),
         };
         return (StatusCode::INTERNAL_SERVER_ERROR, template).into_response();
     }
 
+    let event = match &before {
+        Some(user) => AuditEvent::deleted(user),
+        None => AuditEvent::new("delete", "user", id),
+    };
+    audit::record(&state.db, &admin_user, client_ip, event).await;
+
     Redirect::to(&format!("{}/users", state.base_path)).into_response()
 }
@@ -0,0 +1,491 @@
+use axum::{
+    Form,
+    extract::{Extension, Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Redirect, Response},
+};
+use serde_json::json;
+use tower_sessions::Session;
+use validator::Validate;
+
+use crate::controllers::auth_controller::{AdminUser, RequirePermission};
+use crate::controllers::permissions::{AdminsManage, AdminsView};
+use crate::controllers::request_info::ClientIp;
+use crate::models::{Admin, PasswordOwner};
+use crate::repository;
+use crate::services::admin_sessions;
+use crate::services::audit::{self, AuditEvent};
+use crate::services::password_policy;
+use crate::state::AppState;
+use crate::views::templates::{AdminAdminFormTemplate, AdminAdminsListTemplate, AdminErrorTemplate};
+
+use super::shared::{
+    ensure_csrf_token, sign_out_everywhere, AdminPasswordForm, CreateAdminForm, UpdateAdminForm,
+};
+
+const LAST_SUPER_ADMIN_MESSAGE: &str =
+    "This is the last active super-admin. Give another admin the super-admin role first.";
+
+async fn render_admins_page(
+    state: &AppState,
+    session: &Session,
+    admin_user: AdminUser,
+    error: Option<String>,
+    success: Option<String>,
+) -> Response {
+    let admins = match repository::list_admins(&state.db).await {
+        Ok(admins) => admins,
+        Err(e) => {
+            tracing::error!("Failed to load admins: {:?}", e);
+            return error_page(state, admin_user, StatusCode::INTERNAL_SERVER_ERROR, "Failed to load admins.");
+        }
+    };
+
+    AdminAdminsListTemplate {
+        current_admin_id: admin_user.id,
+        current_admin: Some(admin_user),
+        csrf_token: ensure_csrf_token(session).await,
+        admins,
+        error,
+        success,
+        base_path: state.base_path.clone(),
+    }
+    .into_response()
+}
+
+// Values shown in the create or edit form
+struct AdminFormValues {
+    admin_id: Option<i32>,
+    username: Option<String>,
+    email: Option<String>,
+    role: String,
+}
+
+async fn render_admin_form(
+    state: &AppState,
+    session: &Session,
+    admin_user: AdminUser,
+    values: AdminFormValues,
+    password_errors: Vec<String>,
+    error: Option<String>,
+    success: Option<String>,
+) -> Response {
+    let roles = match repository::find_roles(&state.db).await {
+        Ok(roles) => roles,
+        Err(e) => {
+            tracing::error!("Failed to load roles: {:?}", e);
+            return error_page(state, admin_user, StatusCode::INTERNAL_SERVER_ERROR, "Failed to load roles.");
+        }
+    };
+    let (form_title, form_action, submit_label) = match values.admin_id {
+        Some(id) => ("Edit Admin", format!("{}/admins/{}", state.base_path, id), "Update Admin"),
+        None => ("Create Admin", format!("{}/admins", state.base_path), "Create Admin"),
+    };
+
+    AdminAdminFormTemplate {
+        form_title: form_title.to_string(),
+        form_action,
+        submit_label: submit_label.to_string(),
+        admin_id: values.admin_id,
+        username: values.username,
+        email: values.email,
+        selected_role: values.role,
+        roles,
+        password_errors,
+        error,
+        success,
+        current_admin: Some(admin_user),
+        csrf_token: ensure_csrf_token(session).await,
+        base_path: state.base_path.clone(),
+    }
+    .into_response()
+}
+
+fn error_page(state: &AppState, admin_user: AdminUser, code: StatusCode, message: &str) -> Response {
+    let template = AdminErrorTemplate {
+        error_code: code.as_u16(),
+        error_message: message.to_string(),
+        current_admin: Some(admin_user),
+        base_path: state.base_path.clone(),
+    };
+    (code, template).into_response()
+}
+
+// Loads the admin an action targets, or the page to answer with
+async fn load_admin(state: &AppState, admin_user: AdminUser, id: i32) -> Result<(AdminUser, Admin), Response> {
+    match repository::find_admin_by_id(&state.db, id).await {
+        Ok(Some(admin)) => Ok((admin_user, admin)),
+        Ok(None) => Err(error_page(state, admin_user, StatusCode::NOT_FOUND, "Admin not found.")),
+        Err(e) => {
+            tracing::error!("Failed to load admin {}: {:?}", id, e);
+            Err(error_page(state, admin_user, StatusCode::INTERNAL_SERVER_ERROR, "Failed to load admin."))
+        }
+    }
+}
+
+fn duplicate_message(e: &sqlx::Error, fallback: &str) -> String {
+    if e.to_string().contains("Duplicate entry") {
+        "Username or email already exists".to_string()
+    } else {
+        fallback.to_string()
+    }
+}
+
+// Admin accounts list (GET)
+pub async fn admin_admins_list(
+    RequirePermission(admin_user, _): RequirePermission<AdminsView>,
+    State(state): State<AppState>,
+    Extension(session): Extension<Session>,
+) -> impl IntoResponse {
+    render_admins_page(&state, &session, admin_user, None, None).await
+}
+
+// Admin create form (GET)
+pub async fn admin_admin_create_page(
+    RequirePermission(admin_user, _): RequirePermission<AdminsManage>,
+    State(state): State<AppState>,
+    Extension(session): Extension<Session>,
+) -> impl IntoResponse {
+    let values = AdminFormValues {
+        admin_id: None,
+        username: None,
+        email: None,
+        role: "read-only".to_string(),
+    };
+    render_admin_form(&state, &session, admin_user, values, Vec::new(), None, None).await
+}
+
+// Admin create submission (POST)
+pub async fn admin_admin_create_submit(
+    RequirePermission(admin_user, _): RequirePermission<AdminsManage>,
+    State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    Extension(session): Extension<Session>,
+    Form(form): Form<CreateAdminForm>,
+) -> impl IntoResponse {
+    let values = AdminFormValues {
+        admin_id: None,
+        username: Some(form.username.trim().to_string()),
+        email: Some(form.email.trim().to_string()),
+        role: form.role.clone(),
+    };
+    let error = form
+        .validate()
+        .err()
+        .map(|_| "Please enter a username (up to 50 characters), a valid email and a role".to_string());
+    let password_errors = match state
+        .password_policy
+        .validate(&state.db, "password", &form.password, None)
+        .await
+    {
+        Ok(()) => Vec::new(),
+        Err(errors) => password_policy::field_messages(&errors, "password"),
+    };
+    if error.is_some() || !password_errors.is_empty() {
+        return render_admin_form(&state, &session, admin_user, values, password_errors, error, None).await;
+    }
+
+    let password_hash = match state.password_hash.hash(&form.password).await {
+        Ok(password_hash) => password_hash,
+        Err(e) => {
+            tracing::error!("Password hashing failed: {}", e);
+            let error = Some("Failed to create admin".to_string());
+            return render_admin_form(&state, &session, admin_user, values, Vec::new(), error, None).await;
+        }
+    };
+    let created = repository::create_admin_with_role(
+        &state.db,
+        form.username.trim(),
+        form.email.trim(),
+        &password_hash,
+        &form.role,
+    )
+    .await;
+    let admin_id = match created {
+        Ok(admin_id) => admin_id,
+        Err(sqlx::Error::RowNotFound) => {
+            let error = Some("Please pick an existing role".to_string());
+            return render_admin_form(&state, &session, admin_user, values, Vec::new(), error, None).await;
+        }
+        Err(e) => {
+            tracing::error!("Failed to create admin: {:?}", e);
+            let error = Some(duplicate_message(&e, "Failed to create admin"));
+            return render_admin_form(&state, &session, admin_user, values, Vec::new(), error, None).await;
+        }
+    };
+
+    state
+        .password_policy
+        .remember(&state.db, PasswordOwner::Admin(admin_id), &password_hash)
+        .await;
+    tracing::info!("Admin {} created admin {}", admin_user.username, form.username.trim());
+    let event = match repository::find_admin_by_id(&state.db, admin_id).await {
+        Ok(Some(admin)) => AuditEvent::created(&admin),
+        _ => AuditEvent::new("create", "admin", admin_id),
+    };
+    audit::record(&state.db, &admin_user, client_ip, event.with_after(json!({ "role": form.role }))).await;
+    Redirect::to(&format!("{}/admins", state.base_path)).into_response()
+}
+
+// Admin edit form (GET)
+pub async fn admin_admin_edit_page(
+    RequirePermission(admin_user, _): RequirePermission<AdminsManage>,
+    State(state): State<AppState>,
+    Extension(session): Extension<Session>,
+    Path(id): Path<i32>,
+) -> impl IntoResponse {
+    let (admin_user, admin) = match load_admin(&state, admin_user, id).await {
+        Ok(loaded) => loaded,
+        Err(response) => return response,
+    };
+    let role = repository::find_admin_roles(&state.db, id)
+        .await
+        .ok()
+        .and_then(|roles| roles.into_iter().next())
+        .unwrap_or_default();
+    let values = AdminFormValues {
+        admin_id: Some(admin.id),
+        username: Some(admin.username),
+        email: Some(admin.email),
+        role,
+    };
+    render_admin_form(&state, &session, admin_user, values, Vec::new(), None, None).await
+}
+
+// Admin edit submission (POST): username, email and role
+pub async fn admin_admin_edit_submit(
+    RequirePermission(admin_user, _): RequirePermission<AdminsManage>,
+    State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    Extension(session): Extension<Session>,
+    Path(id): Path<i32>,
+    Form(form): Form<UpdateAdminForm>,
+) -> impl IntoResponse {
+    let (admin_user, before) = match load_admin(&state, admin_user, id).await {
+        Ok(loaded) => loaded,
+        Err(response) => return response,
+    };
+    let values = AdminFormValues {
+        admin_id: Some(id),
+        username: Some(form.username.trim().to_string()),
+        email: Some(form.email.trim().to_string()),
+        role: form.role.clone(),
+    };
+    if form.validate().is_err() {
+        let error = Some("Please enter a username (up to 50 characters), a valid email and a role".to_string());
+        return render_admin_form(&state, &session, admin_user, values, Vec::new(), error, None).await;
+    }
+
+    let previous_roles = repository::find_admin_roles(&state.db, id).await.unwrap_or_default();
+    let updated =
+        repository::update_admin(&state.db, id, form.username.trim(), form.email.trim(), &form.role).await;
+    match updated {
+        Ok(true) => {}
+        Ok(false) => {
+            let error = Some(LAST_SUPER_ADMIN_MESSAGE.to_string());
+            return render_admin_form(&state, &session, admin_user, values, Vec::new(), error, None).await;
+        }
+        Err(sqlx::Error::RowNotFound) => {
+            let error = Some("Please pick an existing role".to_string());
+            return render_admin_form(&state, &session, admin_user, values, Vec::new(), error, None).await;
+        }
+        Err(e) => {
+            tracing::error!("Failed to update admin {}: {:?}", id, e);
+            let error = Some(duplicate_message(&e, "Failed to update admin"));
+            return render_admin_form(&state, &session, admin_user, values, Vec::new(), error, None).await;
+        }
+    }
+
+    tracing::info!("Admin {} updated admin {}", admin_user.username, form.username.trim());
+    if let Ok(Some(after)) = repository::find_admin_by_id(&state.db, id).await {
+        let event = AuditEvent::updated(Some(&before), &after);
+        audit::record(&state.db, &admin_user, client_ip, event).await;
+    }
+    if previous_roles != [form.role.as_str()] {
+        let event = AuditEvent::new("change_role", "admin", id)
+            .with_before(json!({ "roles": previous_roles }))
+            .with_after(json!({ "roles": [form.role] }));
+        audit::record(&state.db, &admin_user, client_ip, event).await;
+    }
+    if id == admin_user.id
+        && let Err(e) = AdminUser::set_username(&session, form.username.trim()).await
+    {
+        tracing::error!("Failed to update session username: {:?}", e);
+    }
+
+    let success = Some("Admin updated.".to_string());
+    render_admin_form(&state, &session, admin_user, values, Vec::new(), None, success).await
+}
+
+// Sets a new password for an admin (POST) and signs them out everywhere
+pub async fn admin_admin_password_submit(
+    RequirePermission(admin_user, _): RequirePermission<AdminsManage>,
+    State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    Extension(session): Extension<Session>,
+    Path(id): Path<i32>,
+    Form(form): Form<AdminPasswordForm>,
+) -> impl IntoResponse {
+    let (admin_user, admin) = match load_admin(&state, admin_user, id).await {
+        Ok(loaded) => loaded,
+        Err(response) => return response,
+    };
+    let role = repository::find_admin_roles(&state.db, id)
+        .await
+        .ok()
+        .and_then(|roles| roles.into_iter().next())
+        .unwrap_or_default();
+    let values = AdminFormValues {
+        admin_id: Some(admin.id),
+        username: Some(admin.username.clone()),
+        email: Some(admin.email.clone()),
+        role,
+    };
+
+    let owner = PasswordOwner::Admin(id);
+    let password_errors = if form.validate().is_err() {
+        vec!["Passwords do not match".to_string()]
+    } else {
+        match state
+            .password_policy
+            .validate(&state.db, "password", &form.password, Some(owner))
+            .await
+        {
+            Ok(()) => Vec::new(),
+            Err(errors) => password_policy::field_messages(&errors, "password"),
+        }
+    };
+    if !password_errors.is_empty() {
+        return render_admin_form(&state, &session, admin_user, values, password_errors, None, None).await;
+    }
+
+    let stored = match state.password_hash.hash(&form.password).await {
+        Ok(password_hash) => repository::update_admin_password(&state.db, id, &password_hash)
+            .await
+            .map(|()| password_hash),
+        Err(e) => Err(sqlx::Error::Protocol(format!("Password hashing failed: {}", e))),
+    };
+    let password_hash = match stored {
+        Ok(password_hash) => password_hash,
+        Err(e) => {
+            tracing::error!("Failed to reset password of admin {}: {:?}", id, e);
+            let error = Some("Failed to reset password".to_string());
+            return render_admin_form(&state, &session, admin_user, values, Vec::new(), error, None).await;
+        }
+    };
+
+    state.password_policy.remember(&state.db, owner, &password_hash).await;
+    // Resetting your own password here keeps the current session
+    let keep_ref = if id == admin_user.id { admin_user.session_ref.as_deref() } else { None };
+    sign_out_everywhere(&state, id, keep_ref).await;
+    tracing::info!("Admin {} reset the password of admin {}", admin_user.username, admin.username);
+    let event = AuditEvent::new("reset_password", "admin", id);
+    audit::record(&state.db, &admin_user, client_ip, event).await;
+
+    let success = Some(format!("Password of {} reset. They have been signed out.", admin.username));
+    render_admin_form(&state, &session, admin_user, values, Vec::new(), None, success).await
+}
+
+// Disables sign-in for an admin (POST) and signs them out everywhere
+pub async fn admin_admin_disable_submit(
+    RequirePermission(admin_user, _): RequirePermission<AdminsManage>,
+    State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    Extension(session): Extension<Session>,
+    Path(id): Path<i32>,
+) -> impl IntoResponse {
+    set_disabled(&state, &session, admin_user, client_ip, id, true).await
+}
+
+// Re-enables sign-in for a disabled admin (POST)
+pub async fn admin_admin_enable_submit(
+    RequirePermission(admin_user, _): RequirePermission<AdminsManage>,
+    State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    Extension(session): Extension<Session>,
+    Path(id): Path<i32>,
+) -> impl IntoResponse {
+    set_disabled(&state, &session, admin_user, client_ip, id, false).await
+}
+
+async fn set_disabled(
+    state: &AppState,
+    session: &Session,
+    admin_user: AdminUser,
+    client_ip: std::net::IpAddr,
+    id: i32,
+    disabled: bool,
+) -> Response {
+    if id == admin_user.id {
+        let error = Some("You cannot disable your own account.".to_string());
+        return render_admins_page(state, session, admin_user, error, None).await;
+    }
+    let (admin_user, before) = match load_admin(state, admin_user, id).await {
+        Ok(loaded) => loaded,
+        Err(response) => return response,
+    };
+
+    match repository::set_admin_disabled(&state.db, id, disabled).await {
+        Ok(true) => {}
+        Ok(false) => {
+            let error = Some(LAST_SUPER_ADMIN_MESSAGE.to_string());
+            return render_admins_page(state, session, admin_user, error, None).await;
+        }
+        Err(e) => {
+            tracing::error!("Failed to change status of admin {}: {:?}", id, e);
+            let error = Some("Failed to update admin.".to_string());
+            return render_admins_page(state, session, admin_user, error, None).await;
+        }
+    }
+
+    let (action, message) = if disabled {
+        sign_out_everywhere(state, id, None).await;
+        ("disable", format!("{} disabled and signed out.", before.username))
+    } else {
+        ("enable", format!("{} enabled.", before.username))
+    };
+    tracing::info!("Admin {} {}d admin {}", admin_user.username, action, before.username);
+    let event = AuditEvent::new(action, "admin", id);
+    audit::record(&state.db, &admin_user, client_ip, event).await;
+    render_admins_page(state, session, admin_user, None, Some(message)).await
+}
+
+// Deletes an admin account (POST)
+pub async fn admin_admin_delete_submit(
+    RequirePermission(admin_user, _): RequirePermission<AdminsManage>,
+    State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    Extension(session): Extension<Session>,
+    Path(id): Path<i32>,
+) -> impl IntoResponse {
+    if id == admin_user.id {
+        let error = Some("You cannot delete your own account.".to_string());
+        return render_admins_page(&state, &session, admin_user, error, None).await;
+    }
+    let (admin_user, before) = match load_admin(&state, admin_user, id).await {
+        Ok(loaded) => loaded,
+        Err(response) => return response,
+    };
+
+    match repository::delete_admin(&state.db, id).await {
+        Ok(true) => {}
+        Ok(false) => {
+            let error = Some(LAST_SUPER_ADMIN_MESSAGE.to_string());
+            return render_admins_page(&state, &session, admin_user, error, None).await;
+        }
+        Err(e) => {
+            tracing::error!("Failed to delete admin {}: {:?}", id, e);
+            let error = Some("Failed to delete admin.".to_string());
+            return render_admins_page(&state, &session, admin_user, error, None).await;
+        }
+    }
+
+    // Tokens went with the row; the Redis session index did not
+    if let Err(e) = admin_sessions::revoke_all(&state.redis, id).await {
+        tracing::error!("Failed to revoke sessions for admin {}: {}", id, e);
+    }
+    tracing::info!("Admin {} deleted admin {}", admin_user.username, before.username);
+    audit::record(&state.db, &admin_user, client_ip, AuditEvent::deleted(&before)).await;
+    let success = Some(format!("{} deleted.", before.username));
+    render_admins_page(&state, &session, admin_user, None, success).await
+}
@@ -0,0 +1,144 @@
+use axum::{
+    extract::{Extension, Path, State},
+    response::IntoResponse,
+};
+use axum_extra::extract::Form as MultiValueForm;
+use std::collections::BTreeSet;
+use tower_sessions::Session;
+use validator::Validate;
+
+use crate::controllers::auth_controller::AdminUser;
+use crate::controllers::request_info::ClientIp;
+use crate::repository;
+use crate::services::api_tokens;
+use crate::services::audit::{self, AuditEvent};
+use crate::state::AppState;
+use crate::views::templates::AdminApiTokensTemplate;
+
+use super::shared::{ensure_csrf_token, CreateApiTokenForm};
+
+async fn render_api_tokens_page(
+    state: &AppState,
+    session: &Session,
+    admin_user: AdminUser,
+    new_token: Option<String>,
+    error: Option<String>,
+    success: Option<String>,
+) -> axum::response::Response {
+    let tokens = match repository::find_api_tokens_by_admin(&state.db, admin_user.id).await {
+        Ok(tokens) => tokens,
+        Err(e) => {
+            tracing::error!("Failed to load API tokens: {:?}", e);
+            Vec::new()
+        }
+    };
+    // Tokens can only carry permissions the admin holds
+    let mut available_scopes: Vec<String> = admin_user.permissions.iter().cloned().collect();
+    available_scopes.sort();
+
+    AdminApiTokensTemplate {
+        current_admin: Some(admin_user),
+        csrf_token: ensure_csrf_token(session).await,
+        tokens,
+        available_scopes,
+        expiry_options: api_tokens::EXPIRY_OPTIONS_DAYS.to_vec(),
+        default_expiry_days: api_tokens::DEFAULT_EXPIRY_DAYS,
+        new_token,
+        error,
+        success,
+        base_path: state.base_path.clone(),
+    }
+    .into_response()
+}
+
+// API tokens of the logged-in admin (GET)
+pub async fn admin_api_tokens_page(
+    admin_user: AdminUser,
+    State(state): State<AppState>,
+    Extension(session): Extension<Session>,
+) -> impl IntoResponse {
+    render_api_tokens_page(&state, &session, admin_user, None, None, None).await
+}
+
+// Mint a token (POST). The plaintext token is shown once in the response.
+pub async fn admin_api_token_create_submit(
+    admin_user: AdminUser,
+    State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    Extension(session): Extension<Session>,
+    MultiValueForm(form): MultiValueForm<CreateApiTokenForm>,
+) -> impl IntoResponse {
+    let scopes: BTreeSet<&str> = form.scopes.iter().map(|s| s.trim()).collect();
+    let error = if form.validate().is_err() {
+        Some("Please enter a name (up to 100 characters) and pick a valid expiry")
+    } else if !api_tokens::EXPIRY_OPTIONS_DAYS.contains(&form.expires_in_days) {
+        Some("Please pick a valid expiry")
+    } else if scopes.is_empty() {
+        Some("Select at least one scope")
+    } else if scopes.iter().any(|scope| !admin_user.can(scope)) {
+        Some("You can only grant scopes you hold yourself")
+    } else {
+        None
+    };
+    if let Some(message) = error {
+        let error = Some(message.to_string());
+        return render_api_tokens_page(&state, &session, admin_user, None, error, None).await;
+    }
+
+    let new_token = api_tokens::generate_token();
+    let scopes = scopes.into_iter().collect::<Vec<_>>().join(" ");
+    let created = repository::create_api_token(
+        &state.db,
+        admin_user.id,
+        form.name.trim(),
+        &new_token.display_prefix,
+        &new_token.hash,
+        &scopes,
+        form.expires_in_days,
+    )
+    .await;
+    let token_id = match created {
+        Ok(token_id) => token_id,
+        Err(e) => {
+            tracing::error!("Failed to create API token: {:?}", e);
+            let error = Some("Failed to create API token.".to_string());
+            return render_api_tokens_page(&state, &session, admin_user, None, error, None).await;
+        }
+    };
+
+    tracing::info!("Admin {} created API token {}", admin_user.username, new_token.display_prefix);
+    let event = AuditEvent::new("create", "api_token", token_id).with_after(serde_json::json!({
+        "name": form.name.trim(),
+        "token_prefix": new_token.display_prefix,
+        "scopes": scopes,
+        "expires_in_days": form.expires_in_days,
+    }));
+    audit::record(&state.db, &admin_user, client_ip, event).await;
+    let token = Some(new_token.token);
+    render_api_tokens_page(&state, &session, admin_user, token, None, None).await
+}
+
+// Revoke a token (POST)
+pub async fn admin_api_token_revoke_submit(
+    admin_user: AdminUser,
+    State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    Extension(session): Extension<Session>,
+    Path(token_id): Path<i32>,
+) -> impl IntoResponse {
+    let revoked = repository::revoke_api_token(&state.db, admin_user.id, token_id).await;
+    let (error, success) = match revoked {
+        Ok(true) => {
+            tracing::info!("Admin {} revoked API token {}", admin_user.username, token_id);
+            let event = AuditEvent::new("revoke", "api_token", token_id);
+            audit::record(&state.db, &admin_user, client_ip, event).await;
+            (None, Some("API token revoked.".to_string()))
+        }
+        Ok(false) => (Some("API token not found or already revoked.".to_string()), None),
+        Err(e) => {
+            tracing::error!("Failed to revoke API token: {:?}", e);
+            (Some("Failed to revoke API token.".to_string()), None)
+        }
+    };
+    render_api_tokens_page(&state, &session, admin_user, None, error, success).await
+}
@@ -0,0 +1,135 @@
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    response::IntoResponse,
+};
+use time::OffsetDateTime;
+
+use crate::controllers::auth_controller::RequirePermission;
+use crate::controllers::permissions::AuditView;
+use crate::models::{AuditLogEntry, AuditLogQuery, AUDIT_ENTITY_TYPES};
+use crate::repository;
+use crate::state::AppState;
+use crate::views::templates::{AdminAuditLogTemplate, AdminErrorTemplate};
+
+const PAGE_SIZE: i64 = 50;
+// Upper bound on rows in one CSV download
+const EXPORT_LIMIT: i64 = 10_000;
+
+// Audit log with filters (GET)
+pub async fn admin_audit_log_page(
+    RequirePermission(admin_user, _): RequirePermission<AuditView>,
+    State(state): State<AppState>,
+    Query(query): Query<AuditLogQuery>,
+) -> impl IntoResponse {
+    let filter = query.filter();
+    let page = i64::from(query.page.unwrap_or(1).max(1));
+
+    let result = async {
+        let total = repository::count_audit_logs(&state.db, &filter).await?;
+        let entries =
+            repository::find_audit_logs(&state.db, &filter, PAGE_SIZE, (page - 1) * PAGE_SIZE)
+                .await?;
+        let admins = repository::find_admin_options(&state.db).await?;
+        Ok::<_, sqlx::Error>((total, entries, admins))
+    }
+    .await;
+
+    let (total, entries, admins) = match result {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            tracing::error!("Failed to load audit log: {:?}", e);
+            let template = AdminErrorTemplate {
+                error_code: 500,
+                error_message: "Failed to load audit log.".to_string(),
+                current_admin: Some(admin_user),
+                base_path: state.base_path.clone(),
+            };
+            return (StatusCode::INTERNAL_SERVER_ERROR, template).into_response();
+        }
+    };
+
+    AdminAuditLogTemplate {
+        current_admin: Some(admin_user),
+        entries,
+        admins,
+        entity_types: AUDIT_ENTITY_TYPES.iter().map(|t| t.to_string()).collect(),
+        selected_admin_id: filter.admin_id.unwrap_or(0),
+        selected_entity_type: filter.entity_type.clone().unwrap_or_default(),
+        entity_id: filter.entity_id.clone().unwrap_or_default(),
+        from: filter.from.map(|d| d.to_string()).unwrap_or_default(),
+        to: filter.to.map(|d| d.to_string()).unwrap_or_default(),
+        filter_query: filter.to_query_string(),
+        total,
+        page,
+        has_previous: page > 1,
+        has_next: page * PAGE_SIZE < total,
+        base_path: state.base_path.clone(),
+    }
+    .into_response()
+}
+
+// CSV download of the filtered audit log (GET)
+pub async fn admin_audit_log_csv(
+    RequirePermission(admin_user, _): RequirePermission<AuditView>,
+    State(state): State<AppState>,
+    Query(query): Query<AuditLogQuery>,
+) -> impl IntoResponse {
+    let filter = query.filter();
+    let entries = match repository::find_audit_logs(&state.db, &filter, EXPORT_LIMIT, 0).await {
+        Ok(entries) => entries,
+        Err(e) => {
+            tracing::error!("Failed to export audit log: {:?}", e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    tracing::info!("Admin {} exported {} audit log rows", admin_user.username, entries.len());
+
+    let mut csv = String::from(
+        "id,created_at,admin_id,admin_username,action,entity_type,entity_id,ip,before,after\r\n",
+    );
+    for entry in &entries {
+        csv.push_str(&csv_row(entry));
+    }
+
+    let date = OffsetDateTime::now_utc().date();
+    let disposition = format!("attachment; filename=\"audit-log-{}.csv\"", date);
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("text/csv; charset=utf-8"),
+    );
+    if let Ok(value) = HeaderValue::from_str(&disposition) {
+        headers.insert(header::CONTENT_DISPOSITION, value);
+    }
+
+    (headers, csv).into_response()
+}
+
+fn csv_row(entry: &AuditLogEntry) -> String {
+    let fields = [
+        entry.id.to_string(),
+        entry.created_at_display(),
+        entry.admin_id.map(|id| id.to_string()).unwrap_or_default(),
+        entry.admin_username.clone(),
+        entry.action.clone(),
+        entry.entity_type.clone(),
+        entry.entity_id.clone().unwrap_or_default(),
+        entry.ip.clone(),
+        entry.before_json.clone().unwrap_or_default(),
+        entry.after_json.clone().unwrap_or_default(),
+    ];
+    let mut row = fields.iter().map(|f| csv_field(f)).collect::<Vec<_>>().join(",");
+    row.push_str("\r\n");
+    row
+}
+
+// Quotes the field and defuses values a spreadsheet would run as a formula
+fn csv_field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    };
+    format!("\"{}\"", value.replace('"', "\"\""))
+}
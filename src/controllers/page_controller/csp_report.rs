@@ -0,0 +1,50 @@
+use axum::{body::Bytes, http::StatusCode, response::IntoResponse};
+use serde_json::Value;
+
+use crate::controllers::request_info::ClientIp;
+
+// Longest value logged per report field; blocked URIs can be arbitrarily long
+const MAX_FIELD_CHARS: usize = 256;
+
+fn field(report: &Value, names: &[&str]) -> String {
+    let value = names
+        .iter()
+        .find_map(|name| report.get(*name))
+        .map(|value| match value {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        })
+        .unwrap_or_default();
+    value.chars().take(MAX_FIELD_CHARS).collect()
+}
+
+// Violation reports sent by browsers (POST, no session or CSRF token).
+// Accepts both the `report-uri` format (`{"csp-report": {...}}`) and the
+// Reporting API format (`[{"type": "csp-violation", "body": {...}}]`).
+pub async fn csp_report_submit(ClientIp(client_ip): ClientIp, body: Bytes) -> impl IntoResponse {
+    let reports = match serde_json::from_slice::<Value>(&body) {
+        Ok(Value::Array(items)) => items
+            .into_iter()
+            .filter_map(|item| item.get("body").cloned())
+            .collect(),
+        Ok(value) => value.get("csp-report").cloned().into_iter().collect(),
+        Err(_) => Vec::new(),
+    };
+    if reports.is_empty() {
+        return StatusCode::BAD_REQUEST;
+    }
+
+    for report in &reports {
+        tracing::warn!(
+            client_ip = %client_ip,
+            document = %field(report, &["document-uri", "documentURL"]),
+            directive = %field(report, &["effective-directive", "effectiveDirective", "violated-directive"]),
+            blocked = %field(report, &["blocked-uri", "blockedURL"]),
+            source = %field(report, &["source-file", "sourceFile"]),
+            line = %field(report, &["line-number", "lineNumber"]),
+            disposition = %field(report, &["disposition"]),
+            "Content Security Policy violation"
+        );
+    }
+    StatusCode::NO_CONTENT
+}
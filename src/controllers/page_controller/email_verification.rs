@@ -0,0 +1,159 @@
+use askama::Template;
+use axum::{
+    extract::{Extension, Path, Query, State},
+    response::IntoResponse,
+};
+use tower_sessions::Session;
+use tower_sessions_redis_store::fred::prelude::KeysInterface;
+use tower_sessions_redis_store::fred::types::{Expiration, SetOptions};
+
+use crate::controllers::auth_controller::RequirePermission;
+use crate::controllers::permissions::UsersEdit;
+use crate::controllers::request_info::ClientIp;
+use crate::models;
+use crate::repository;
+use crate::services::audit::{self, AuditEvent};
+use crate::services::email_verification::{EmailVerificationConfig, TokenError};
+use crate::state::AppState;
+use crate::views::templates::{UserEmailVerificationEmail, VerifyEmailTemplate};
+
+use super::admin::render_user_detail;
+use super::shared::{impersonation_banner, VerifyEmailQuery};
+
+// At most one resent verification email per user in this window
+const RESEND_COOLDOWN_SECONDS: i64 = 60;
+
+// Emails a signed verification link for the user's current address
+pub(crate) async fn send_verification_email(
+    state: &AppState,
+    user: &models::User,
+) -> Result<(), String> {
+    let token = state.email_verification.generate_token(user.id, &user.email);
+    let email = UserEmailVerificationEmail {
+        username: user.username.clone(),
+        email: user.email.clone(),
+        verify_url: format!("{}/verify-email?token={}", state.public_url, token),
+        ttl_hours: state.email_verification.ttl_hours,
+    };
+    let body = email.render().map_err(|e| e.to_string())?;
+    state
+        .mailer
+        .send(&user.email, "Confirm your email address", body)
+        .await?;
+    tracing::info!("Verification email sent to user {}", user.username);
+    Ok(())
+}
+
+// A verified email is what moves a new account from pending to active
+async fn activate_pending_user(state: &AppState, user: &models::User) {
+    if user.status() != models::UserStatus::Pending {
+        return;
+    }
+    let reason = "Email address verified";
+    let (from, to) = (models::UserStatus::Pending, models::UserStatus::Active);
+    match repository::change_user_status(&state.db, user.id, from, to, reason, None).await {
+        Ok(true) => tracing::info!("User {} is now active", user.username),
+        Ok(false) => {}
+        Err(e) => tracing::error!("Failed to activate user {}: {:?}", user.id, e),
+    }
+}
+
+// Resend the verification email from the user detail page (POST)
+pub async fn user_resend_verification_submit(
+    RequirePermission(admin_user, _): RequirePermission<UsersEdit>,
+    State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    Extension(session): Extension<Session>,
+    Path(id): Path<i32>,
+) -> impl IntoResponse {
+    let user = match repository::find_user_by_id(&state.db, id).await {
+        Ok(Some(user)) => user,
+        // The detail page renders the 404/500
+        _ => return render_user_detail(&state, &session, admin_user, id, None, None).await,
+    };
+    if user.email_verified_at.is_some() {
+        let error = Some("This email address is already verified.".to_string());
+        return render_user_detail(&state, &session, admin_user, id, error, None).await;
+    }
+
+    let cooldown: Result<Option<String>, _> = state
+        .redis
+        .set(
+            format!("email_verification:cooldown:{}", id),
+            "1",
+            Some(Expiration::EX(RESEND_COOLDOWN_SECONDS)),
+            Some(SetOptions::NX),
+            false,
+        )
+        .await;
+    if let Ok(None) = cooldown {
+        let error = Some("A verification email was sent less than a minute ago.".to_string());
+        return render_user_detail(&state, &session, admin_user, id, error, None).await;
+    }
+
+    if let Err(e) = send_verification_email(&state, &user).await {
+        tracing::error!("Failed to resend verification email to user {}: {}", id, e);
+        let error = Some("Failed to send the verification email.".to_string());
+        return render_user_detail(&state, &session, admin_user, id, error, None).await;
+    }
+
+    let event = AuditEvent::new("resend_verification", "user", id)
+        .with_after(serde_json::json!({ "email": user.email }));
+    audit::record(&state.db, &admin_user, client_ip, event).await;
+    let success = Some(format!("Verification email sent to {}.", user.email));
+    render_user_detail(&state, &session, admin_user, id, None, success).await
+}
+
+// Target of the emailed link (GET)
+pub async fn verify_email_page(
+    State(state): State<AppState>,
+    Extension(session): Extension<Session>,
+    Query(query): Query<VerifyEmailQuery>,
+) -> impl IntoResponse {
+    let token = query.token.unwrap_or_default();
+    let result = match EmailVerificationConfig::token_user_id(&token) {
+        Ok(user_id) => match repository::find_user_by_id(&state.db, user_id).await {
+            Ok(Some(user)) => state
+                .email_verification
+                .verify_token(&token, user.id, &user.email)
+                .map(|()| user),
+            Ok(None) => Err(TokenError::BadSignature),
+            Err(e) => {
+                tracing::error!("Failed to load user for email verification: {:?}", e);
+                Err(TokenError::Malformed)
+            }
+        },
+        Err(e) => Err(e),
+    };
+
+    let (verified, message) = match result {
+        Ok(user) => match repository::mark_email_verified(&state.db, user.id).await {
+            Ok(newly_verified) => {
+                if newly_verified {
+                    tracing::info!("User {} verified their email address", user.username);
+                }
+                activate_pending_user(&state, &user).await;
+                (true, format!("Thanks, {} is verified.", user.email))
+            }
+            Err(e) => {
+                tracing::error!("Failed to mark email verified for user {}: {:?}", user.id, e);
+                (false, "Something went wrong. Please try again.".to_string())
+            }
+        },
+        Err(TokenError::Expired) => (
+            false,
+            "This verification link has expired. Ask for a new one.".to_string(),
+        ),
+        Err(TokenError::Malformed | TokenError::BadSignature) => (
+            false,
+            "This verification link is invalid or was sent to a previous address.".to_string(),
+        ),
+    };
+
+    VerifyEmailTemplate {
+        verified,
+        message,
+        impersonation: impersonation_banner(&session).await,
+        base_path: state.base_path.clone(),
+    }
+}
@@ -0,0 +1,119 @@
+use std::net::IpAddr;
+
+use axum::{
+    extract::{Extension, Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Redirect},
+};
+use time::OffsetDateTime;
+use tower_sessions::Session;
+
+use crate::controllers::auth_controller::{AdminUser, AuthUser, Impersonation, RequirePermission};
+use crate::controllers::permissions::UsersImpersonate;
+use crate::controllers::request_info::ClientIp;
+use crate::repository;
+use crate::services::audit::{self, AuditEvent};
+use crate::state::AppState;
+use crate::views::templates::AdminErrorTemplate;
+
+use super::admin::render_user_detail;
+
+// Ends the current impersonation, if any, and records how long it lasted.
+// The audit entry is attributed to the admin who started it.
+pub(crate) async fn end_impersonation(
+    state: &AppState,
+    session: &Session,
+    client_ip: IpAddr,
+) -> Option<Impersonation> {
+    let impersonation = match Impersonation::end(session).await {
+        Ok(Some(impersonation)) => impersonation,
+        Ok(None) => return None,
+        Err(e) => {
+            tracing::error!("Failed to end impersonation: {:?}", e);
+            return None;
+        }
+    };
+
+    let duration = OffsetDateTime::now_utc().unix_timestamp() - impersonation.started_at;
+    tracing::info!(
+        "Admin {} stopped impersonating user {}",
+        impersonation.admin_username,
+        impersonation.username
+    );
+    let actor = AdminUser::new(impersonation.admin_id, impersonation.admin_username.clone());
+    let event = AuditEvent::new("impersonate_end", "user", impersonation.user_id)
+        .with_after(serde_json::json!({ "duration_seconds": duration.max(0) }));
+    audit::record(&state.db, &actor, client_ip, event).await;
+    Some(impersonation)
+}
+
+// Sign in to the public site as a user (POST)
+pub async fn user_impersonate_submit(
+    RequirePermission(admin_user, _): RequirePermission<UsersImpersonate>,
+    State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    Extension(session): Extension<Session>,
+    Path(id): Path<i32>,
+) -> impl IntoResponse {
+    let user = match repository::find_user_by_id(&state.db, id).await {
+        Ok(Some(user)) => user,
+        Ok(None) => {
+            let template = AdminErrorTemplate {
+                error_code: 404,
+                error_message: "User not found.".to_string(),
+                current_admin: Some(admin_user),
+                base_path: state.base_path.clone(),
+            };
+            return (StatusCode::NOT_FOUND, template).into_response();
+        }
+        Err(_) => {
+            let template = AdminErrorTemplate {
+                error_code: 500,
+                error_message: "Failed to load user.".to_string(),
+                current_admin: Some(admin_user),
+                base_path: state.base_path.clone(),
+            };
+            return (StatusCode::INTERNAL_SERVER_ERROR, template).into_response();
+        }
+    };
+    // The user could not sign in themselves, so the admin cannot either
+    if user.status().sign_in_refusal().is_some() {
+        let error = Some(format!("A {} user cannot be signed in as", user.status));
+        return render_user_detail(&state, &session, admin_user, id, error, None).await;
+    }
+
+    end_impersonation(&state, &session, client_ip).await;
+
+    let auth_user = AuthUser::new(user.id, user.username.clone());
+    if let Err(e) = Impersonation::begin(&session, &admin_user, &auth_user).await {
+        tracing::error!("Failed to start impersonation: {:?}", e);
+        let template = AdminErrorTemplate {
+            error_code: 500,
+            error_message: "Failed to sign in as user.".to_string(),
+            current_admin: Some(admin_user),
+            base_path: state.base_path.clone(),
+        };
+        return (StatusCode::INTERNAL_SERVER_ERROR, template).into_response();
+    }
+
+    tracing::info!("Admin {} started impersonating user {}", admin_user.username, user.username);
+    let event = AuditEvent::new("impersonate_start", "user", user.id)
+        .with_after(serde_json::json!({ "username": user.username }));
+    audit::record(&state.db, &admin_user, client_ip, event).await;
+    Redirect::to("/").into_response()
+}
+
+// Leave the impersonated session and go back to the user in the admin (POST)
+pub async fn impersonation_stop_submit(
+    State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    Extension(session): Extension<Session>,
+) -> impl IntoResponse {
+    match end_impersonation(&state, &session, client_ip).await {
+        Some(impersonation) => Redirect::to(&format!(
+            "{}/users/{}",
+            state.base_path, impersonation.user_id
+        )),
+        None => Redirect::to("/"),
+    }
+}
@@ -1,13 +1,70 @@
+mod account;
 mod admin;
+mod admins;
+mod api_tokens;
+mod audit;
+mod csp_report;
+mod email_verification;
+mod impersonation;
+mod oidc;
+mod passkeys;
+mod password_reset;
+mod profile;
 mod public;
+mod security;
+mod sessions;
 mod shared;
+mod trash;
+mod user_status;
 
+pub use account::{
+    user_login_page, user_login_submit, user_password_submit, user_profile_page,
+    user_profile_submit, user_register_page, user_register_submit, user_states_api,
+};
 pub use admin::{
     admin_countries_list, admin_country_create_page, admin_country_create_submit,
     admin_country_delete, admin_country_edit_page, admin_country_edit_submit, admin_dashboard,
     admin_index, admin_logout, admin_state_create_page, admin_state_create_submit,
     admin_state_delete, admin_state_edit_page, admin_state_edit_submit, admin_states_api,
-    admin_states_list, admin_users_pdf, user_create_page, user_create_submit, user_delete,
+    admin_states_list, admin_users_pdf, admin_users_pdf_link, admin_users_pdf_shared,
+    user_create_page, user_create_submit, user_delete,
     user_detail, user_edit_page, user_edit_submit, users_datatable_api, users_list,
 };
-pub use public::{admin_login_page, admin_login_submit, handle_404, index, logout};
+pub use admins::{
+    admin_admin_create_page, admin_admin_create_submit, admin_admin_delete_submit,
+    admin_admin_disable_submit, admin_admin_edit_page, admin_admin_edit_submit,
+    admin_admin_enable_submit, admin_admin_password_submit, admin_admins_list,
+};
+pub use api_tokens::{
+    admin_api_token_create_submit, admin_api_token_revoke_submit, admin_api_tokens_page,
+};
+pub use audit::{admin_audit_log_csv, admin_audit_log_page};
+pub use csp_report::csp_report_submit;
+pub use email_verification::{user_resend_verification_submit, verify_email_page};
+pub use impersonation::{impersonation_stop_submit, user_impersonate_submit};
+pub use oidc::{admin_oidc_callback, admin_oidc_login, admin_oidc_reauth_submit};
+pub use passkeys::{
+    admin_passkey_delete_submit, admin_passkey_login_options, admin_passkey_login_submit,
+    admin_passkey_options_submit, admin_passkey_reauth_options, admin_passkey_reauth_submit,
+    admin_passkey_register_submit, admin_passkey_rename_submit, admin_passkeys_page,
+};
+pub use password_reset::{
+    admin_forgot_password_page, admin_forgot_password_submit, admin_reset_password_page,
+    admin_reset_password_submit,
+};
+pub use profile::{admin_profile_page, admin_profile_password_submit, admin_profile_submit};
+pub use public::{
+    admin_login_2fa_page, admin_login_2fa_submit, admin_login_page, admin_login_submit,
+    handle_404, index, logout,
+};
+pub use security::{
+    admin_recovery_codes_submit, admin_security_page, admin_totp_disable_submit,
+    admin_totp_enable_submit, admin_totp_setup_submit,
+};
+// Also used by middleware::reauth
+pub(crate) use shared::{verify_second_factor, SecondFactorCheck};
+pub use sessions::{
+    admin_session_revoke_submit, admin_sessions_page, admin_sessions_revoke_all_submit,
+};
+pub use trash::{admin_trash_page, admin_trash_purge_submit, admin_trash_restore_submit};
+pub use user_status::user_status_submit;
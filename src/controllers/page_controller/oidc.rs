@@ -0,0 +1,367 @@
+use axum::{
+    Form,
+    extract::{Extension, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Redirect},
+};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use rand::RngCore;
+use subtle::ConstantTimeEq;
+use tower_sessions::Session;
+
+use crate::controllers::auth_controller::{AdminUser, OptionalAdminUser, PendingTwoFactor};
+use crate::controllers::request_info::{ClientIp, UserAgent};
+use crate::middleware::reauth::{self, ConfirmPage, PendingAction};
+use crate::middleware::{csrf, login_notice};
+use crate::models::{self, PasswordOwner};
+use crate::repository;
+use crate::services::audit::{self, AuditEvent};
+use crate::services::login_history::{self, LoginAttempt, LoginMethod};
+use crate::services::oidc::{OidcIdentity, PendingOidcLogin};
+use crate::state::AppState;
+use crate::views::templates::AdminLoginTemplate;
+
+use super::shared::{ensure_csrf_token, sso_label, OidcCallbackQuery};
+
+const OIDC_PENDING_KEY: &str = "oidc_pending";
+// The sudo-mode form to re-post once the provider confirms a signed-in admin
+const OIDC_REAUTH_KEY: &str = "oidc_reauth";
+// Attempts at a free username for a provisioned admin (name, name-2, ...)
+const USERNAME_ATTEMPTS: usize = 10;
+
+async fn login_error(state: &AppState, session: &Session, message: &str) -> axum::response::Response {
+    AdminLoginTemplate {
+        error: Some(message.to_string()),
+        lockout: None,
+        sso_label: sso_label(state),
+        remember_me_days: state.remember_me.lifetime_days,
+        csrf_token: ensure_csrf_token(session).await,
+        base_path: state.base_path.clone(),
+    }
+    .into_response()
+}
+
+// Redirect to the identity provider (GET)
+pub async fn admin_oidc_login(
+    OptionalAdminUser(admin_user): OptionalAdminUser,
+    State(state): State<AppState>,
+    Extension(session): Extension<Session>,
+) -> impl IntoResponse {
+    if admin_user.is_some() {
+        return Redirect::to(&format!("{}/dashboard", state.base_path)).into_response();
+    }
+    let Some(oidc) = state.oidc.as_ref() else {
+        return Redirect::to(&format!("{}/login", state.base_path)).into_response();
+    };
+
+    let (url, pending) = match oidc.begin_login().await {
+        Ok(started) => started,
+        Err(e) => {
+            tracing::error!("Failed to start OIDC login: {}", e);
+            return login_error(&state, &session, "Single sign-on is unavailable. Please try again later.")
+                .await;
+        }
+    };
+    if let Err(e) = session.insert(OIDC_PENDING_KEY, pending).await {
+        tracing::error!("Failed to store OIDC login state: {:?}", e);
+        return login_error(&state, &session, "Session error. Please try again.").await;
+    }
+    Redirect::to(&url).into_response()
+}
+
+// Confirm the signed-in admin through the identity provider, from the
+// sudo-mode page (POST). The form to re-post waits in the session.
+pub async fn admin_oidc_reauth_submit(
+    admin_user: AdminUser,
+    State(state): State<AppState>,
+    Extension(session): Extension<Session>,
+    Form(fields): Form<Vec<(String, String)>>,
+) -> impl IntoResponse {
+    let Some(pending_action) = PendingAction::from_form(fields) else {
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+    let admin = match reauth::load_admin(&state, &admin_user).await {
+        Ok(admin) => admin,
+        Err(response) => return response,
+    };
+    let page = ConfirmPage::new(&state, &session, &admin, pending_action.clone()).await;
+    let (Some(oidc), Some(_)) = (state.oidc.as_ref(), page.sso_label()) else {
+        let error = Some("Single sign-on cannot confirm this account.".to_string());
+        return page.render(admin_user, error).await;
+    };
+
+    let (url, pending) = match oidc.begin_reauth().await {
+        Ok(started) => started,
+        Err(e) => {
+            tracing::error!("Failed to start OIDC re-authentication: {}", e);
+            let error = Some("Single sign-on is unavailable. Please try again later.".to_string());
+            return page.render(admin_user, error).await;
+        }
+    };
+    let stored = match session.insert(OIDC_PENDING_KEY, pending).await {
+        Ok(()) => session.insert(OIDC_REAUTH_KEY, pending_action).await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = stored {
+        tracing::error!("Failed to store OIDC re-authentication state: {:?}", e);
+        let error = Some("Session error. Please try again.".to_string());
+        return page.render(admin_user, error).await;
+    }
+    Redirect::to(&url).into_response()
+}
+
+// Checks the provider's answer against the sign-in in progress; returns the
+// identity and when the sign-in started, or the message to show
+async fn verified_identity(
+    state: &AppState,
+    session: &Session,
+    client_ip: std::net::IpAddr,
+    query: &OidcCallbackQuery,
+) -> Result<(OidcIdentity, i64), &'static str> {
+    let oidc = state.oidc.as_ref().ok_or("Single sign-on is not configured.")?;
+    // One use only, whatever the outcome
+    let pending = session
+        .remove::<PendingOidcLogin>(OIDC_PENDING_KEY)
+        .await
+        .ok()
+        .flatten();
+    let Some(pending) = pending.filter(|p| !p.is_expired()) else {
+        return Err("Your sign-in has expired. Please try again.");
+    };
+
+    if let Some(error) = &query.error {
+        tracing::warn!(
+            "OIDC provider returned {} from {}: {}",
+            error,
+            client_ip,
+            query.error_description.as_deref().unwrap_or("")
+        );
+        return Err("The identity provider did not complete the sign-in.");
+    }
+    let returned_state = query.state.as_deref().unwrap_or("");
+    let state_matches: bool = returned_state.as_bytes().ct_eq(pending.state.as_bytes()).into();
+    let (true, Some(code)) = (state_matches, query.code.as_deref()) else {
+        tracing::warn!("OIDC callback with invalid state from {}", client_ip);
+        return Err("Invalid sign-in response. Please try again.");
+    };
+
+    match oidc.complete_login(code, &pending).await {
+        Ok(identity) => Ok((identity, pending.started_at)),
+        Err(e) => {
+            tracing::error!("OIDC login failed: {}", e);
+            Err("Single sign-on failed. Please try again.")
+        }
+    }
+}
+
+// Return from the provider for sudo mode: the identity must be the signed-in
+// admin's, and the provider must have authenticated them again just now
+async fn finish_reauth(
+    state: &AppState,
+    session: &Session,
+    admin_user: AdminUser,
+    pending_action: PendingAction,
+    verified: Result<(OidcIdentity, i64), &'static str>,
+) -> axum::response::Response {
+    let admin = match reauth::load_admin(state, &admin_user).await {
+        Ok(admin) => admin,
+        Err(response) => return response,
+    };
+    let page = ConfirmPage::new(state, session, &admin, pending_action).await;
+
+    let error = match &verified {
+        Err(message) => Some(*message),
+        Ok(_) if page.sso_label().is_none() => Some("Single sign-on cannot confirm this account."),
+        Ok((identity, _)) if !identity.email.eq_ignore_ascii_case(&admin.email) => {
+            tracing::warn!(
+                "OIDC re-authentication of {} returned another identity ({})",
+                admin.username,
+                identity.email
+            );
+            Some("You signed in to the identity provider as someone else.")
+        }
+        Ok((identity, started_at)) if !identity.authenticated_since(*started_at) => {
+            Some("The identity provider did not ask you to sign in again. Please try again.")
+        }
+        Ok(_) => None,
+    };
+    if let Some(error) = error {
+        return page.render(admin_user, Some(error.to_string())).await;
+    }
+
+    if let Err(e) = AdminUser::mark_reauthenticated(session).await {
+        tracing::error!("Failed to store re-authentication time: {:?}", e);
+        let error = Some("Session error. Please try again.".to_string());
+        return page.render(admin_user, error).await;
+    }
+    tracing::info!("Admin {} re-authenticated via OIDC", admin.username);
+    page.render_confirmed(admin_user).await
+}
+
+// Return from the identity provider (GET)
+pub async fn admin_oidc_callback(
+    State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    UserAgent(user_agent): UserAgent,
+    Extension(session): Extension<Session>,
+    Query(query): Query<OidcCallbackQuery>,
+) -> impl IntoResponse {
+    let Some(oidc) = state.oidc.as_ref() else {
+        return Redirect::to(&format!("{}/login", state.base_path)).into_response();
+    };
+    let verified = verified_identity(&state, &session, client_ip, &query).await;
+    // Set when a signed-in admin is confirming their identity for sudo mode
+    let pending_action = session.remove::<PendingAction>(OIDC_REAUTH_KEY).await.ok().flatten();
+    let signed_in = AdminUser::from_session(&session).await;
+    if let (Some(pending_action), Some(admin_user)) = (pending_action, signed_in) {
+        return finish_reauth(&state, &session, admin_user, pending_action, verified).await;
+    }
+
+    let identity = match verified {
+        Ok((identity, _)) => identity,
+        Err(message) => return login_error(&state, &session, message).await,
+    };
+
+    let admin = match repository::find_admin_by_email(&state.db, &identity.email).await {
+        Ok(Some(admin)) => admin,
+        Ok(None) if oidc.config.may_provision(&identity) => {
+            match provision_admin(&state, &identity, client_ip).await {
+                Ok(admin) => admin,
+                Err(e) => {
+                    tracing::error!("Failed to provision admin {}: {}", identity.email, e);
+                    return login_error(&state, &session, "Your admin account could not be created.")
+                        .await;
+                }
+            }
+        }
+        Ok(None) => {
+            tracing::warn!(
+                "OIDC login for {} (sub {}) has no admin account",
+                identity.email,
+                identity.subject
+            );
+            let attempt = LoginAttempt::new(LoginMethod::Sso, &identity.email, client_ip, &user_agent);
+            login_history::record_failure(&state.db, state.geoip.as_ref(), &attempt, "unknown_user").await;
+            return login_error(&state, &session, "There is no admin account for this identity.")
+                .await;
+        }
+        Err(e) => {
+            tracing::error!("Admin lookup for OIDC login failed: {:?}", e);
+            return login_error(&state, &session, "Database error. Please try again.").await;
+        }
+    };
+
+    let attempt =
+        LoginAttempt::new(LoginMethod::Sso, &admin.username, client_ip, &user_agent).for_admin(admin.id);
+    if admin.is_disabled() {
+        tracing::warn!("OIDC login refused: account disabled - {}", admin.username);
+        login_history::record_failure(&state.db, state.geoip.as_ref(), &attempt, "account_disabled").await;
+        return login_error(&state, &session, "This admin account is disabled.").await;
+    }
+
+    // The local second factor still applies to admins who enrolled one
+    if admin.has_two_factor() {
+        tracing::info!("OIDC sign-in accepted, awaiting second factor: {}", admin.username);
+        if let Err(e) = PendingTwoFactor::begin(&session, admin.id, &admin.username, false).await {
+            tracing::error!("Failed to set pending 2FA session: {:?}", e);
+            return login_error(&state, &session, "Session error. Please try again.").await;
+        }
+        if let Err(e) = session.cycle_id().await {
+            tracing::error!("Failed to cycle session ID: {:?}", e);
+        }
+        return Redirect::to(&format!("{}/login/2fa", state.base_path)).into_response();
+    }
+
+    let _ = PendingTwoFactor::clear(&session).await;
+    let admin_user = AdminUser::new(admin.id, admin.username.clone());
+    let ip = client_ip.to_string();
+    if let Err(e) = admin_user.login(&session, &state, &ip, &user_agent).await {
+        tracing::error!("Failed to set admin session: {:?}", e);
+        return login_error(&state, &session, "Session error. Please try again.").await;
+    }
+    if let Err(e) = session.cycle_id().await {
+        tracing::error!("Failed to cycle session ID: {:?}", e);
+        return login_error(&state, &session, "Session error. Please try again.").await;
+    }
+    csrf::rotate_token(&session).await;
+    let _ = AdminUser::mark_reauthenticated(&session).await;
+    if let Some(notice) = login_history::record_success(&state.db, state.geoip.as_ref(), &attempt).await {
+        login_notice::set(&session, notice).await;
+    }
+
+    tracing::info!("Admin login successful via OIDC: {}", admin.username);
+    Redirect::to(&format!("{}/dashboard", state.base_path)).into_response()
+}
+
+// Usernames are derived from preferred_username or the email's local part
+fn username_base(identity: &OidcIdentity) -> String {
+    let source = identity
+        .preferred_username
+        .as_deref()
+        .filter(|name| !name.contains('@'))
+        .unwrap_or_else(|| identity.email.split('@').next().unwrap_or_default());
+    let name: String = source
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+        .take(40)
+        .collect();
+    if name.is_empty() { "admin".to_string() } else { name }
+}
+
+// Creates an admin for an allowed identity. The random local password is
+// never shown; the admin can set one through the forgot-password flow.
+async fn provision_admin(
+    state: &AppState,
+    identity: &OidcIdentity,
+    client_ip: std::net::IpAddr,
+) -> Result<models::Admin, String> {
+    let oidc = state.oidc.as_ref().ok_or("OIDC is not configured")?;
+    let mut secret = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut secret);
+    let password_hash = state.password_hash.hash(&URL_SAFE_NO_PAD.encode(secret)).await?;
+
+    let base = username_base(identity);
+    for attempt in 1..=USERNAME_ATTEMPTS {
+        let username = if attempt == 1 { base.clone() } else { format!("{}-{}", base, attempt) };
+        match repository::find_admin_by_username(&state.db, &username).await {
+            Ok(Some(_)) => continue,
+            Ok(None) => {}
+            Err(e) => return Err(format!("{:?}", e)),
+        }
+
+        let admin_id = repository::create_admin_with_role(
+            &state.db,
+            &username,
+            &identity.email,
+            &password_hash,
+            &oidc.config.provision_role,
+        )
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => {
+                format!("OIDC_PROVISION_ROLE {} does not exist", oidc.config.provision_role)
+            }
+            other => format!("{:?}", other),
+        })?;
+        state
+            .password_policy
+            .remember(&state.db, PasswordOwner::Admin(admin_id), &password_hash)
+            .await;
+
+        tracing::info!("Provisioned admin {} for {} via OIDC", username, identity.email);
+        let actor = AdminUser::new(admin_id, username.clone());
+        let event = AuditEvent::new("provision", "admin", admin_id).with_after(serde_json::json!({
+            "username": username,
+            "email": identity.email,
+            "role": oidc.config.provision_role,
+            "groups": identity.groups,
+        }));
+        audit::record(&state.db, &actor, client_ip, event).await;
+
+        return repository::find_admin_by_id(&state.db, admin_id)
+            .await
+            .map_err(|e| format!("{:?}", e))?
+            .ok_or_else(|| "Provisioned admin disappeared".to_string());
+    }
+    Err(format!("No free username for {}", base))
+}
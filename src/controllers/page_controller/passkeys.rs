@@ -0,0 +1,522 @@
+use axum::{
+    Form, Json,
+    extract::{Extension, Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde_json::json;
+use tower_sessions::Session;
+use validator::Validate;
+
+use crate::controllers::auth_controller::{AdminUser, PendingTwoFactor};
+use crate::controllers::request_info::{ClientIp, UserAgent};
+use crate::middleware::{csrf, login_notice};
+use crate::repository;
+use crate::services::audit::{self, AuditEvent};
+use crate::services::login_history::{self, LoginAttempt, LoginMethod};
+use crate::services::login_throttle::{self, LoginRealm};
+use crate::services::webauthn::{self, PendingCeremony};
+use crate::state::AppState;
+use crate::views::templates::AdminPasskeysTemplate;
+
+use super::shared::{
+    ensure_csrf_token, with_remember_me, PasskeyLoginRequest, PasskeyRegistrationRequest, RenamePasskeyForm,
+};
+
+// Session keys of the ceremony in progress; each is used once
+const REGISTRATION_KEY: &str = "webauthn_registration";
+const LOGIN_KEY: &str = "webauthn_login";
+const REAUTH_KEY: &str = "webauthn_reauth";
+
+// Registration and login are driven by static/js/passkeys.js, which reads
+// `error` from failed responses
+fn json_error(status: StatusCode, message: &str) -> Response {
+    (status, Json(json!({ "error": message }))).into_response()
+}
+
+async fn take_ceremony(session: &Session, key: &str) -> Option<PendingCeremony> {
+    session
+        .remove::<PendingCeremony>(key)
+        .await
+        .ok()
+        .flatten()
+        .filter(|pending| !pending.is_expired())
+}
+
+async fn render_passkeys_page(
+    state: &AppState,
+    session: &Session,
+    admin_user: AdminUser,
+    error: Option<String>,
+    success: Option<String>,
+) -> Response {
+    let passkeys = match repository::find_passkeys_by_admin(&state.db, admin_user.id).await {
+        Ok(passkeys) => passkeys,
+        Err(e) => {
+            tracing::error!("Failed to load passkeys: {:?}", e);
+            Vec::new()
+        }
+    };
+
+    AdminPasskeysTemplate {
+        current_admin: Some(admin_user),
+        csrf_token: ensure_csrf_token(session).await,
+        passkeys,
+        error,
+        success,
+        base_path: state.base_path.clone(),
+    }
+    .into_response()
+}
+
+// Passkeys of the logged-in admin (GET)
+pub async fn admin_passkeys_page(
+    admin_user: AdminUser,
+    State(state): State<AppState>,
+    Extension(session): Extension<Session>,
+) -> impl IntoResponse {
+    render_passkeys_page(&state, &session, admin_user, None, None).await
+}
+
+// Start registering a passkey: options for navigator.credentials.create() (POST)
+pub async fn admin_passkey_options_submit(
+    admin_user: AdminUser,
+    State(state): State<AppState>,
+    Extension(session): Extension<Session>,
+) -> impl IntoResponse {
+    let existing = match repository::find_passkeys_by_admin(&state.db, admin_user.id).await {
+        Ok(passkeys) => passkeys,
+        Err(e) => {
+            tracing::error!("Failed to load passkeys: {:?}", e);
+            return json_error(StatusCode::INTERNAL_SERVER_ERROR, "Database error. Please try again.");
+        }
+    };
+    let exclude: Vec<Vec<u8>> = existing.into_iter().map(|p| p.credential_id).collect();
+    let (options, pending) =
+        state
+            .webauthn
+            .creation_options(admin_user.id, &admin_user.username, &exclude);
+
+    if let Err(e) = session.insert(REGISTRATION_KEY, pending).await {
+        tracing::error!("Failed to store passkey registration state: {:?}", e);
+        return json_error(StatusCode::INTERNAL_SERVER_ERROR, "Session error. Please try again.");
+    }
+    Json(options).into_response()
+}
+
+// Finish registering a passkey (POST)
+pub async fn admin_passkey_register_submit(
+    admin_user: AdminUser,
+    State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    Extension(session): Extension<Session>,
+    Json(request): Json<PasskeyRegistrationRequest>,
+) -> impl IntoResponse {
+    let pending = take_ceremony(&session, REGISTRATION_KEY)
+        .await
+        .filter(|pending| pending.admin_id == Some(admin_user.id));
+    let Some(pending) = pending else {
+        return json_error(StatusCode::BAD_REQUEST, "The request expired. Please try again.");
+    };
+    if request.validate().is_err() || request.name.trim().is_empty() {
+        return json_error(StatusCode::BAD_REQUEST, "Please enter a name of up to 100 characters.");
+    }
+
+    let credential = webauthn::decode_b64url(&request.client_data_json)
+        .and_then(|client_data| {
+            let attestation = webauthn::decode_b64url(&request.attestation_object)?;
+            state
+                .webauthn
+                .verify_registration(&pending, &client_data, &attestation)
+        });
+    let credential = match credential {
+        Ok(credential) => credential,
+        Err(e) => {
+            tracing::warn!("Passkey registration failed for {}: {}", admin_user.username, e);
+            return json_error(StatusCode::BAD_REQUEST, "The passkey could not be verified.");
+        }
+    };
+
+    let name = request.name.trim();
+    let created = repository::create_passkey(
+        &state.db,
+        admin_user.id,
+        name,
+        &credential.credential_id,
+        &credential.public_key,
+        credential.algorithm,
+        credential.sign_count,
+    )
+    .await;
+    let passkey_id = match created {
+        Ok(passkey_id) => passkey_id,
+        Err(sqlx::Error::Database(e)) if e.is_unique_violation() => {
+            return json_error(StatusCode::CONFLICT, "This passkey is already registered.");
+        }
+        Err(e) => {
+            tracing::error!("Failed to store passkey: {:?}", e);
+            return json_error(StatusCode::INTERNAL_SERVER_ERROR, "Failed to save the passkey.");
+        }
+    };
+
+    tracing::info!("Admin {} added passkey {}", admin_user.username, passkey_id);
+    let event = AuditEvent::new("create", "passkey", passkey_id).with_after(json!({
+        "name": name,
+        "algorithm": credential.algorithm,
+    }));
+    audit::record(&state.db, &admin_user, client_ip, event).await;
+    Json(json!({ "id": passkey_id })).into_response()
+}
+
+// Rename a passkey (POST)
+pub async fn admin_passkey_rename_submit(
+    admin_user: AdminUser,
+    State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    Extension(session): Extension<Session>,
+    Path(passkey_id): Path<i32>,
+    Form(form): Form<RenamePasskeyForm>,
+) -> impl IntoResponse {
+    if form.validate().is_err() || form.name.trim().is_empty() {
+        let error = Some("Please enter a name of up to 100 characters.".to_string());
+        return render_passkeys_page(&state, &session, admin_user, error, None).await;
+    }
+
+    let name = form.name.trim();
+    let renamed = repository::rename_passkey(&state.db, admin_user.id, passkey_id, name).await;
+    let (error, success) = match renamed {
+        Ok(true) => {
+            let event = AuditEvent::new("rename", "passkey", passkey_id)
+                .with_after(json!({ "name": name }));
+            audit::record(&state.db, &admin_user, client_ip, event).await;
+            (None, Some("Passkey renamed.".to_string()))
+        }
+        Ok(false) => (Some("Passkey not found.".to_string()), None),
+        Err(e) => {
+            tracing::error!("Failed to rename passkey: {:?}", e);
+            (Some("Failed to rename passkey.".to_string()), None)
+        }
+    };
+    render_passkeys_page(&state, &session, admin_user, error, success).await
+}
+
+// Remove a passkey (POST)
+pub async fn admin_passkey_delete_submit(
+    admin_user: AdminUser,
+    State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    Extension(session): Extension<Session>,
+    Path(passkey_id): Path<i32>,
+) -> impl IntoResponse {
+    let deleted = repository::delete_passkey(&state.db, admin_user.id, passkey_id).await;
+    let (error, success) = match deleted {
+        Ok(true) => {
+            tracing::info!("Admin {} removed passkey {}", admin_user.username, passkey_id);
+            let event = AuditEvent::new("delete", "passkey", passkey_id);
+            audit::record(&state.db, &admin_user, client_ip, event).await;
+            (None, Some("Passkey removed.".to_string()))
+        }
+        Ok(false) => (Some("Passkey not found or already removed.".to_string()), None),
+        Err(e) => {
+            tracing::error!("Failed to remove passkey: {:?}", e);
+            (Some("Failed to remove passkey.".to_string()), None)
+        }
+    };
+    render_passkeys_page(&state, &session, admin_user, error, success).await
+}
+
+// Start a passkey login: options for navigator.credentials.get() (POST)
+pub async fn admin_passkey_login_options(
+    State(state): State<AppState>,
+    Extension(session): Extension<Session>,
+) -> impl IntoResponse {
+    let (options, pending) = state.webauthn.request_options();
+    if let Err(e) = session.insert(LOGIN_KEY, pending).await {
+        tracing::error!("Failed to store passkey login state: {:?}", e);
+        return json_error(StatusCode::INTERNAL_SERVER_ERROR, "Session error. Please try again.");
+    }
+    Json(options).into_response()
+}
+
+// Finish a passkey login (POST). Passkeys require user verification, so they
+// stand in for both the password and the second factor.
+pub async fn admin_passkey_login_submit(
+    State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    UserAgent(user_agent): UserAgent,
+    Extension(session): Extension<Session>,
+    Json(request): Json<PasskeyLoginRequest>,
+) -> impl IntoResponse {
+    let Some(pending) = take_ceremony(&session, LOGIN_KEY).await else {
+        return json_error(StatusCode::BAD_REQUEST, "The request expired. Please try again.");
+    };
+    let decoded = (
+        webauthn::decode_b64url(&request.raw_id),
+        webauthn::decode_b64url(&request.client_data_json),
+        webauthn::decode_b64url(&request.authenticator_data),
+        webauthn::decode_b64url(&request.signature),
+    );
+    let (Ok(credential_id), Ok(client_data), Ok(authenticator_data), Ok(signature)) = decoded
+    else {
+        return json_error(StatusCode::BAD_REQUEST, "Malformed passkey response.");
+    };
+
+    let credential = match repository::find_passkey_by_credential_id(&state.db, &credential_id).await {
+        Ok(Some(credential)) => credential,
+        Ok(None) => {
+            tracing::warn!("Passkey login with unknown credential from {}", client_ip);
+            let attempt = LoginAttempt::new(LoginMethod::Passkey, "", client_ip, &user_agent);
+            login_history::record_failure(&state.db, state.geoip.as_ref(), &attempt, "unknown_passkey").await;
+            return json_error(StatusCode::UNAUTHORIZED, "This passkey is not registered.");
+        }
+        Err(e) => {
+            tracing::error!("Passkey lookup failed: {:?}", e);
+            return json_error(StatusCode::INTERNAL_SERVER_ERROR, "Database error. Please try again.");
+        }
+    };
+
+    let attempt = LoginAttempt::new(LoginMethod::Passkey, &credential.username, client_ip, &user_agent)
+        .for_admin(credential.admin_id);
+    let throttle =
+        login_throttle::check(&state.redis, LoginRealm::Admin, &credential.username, client_ip).await;
+    if let Some(message) = throttle.message() {
+        tracing::warn!(
+            "Passkey login throttled for {} from {}: {:?}",
+            credential.username,
+            client_ip,
+            throttle
+        );
+        login_history::record_failure(&state.db, state.geoip.as_ref(), &attempt, "throttled").await;
+        return json_error(StatusCode::TOO_MANY_REQUESTS, &message);
+    }
+
+    let handle_matches = request
+        .user_handle
+        .as_deref()
+        .filter(|handle| !handle.is_empty())
+        .is_none_or(|handle| {
+            webauthn::decode_b64url(handle)
+                .ok()
+                .and_then(|bytes| webauthn::admin_id_from_user_handle(&bytes))
+                == Some(credential.admin_id)
+        });
+    let verified = if handle_matches {
+        state.webauthn.verify_assertion(
+            &pending,
+            &credential.public_key,
+            i64::from(credential.algorithm),
+            &client_data,
+            &authenticator_data,
+            &signature,
+        )
+    } else {
+        Err("User handle does not match the credential".to_string())
+    };
+    let sign_count = match verified {
+        Ok(sign_count) => sign_count,
+        Err(e) => {
+            tracing::warn!("Passkey login failed for {}: {}", credential.username, e);
+            login_history::record_failure(&state.db, state.geoip.as_ref(), &attempt, "invalid_passkey").await;
+            let throttle = login_throttle::record_failure(
+                &state.redis,
+                &state.login_throttle,
+                LoginRealm::Admin,
+                &credential.username,
+                client_ip,
+            )
+            .await;
+            let message = throttle
+                .message()
+                .unwrap_or_else(|| "The passkey could not be verified.".to_string());
+            return json_error(StatusCode::UNAUTHORIZED, &message);
+        }
+    };
+
+    // A counter that went backwards means the credential may have been cloned
+    if !webauthn::sign_count_ok(credential.sign_count, sign_count) {
+        tracing::warn!(
+            "Passkey {} of {} reported sign count {} after {}; possible cloned authenticator",
+            credential.id,
+            credential.username,
+            sign_count,
+            credential.sign_count
+        );
+        login_history::record_failure(&state.db, state.geoip.as_ref(), &attempt, "sign_count_mismatch").await;
+        return json_error(
+            StatusCode::UNAUTHORIZED,
+            "This passkey was refused. Remove it and add it again, or sign in with your password.",
+        );
+    }
+    match repository::record_passkey_use(&state.db, credential.id, credential.sign_count, sign_count).await {
+        Ok(true) => {}
+        Ok(false) => {
+            tracing::warn!("Passkey {} was used concurrently; login refused", credential.id);
+            login_history::record_failure(&state.db, state.geoip.as_ref(), &attempt, "sign_count_mismatch").await;
+            return json_error(StatusCode::UNAUTHORIZED, "The passkey could not be verified.");
+        }
+        Err(e) => {
+            tracing::error!("Failed to update passkey sign count: {:?}", e);
+            return json_error(StatusCode::INTERNAL_SERVER_ERROR, "Database error. Please try again.");
+        }
+    }
+
+    login_throttle::record_success(&state.redis, LoginRealm::Admin, &credential.username).await;
+    let _ = PendingTwoFactor::clear(&session).await;
+    let admin_user = AdminUser::new(credential.admin_id, credential.username.clone());
+    let ip = client_ip.to_string();
+    let session_ref = match admin_user.login(&session, &state, &ip, &user_agent).await {
+        Ok(session_ref) => session_ref,
+        Err(e) => {
+            tracing::error!("Failed to set admin session: {:?}", e);
+            return json_error(StatusCode::INTERNAL_SERVER_ERROR, "Session error. Please try again.");
+        }
+    };
+    if let Err(e) = session.cycle_id().await {
+        tracing::error!("Failed to cycle session ID: {:?}", e);
+        return json_error(StatusCode::INTERNAL_SERVER_ERROR, "Session error. Please try again.");
+    }
+    csrf::rotate_token(&session).await;
+    let _ = AdminUser::mark_reauthenticated(&session).await;
+    if let Some(notice) = login_history::record_success(&state.db, state.geoip.as_ref(), &attempt).await {
+        login_notice::set(&session, notice).await;
+    }
+
+    tracing::info!("Admin login successful via passkey: {}", credential.username);
+    let response =
+        Json(json!({ "redirect": format!("{}/dashboard", state.base_path) })).into_response();
+    if request.remember_me && state.remember_me.enabled() {
+        return with_remember_me(&state, credential.admin_id, &session_ref, response).await;
+    }
+    response
+}
+
+// Start confirming the signed-in admin with one of their passkeys, for the
+// sudo-mode page (POST)
+pub async fn admin_passkey_reauth_options(
+    admin_user: AdminUser,
+    State(state): State<AppState>,
+    Extension(session): Extension<Session>,
+) -> impl IntoResponse {
+    let passkeys = repository::find_passkeys_by_admin(&state.db, admin_user.id).await;
+    let credential_ids: Vec<Vec<u8>> = match passkeys {
+        Ok(passkeys) => passkeys.into_iter().map(|passkey| passkey.credential_id).collect(),
+        Err(e) => {
+            tracing::error!("Failed to load passkeys: {:?}", e);
+            return json_error(StatusCode::INTERNAL_SERVER_ERROR, "Database error. Please try again.");
+        }
+    };
+    if credential_ids.is_empty() {
+        return json_error(StatusCode::BAD_REQUEST, "You have no passkeys.");
+    }
+
+    let (options, pending) = state.webauthn.reauth_options(admin_user.id, &credential_ids);
+    if let Err(e) = session.insert(REAUTH_KEY, pending).await {
+        tracing::error!("Failed to store passkey re-authentication state: {:?}", e);
+        return json_error(StatusCode::INTERNAL_SERVER_ERROR, "Session error. Please try again.");
+    }
+    Json(options).into_response()
+}
+
+// Finish a passkey confirmation (POST). Opens the sudo-mode window; the page
+// then re-posts the original form, which the reauth layer now lets through.
+pub async fn admin_passkey_reauth_submit(
+    admin_user: AdminUser,
+    State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    Extension(session): Extension<Session>,
+    Json(request): Json<PasskeyLoginRequest>,
+) -> impl IntoResponse {
+    let Some(pending) = take_ceremony(&session, REAUTH_KEY)
+        .await
+        .filter(|pending| pending.admin_id == Some(admin_user.id))
+    else {
+        return json_error(StatusCode::BAD_REQUEST, "The request expired. Please try again.");
+    };
+    let decoded = (
+        webauthn::decode_b64url(&request.raw_id),
+        webauthn::decode_b64url(&request.client_data_json),
+        webauthn::decode_b64url(&request.authenticator_data),
+        webauthn::decode_b64url(&request.signature),
+    );
+    let (Ok(credential_id), Ok(client_data), Ok(authenticator_data), Ok(signature)) = decoded
+    else {
+        return json_error(StatusCode::BAD_REQUEST, "Malformed passkey response.");
+    };
+
+    let credential = match repository::find_passkey_by_credential_id(&state.db, &credential_id).await {
+        Ok(Some(credential)) if credential.admin_id == admin_user.id => credential,
+        Ok(_) => {
+            tracing::warn!(
+                "Re-authentication of {} with another account's passkey from {}",
+                admin_user.username,
+                client_ip
+            );
+            return json_error(StatusCode::UNAUTHORIZED, "This passkey does not belong to your account.");
+        }
+        Err(e) => {
+            tracing::error!("Passkey lookup failed: {:?}", e);
+            return json_error(StatusCode::INTERNAL_SERVER_ERROR, "Database error. Please try again.");
+        }
+    };
+
+    let throttle =
+        login_throttle::check(&state.redis, LoginRealm::Admin, &credential.username, client_ip).await;
+    if let Some(message) = throttle.message() {
+        return json_error(StatusCode::TOO_MANY_REQUESTS, &message);
+    }
+    let sign_count = match state.webauthn.verify_assertion(
+        &pending,
+        &credential.public_key,
+        i64::from(credential.algorithm),
+        &client_data,
+        &authenticator_data,
+        &signature,
+    ) {
+        Ok(sign_count) => sign_count,
+        Err(e) => {
+            tracing::warn!("Passkey re-authentication failed for {}: {}", credential.username, e);
+            let throttle = login_throttle::record_failure(
+                &state.redis,
+                &state.login_throttle,
+                LoginRealm::Admin,
+                &credential.username,
+                client_ip,
+            )
+            .await;
+            let message = throttle
+                .message()
+                .unwrap_or_else(|| "The passkey could not be verified.".to_string());
+            return json_error(StatusCode::UNAUTHORIZED, &message);
+        }
+    };
+
+    if !webauthn::sign_count_ok(credential.sign_count, sign_count) {
+        tracing::warn!(
+            "Passkey {} of {} reported sign count {} after {}; possible cloned authenticator",
+            credential.id,
+            credential.username,
+            sign_count,
+            credential.sign_count
+        );
+        return json_error(
+            StatusCode::UNAUTHORIZED,
+            "This passkey was refused. Remove it and add it again.",
+        );
+    }
+    match repository::record_passkey_use(&state.db, credential.id, credential.sign_count, sign_count).await {
+        Ok(true) => {}
+        Ok(false) => return json_error(StatusCode::UNAUTHORIZED, "The passkey could not be verified."),
+        Err(e) => {
+            tracing::error!("Failed to update passkey sign count: {:?}", e);
+            return json_error(StatusCode::INTERNAL_SERVER_ERROR, "Database error. Please try again.");
+        }
+    }
+
+    login_throttle::record_success(&state.redis, LoginRealm::Admin, &credential.username).await;
+    if let Err(e) = AdminUser::mark_reauthenticated(&session).await {
+        tracing::error!("Failed to store re-authentication time: {:?}", e);
+        return json_error(StatusCode::INTERNAL_SERVER_ERROR, "Session error. Please try again.");
+    }
+    tracing::info!("Admin {} re-authenticated with a passkey", credential.username);
+    Json(json!({ "ok": true })).into_response()
+}
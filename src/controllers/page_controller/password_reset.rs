@@ -0,0 +1,267 @@
+use askama::Template;
+use axum::{
+    Form,
+    extract::{Extension, Query, State},
+    response::IntoResponse,
+};
+use tower_sessions::Session;
+use tower_sessions_redis_store::fred::prelude::KeysInterface;
+use tower_sessions_redis_store::fred::types::{Expiration, SetOptions};
+use validator::Validate;
+
+use crate::controllers::auth_controller::AdminUser;
+use crate::controllers::request_info::ClientIp;
+use crate::models::PasswordOwner;
+use crate::repository;
+use crate::services::audit::{self, AuditEvent};
+use crate::services::login_throttle::{self, LoginRealm};
+use crate::services::{admin_sessions, password_policy, password_reset};
+use crate::state::AppState;
+use crate::views::templates::{
+    AdminForgotPasswordTemplate, AdminPasswordResetEmail, AdminResetPasswordTemplate,
+};
+
+use super::shared::{
+    ensure_csrf_token, ForgotPasswordForm, ResetPasswordForm, ResetPasswordQuery,
+};
+
+// At most one reset email per admin in this window
+const RESET_EMAIL_COOLDOWN_SECONDS: i64 = 60;
+
+// Forgot password page (GET)
+pub async fn admin_forgot_password_page(
+    State(state): State<AppState>,
+    Extension(session): Extension<Session>,
+) -> impl IntoResponse {
+    AdminForgotPasswordTemplate {
+        error: None,
+        sent: false,
+        csrf_token: ensure_csrf_token(&session).await,
+        base_path: state.base_path.clone(),
+    }
+}
+
+// Forgot password submission (POST).
+// The response is the same whether or not the email belongs to an admin.
+pub async fn admin_forgot_password_submit(
+    State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    Extension(session): Extension<Session>,
+    Form(form): Form<ForgotPasswordForm>,
+) -> impl IntoResponse {
+    let error = if form.validate().is_err() {
+        Some("Please enter a valid email address".to_string())
+    } else {
+        None
+    };
+    if error.is_some() {
+        return AdminForgotPasswordTemplate {
+            error,
+            sent: false,
+            csrf_token: ensure_csrf_token(&session).await,
+            base_path: state.base_path.clone(),
+        };
+    }
+
+    match repository::find_admin_by_email(&state.db, form.email.trim()).await {
+        Ok(Some(admin)) if admin.is_disabled() => {
+            tracing::info!("Password reset requested for disabled admin {}", admin.username);
+        }
+        Ok(Some(admin)) => send_reset_email(&state, &admin, &client_ip.to_string()).await,
+        Ok(None) => {
+            tracing::info!("Password reset requested for unknown email from {}", client_ip);
+        }
+        Err(e) => {
+            tracing::error!("Failed to look up admin for password reset: {:?}", e);
+        }
+    }
+
+    AdminForgotPasswordTemplate {
+        error: None,
+        sent: true,
+        csrf_token: ensure_csrf_token(&session).await,
+        base_path: state.base_path.clone(),
+    }
+}
+
+async fn send_reset_email(state: &AppState, admin: &crate::models::Admin, requested_ip: &str) {
+    let cooldown: Result<Option<String>, _> = state
+        .redis
+        .set(
+            format!("password_reset:cooldown:{}", admin.id),
+            "1",
+            Some(Expiration::EX(RESET_EMAIL_COOLDOWN_SECONDS)),
+            Some(SetOptions::NX),
+            false,
+        )
+        .await;
+    if let Ok(None) = cooldown {
+        tracing::info!("Password reset for {} skipped: cooldown active", admin.username);
+        return;
+    }
+
+    let (token, token_hash) = password_reset::generate_token();
+    if let Err(e) = repository::create_admin_password_reset(
+        &state.db,
+        admin.id,
+        &token_hash,
+        password_reset::RESET_TOKEN_TTL_MINUTES,
+        requested_ip,
+    )
+    .await
+    {
+        tracing::error!("Failed to store password reset token: {:?}", e);
+        return;
+    }
+
+    let email = AdminPasswordResetEmail {
+        username: admin.username.clone(),
+        reset_url: format!(
+            "{}{}/reset-password?token={}",
+            state.public_url, state.base_path, token
+        ),
+        ttl_minutes: password_reset::RESET_TOKEN_TTL_MINUTES,
+    };
+    let body = match email.render() {
+        Ok(body) => body,
+        Err(e) => {
+            tracing::error!("Failed to render password reset email: {:?}", e);
+            return;
+        }
+    };
+
+    match state
+        .mailer
+        .send(&admin.email, "Reset your admin password", body)
+        .await
+    {
+        Ok(()) => tracing::info!("Password reset email sent to admin {}", admin.username),
+        Err(e) => tracing::error!("Failed to send password reset email: {}", e),
+    }
+}
+
+// Reset password page (GET)
+pub async fn admin_reset_password_page(
+    State(state): State<AppState>,
+    Extension(session): Extension<Session>,
+    Query(query): Query<ResetPasswordQuery>,
+) -> impl IntoResponse {
+    let token = match query.token {
+        Some(token) => {
+            let token_hash = password_reset::hash_token(&token);
+            match repository::find_valid_admin_password_reset(&state.db, &token_hash).await {
+                Ok(Some(_)) => Some(token),
+                Ok(None) => None,
+                Err(e) => {
+                    tracing::error!("Failed to look up password reset token: {:?}", e);
+                    None
+                }
+            }
+        }
+        None => None,
+    };
+
+    AdminResetPasswordTemplate {
+        error: None,
+        token,
+        completed: false,
+        password_errors: Vec::new(),
+        csrf_token: ensure_csrf_token(&session).await,
+        base_path: state.base_path.clone(),
+    }
+}
+
+async fn render_reset_page(
+    state: &AppState,
+    session: &Session,
+    error: Option<String>,
+    token: Option<String>,
+    completed: bool,
+) -> AdminResetPasswordTemplate {
+    AdminResetPasswordTemplate {
+        error,
+        token,
+        completed,
+        password_errors: Vec::new(),
+        csrf_token: ensure_csrf_token(session).await,
+        base_path: state.base_path.clone(),
+    }
+}
+
+// Reset password submission (POST)
+pub async fn admin_reset_password_submit(
+    State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    Extension(session): Extension<Session>,
+    Form(form): Form<ResetPasswordForm>,
+) -> impl IntoResponse {
+    let token_hash = password_reset::hash_token(&form.token);
+    let (reset_id, admin_id) =
+        match repository::find_valid_admin_password_reset(&state.db, &token_hash).await {
+            Ok(Some(reset)) => reset,
+            Ok(None) => return render_reset_page(&state, &session, None, None, false).await,
+            Err(e) => {
+                tracing::error!("Failed to look up password reset token: {:?}", e);
+                return render_reset_page(&state, &session, None, None, false).await;
+            }
+        };
+
+    if let Err(errors) = form.validate() {
+        let message = if errors.field_errors().contains_key("password_confirm") {
+            "Passwords do not match"
+        } else {
+            "Please enter a new password"
+        };
+        let error = Some(message.to_string());
+        return render_reset_page(&state, &session, error, Some(form.token), false).await;
+    }
+
+    let owner = PasswordOwner::Admin(admin_id);
+    if let Err(errors) = state
+        .password_policy
+        .validate(&state.db, "password", &form.password, Some(owner))
+        .await
+    {
+        let mut page = render_reset_page(&state, &session, None, Some(form.token), false).await;
+        page.password_errors = password_policy::field_messages(&errors, "password");
+        return page;
+    }
+
+    let password_hash = match state.password_hash.hash(&form.password).await {
+        Ok(hash) => hash,
+        Err(e) => {
+            tracing::error!("Failed to hash password: {:?}", e);
+            let error = Some("Failed to change password. Please try again.".to_string());
+            return render_reset_page(&state, &session, error, Some(form.token), false).await;
+        }
+    };
+
+    match repository::complete_admin_password_reset(&state.db, reset_id, admin_id, &password_hash)
+        .await
+    {
+        Ok(true) => {}
+        Ok(false) => return render_reset_page(&state, &session, None, None, false).await,
+        Err(e) => {
+            tracing::error!("Failed to complete password reset: {:?}", e);
+            let error = Some("Failed to change password. Please try again.".to_string());
+            return render_reset_page(&state, &session, error, Some(form.token), false).await;
+        }
+    }
+
+    state.password_policy.remember(&state.db, owner, &password_hash).await;
+
+    if let Err(e) = admin_sessions::revoke_all(&state.redis, admin_id).await {
+        tracing::error!("Failed to revoke sessions for admin {}: {}", admin_id, e);
+    }
+    let _ = repository::delete_remember_tokens_by_admin(&state.db, admin_id).await;
+    if let Ok(Some(admin)) = repository::find_admin_by_id(&state.db, admin_id).await {
+        let _ =
+            login_throttle::unlock_username(&state.redis, LoginRealm::Admin, &admin.username).await;
+        tracing::info!("Admin {} reset their password", admin.username);
+        let actor = AdminUser::new(admin.id, admin.username);
+        let event = AuditEvent::new("password_reset", "admin", admin_id);
+        audit::record(&state.db, &actor, client_ip, event).await;
+    }
+
+    render_reset_page(&state, &session, None, None, true).await
+}
@@ -0,0 +1,213 @@
+use axum::{
+    Form,
+    extract::{Extension, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use tower_sessions::Session;
+use validator::Validate;
+
+use crate::controllers::auth_controller::AdminUser;
+use crate::controllers::request_info::ClientIp;
+use crate::models::{self, PasswordOwner};
+use crate::repository;
+use crate::services::audit::{self, AuditEvent};
+use crate::services::password_hash;
+use crate::services::password_policy;
+use crate::state::AppState;
+use crate::views::templates::{AdminErrorTemplate, AdminProfileTemplate};
+
+use super::shared::{ensure_csrf_token, sign_out_everywhere, AdminEmailForm, ChangePasswordForm};
+
+struct ProfileMessages {
+    error: Option<String>,
+    success: Option<String>,
+    password_error: Option<String>,
+    password_errors: Vec<String>,
+    password_success: Option<String>,
+}
+
+impl ProfileMessages {
+    fn none() -> Self {
+        Self {
+            error: None,
+            success: None,
+            password_error: None,
+            password_errors: Vec::new(),
+            password_success: None,
+        }
+    }
+}
+
+fn error_page(state: &AppState, admin_user: AdminUser, code: StatusCode, message: &str) -> Response {
+    let template = AdminErrorTemplate {
+        error_code: code.as_u16(),
+        error_message: message.to_string(),
+        current_admin: Some(admin_user),
+        base_path: state.base_path.clone(),
+    };
+    (code, template).into_response()
+}
+
+async fn load_admin(state: &AppState, admin_user: AdminUser) -> Result<(AdminUser, models::Admin), Response> {
+    match repository::find_admin_by_id(&state.db, admin_user.id).await {
+        Ok(Some(admin)) => Ok((admin_user, admin)),
+        Ok(None) => Err(error_page(state, admin_user, StatusCode::NOT_FOUND, "Admin account not found.")),
+        Err(e) => {
+            tracing::error!("Failed to load admin {}: {:?}", admin_user.id, e);
+            Err(error_page(state, admin_user, StatusCode::INTERNAL_SERVER_ERROR, "Failed to load admin account."))
+        }
+    }
+}
+
+// Renders the profile page; `email` overrides the stored address so a
+// rejected submission keeps what the admin entered.
+async fn render_profile_page(
+    state: &AppState,
+    session: &Session,
+    admin_user: AdminUser,
+    email: Option<String>,
+    messages: ProfileMessages,
+) -> Response {
+    let (admin_user, admin) = match load_admin(state, admin_user).await {
+        Ok(loaded) => loaded,
+        Err(response) => return response,
+    };
+    let roles = repository::find_admin_roles(&state.db, admin.id).await.unwrap_or_default();
+
+    AdminProfileTemplate {
+        username: admin.username.clone(),
+        email: email.unwrap_or_else(|| admin.email.clone()),
+        roles,
+        two_factor_enabled: admin.has_two_factor(),
+        created_at: admin.created_at.as_ref().map(crate::utils::format_datetime).unwrap_or_default(),
+        error: messages.error,
+        success: messages.success,
+        password_error: messages.password_error,
+        password_errors: messages.password_errors,
+        password_success: messages.password_success,
+        current_admin: Some(admin_user),
+        csrf_token: ensure_csrf_token(session).await,
+        base_path: state.base_path.clone(),
+    }
+    .into_response()
+}
+
+// Own account details (GET)
+pub async fn admin_profile_page(
+    admin_user: AdminUser,
+    State(state): State<AppState>,
+    Extension(session): Extension<Session>,
+) -> impl IntoResponse {
+    render_profile_page(&state, &session, admin_user, None, ProfileMessages::none()).await
+}
+
+// Email change (POST)
+pub async fn admin_profile_submit(
+    admin_user: AdminUser,
+    State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    Extension(session): Extension<Session>,
+    Form(form): Form<AdminEmailForm>,
+) -> impl IntoResponse {
+    let (admin_user, before) = match load_admin(&state, admin_user).await {
+        Ok(loaded) => loaded,
+        Err(response) => return response,
+    };
+    let email = form.email.trim().to_string();
+
+    if form.validate().is_err() {
+        let messages = ProfileMessages {
+            error: Some("Please enter a valid email address".to_string()),
+            ..ProfileMessages::none()
+        };
+        return render_profile_page(&state, &session, admin_user, Some(email), messages).await;
+    }
+    if email == before.email {
+        let messages = ProfileMessages { success: Some("Email unchanged".to_string()), ..ProfileMessages::none() };
+        return render_profile_page(&state, &session, admin_user, None, messages).await;
+    }
+
+    if let Err(e) = repository::update_admin_email(&state.db, admin_user.id, &email).await {
+        tracing::error!("Failed to change email of admin {}: {:?}", admin_user.id, e);
+        let error = if e.to_string().contains("Duplicate entry") {
+            "Email already in use"
+        } else {
+            "Failed to update email"
+        };
+        let messages = ProfileMessages { error: Some(error.to_string()), ..ProfileMessages::none() };
+        return render_profile_page(&state, &session, admin_user, Some(email), messages).await;
+    }
+
+    tracing::info!("Admin {} changed their email", admin_user.username);
+    if let Ok(Some(after)) = repository::find_admin_by_id(&state.db, admin_user.id).await {
+        audit::record(&state.db, &admin_user, client_ip, AuditEvent::updated(Some(&before), &after)).await;
+    }
+    let messages = ProfileMessages { success: Some("Email updated".to_string()), ..ProfileMessages::none() };
+    render_profile_page(&state, &session, admin_user, None, messages).await
+}
+
+// Password change (POST); signs the admin out of every other session
+pub async fn admin_profile_password_submit(
+    admin_user: AdminUser,
+    State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    Extension(session): Extension<Session>,
+    Form(form): Form<ChangePasswordForm>,
+) -> impl IntoResponse {
+    let (admin_user, admin) = match load_admin(&state, admin_user).await {
+        Ok(loaded) => loaded,
+        Err(response) => return response,
+    };
+
+    let owner = PasswordOwner::Admin(admin.id);
+    let mut password_errors = Vec::new();
+    let password_error = if !password_hash::verify(&form.current_password, &admin.password_hash).await {
+        Some("Current password is incorrect".to_string())
+    } else if form.validate().is_err() {
+        Some("New passwords do not match".to_string())
+    } else if let Err(errors) = state
+        .password_policy
+        .validate(&state.db, "new_password", &form.new_password, Some(owner))
+        .await
+    {
+        password_errors = password_policy::field_messages(&errors, "new_password");
+        None
+    } else {
+        None
+    };
+    if password_error.is_some() || !password_errors.is_empty() {
+        let messages = ProfileMessages { password_error, password_errors, ..ProfileMessages::none() };
+        return render_profile_page(&state, &session, admin_user, None, messages).await;
+    }
+
+    let stored = match state.password_hash.hash(&form.new_password).await {
+        Ok(password_hash) => repository::update_admin_password(&state.db, admin.id, &password_hash)
+            .await
+            .map(|()| password_hash),
+        Err(e) => Err(sqlx::Error::Protocol(format!("Password hashing failed: {}", e))),
+    };
+    let password_hash = match stored {
+        Ok(password_hash) => password_hash,
+        Err(e) => {
+            tracing::error!("Failed to change password of admin {}: {:?}", admin.id, e);
+            let messages = ProfileMessages {
+                password_error: Some("Failed to change password".to_string()),
+                ..ProfileMessages::none()
+            };
+            return render_profile_page(&state, &session, admin_user, None, messages).await;
+        }
+    };
+
+    state.password_policy.remember(&state.db, owner, &password_hash).await;
+    sign_out_everywhere(&state, admin.id, admin_user.session_ref.as_deref()).await;
+    tracing::info!("Admin {} changed their password", admin_user.username);
+    let event = AuditEvent::new("change_password", "admin", admin.id);
+    audit::record(&state.db, &admin_user, client_ip, event).await;
+
+    let messages = ProfileMessages {
+        password_success: Some("Password changed. Your other sessions have been signed out.".to_string()),
+        ..ProfileMessages::none()
+    };
+    render_profile_page(&state, &session, admin_user, None, messages).await
+}
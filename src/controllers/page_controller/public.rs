@@ -8,17 +8,27 @@ use tower_sessions::Session;
 use validator::Validate;
 
 use crate::controllers::auth_controller::{
-    AdminUser, AuthUser, OptionalAdminUser, OptionalAuthUser,
+    AdminUser, AuthUser, OptionalAdminUser, OptionalAuthUser, PendingTwoFactor,
+    PENDING_2FA_MAX_ATTEMPTS,
 };
-use crate::models;
+use crate::controllers::request_info::{ClientIp, UserAgent};
+use crate::middleware::csrf;
+use crate::models::PasswordOwner;
+use crate::middleware::login_notice;
 use crate::repository;
+use crate::services::login_history::{self, LoginAttempt, LoginMethod};
+use crate::services::login_throttle::{self, LoginRealm};
+use crate::services::password_hash;
 use crate::state::AppState;
-use crate::utils;
 use crate::views::templates::{
-    AdminLoginTemplate, ErrorTemplate, IndexTemplate,
+    AdminLoginTemplate, AdminLoginTwoFactorTemplate, ErrorTemplate, IndexTemplate,
 };
 
-use super::shared::{ensure_csrf_token, validate_csrf, LoginForm};
+use super::impersonation::end_impersonation;
+use super::shared::{
+    ensure_csrf_token, impersonation_banner, sso_label, verify_second_factor, with_remember_me,
+    LoginForm, SecondFactorCheck, TwoFactorCodeForm,
+};
 
 // Index handler
 pub async fn index(
@@ -39,6 +49,7 @@ pub async fn index(
         message: "Hello from Axum + Askama!".to_string(),
         user: user.map(|u| u.username),
         flash_success,
+        impersonation: impersonation_banner(&session).await,
         base_path: state.base_path.clone(),
     };
 
@@ -59,6 +70,9 @@ pub async fn admin_login_page(
 
     AdminLoginTemplate {
         error: None,
+        lockout: None,
+        sso_label: sso_label(&state),
+        remember_me_days: state.remember_me.lifetime_days,
         csrf_token: ensure_csrf_token(&session).await,
         base_path: state.base_path.clone(),
     }
@@ -68,25 +82,45 @@ pub async fn admin_login_page(
 // Admin login submission (POST)
 pub async fn admin_login_submit(
     State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    UserAgent(user_agent): UserAgent,
     Extension(session): Extension<Session>,
     Form(credentials): Form<LoginForm>,
 ) -> impl IntoResponse {
     tracing::debug!("Admin login attempt for user: {}", credentials.username);
+    let remember_me = credentials.remember_me.is_some() && state.remember_me.enabled();
+    let attempt = LoginAttempt::new(LoginMethod::Password, &credentials.username, client_ip, &user_agent);
 
-    if !validate_csrf(&session, &credentials.csrf_token).await {
-        tracing::warn!("Admin login failed: Invalid CSRF token");
+    if credentials.validate().is_err() {
+        tracing::warn!("Admin login failed: Invalid login data");
+        login_history::record_failure(&state.db, state.geoip.as_ref(), &attempt, "invalid_request").await;
         return AdminLoginTemplate {
-            error: Some("Invalid CSRF token".to_string()),
+            error: Some("Invalid login data".to_string()),
+            lockout: None,
+            sso_label: sso_label(&state),
+            remember_me_days: state.remember_me.lifetime_days,
             csrf_token: ensure_csrf_token(&session).await,
             base_path: state.base_path.clone(),
         }
         .into_response();
     }
 
-    if credentials.validate().is_err() {
-        tracing::warn!("Admin login failed: Invalid login data");
+    let throttle =
+        login_throttle::check(&state.redis, LoginRealm::Admin, &credentials.username, client_ip)
+            .await;
+    if let Some(message) = throttle.message() {
+        tracing::warn!(
+            "Admin login throttled for {} from {}: {:?}",
+            credentials.username,
+            client_ip,
+            throttle
+        );
+        login_history::record_failure(&state.db, state.geoip.as_ref(), &attempt, "throttled").await;
         return AdminLoginTemplate {
-            error: Some("Invalid login data".to_string()),
+            error: None,
+            lockout: Some(message),
+            sso_label: sso_label(&state),
+            remember_me_days: state.remember_me.lifetime_days,
             csrf_token: ensure_csrf_token(&session).await,
             base_path: state.base_path.clone(),
         }
@@ -96,36 +130,119 @@ pub async fn admin_login_submit(
     match repository::find_admin_by_username(&state.db, &credentials.username).await {
         Ok(Some(admin)) => {
             tracing::debug!("Admin user found: {}", admin.username);
-            if utils::verify_password_hash(&admin.password_hash, &credentials.password).await {
-                tracing::info!("Admin login successful: {}", admin.username);
-                let admin_user = AdminUser::new(admin.id, admin.username.clone());
-                if let Err(e) = admin_user.login(&session).await {
-                    tracing::error!("Failed to set admin session: {:?}", e);
+            let attempt = attempt.for_admin(admin.id);
+            if password_hash::verify(&credentials.password, &admin.password_hash).await {
+                if admin.is_disabled() {
+                    tracing::warn!("Admin login refused: account disabled - {}", admin.username);
+                    login_history::record_failure(&state.db, state.geoip.as_ref(), &attempt, "account_disabled")
+                        .await;
                     return AdminLoginTemplate {
-                        error: Some("Session error. Please try again.".to_string()),
+                        error: Some("This admin account is disabled.".to_string()),
+                        lockout: None,
+                        sso_label: sso_label(&state),
+                        remember_me_days: state.remember_me.lifetime_days,
                         csrf_token: ensure_csrf_token(&session).await,
                         base_path: state.base_path.clone(),
                     }
                     .into_response();
                 }
+                // The password alone must not cause writes to the admin row:
+                // hashes are upgraded below, once the login is complete, so a
+                // 2FA admin's hash keeps its old settings until a password
+                // change
+                if admin.has_two_factor() {
+                    tracing::info!("Admin password accepted, awaiting second factor: {}", admin.username);
+                    if let Err(e) =
+                        PendingTwoFactor::begin(&session, admin.id, &admin.username, remember_me).await
+                    {
+                        tracing::error!("Failed to set pending 2FA session: {:?}", e);
+                        return AdminLoginTemplate {
+                            error: Some("Session error. Please try again.".to_string()),
+                            lockout: None,
+                            sso_label: sso_label(&state),
+                            remember_me_days: state.remember_me.lifetime_days,
+                            csrf_token: ensure_csrf_token(&session).await,
+                            base_path: state.base_path.clone(),
+                        }
+                        .into_response();
+                    }
+                    if let Err(e) = session.cycle_id().await {
+                        tracing::error!("Failed to cycle session ID: {:?}", e);
+                    }
+                    return Redirect::to(&format!("{}/login/2fa", state.base_path)).into_response();
+                }
+
+                tracing::info!("Admin login successful: {}", admin.username);
+                login_throttle::record_success(&state.redis, LoginRealm::Admin, &admin.username).await;
+                state
+                    .password_hash
+                    .upgrade_if_needed(
+                        &state.db,
+                        PasswordOwner::Admin(admin.id),
+                        &admin.password_hash,
+                        &credentials.password,
+                    )
+                    .await;
+                let _ = PendingTwoFactor::clear(&session).await;
+                let admin_user = AdminUser::new(admin.id, admin.username.clone());
+                let ip = client_ip.to_string();
+                let session_ref = match admin_user.login(&session, &state, &ip, &user_agent).await {
+                    Ok(session_ref) => session_ref,
+                    Err(e) => {
+                        tracing::error!("Failed to set admin session: {:?}", e);
+                        return AdminLoginTemplate {
+                            error: Some("Session error. Please try again.".to_string()),
+                            lockout: None,
+                            sso_label: sso_label(&state),
+                            remember_me_days: state.remember_me.lifetime_days,
+                            csrf_token: ensure_csrf_token(&session).await,
+                            base_path: state.base_path.clone(),
+                        }
+                        .into_response();
+                    }
+                };
 
                 // Cycle session ID for security (prevent session fixation)
                 if let Err(e) = session.cycle_id().await {
                     tracing::error!("Failed to cycle session ID: {:?}", e);
                     return AdminLoginTemplate {
                         error: Some("Session error. Please try again.".to_string()),
+                        lockout: None,
+                        sso_label: sso_label(&state),
+                        remember_me_days: state.remember_me.lifetime_days,
                         csrf_token: ensure_csrf_token(&session).await,
                         base_path: state.base_path.clone(),
                     }
                     .into_response();
                 }
+                csrf::rotate_token(&session).await;
+                let _ = AdminUser::mark_reauthenticated(&session).await;
+                if let Some(notice) = login_history::record_success(&state.db, state.geoip.as_ref(), &attempt).await {
+                    login_notice::set(&session, notice).await;
+                }
 
                 tracing::info!("Admin session created, redirecting to {}/dashboard", state.base_path);
-                Redirect::to(&format!("{}/dashboard", state.base_path)).into_response()
+                let response = Redirect::to(&format!("{}/dashboard", state.base_path)).into_response();
+                if remember_me {
+                    return with_remember_me(&state, admin.id, &session_ref, response).await;
+                }
+                response
             } else {
                 tracing::warn!("Admin login failed: Invalid password for {}", credentials.username);
+                login_history::record_failure(&state.db, state.geoip.as_ref(), &attempt, "invalid_password").await;
+                let throttle = login_throttle::record_failure(
+                    &state.redis,
+                    &state.login_throttle,
+                    LoginRealm::Admin,
+                    &credentials.username,
+                    client_ip,
+                )
+                .await;
                 AdminLoginTemplate {
                     error: Some("Invalid username or password".to_string()),
+                    lockout: throttle.message(),
+                    sso_label: sso_label(&state),
+                    remember_me_days: state.remember_me.lifetime_days,
                     csrf_token: ensure_csrf_token(&session).await,
                     base_path: state.base_path.clone(),
                 }
@@ -134,8 +251,21 @@ pub async fn admin_login_submit(
         }
         Ok(None) => {
             tracing::warn!("Admin login failed: User not found - {}", credentials.username);
+            state.password_hash.verify_unknown_account(&credentials.password).await;
+            login_history::record_failure(&state.db, state.geoip.as_ref(), &attempt, "unknown_user").await;
+            let throttle = login_throttle::record_failure(
+                &state.redis,
+                &state.login_throttle,
+                LoginRealm::Admin,
+                &credentials.username,
+                client_ip,
+            )
+            .await;
             AdminLoginTemplate {
                 error: Some("Invalid username or password".to_string()),
+                lockout: throttle.message(),
+                sso_label: sso_label(&state),
+                remember_me_days: state.remember_me.lifetime_days,
                 csrf_token: ensure_csrf_token(&session).await,
                 base_path: state.base_path.clone(),
             }
@@ -145,6 +275,9 @@ pub async fn admin_login_submit(
             tracing::error!("Admin login database error: {:?}", e);
             AdminLoginTemplate {
                 error: Some("Database error. Please try again.".to_string()),
+                lockout: None,
+                sso_label: sso_label(&state),
+                remember_me_days: state.remember_me.lifetime_days,
                 csrf_token: ensure_csrf_token(&session).await,
                 base_path: state.base_path.clone(),
             }
@@ -153,17 +286,180 @@ pub async fn admin_login_submit(
     }
 }
 
+// Admin second login step (GET)
+pub async fn admin_login_2fa_page(
+    State(state): State<AppState>,
+    Extension(session): Extension<Session>,
+) -> impl IntoResponse {
+    let Some(pending) = PendingTwoFactor::from_session(&session).await else {
+        return Redirect::to(&format!("{}/login", state.base_path)).into_response();
+    };
+
+    AdminLoginTwoFactorTemplate {
+        error: None,
+        username: pending.username,
+        csrf_token: ensure_csrf_token(&session).await,
+        base_path: state.base_path.clone(),
+    }
+    .into_response()
+}
+
+// Admin second login step (POST)
+pub async fn admin_login_2fa_submit(
+    State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    UserAgent(user_agent): UserAgent,
+    Extension(session): Extension<Session>,
+    Form(form): Form<TwoFactorCodeForm>,
+) -> impl IntoResponse {
+    let Some(pending) = PendingTwoFactor::from_session(&session).await else {
+        return AdminLoginTemplate {
+            error: Some("Your sign-in has expired. Please log in again.".to_string()),
+            lockout: None,
+            sso_label: sso_label(&state),
+            remember_me_days: state.remember_me.lifetime_days,
+            csrf_token: ensure_csrf_token(&session).await,
+            base_path: state.base_path.clone(),
+        }
+        .into_response();
+    };
+
+    let admin = match repository::find_admin_by_id(&state.db, pending.admin_id).await {
+        Ok(Some(admin)) if admin.has_two_factor() && !admin.is_disabled() => admin,
+        Ok(_) => {
+            let _ = PendingTwoFactor::clear(&session).await;
+            return Redirect::to(&format!("{}/login", state.base_path)).into_response();
+        }
+        Err(e) => {
+            tracing::error!("Admin 2FA database error: {:?}", e);
+            return AdminLoginTwoFactorTemplate {
+                error: Some("Database error. Please try again.".to_string()),
+                username: pending.username,
+                csrf_token: ensure_csrf_token(&session).await,
+                base_path: state.base_path.clone(),
+            }
+            .into_response();
+        }
+    };
+
+    let attempt =
+        LoginAttempt::new(LoginMethod::TwoFactor, &admin.username, client_ip, &user_agent).for_admin(admin.id);
+    let check = if form.validate().is_err() {
+        SecondFactorCheck::Invalid
+    } else {
+        verify_second_factor(&state, &admin, &form.code).await
+    };
+
+    match check {
+        SecondFactorCheck::Valid => {}
+        SecondFactorCheck::Invalid => {
+            let attempts = pending.record_failure(&session).await.unwrap_or(PENDING_2FA_MAX_ATTEMPTS);
+            tracing::warn!("Admin 2FA failed for {} (attempt {})", admin.username, attempts);
+            login_history::record_failure(&state.db, state.geoip.as_ref(), &attempt, "invalid_code").await;
+            let throttle = login_throttle::record_failure(
+                &state.redis,
+                &state.login_throttle,
+                LoginRealm::Admin,
+                &admin.username,
+                client_ip,
+            )
+            .await;
+            if attempts >= PENDING_2FA_MAX_ATTEMPTS
+                || matches!(throttle, login_throttle::ThrottleStatus::Locked(_))
+            {
+                let _ = PendingTwoFactor::clear(&session).await;
+                return AdminLoginTemplate {
+                    error: Some("Too many invalid codes. Please log in again.".to_string()),
+                    lockout: throttle.message(),
+                    sso_label: sso_label(&state),
+                    remember_me_days: state.remember_me.lifetime_days,
+                    csrf_token: ensure_csrf_token(&session).await,
+                    base_path: state.base_path.clone(),
+                }
+                .into_response();
+            }
+            return AdminLoginTwoFactorTemplate {
+                error: Some("Invalid authentication code".to_string()),
+                username: pending.username,
+                csrf_token: ensure_csrf_token(&session).await,
+                base_path: state.base_path.clone(),
+            }
+            .into_response();
+        }
+        SecondFactorCheck::Unavailable => {
+            return AdminLoginTwoFactorTemplate {
+                error: Some("Two-factor verification is unavailable. Please try again later.".to_string()),
+                username: pending.username,
+                csrf_token: ensure_csrf_token(&session).await,
+                base_path: state.base_path.clone(),
+            }
+            .into_response();
+        }
+    }
+
+    let admin_user = AdminUser::new(admin.id, admin.username.clone());
+    let login_result = match PendingTwoFactor::clear(&session).await {
+        Ok(()) => {
+            admin_user
+                .login(&session, &state, &client_ip.to_string(), &user_agent)
+                .await
+        }
+        Err(e) => Err(e),
+    };
+    let session_ref = match login_result {
+        Ok(session_ref) => session_ref,
+        Err(e) => {
+            tracing::error!("Failed to set admin session: {:?}", e);
+            return AdminLoginTemplate {
+                error: Some("Session error. Please try again.".to_string()),
+                lockout: None,
+                sso_label: sso_label(&state),
+                remember_me_days: state.remember_me.lifetime_days,
+                csrf_token: ensure_csrf_token(&session).await,
+                base_path: state.base_path.clone(),
+            }
+            .into_response();
+        }
+    };
+
+    if let Err(e) = session.cycle_id().await {
+        tracing::error!("Failed to cycle session ID: {:?}", e);
+    }
+    csrf::rotate_token(&session).await;
+    let _ = AdminUser::mark_reauthenticated(&session).await;
+
+    login_throttle::record_success(&state.redis, LoginRealm::Admin, &admin.username).await;
+    if let Some(notice) = login_history::record_success(&state.db, state.geoip.as_ref(), &attempt).await {
+        login_notice::set(&session, notice).await;
+    }
+    tracing::info!("Admin login successful with 2FA: {}", admin.username);
+    let response = Redirect::to(&format!("{}/dashboard", state.base_path)).into_response();
+    if pending.remember_me && state.remember_me.enabled() {
+        return with_remember_me(&state, admin.id, &session_ref, response).await;
+    }
+    response
+}
+
 // Logout handler
-pub async fn logout(Extension(session): Extension<Session>) -> impl IntoResponse {
+pub async fn logout(
+    State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    Extension(session): Extension<Session>,
+) -> impl IntoResponse {
+    end_impersonation(&state, &session, client_ip).await;
     let _ = AuthUser::logout(&session).await;
     Redirect::to("/")
 }
 
 // Error handler
-pub async fn handle_404(State(state): State<AppState>) -> impl IntoResponse {
+pub async fn handle_404(
+    State(state): State<AppState>,
+    Extension(session): Extension<Session>,
+) -> impl IntoResponse {
     let template = ErrorTemplate {
         error_code: 404,
         error_message: "Page not found".to_string(),
+        impersonation: impersonation_banner(&session).await,
         base_path: state.base_path.clone(),
     };
 
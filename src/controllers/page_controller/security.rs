@@ -0,0 +1,309 @@
+use axum::{
+    Form,
+    extract::{Extension, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use tower_sessions::Session;
+use validator::Validate;
+
+use crate::controllers::auth_controller::AdminUser;
+use crate::controllers::request_info::ClientIp;
+use crate::models;
+use crate::repository;
+use crate::services::audit::{self, AuditEvent};
+use crate::services::two_factor;
+use crate::state::AppState;
+use crate::views::templates::{
+    AdminErrorTemplate, AdminRecoveryCodesTemplate, AdminSecurityTemplate, AdminTotpSetupTemplate,
+};
+
+use super::shared::{
+    ensure_csrf_token, verify_second_factor, SecondFactorCheck,
+    TwoFactorCodeForm,
+};
+
+// Secret generated during enrollment, kept server-side until the first code is confirmed
+const TOTP_SETUP_SECRET_KEY: &str = "totp_setup_secret";
+
+async fn load_admin(
+    state: &AppState,
+    admin_user: &AdminUser,
+) -> Result<models::Admin, axum::response::Response> {
+    match repository::find_admin_by_id(&state.db, admin_user.id).await {
+        Ok(Some(admin)) => Ok(admin),
+        Ok(None) => {
+            let template = AdminErrorTemplate {
+                error_code: 404,
+                error_message: "Admin account not found.".to_string(),
+                current_admin: Some(admin_user.clone()),
+                base_path: state.base_path.clone(),
+            };
+            Err((StatusCode::NOT_FOUND, template).into_response())
+        }
+        Err(_) => {
+            let template = AdminErrorTemplate {
+                error_code: 500,
+                error_message: "Failed to load admin account.".to_string(),
+                current_admin: Some(admin_user.clone()),
+                base_path: state.base_path.clone(),
+            };
+            Err((StatusCode::INTERNAL_SERVER_ERROR, template).into_response())
+        }
+    }
+}
+
+async fn render_security_page(
+    state: &AppState,
+    session: &Session,
+    admin_user: AdminUser,
+    error: Option<String>,
+    success: Option<String>,
+) -> axum::response::Response {
+    let admin = match load_admin(state, &admin_user).await {
+        Ok(admin) => admin,
+        Err(response) => return response,
+    };
+
+    let recovery_codes_remaining = if admin.has_two_factor() {
+        repository::count_unused_recovery_codes(&state.db, admin.id)
+            .await
+            .unwrap_or(0)
+    } else {
+        0
+    };
+
+    AdminSecurityTemplate {
+        current_admin: Some(admin_user),
+        csrf_token: ensure_csrf_token(session).await,
+        two_factor_available: state.two_factor.is_some(),
+        two_factor_enabled: admin.has_two_factor(),
+        recovery_codes_remaining,
+        error,
+        success,
+        base_path: state.base_path.clone(),
+    }
+    .into_response()
+}
+
+// Account security overview (GET)
+pub async fn admin_security_page(
+    admin_user: AdminUser,
+    State(state): State<AppState>,
+    Extension(session): Extension<Session>,
+) -> impl IntoResponse {
+    render_security_page(&state, &session, admin_user, None, None).await
+}
+
+const ALREADY_ENABLED: &str =
+    "Two-factor authentication is already enabled. Disable it first to enroll another device.";
+
+// Start TOTP enrollment (POST). An enrolled secret is only replaced after
+// disabling, which needs a current code.
+pub async fn admin_totp_setup_submit(
+    admin_user: AdminUser,
+    State(state): State<AppState>,
+    Extension(session): Extension<Session>,
+) -> impl IntoResponse {
+    let Some(config) = state.two_factor.as_ref() else {
+        let error = Some("Two-factor authentication is not configured.".to_string());
+        return render_security_page(&state, &session, admin_user, error, None).await;
+    };
+    match load_admin(&state, &admin_user).await {
+        Ok(admin) if admin.has_two_factor() => {
+            let error = Some(ALREADY_ENABLED.to_string());
+            return render_security_page(&state, &session, admin_user, error, None).await;
+        }
+        Ok(_) => {}
+        Err(response) => return response,
+    }
+
+    let secret = two_factor::generate_secret();
+    let totp = match config.totp(secret, &admin_user.username) {
+        Ok(totp) => totp,
+        Err(e) => {
+            tracing::error!("Failed to build TOTP for {}: {}", admin_user.username, e);
+            let error = Some("Failed to start two-factor setup.".to_string());
+            return render_security_page(&state, &session, admin_user, error, None).await;
+        }
+    };
+
+    if let Err(e) = session
+        .insert(TOTP_SETUP_SECRET_KEY, totp.get_secret_base32())
+        .await
+    {
+        tracing::error!("Failed to store TOTP setup secret: {:?}", e);
+        let error = Some("Session error. Please try again.".to_string());
+        return render_security_page(&state, &session, admin_user, error, None).await;
+    }
+
+    AdminTotpSetupTemplate {
+        current_admin: Some(admin_user),
+        csrf_token: ensure_csrf_token(&session).await,
+        secret_base32: totp.get_secret_base32(),
+        qr_svg: two_factor::qr_code_svg(&totp).unwrap_or_default(),
+        error: None,
+        base_path: state.base_path.clone(),
+    }
+    .into_response()
+}
+
+// Confirm TOTP enrollment with a first code (POST)
+pub async fn admin_totp_enable_submit(
+    admin_user: AdminUser,
+    State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    Extension(session): Extension<Session>,
+    Form(form): Form<TwoFactorCodeForm>,
+) -> impl IntoResponse {
+    let (Some(config), Ok(Some(secret_base32))) = (
+        state.two_factor.as_ref(),
+        session.get::<String>(TOTP_SETUP_SECRET_KEY).await,
+    ) else {
+        let error = Some("Two-factor setup has expired. Please start again.".to_string());
+        return render_security_page(&state, &session, admin_user, error, None).await;
+    };
+
+    let totp = match totp_rs::Secret::Encoded(secret_base32)
+        .to_bytes()
+        .map_err(|e| format!("{:?}", e))
+        .and_then(|secret| config.totp(secret, &admin_user.username))
+    {
+        Ok(totp) => totp,
+        Err(e) => {
+            tracing::error!("Invalid TOTP setup secret in session: {}", e);
+            let _ = session.remove::<String>(TOTP_SETUP_SECRET_KEY).await;
+            let error = Some("Two-factor setup failed. Please start again.".to_string());
+            return render_security_page(&state, &session, admin_user, error, None).await;
+        }
+    };
+
+    let error = if form.validate().is_err() || !two_factor::verify_code(&totp, &form.code) {
+        Some("Invalid authentication code. Check your device clock and try again.".to_string())
+    } else {
+        None
+    };
+    if error.is_some() {
+        return AdminTotpSetupTemplate {
+            current_admin: Some(admin_user),
+            csrf_token: ensure_csrf_token(&session).await,
+            secret_base32: totp.get_secret_base32(),
+            qr_svg: two_factor::qr_code_svg(&totp).unwrap_or_default(),
+            error,
+            base_path: state.base_path.clone(),
+        }
+        .into_response();
+    }
+
+    let encrypted = match config.encrypt_secret(&totp.secret) {
+        Ok(encrypted) => encrypted,
+        Err(e) => {
+            tracing::error!("Failed to encrypt TOTP secret: {}", e);
+            let error = Some("Failed to enable two-factor authentication.".to_string());
+            return render_security_page(&state, &session, admin_user, error, None).await;
+        }
+    };
+
+    let codes = two_factor::generate_recovery_codes();
+    let hashes: Vec<String> = codes.iter().map(|c| two_factor::hash_recovery_code(c)).collect();
+
+    match repository::enable_admin_totp(&state.db, admin_user.id, &encrypted, &hashes).await {
+        Ok(true) => {}
+        Ok(false) => {
+            let _ = session.remove::<String>(TOTP_SETUP_SECRET_KEY).await;
+            let error = Some(ALREADY_ENABLED.to_string());
+            return render_security_page(&state, &session, admin_user, error, None).await;
+        }
+        Err(e) => {
+            tracing::error!("Failed to enable TOTP: {:?}", e);
+            let error = Some("Failed to enable two-factor authentication.".to_string());
+            return render_security_page(&state, &session, admin_user, error, None).await;
+        }
+    }
+
+    let _ = session.remove::<String>(TOTP_SETUP_SECRET_KEY).await;
+    tracing::info!("Admin {} enabled two-factor authentication", admin_user.username);
+    let event = AuditEvent::new("2fa_enable", "admin", admin_user.id);
+    audit::record(&state.db, &admin_user, client_ip, event).await;
+
+    AdminRecoveryCodesTemplate {
+        current_admin: Some(admin_user),
+        codes,
+        base_path: state.base_path.clone(),
+    }
+    .into_response()
+}
+
+// Disable TOTP after re-confirming a code (POST)
+pub async fn admin_totp_disable_submit(
+    admin_user: AdminUser,
+    State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    Extension(session): Extension<Session>,
+    Form(form): Form<TwoFactorCodeForm>,
+) -> impl IntoResponse {
+    let admin = match load_admin(&state, &admin_user).await {
+        Ok(admin) => admin,
+        Err(response) => return response,
+    };
+
+    if !matches!(
+        verify_second_factor(&state, &admin, &form.code).await,
+        SecondFactorCheck::Valid
+    ) {
+        let error = Some("Invalid authentication code".to_string());
+        return render_security_page(&state, &session, admin_user, error, None).await;
+    }
+
+    if let Err(e) = repository::disable_admin_totp(&state.db, admin.id).await {
+        tracing::error!("Failed to disable TOTP: {:?}", e);
+        let error = Some("Failed to disable two-factor authentication.".to_string());
+        return render_security_page(&state, &session, admin_user, error, None).await;
+    }
+
+    tracing::info!("Admin {} disabled two-factor authentication", admin_user.username);
+    let event = AuditEvent::new("2fa_disable", "admin", admin_user.id);
+    audit::record(&state.db, &admin_user, client_ip, event).await;
+    let success = Some("Two-factor authentication disabled.".to_string());
+    render_security_page(&state, &session, admin_user, None, success).await
+}
+
+// Replace all recovery codes (POST)
+pub async fn admin_recovery_codes_submit(
+    admin_user: AdminUser,
+    State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    Extension(session): Extension<Session>,
+    Form(form): Form<TwoFactorCodeForm>,
+) -> impl IntoResponse {
+    let admin = match load_admin(&state, &admin_user).await {
+        Ok(admin) => admin,
+        Err(response) => return response,
+    };
+
+    if !matches!(
+        verify_second_factor(&state, &admin, &form.code).await,
+        SecondFactorCheck::Valid
+    ) {
+        let error = Some("Invalid authentication code".to_string());
+        return render_security_page(&state, &session, admin_user, error, None).await;
+    }
+
+    let codes = two_factor::generate_recovery_codes();
+    let hashes: Vec<String> = codes.iter().map(|c| two_factor::hash_recovery_code(c)).collect();
+
+    if let Err(e) = repository::replace_admin_recovery_codes(&state.db, admin.id, &hashes).await {
+        tracing::error!("Failed to replace recovery codes: {:?}", e);
+        let error = Some("Failed to generate recovery codes.".to_string());
+        return render_security_page(&state, &session, admin_user, error, None).await;
+    }
+    let event = AuditEvent::new("recovery_codes_regenerate", "admin", admin_user.id);
+    audit::record(&state.db, &admin_user, client_ip, event).await;
+
+    AdminRecoveryCodesTemplate {
+        current_admin: Some(admin_user),
+        codes,
+        base_path: state.base_path.clone(),
+    }
+    .into_response()
+}
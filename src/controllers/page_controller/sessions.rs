@@ -0,0 +1,104 @@
+use axum::{
+    extract::{Extension, Path, State},
+    response::{IntoResponse, Redirect},
+};
+use tower_sessions::Session;
+
+use crate::controllers::auth_controller::AdminUser;
+use crate::controllers::request_info::ClientIp;
+use crate::repository;
+use crate::services::admin_sessions;
+use crate::services::audit::{self, AuditEvent};
+use crate::state::AppState;
+use crate::views::templates::AdminSessionsTemplate;
+
+use super::shared::ensure_csrf_token;
+
+async fn render_sessions_page(
+    state: &AppState,
+    session: &Session,
+    admin_user: AdminUser,
+    error: Option<String>,
+    success: Option<String>,
+) -> axum::response::Response {
+    AdminSessionsTemplate {
+        sessions: admin_sessions::list(&state.redis, admin_user.id).await,
+        current_ref: admin_user.session_ref.clone().unwrap_or_default(),
+        current_admin: Some(admin_user),
+        csrf_token: ensure_csrf_token(session).await,
+        error,
+        success,
+        base_path: state.base_path.clone(),
+    }
+    .into_response()
+}
+
+// Active sessions of the logged-in admin (GET)
+pub async fn admin_sessions_page(
+    admin_user: AdminUser,
+    State(state): State<AppState>,
+    Extension(session): Extension<Session>,
+) -> impl IntoResponse {
+    render_sessions_page(&state, &session, admin_user, None, None).await
+}
+
+// Revoke one session (POST). Revoking the current session logs out.
+pub async fn admin_session_revoke_submit(
+    admin_user: AdminUser,
+    State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    Extension(session): Extension<Session>,
+    Path(session_ref): Path<String>,
+) -> impl IntoResponse {
+    match admin_sessions::revoke(&state.redis, admin_user.id, &session_ref).await {
+        Ok(true) => {}
+        Ok(false) => {
+            let error = Some("Session not found. It may have already expired.".to_string());
+            return render_sessions_page(&state, &session, admin_user, error, None).await;
+        }
+        Err(e) => {
+            tracing::error!("Failed to revoke admin session: {}", e);
+            let error = Some("Failed to revoke session.".to_string());
+            return render_sessions_page(&state, &session, admin_user, error, None).await;
+        }
+    }
+
+    // A remember-me token tied to the session would otherwise restore it
+    let _ = repository::delete_remember_tokens_by_session(&state.db, admin_user.id, &session_ref).await;
+    tracing::info!("Admin {} revoked a session", admin_user.username);
+    let event = AuditEvent::new("revoke", "admin_session", &session_ref);
+    audit::record(&state.db, &admin_user, client_ip, event).await;
+    if admin_user.session_ref.as_deref() == Some(session_ref.as_str()) {
+        let _ = AdminUser::logout(&session).await;
+        return Redirect::to(&format!("{}/login", state.base_path)).into_response();
+    }
+
+    let success = Some("Session revoked.".to_string());
+    render_sessions_page(&state, &session, admin_user, None, success).await
+}
+
+// Log out everywhere, including this session (POST)
+pub async fn admin_sessions_revoke_all_submit(
+    admin_user: AdminUser,
+    State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    Extension(session): Extension<Session>,
+) -> impl IntoResponse {
+    match admin_sessions::revoke_all(&state.redis, admin_user.id).await {
+        Ok(count) => {
+            let _ = repository::delete_remember_tokens_by_admin(&state.db, admin_user.id).await;
+            tracing::info!("Admin {} logged out of {} session(s)", admin_user.username, count);
+            let event = AuditEvent::new("revoke_sessions", "admin", admin_user.id)
+                .with_after(serde_json::json!({ "revoked": count }));
+            audit::record(&state.db, &admin_user, client_ip, event).await;
+        }
+        Err(e) => {
+            tracing::error!("Failed to revoke admin sessions: {}", e);
+            let error = Some("Failed to log out other sessions.".to_string());
+            return render_sessions_page(&state, &session, admin_user, error, None).await;
+        }
+    }
+
+    let _ = AdminUser::logout(&session).await;
+    Redirect::to(&format!("{}/login", state.base_path)).into_response()
+}
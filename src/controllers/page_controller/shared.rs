@@ -1,52 +1,82 @@
 use axum::http::StatusCode;
-use rand::{Rng, distributions::Alphanumeric};
+use axum::response::{IntoResponse, Response};
+use axum_extra::extract::cookie::CookieJar;
 use serde::{Deserialize, Serialize};
 use serde_json;
 use tower_sessions::Session;
 use tower_sessions_redis_store::fred::prelude::KeysInterface;
-use tower_sessions_redis_store::fred::types::Expiration;
+use tower_sessions_redis_store::fred::types::{Expiration, SetOptions};
 use validator::Validate;
 
+use crate::controllers::auth_controller::Impersonation;
 use crate::models;
 use crate::repository;
+use crate::services::admin_sessions;
+use crate::services::two_factor;
 use crate::state::AppState;
-use crate::views::templates::{CountryOption, StateOption};
+use crate::views::templates::{CountryOption, ImpersonationBanner, StateOption};
 
 // Re-export form and request/response structs from entities for convenience
 pub(crate) use crate::models::{
-    CountryForm, StateForm, CreateUserForm, LoginForm, CsrfOnlyForm,
+    CountryForm, StateForm, CreateUserForm, LoginForm,
     UpdateUserForm, DataTablesRequest, DataTablesSearch, DataTablesOrder,
-    StatesQuery, DataTablesResponseLegacy, UserRow, PdfExportParams,
+    StatesQuery, DataTablesResponseLegacy, UserRow, PdfExportParams, TwoFactorCodeForm,
+    ForgotPasswordForm, ResetPasswordForm, ResetPasswordQuery, RegisterForm, ProfileForm,
+    ChangePasswordForm, CreateApiTokenForm, VerifyEmailQuery, OidcCallbackQuery,
+    PasskeyRegistrationRequest, PasskeyLoginRequest, RenamePasskeyForm, CreateAdminForm,
+    UpdateAdminForm, AdminPasswordForm, AdminEmailForm, SignedPdfExportQuery,
 };
 
-const CSRF_KEY: &str = "csrf_token";
 const CACHE_TTL_SECONDS: i64 = 300;
+// A TOTP code stays valid for its own step plus one step of skew either side
+const TOTP_REPLAY_WINDOW_SECONDS: i64 = 90;
 
-fn generate_csrf_token() -> String {
-    rand::thread_rng()
-        .sample_iter(&Alphanumeric)
-        .take(32)
-        .map(char::from)
-        .collect()
-}
+// Tokens are checked centrally by middleware::csrf; handlers only render them
+pub(crate) use crate::middleware::csrf::ensure_token as ensure_csrf_token;
 
-pub(crate) async fn ensure_csrf_token(session: &Session) -> String {
-    if let Ok(Some(token)) = session.get::<String>(CSRF_KEY).await {
-        token
-    } else {
-        let token = generate_csrf_token();
-        let _ = session.insert(CSRF_KEY, token.clone()).await;
-        token
+// Signs the admin out everywhere and drops their remember-me tokens
+pub(crate) async fn sign_out_everywhere(state: &AppState, admin_id: i32, keep_ref: Option<&str>) {
+    if let Err(e) = admin_sessions::revoke_all_except(&state.redis, admin_id, keep_ref).await {
+        tracing::error!("Failed to revoke sessions for admin {}: {}", admin_id, e);
+    }
+    if let Err(e) = repository::delete_remember_tokens_by_admin(&state.db, admin_id).await {
+        tracing::error!("Failed to delete remember-me tokens for admin {}: {:?}", admin_id, e);
     }
 }
 
-pub(crate) async fn validate_csrf(session: &Session, token: &str) -> bool {
-    match session.get::<String>(CSRF_KEY).await {
-        Ok(Some(stored)) => stored == token,
-        _ => false,
+// Banner shown on public pages while an admin is signed in as a user
+pub(crate) async fn impersonation_banner(session: &Session) -> Option<ImpersonationBanner> {
+    let impersonation = Impersonation::from_session(session).await?;
+    Some(ImpersonationBanner {
+        started_at: impersonation.started_at_display(),
+        admin_username: impersonation.admin_username,
+        username: impersonation.username,
+        csrf_token: ensure_csrf_token(session).await,
+    })
+}
+
+// Adds a remember-me cookie to a successful login response. Failing to issue
+// one only logs: the admin is still signed in for this session.
+pub(crate) async fn with_remember_me(
+    state: &AppState,
+    admin_id: i32,
+    session_ref: &str,
+    response: Response,
+) -> Response {
+    match state.remember_me.issue(&state.db, admin_id, session_ref).await {
+        Ok(cookie) => (CookieJar::new().add(cookie), response).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to issue remember-me token: {:?}", e);
+            response
+        }
     }
 }
 
+// Label of the single sign-on button on the admin login page
+pub(crate) fn sso_label(state: &AppState) -> Option<String> {
+    state.oidc.as_ref().map(|oidc| oidc.config.button_label.clone())
+}
+
 pub(crate) fn map_country_options(countries: Vec<models::Country>) -> Vec<CountryOption> {
     countries
         .into_iter()
@@ -123,3 +153,80 @@ pub(crate) async fn invalidate_geo_cache(state: &AppState) {
         }
     }
 }
+
+pub(crate) enum SecondFactorCheck {
+    Valid,
+    Invalid,
+    Unavailable,
+}
+
+// Accepts either a current TOTP code or an unused recovery code for the admin.
+// TOTP codes are remembered in Redis so the same code cannot be replayed.
+pub(crate) async fn verify_second_factor(
+    state: &AppState,
+    admin: &models::Admin,
+    code: &str,
+) -> SecondFactorCheck {
+    let (Some(config), Some(encrypted)) = (
+        state.two_factor.as_ref(),
+        admin.totp_secret_encrypted.as_deref(),
+    ) else {
+        return SecondFactorCheck::Unavailable;
+    };
+
+    let code: String = code.chars().filter(|c| !c.is_whitespace()).collect();
+    let is_totp = code.len() == 6 && code.chars().all(|c| c.is_ascii_digit());
+
+    if !is_totp {
+        let code_hash = two_factor::hash_recovery_code(&code);
+        return match repository::consume_admin_recovery_code(&state.db, admin.id, &code_hash).await {
+            Ok(true) => {
+                tracing::info!("Admin {} used a recovery code", admin.username);
+                SecondFactorCheck::Valid
+            }
+            Ok(false) => SecondFactorCheck::Invalid,
+            Err(e) => {
+                tracing::error!("Failed to check recovery code: {:?}", e);
+                SecondFactorCheck::Unavailable
+            }
+        };
+    }
+
+    let totp = match config
+        .decrypt_secret(encrypted)
+        .and_then(|secret| config.totp(secret, &admin.username))
+    {
+        Ok(totp) => totp,
+        Err(e) => {
+            tracing::error!("Failed to load TOTP secret for admin {}: {}", admin.username, e);
+            return SecondFactorCheck::Unavailable;
+        }
+    };
+
+    if !two_factor::verify_code(&totp, &code) {
+        return SecondFactorCheck::Invalid;
+    }
+
+    let key = format!("2fa:used:{}:{}", admin.id, code);
+    let first_use: Result<Option<String>, _> = state
+        .redis
+        .set(
+            key,
+            "1",
+            Some(Expiration::EX(TOTP_REPLAY_WINDOW_SECONDS)),
+            Some(SetOptions::NX),
+            false,
+        )
+        .await;
+    match first_use {
+        Ok(Some(_)) => SecondFactorCheck::Valid,
+        Ok(None) => {
+            tracing::warn!("Rejected replayed TOTP code for admin {}", admin.username);
+            SecondFactorCheck::Invalid
+        }
+        Err(e) => {
+            tracing::error!("Failed to record TOTP use: {:?}", e);
+            SecondFactorCheck::Unavailable
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use axum::{
+    extract::{Extension, Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde_json::Value;
+use tower_sessions::Session;
+
+use crate::controllers::auth_controller::AdminUser;
+use crate::controllers::request_info::ClientIp;
+use crate::models::{TrashKind, TrashSection};
+use crate::repository;
+use crate::services::audit::{self, AuditEvent, Auditable};
+use crate::state::AppState;
+use crate::views::templates::{AdminErrorTemplate, AdminTrashTemplate};
+
+use super::shared::{ensure_csrf_token, invalidate_geo_cache};
+
+const KINDS: [TrashKind; 3] = [TrashKind::Users, TrashKind::Countries, TrashKind::States];
+
+fn error_page(state: &AppState, admin_user: AdminUser, code: StatusCode, message: &str) -> Response {
+    let template = AdminErrorTemplate {
+        error_code: code.as_u16(),
+        error_message: message.to_string(),
+        current_admin: Some(admin_user),
+        base_path: state.base_path.clone(),
+    };
+    (code, template).into_response()
+}
+
+fn forbidden(state: &AppState, admin_user: AdminUser) -> Response {
+    let message = "You do not have permission to manage the trash.";
+    error_page(state, admin_user, StatusCode::FORBIDDEN, message)
+}
+
+// Lists the kinds the admin may restore and purge (the delete permission of each)
+async fn render_trash_page(
+    state: &AppState,
+    session: &Session,
+    admin_user: AdminUser,
+    error: Option<String>,
+    success: Option<String>,
+) -> Response {
+    let mut sections = Vec::new();
+    for kind in KINDS.into_iter().filter(|kind| admin_user.can(kind.permission())) {
+        match repository::find_trash_items(&state.db, kind).await {
+            Ok(items) => sections.push(TrashSection { kind, items }),
+            Err(e) => {
+                tracing::error!("Failed to load trashed {}: {:?}", kind.as_str(), e);
+                let message = "Failed to load the trash.";
+                return error_page(state, admin_user, StatusCode::INTERNAL_SERVER_ERROR, message);
+            }
+        }
+    }
+    if sections.is_empty() {
+        return forbidden(state, admin_user);
+    }
+
+    AdminTrashTemplate {
+        sections,
+        retention_days: state.trash.retention_days,
+        error,
+        success,
+        current_admin: Some(admin_user),
+        csrf_token: ensure_csrf_token(session).await,
+        base_path: state.base_path.clone(),
+    }
+    .into_response()
+}
+
+// Trash (GET)
+pub async fn admin_trash_page(
+    admin_user: AdminUser,
+    State(state): State<AppState>,
+    Extension(session): Extension<Session>,
+) -> impl IntoResponse {
+    render_trash_page(&state, &session, admin_user, None, None).await
+}
+
+// Snapshot of a row that is live again, for the audit log
+async fn restored_snapshot(state: &AppState, kind: TrashKind, id: i32) -> Option<Value> {
+    let snapshot = match kind {
+        TrashKind::Users => {
+            repository::find_user_by_id(&state.db, id).await.ok()??.audit_snapshot()
+        }
+        TrashKind::Countries => {
+            repository::get_country_by_id(&state.db, id).await.ok()??.audit_snapshot()
+        }
+        TrashKind::States => {
+            repository::get_state_by_id(&state.db, id).await.ok()??.audit_snapshot()
+        }
+    };
+    Some(snapshot)
+}
+
+// Restore from the trash (POST)
+pub async fn admin_trash_restore_submit(
+    admin_user: AdminUser,
+    State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    Extension(session): Extension<Session>,
+    Path((kind, id)): Path<(TrashKind, i32)>,
+) -> impl IntoResponse {
+    if !admin_user.can(kind.permission()) {
+        return forbidden(&state, admin_user);
+    }
+
+    let restored = match repository::restore_trash_item(&state.db, kind, id).await {
+        Ok(restored) => restored,
+        Err(e) => {
+            tracing::error!("Failed to restore {} {}: {:?}", kind.entity_type(), id, e);
+            let error = "Failed to restore the item.".to_string();
+            return render_trash_page(&state, &session, admin_user, Some(error), None).await;
+        }
+    };
+    if !restored {
+        let error = match kind {
+            TrashKind::Users => "The user is not in the trash, or their country or state is. Restore those first.",
+            TrashKind::Countries => "The country is not in the trash.",
+            TrashKind::States => "The state is not in the trash, or its country is. Restore the country first.",
+        };
+        return render_trash_page(&state, &session, admin_user, Some(error.to_string()), None).await;
+    }
+
+    tracing::info!("Admin {} restored {} {}", admin_user.username, kind.entity_type(), id);
+    let mut event = AuditEvent::new("restore", kind.entity_type(), id);
+    if let Some(after) = restored_snapshot(&state, kind, id).await {
+        event = event.with_after(after);
+    }
+    audit::record(&state.db, &admin_user, client_ip, event).await;
+    if kind != TrashKind::Users {
+        invalidate_geo_cache(&state).await;
+    }
+
+    let success = format!("Restored {} #{}.", kind.entity_type(), id);
+    render_trash_page(&state, &session, admin_user, None, Some(success)).await
+}
+
+// Permanent delete from the trash (POST)
+pub async fn admin_trash_purge_submit(
+    admin_user: AdminUser,
+    State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    Extension(session): Extension<Session>,
+    Path((kind, id)): Path<(TrashKind, i32)>,
+) -> impl IntoResponse {
+    if !admin_user.can(kind.permission()) {
+        return forbidden(&state, admin_user);
+    }
+
+    let purged = match repository::purge_trash_item(&state.db, kind, id).await {
+        Ok(purged) => purged,
+        Err(e) => {
+            tracing::error!("Failed to purge {} {}: {:?}", kind.entity_type(), id, e);
+            let error = "Failed to delete the item.".to_string();
+            return render_trash_page(&state, &session, admin_user, Some(error), None).await;
+        }
+    };
+    if !purged {
+        let error = match kind {
+            TrashKind::Users => "The user is not in the trash.",
+            TrashKind::Countries => "The country is not in the trash, or states or users still refer to it.",
+            TrashKind::States => "The state is not in the trash, or users still refer to it.",
+        };
+        return render_trash_page(&state, &session, admin_user, Some(error.to_string()), None).await;
+    }
+
+    tracing::info!("Admin {} purged {} {}", admin_user.username, kind.entity_type(), id);
+    audit::record(&state.db, &admin_user, client_ip, AuditEvent::new("purge", kind.entity_type(), id)).await;
+
+    let success = format!("Deleted {} #{} permanently.", kind.entity_type(), id);
+    render_trash_page(&state, &session, admin_user, None, Some(success)).await
+}
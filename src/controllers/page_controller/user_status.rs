@@ -0,0 +1,75 @@
+use axum::{
+    Form,
+    extract::{Extension, Path, State},
+    response::IntoResponse,
+};
+use serde_json::json;
+use tower_sessions::Session;
+use validator::Validate;
+
+use crate::controllers::auth_controller::RequirePermission;
+use crate::controllers::permissions::UsersStatus;
+use crate::controllers::request_info::ClientIp;
+use crate::models::{UserStatus, UserStatusForm};
+use crate::repository;
+use crate::services::audit::{self, AuditEvent};
+use crate::state::AppState;
+
+use super::admin::render_user_detail;
+
+// Status change from the user detail page (POST). Only moves in the
+// transition table are accepted, and each needs a reason.
+pub async fn user_status_submit(
+    RequirePermission(admin_user, _): RequirePermission<UsersStatus>,
+    State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    Extension(session): Extension<Session>,
+    Path(id): Path<i32>,
+    Form(form): Form<UserStatusForm>,
+) -> impl IntoResponse {
+    let user = match repository::find_user_by_id(&state.db, id).await {
+        Ok(Some(user)) => user,
+        // The detail page renders the 404/500
+        _ => return render_user_detail(&state, &session, admin_user, id, None, None).await,
+    };
+    let from = user.status();
+    let reason = form.reason.trim().to_string();
+
+    let Some(to) = UserStatus::parse(&form.status) else {
+        let error = Some("Unknown status".to_string());
+        return render_user_detail(&state, &session, admin_user, id, error, None).await;
+    };
+    let error = if reason.is_empty() || form.validate().is_err() {
+        Some("Please give a reason of at most 255 characters".to_string())
+    } else if !from.can_transition_to(to) {
+        Some(format!("A {} user cannot be made {}", from.as_str(), to.as_str()))
+    } else {
+        None
+    };
+    if error.is_some() {
+        return render_user_detail(&state, &session, admin_user, id, error, None).await;
+    }
+
+    let changed_by = Some((admin_user.id, admin_user.username.as_str()));
+    match repository::change_user_status(&state.db, id, from, to, &reason, changed_by).await {
+        Ok(true) => {}
+        Ok(false) => {
+            let error = Some("The user's status changed in the meantime. Please try again.".to_string());
+            return render_user_detail(&state, &session, admin_user, id, error, None).await;
+        }
+        Err(e) => {
+            tracing::error!("Failed to change status of user {}: {:?}", id, e);
+            let error = Some("Failed to change status".to_string());
+            return render_user_detail(&state, &session, admin_user, id, error, None).await;
+        }
+    }
+
+    tracing::info!("Admin {} moved user {} from {} to {}", admin_user.username, id, from.as_str(), to.as_str());
+    let event = AuditEvent::new("change_status", "user", id)
+        .with_before(json!({ "status": from.as_str() }))
+        .with_after(json!({ "status": to.as_str(), "reason": reason }));
+    audit::record(&state.db, &admin_user, client_ip, event).await;
+
+    let success = Some(format!("Status changed to {}", to.label()));
+    render_user_detail(&state, &session, admin_user, id, None, success).await
+}
@@ -0,0 +1,41 @@
+// Permission markers used with the `RequirePermission<P>` extractor.
+//
+// Each marker maps to a row in the `permissions` table (see schema.sql).
+// Roles are granted permissions through `role_permissions`, and admins get
+// roles through `admin_roles`. Templates check the same names with
+// `admin.can("...")`, so keep the strings in sync with schema.sql.
+pub trait Permission: Send + Sync + 'static {
+    const NAME: &'static str;
+    // Shown in the 403 page ("You do not have permission to ...")
+    const ACTION: &'static str;
+}
+
+macro_rules! permissions {
+    ($($marker:ident => $name:literal, $action:literal;)+) => {
+        $(
+            #[derive(Debug, Clone, Copy)]
+            pub struct $marker;
+
+            impl Permission for $marker {
+                const NAME: &'static str = $name;
+                const ACTION: &'static str = $action;
+            }
+        )+
+    };
+}
+
+permissions! {
+    UsersView => "users.view", "view users";
+    UsersCreate => "users.create", "create users";
+    UsersEdit => "users.edit", "edit users";
+    UsersDelete => "users.delete", "delete users";
+    UsersExport => "users.export", "export users";
+    UsersImpersonate => "users.impersonate", "sign in as users";
+    UsersStatus => "users.status", "change the status of users";
+    GeoView => "geo.view", "view countries and states";
+    GeoEdit => "geo.edit", "create or edit countries and states";
+    GeoDelete => "geo.delete", "delete countries and states";
+    AuditView => "audit.view", "view the audit log";
+    AdminsView => "admins.view", "view admin accounts";
+    AdminsManage => "admins.manage", "manage admin accounts";
+}
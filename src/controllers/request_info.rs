@@ -0,0 +1,176 @@
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+use axum::{
+    async_trait,
+    extract::{ConnectInfo, FromRequestParts},
+    http::{HeaderMap, header::USER_AGENT, request::Parts},
+};
+use ipnet::IpNet;
+
+use crate::services::access_policy::parse_cidrs;
+
+// Address of the client. middleware::client_ip resolves it once per request
+// (through TrustedProxies); without that layer it is the socket peer address.
+#[derive(Debug, Clone, Copy)]
+pub struct ClientIp(pub IpAddr);
+
+// Reverse proxies whose X-Forwarded-For header is believed (TRUSTED_PROXIES).
+// Empty means the peer address is always the client.
+#[derive(Debug, Clone, Default)]
+pub struct TrustedProxies(Vec<IpNet>);
+
+impl TrustedProxies {
+    pub fn from_env() -> Result<Self, String> {
+        let value = std::env::var("TRUSTED_PROXIES").unwrap_or_default();
+        parse_cidrs(&value)
+            .map(Self)
+            .map_err(|e| format!("Invalid TRUSTED_PROXIES: {}", e))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn trusts(&self, ip: IpAddr) -> bool {
+        self.0.iter().any(|range| range.contains(&ip))
+    }
+
+    // Walks X-Forwarded-For from the nearest hop and returns the first
+    // address that is not a trusted proxy. Entries further left were written
+    // by the client and cannot be relied on.
+    pub fn client_ip(&self, peer: IpAddr, headers: &HeaderMap) -> IpAddr {
+        if !self.trusts(peer) {
+            return peer;
+        }
+        let hops: Vec<&str> = headers
+            .get_all("x-forwarded-for")
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(str::trim)
+            .collect();
+        let mut client = peer;
+        for hop in hops.iter().rev() {
+            let Ok(ip) = hop.parse::<IpAddr>() else {
+                break;
+            };
+            client = ip;
+            if !self.trusts(ip) {
+                break;
+            }
+        }
+        client
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for ClientIp
+where
+    S: Send + Sync,
+{
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        if let Some(client_ip) = parts.extensions.get::<ClientIp>() {
+            return Ok(*client_ip);
+        }
+        let ip = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip())
+            .unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+        Ok(ClientIp(ip))
+    }
+}
+
+// Raw User-Agent header, empty when the client did not send one
+#[derive(Debug, Clone)]
+pub struct UserAgent(pub String);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for UserAgent
+where
+    S: Send + Sync,
+{
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let user_agent = parts
+            .headers
+            .get(USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+            .to_string();
+        Ok(UserAgent(user_agent))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+
+    use super::*;
+
+    fn ip(value: &str) -> IpAddr {
+        value.parse().unwrap()
+    }
+
+    fn proxies(value: &str) -> TrustedProxies {
+        TrustedProxies(parse_cidrs(value).unwrap())
+    }
+
+    fn forwarded_for(values: &[&str]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for value in values {
+            headers.append("x-forwarded-for", HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn untrusted_peer_is_the_client() {
+        let headers = forwarded_for(&["198.51.100.7"]);
+        assert_eq!(TrustedProxies::default().client_ip(ip("203.0.113.1"), &headers), ip("203.0.113.1"));
+        assert_eq!(proxies("10.0.0.0/8").client_ip(ip("203.0.113.1"), &headers), ip("203.0.113.1"));
+    }
+
+    #[test]
+    fn trusted_peer_forwards_the_client() {
+        let headers = forwarded_for(&["198.51.100.7"]);
+        assert_eq!(proxies("10.0.0.0/8").client_ip(ip("10.0.0.1"), &headers), ip("198.51.100.7"));
+        // No header: the proxy itself
+        assert_eq!(proxies("10.0.0.0/8").client_ip(ip("10.0.0.1"), &HeaderMap::new()), ip("10.0.0.1"));
+    }
+
+    #[test]
+    fn spoofed_leftmost_entries_are_ignored() {
+        let proxies = proxies("10.0.0.0/8");
+        let headers = forwarded_for(&["1.2.3.4, 5.6.7.8, 198.51.100.7"]);
+        assert_eq!(proxies.client_ip(ip("10.0.0.1"), &headers), ip("198.51.100.7"));
+
+        // A client claiming to be one of our proxies
+        let headers = forwarded_for(&["10.9.9.9, 198.51.100.7"]);
+        assert_eq!(proxies.client_ip(ip("10.0.0.1"), &headers), ip("198.51.100.7"));
+    }
+
+    #[test]
+    fn walks_through_chained_proxies() {
+        let proxies = proxies("10.0.0.0/8, 2001:db8::/32");
+        let headers = forwarded_for(&["1.2.3.4, 198.51.100.7", "2001:db8::2, 10.0.0.2"]);
+        assert_eq!(proxies.client_ip(ip("10.0.0.1"), &headers), ip("198.51.100.7"));
+
+        // Every hop trusted: the left-most one is as far as we can see
+        let headers = forwarded_for(&["10.0.0.3, 10.0.0.2"]);
+        assert_eq!(proxies.client_ip(ip("10.0.0.1"), &headers), ip("10.0.0.3"));
+    }
+
+    #[test]
+    fn stops_at_unparsable_hops() {
+        let proxies = proxies("10.0.0.0/8");
+        let headers = forwarded_for(&["198.51.100.7, unknown"]);
+        assert_eq!(proxies.client_ip(ip("10.0.0.1"), &headers), ip("10.0.0.1"));
+
+        let headers = forwarded_for(&["garbage, 198.51.100.7, 10.0.0.2"]);
+        assert_eq!(proxies.client_ip(ip("10.0.0.1"), &headers), ip("198.51.100.7"));
+    }
+}
@@ -0,0 +1,11 @@
+// Application modules, shared by the web server and the CLI tools in src/bin
+pub mod controllers;
+pub mod middleware;
+pub mod models;
+pub mod repository;
+pub mod pool;
+pub mod utils;
+pub mod views;
+pub mod routes;
+pub mod services;
+pub mod state;
@@ -1,12 +1,3 @@
-mod controllers;
-mod models;
-mod repository;
-mod pool;
-mod utils;
-mod views;
-mod routes;
-mod state;
-
 use std::net::SocketAddr;
 use time::Duration;
 use tower_sessions::{Expiry, SessionManagerLayer};
@@ -17,8 +8,12 @@ use tower_sessions_redis_store::{
 };
 use tracing_subscriber::{EnvFilter, fmt, layer::SubscriberExt, util::SubscriberInitExt};
 use std::env;
-use crate::routes::app;
-use crate::state::AppState;
+use axum_askama_admin_template::routes::app;
+use axum_askama_admin_template::controllers;
+use axum_askama_admin_template::middleware;
+use axum_askama_admin_template::services;
+use axum_askama_admin_template::state::AppState;
+use axum_askama_admin_template::pool;
 
 #[tokio::main]
 async fn main() {
@@ -91,6 +86,13 @@ async fn main() {
         .filter(|v| *v > 0)
         .unwrap_or(60 * 60 * 24 * 7);
 
+    // Sudo mode window for sensitive admin actions
+    let reauth_window_minutes: i64 = std::env::var("SUDO_MODE_MINUTES")
+        .ok()
+        .and_then(|val| val.parse::<i64>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(10);
+
     let session_layer = SessionManagerLayer::new(session_store)
         .with_expiry(Expiry::OnInactivity(Duration::seconds(session_timeout_secs)));
 
@@ -98,10 +100,109 @@ async fn main() {
     let base_path = std::env::var("APP_BASE_PATH")
         .unwrap_or_else(|_| "/admin".to_string());
 
+    let two_factor = services::two_factor::TwoFactorConfig::from_env();
+    if two_factor.is_none() {
+        tracing::warn!("TOTP_ENCRYPTION_KEY not set; admin two-factor enrollment is disabled");
+    }
+
+    let mailer = services::mailer::Mailer::from_env().expect("Invalid mail configuration");
+    tracing::info!("Mail transport: {}", mailer.transport_name());
+
+    let email_verification = services::email_verification::EmailVerificationConfig::from_env()
+        .expect("Invalid email verification configuration");
+
+    let export_links = services::export_links::ExportLinkConfig::from_env()
+        .expect("Invalid export link configuration");
+
+    let password_policy = services::password_policy::PasswordPolicy::from_env()
+        .expect("Invalid password policy configuration");
+    tracing::info!(
+        "Password policy: min length {}, history {}, {} breached hashes",
+        password_policy.min_length,
+        password_policy.history_size,
+        password_policy.breached_count()
+    );
+
+    let password_hash = services::password_hash::PasswordHashConfig::from_env()
+        .expect("Invalid password hashing configuration");
+    tracing::info!("Password hashing: {}", password_hash.algorithm_name());
+
+    let public_url = std::env::var("APP_PUBLIC_URL")
+        .unwrap_or_else(|_| {
+            let host = std::env::var("APP_HOST").unwrap_or_else(|_| "127.0.0.1".to_string());
+            let port = std::env::var("APP_PORT").unwrap_or_else(|_| "3000".to_string());
+            format!("http://{}:{}", host, port)
+        })
+        .trim_end_matches('/')
+        .to_string();
+
+    let oidc_callback = format!("{}{}/login/oidc/callback", public_url, base_path);
+    let oidc = services::oidc::OidcConfig::from_env(&oidc_callback)
+        .expect("Invalid OIDC configuration")
+        .map(|config| {
+            tracing::info!("Admin single sign-on via {}", config.issuer);
+            services::oidc::OidcClient::new(config).expect("Failed to set up OIDC client")
+        });
+
+    let remember_me = services::remember_me::RememberMeConfig::from_env(&base_path);
+    if remember_me.enabled() {
+        tracing::info!("Admin remember-me tokens last {} days", remember_me.lifetime_days);
+    }
+
+    let webauthn = services::webauthn::WebAuthnConfig::from_env(&public_url)
+        .expect("Invalid WebAuthn configuration");
+    tracing::info!("Passkeys for relying party {} ({})", webauthn.rp_id, webauthn.origin);
+
+    let security_headers = middleware::security_headers::SecurityHeadersConfig::from_env();
+    if security_headers.report_only {
+        tracing::warn!("Content Security Policy is in report-only mode");
+    }
+
+    let trusted_proxies = controllers::request_info::TrustedProxies::from_env()
+        .expect("Invalid trusted proxy configuration");
+    if !trusted_proxies.is_empty() {
+        tracing::info!("Client addresses are taken from X-Forwarded-For set by trusted proxies");
+    }
+    let access_policy = services::access_policy::AccessPolicyConfig::from_env()
+        .expect("Invalid admin access policy configuration");
+    if !access_policy.allowlist.is_empty() {
+        tracing::info!("Admin area restricted to {} address range(s)", access_policy.allowlist.len());
+    }
+    let geoip = services::login_history::GeoIp::from_env().expect("Invalid GeoIP configuration");
+    match &geoip {
+        Some(geoip) => tracing::info!("Login locations resolved with {}", geoip.database_type()),
+        None => tracing::info!("GEOIP_DATABASE not set; new-country login alerts are disabled"),
+    }
+
+    let trash = services::trash::TrashConfig::from_env().expect("Invalid trash configuration");
+    match trash.retention_days {
+        Some(days) => tracing::info!("Deleted users, countries and states are purged after {} days", days),
+        None => tracing::info!("TRASH_RETENTION_DAYS is 0; the trash is only emptied by hand"),
+    }
+    services::trash::spawn_auto_purge(pool.clone(), trash);
+
     let app_state = AppState {
         db: pool,
         redis: redis_pool,
         base_path,
+        two_factor,
+        login_throttle: services::login_throttle::LoginThrottleConfig::from_env(),
+        oidc,
+        remember_me,
+        webauthn,
+        mailer,
+        email_verification,
+        export_links,
+        password_policy,
+        password_hash,
+        public_url,
+        session_timeout_seconds: session_timeout_secs,
+        reauth_window_seconds: reauth_window_minutes * 60,
+        security_headers,
+        trusted_proxies,
+        access_policy,
+        geoip,
+        trash,
     };
     let app = app(app_state, session_layer);
 
@@ -123,6 +224,8 @@ async fn main() {
     tracing::info!("Listening on http://{}", addr);
 
     let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
-    axum::serve(listener, app).await.unwrap();
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+        .await
+        .unwrap();
 }
 
@@ -0,0 +1,102 @@
+use axum::{
+    extract::{Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use time::OffsetDateTime;
+use tower_sessions::Session;
+
+use crate::controllers::auth_controller::AdminUser;
+use crate::controllers::request_info::ClientIp;
+use crate::repository;
+use crate::services::access_policy::{self, AccessDenial};
+use crate::state::AppState;
+use crate::views::templates::AdminErrorTemplate;
+
+// Applies the access policies to every path under `base_path`: the IP
+// allowlist before sign-in, and the admin's own IP and time restrictions
+// after it. Must run after restore_admin_session so restored logins are
+// checked too.
+pub async fn enforce_admin_access(
+    State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    request: Request,
+    next: Next,
+) -> Response {
+    let path = request.uri().path().to_string();
+    let base_path = state.base_path.as_str();
+    if path != base_path && !path.starts_with(&format!("{}/", base_path)) {
+        return next.run(request).await;
+    }
+
+    let session = request.extensions().get::<Session>().cloned();
+    let admin = match &session {
+        Some(session) => AdminUser::from_session(session).await,
+        None => None,
+    };
+
+    let decision = match &admin {
+        Some(admin) => match repository::find_admin_access_policy(&state.db, admin.id).await {
+            Ok(policy) => state
+                .access_policy
+                .check_admin(client_ip, policy.as_ref(), OffsetDateTime::now_utc()),
+            Err(e) => {
+                tracing::error!("Failed to load access policy for admin {}: {:?}", admin.id, e);
+                return error_page(&state, StatusCode::INTERNAL_SERVER_ERROR, "Internal server error.");
+            }
+        },
+        None => check_anonymous(&state, client_ip).await,
+    };
+    let Err(denial) = decision else {
+        return next.run(request).await;
+    };
+
+    let admin_id = admin.as_ref().map(|admin| admin.id);
+    tracing::warn!(
+        "Admin area request to {} from {} denied ({}){}",
+        path,
+        client_ip,
+        denial.reason(),
+        admin.as_ref().map(|admin| format!(" for admin {}", admin.username)).unwrap_or_default()
+    );
+    let path: String = path.chars().take(255).collect();
+    if let Err(e) = repository::record_admin_access_denial(
+        &state.db,
+        admin_id,
+        &client_ip.to_string(),
+        &path,
+        denial.reason(),
+    )
+    .await
+    {
+        tracing::error!("Failed to record access denial: {:?}", e);
+    }
+    error_page(&state, StatusCode::FORBIDDEN, denial.message())
+}
+
+// Before sign-in the admin is unknown, so an address outside the global list
+// is let through to the login pages if any admin's own list allows it
+async fn check_anonymous(state: &AppState, client_ip: std::net::IpAddr) -> Result<(), AccessDenial> {
+    if state.access_policy.allows_ip(client_ip) {
+        return Ok(());
+    }
+    match repository::find_admin_ip_allowlists(&state.db).await {
+        Ok(lists) if lists.iter().any(|list| access_policy::list_allows(list, client_ip)) => Ok(()),
+        Ok(_) => Err(AccessDenial::IpNotAllowed),
+        Err(e) => {
+            tracing::error!("Failed to load admin IP allowlists: {:?}", e);
+            Err(AccessDenial::IpNotAllowed)
+        }
+    }
+}
+
+fn error_page(state: &AppState, status: StatusCode, message: &str) -> Response {
+    let template = AdminErrorTemplate {
+        error_code: status.as_u16(),
+        error_message: message.to_string(),
+        current_admin: None,
+        base_path: state.base_path.clone(),
+    };
+    (status, template).into_response()
+}
@@ -0,0 +1,27 @@
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::controllers::request_info::{ClientIp, TrustedProxies};
+
+// Resolves the client address once, honouring X-Forwarded-For from trusted
+// proxies, and stores it for the ClientIp extractor. Runs outside every
+// other layer so login throttling, audit rows and access policies agree.
+pub async fn resolve_client_ip(
+    State(proxies): State<TrustedProxies>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let peer = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip())
+        .unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+    let client_ip = proxies.client_ip(peer, request.headers());
+    request.extensions_mut().insert(ClientIp(client_ip));
+    next.run(request).await
+}
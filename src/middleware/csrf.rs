@@ -0,0 +1,300 @@
+use axum::{
+    body::{Body, to_bytes},
+    extract::{Request, State},
+    http::{Method, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use rand::{Rng, distributions::Alphanumeric};
+use serde::Deserialize;
+use subtle::ConstantTimeEq;
+use tower_sessions::Session;
+
+use crate::services::api_tokens;
+use crate::views::templates::{AdminErrorTemplate, ErrorTemplate};
+
+const CSRF_KEY: &str = "csrf_token";
+pub const CSRF_HEADER: &str = "x-csrf-token";
+// Form bodies larger than this are rejected instead of buffered
+const MAX_FORM_BYTES: usize = 2 * 1024 * 1024;
+
+#[derive(Deserialize)]
+struct CsrfField {
+    csrf_token: Option<String>,
+}
+
+// Which requests the CSRF check applies to. Paths listed with `exempt`
+// (matched as prefixes) skip the check entirely.
+#[derive(Debug, Clone)]
+pub struct CsrfConfig {
+    base_path: String,
+    exempt: Vec<String>,
+}
+
+impl CsrfConfig {
+    pub fn new(base_path: &str) -> Self {
+        Self {
+            base_path: base_path.to_string(),
+            exempt: Vec::new(),
+        }
+    }
+
+    pub fn exempt(mut self, path: impl Into<String>) -> Self {
+        self.exempt.push(path.into());
+        self
+    }
+
+    fn is_exempt(&self, path: &str) -> bool {
+        self.exempt.iter().any(|prefix| path.starts_with(prefix.as_str()))
+    }
+}
+
+fn generate_token() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(32)
+        .map(char::from)
+        .collect()
+}
+
+// Token for the current session, created on first use
+pub async fn ensure_token(session: &Session) -> String {
+    if let Ok(Some(token)) = session.get::<String>(CSRF_KEY).await {
+        token
+    } else {
+        rotate_token(session).await
+    }
+}
+
+// Replaces the session token; called whenever the session is elevated (login)
+pub async fn rotate_token(session: &Session) -> String {
+    let token = generate_token();
+    let _ = session.insert(CSRF_KEY, token.clone()).await;
+    token
+}
+
+fn is_safe_method(method: &Method) -> bool {
+    matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS | Method::TRACE)
+}
+
+// Bearer-authenticated requests never use the session cookie, so a
+// cross-site form cannot forge them
+fn has_bearer_auth(request: &Request) -> bool {
+    api_tokens::bearer_token(request.headers()).is_some()
+}
+
+fn is_form_body(request: &Request) -> bool {
+    request
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/x-www-form-urlencoded"))
+}
+
+fn tokens_match(expected: &str, submitted: &str) -> bool {
+    expected.as_bytes().ct_eq(submitted.as_bytes()).into()
+}
+
+// Rejects unsafe requests whose token (X-CSRF-Token header or csrf_token
+// form field) does not match the session token
+pub async fn csrf_protect(
+    State(config): State<CsrfConfig>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if is_safe_method(request.method())
+        || config.is_exempt(request.uri().path())
+        || has_bearer_auth(&request)
+    {
+        return next.run(request).await;
+    }
+
+    let Some(session) = request.extensions().get::<Session>().cloned() else {
+        tracing::error!("CSRF check ran without a session layer");
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    let expected = session.get::<String>(CSRF_KEY).await.ok().flatten();
+
+    let header_token = request
+        .headers()
+        .get(CSRF_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+
+    let (request, submitted) = match header_token {
+        Some(token) => (request, Some(token)),
+        None if is_form_body(&request) => {
+            // The handler still needs the body, so buffer it and put it back
+            let (parts, body) = request.into_parts();
+            let bytes = match to_bytes(body, MAX_FORM_BYTES).await {
+                Ok(bytes) => bytes,
+                Err(_) => return StatusCode::PAYLOAD_TOO_LARGE.into_response(),
+            };
+            let token = serde_urlencoded::from_bytes::<CsrfField>(&bytes)
+                .ok()
+                .and_then(|field| field.csrf_token);
+            (Request::from_parts(parts, Body::from(bytes)), token)
+        }
+        None => (request, None),
+    };
+
+    match (expected, submitted) {
+        (Some(expected), Some(submitted)) if tokens_match(&expected, &submitted) => {
+            next.run(request).await
+        }
+        _ => {
+            tracing::warn!(
+                "CSRF check failed for {} {}",
+                request.method(),
+                request.uri().path()
+            );
+            forbidden(&config, request.uri().path())
+        }
+    }
+}
+
+fn forbidden(config: &CsrfConfig, path: &str) -> Response {
+    let error_message =
+        "Invalid or expired form token. Please reload the page and try again.".to_string();
+    if path.starts_with(config.base_path.as_str()) {
+        let template = AdminErrorTemplate {
+            error_code: 403,
+            error_message,
+            current_admin: None,
+            base_path: config.base_path.clone(),
+        };
+        (StatusCode::FORBIDDEN, template).into_response()
+    } else {
+        let template = ErrorTemplate {
+            error_code: 403,
+            error_message,
+            impersonation: None,
+            base_path: config.base_path.clone(),
+        };
+        (StatusCode::FORBIDDEN, template).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{Router, middleware, routing::get};
+    use tower::Service;
+    use tower_sessions::{MemoryStore, SessionManagerLayer};
+
+    use super::*;
+
+    fn app() -> Router {
+        async fn issue(session: Session) -> String {
+            ensure_token(&session).await
+        }
+        async fn echo(body: String) -> String {
+            body
+        }
+
+        Router::new()
+            .route("/form", get(issue).post(echo))
+            .layer(middleware::from_fn_with_state(CsrfConfig::new("/admin"), csrf_protect))
+            .layer(SessionManagerLayer::new(MemoryStore::default()))
+    }
+
+    async fn send(app: &Router, request: Request) -> Response {
+        app.clone().call(request).await.unwrap()
+    }
+
+    async fn body_text(response: Response) -> String {
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    // Session cookie and CSRF token of a fresh session
+    async fn start_session(app: &Router) -> (String, String) {
+        let request = Request::get("/form").body(Body::empty()).unwrap();
+        let response = send(app, request).await;
+        let cookie = response.headers()[header::SET_COOKIE].to_str().unwrap();
+        let cookie = cookie.split(';').next().unwrap().to_string();
+        (cookie, body_text(response).await)
+    }
+
+    fn post(cookie: &str) -> axum::http::request::Builder {
+        Request::post("/form").header(header::COOKIE, cookie)
+    }
+
+    #[tokio::test]
+    async fn rejects_missing_token() {
+        let app = app();
+        let (cookie, _) = start_session(&app).await;
+        let request = post(&cookie).body(Body::empty()).unwrap();
+        let response = send(&app, request).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn rejects_wrong_token() {
+        let app = app();
+        let (cookie, token) = start_session(&app).await;
+        let wrong = format!("{}x", &token[..token.len() - 1]);
+        let request = post(&cookie).header(CSRF_HEADER, wrong).body(Body::empty()).unwrap();
+        let response = send(&app, request).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn rejects_token_of_another_session() {
+        let app = app();
+        let (cookie, _) = start_session(&app).await;
+        let (_, other_token) = start_session(&app).await;
+        let request = post(&cookie).header(CSRF_HEADER, other_token).body(Body::empty()).unwrap();
+        let response = send(&app, request).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn accepts_header_token() {
+        let app = app();
+        let (cookie, token) = start_session(&app).await;
+        let request = post(&cookie).header(CSRF_HEADER, token).body(Body::empty()).unwrap();
+        let response = send(&app, request).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn accepts_form_token_and_keeps_body() {
+        let app = app();
+        let (cookie, token) = start_session(&app).await;
+        let form = format!("name=a&csrf_token={}", token);
+        let request = post(&cookie)
+            .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .body(Body::from(form.clone()))
+            .unwrap();
+        let response = send(&app, request).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body_text(response).await, form);
+    }
+
+    #[tokio::test]
+    async fn ignores_form_token_in_other_content_types() {
+        let app = app();
+        let (cookie, token) = start_session(&app).await;
+        let request = post(&cookie)
+            .header(header::CONTENT_TYPE, "text/plain")
+            .body(Body::from(format!("csrf_token={}", token)))
+            .unwrap();
+        let response = send(&app, request).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn skips_bearer_requests_and_safe_methods() {
+        let app = app();
+        let request = Request::post("/form")
+            .header(header::AUTHORIZATION, "bearer aat_token")
+            .body(Body::empty())
+            .unwrap();
+        let response = send(&app, request).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let request = Request::get("/form").body(Body::empty()).unwrap();
+        let response = send(&app, request).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
@@ -0,0 +1,53 @@
+use axum::{
+    extract::{Request, State},
+    http::{Method, header},
+    middleware::Next,
+    response::Response,
+};
+use tower_sessions::Session;
+
+// Set by a sign-in flagged in services::login_history, shown once
+const LOGIN_NOTICE_KEY: &str = "login_notice";
+
+tokio::task_local! {
+    static LOGIN_NOTICE: String;
+}
+
+// Notice for the page being rendered, for the admin base template:
+// `{{ crate::middleware::login_notice::current() }}`. None outside the layer.
+pub fn current() -> Option<String> {
+    LOGIN_NOTICE.try_with(Clone::clone).ok()
+}
+
+pub async fn set(session: &Session, notice: String) {
+    if let Err(e) = session.insert(LOGIN_NOTICE_KEY, notice).await {
+        tracing::error!("Failed to store login notice: {:?}", e);
+    }
+}
+
+// Hands a pending notice to the next HTML page load under `base_path`. JSON
+// and form requests leave it in the session so it is not lost on a
+// background call.
+pub async fn show_login_notice(
+    State(base_path): State<String>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let is_page_load = request.method() == Method::GET
+        && request.uri().path().starts_with(base_path.as_str())
+        && request
+            .headers()
+            .get(header::ACCEPT)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|accept| accept.contains("text/html"));
+    let session = request.extensions().get::<Session>().cloned();
+    let notice = match session {
+        Some(session) if is_page_load => session.remove::<String>(LOGIN_NOTICE_KEY).await.ok().flatten(),
+        _ => None,
+    };
+
+    match notice {
+        Some(notice) => LOGIN_NOTICE.scope(notice, next.run(request)).await,
+        None => next.run(request).await,
+    }
+}
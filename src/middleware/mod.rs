@@ -0,0 +1,8 @@
+// Request middleware applied in routes::app
+pub mod admin_access;
+pub mod client_ip;
+pub mod csrf;
+pub mod login_notice;
+pub mod reauth;
+pub mod remember_me;
+pub mod security_headers;
@@ -0,0 +1,270 @@
+use axum::{
+    body::{Body, to_bytes},
+    extract::{OriginalUri, Request, State},
+    http::{HeaderValue, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+use tower_sessions::Session;
+
+use crate::controllers::auth_controller::AdminUser;
+use crate::controllers::page_controller::{verify_second_factor, SecondFactorCheck};
+use crate::controllers::request_info::ClientIp;
+use crate::middleware::csrf;
+use crate::models::Admin;
+use crate::repository;
+use crate::services::login_throttle::{self, LoginRealm};
+use crate::services::password_hash;
+use crate::state::AppState;
+use crate::views::templates::{AdminErrorTemplate, AdminReauthTemplate};
+
+// Fields the confirmation page adds to the re-posted form
+const PASSWORD_FIELD: &str = "reauth_password";
+const CODE_FIELD: &str = "reauth_code";
+// Where the single sign-on form sends the admin back to
+const ACTION_FIELD: &str = "reauth_action";
+// Sensitive actions are plain forms; anything larger is not one of them
+const MAX_FORM_BYTES: usize = 64 * 1024;
+
+// Sudo mode for the routes it is layered on: unless the admin signed in or
+// re-authenticated within `state.reauth_window_seconds`, answers with a
+// confirmation page that re-posts the original form fields together with
+// the password (or second-factor code), then lets the request through.
+// Admins without a usable password can confirm with a passkey or through
+// single sign-on instead; both open the window before the form is re-posted.
+// Runs after the CSRF check, so the re-posted form carries the token.
+pub async fn require_reauth(
+    State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(session) = request.extensions().get::<Session>().cloned() else {
+        return next.run(request).await;
+    };
+    // The handler's own extractor rejects signed-out requests
+    let Some(admin_user) = AdminUser::from_session(&session).await else {
+        return next.run(request).await;
+    };
+    if AdminUser::reauthenticated_within(&session, state.reauth_window_seconds).await {
+        return next.run(request).await;
+    }
+
+    let (mut parts, body) = request.into_parts();
+    let Ok(bytes) = to_bytes(body, MAX_FORM_BYTES).await else {
+        return StatusCode::PAYLOAD_TOO_LARGE.into_response();
+    };
+    let mut fields: Vec<(String, String)> = serde_urlencoded::from_bytes(&bytes).unwrap_or_default();
+    let password = take_field(&mut fields, PASSWORD_FIELD);
+    let code = take_field(&mut fields, CODE_FIELD);
+    fields.retain(|(name, _)| name != "csrf_token");
+    // Routes under base_path are nested, which strips the prefix from parts.uri
+    let uri = parts.extensions.get::<OriginalUri>().map(|original| &original.0).unwrap_or(&parts.uri);
+    let action = uri.path_and_query().map(|path| path.as_str()).unwrap_or("/").to_string();
+
+    let admin = match load_admin(&state, &admin_user).await {
+        Ok(admin) => admin,
+        Err(response) => return response,
+    };
+    let page = ConfirmPage::new(&state, &session, &admin, PendingAction { action, fields }).await;
+
+    if password.is_none() && code.is_none() {
+        return page.render(admin_user, None).await;
+    }
+
+    let throttle = login_throttle::check(&state.redis, LoginRealm::Admin, &admin.username, client_ip).await;
+    if let Some(message) = throttle.message() {
+        return page.render(admin_user, Some(message)).await;
+    }
+    let (verified, error) = if page.uses_code() {
+        let code = code.unwrap_or_default();
+        match verify_second_factor(&state, &admin, &code).await {
+            SecondFactorCheck::Valid => (true, ""),
+            SecondFactorCheck::Invalid => (false, "Invalid authentication code"),
+            SecondFactorCheck::Unavailable => {
+                (false, "Two-factor verification is unavailable. Please try again later.")
+            }
+        }
+    } else {
+        let password = password.unwrap_or_default();
+        (password_hash::verify(&password, &admin.password_hash).await, "Incorrect password")
+    };
+    if !verified {
+        tracing::warn!("Re-authentication failed for admin {} from {}", admin.username, client_ip);
+        let throttle = login_throttle::record_failure(
+            &state.redis,
+            &state.login_throttle,
+            LoginRealm::Admin,
+            &admin.username,
+            client_ip,
+        )
+        .await;
+        let error = throttle.message().unwrap_or_else(|| error.to_string());
+        return page.render(admin_user, Some(error)).await;
+    }
+
+    login_throttle::record_success(&state.redis, LoginRealm::Admin, &admin.username).await;
+    if let Err(e) = AdminUser::mark_reauthenticated(&session).await {
+        tracing::error!("Failed to store re-authentication time: {:?}", e);
+    }
+    tracing::info!("Admin {} re-authenticated for {}", admin.username, page.pending.action);
+
+    // Resume the original POST without the confirmation fields
+    let body = serde_urlencoded::to_string(&page.pending.fields).unwrap_or_default();
+    parts.headers.remove(header::CONTENT_LENGTH);
+    parts.headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/x-www-form-urlencoded"),
+    );
+    next.run(Request::from_parts(parts, Body::from(body))).await
+}
+
+// The signed-in admin's account, or the error page to answer with
+pub(crate) async fn load_admin(state: &AppState, admin_user: &AdminUser) -> Result<Admin, Response> {
+    match repository::find_admin_by_id(&state.db, admin_user.id).await {
+        Ok(Some(admin)) => Ok(admin),
+        Ok(None) => Err(error_page(state, admin_user, StatusCode::NOT_FOUND, "Admin account not found.")),
+        Err(e) => {
+            tracing::error!("Failed to load admin {} for re-authentication: {:?}", admin_user.id, e);
+            let message = "Failed to load admin account.";
+            Err(error_page(state, admin_user, StatusCode::INTERNAL_SERVER_ERROR, message))
+        }
+    }
+}
+
+fn take_field(fields: &mut Vec<(String, String)>, name: &str) -> Option<String> {
+    let index = fields.iter().position(|(field, _)| field == name)?;
+    Some(fields.remove(index).1)
+}
+
+// The form a confirmation page re-posts. Kept in the session while the admin
+// confirms through the identity provider.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingAction {
+    pub action: String,
+    pub fields: Vec<(String, String)>,
+}
+
+impl PendingAction {
+    // Rebuilds the action from the confirmation page's single sign-on form;
+    // only paths on this site are accepted as the action
+    pub(crate) fn from_form(mut fields: Vec<(String, String)>) -> Option<Self> {
+        let action = take_field(&mut fields, ACTION_FIELD)?;
+        // Browsers read "//host" and "/\host" as another site
+        if !action.starts_with('/') || action.starts_with("//") || action.starts_with("/\\") {
+            return None;
+        }
+        fields.retain(|(name, _)| !matches!(name.as_str(), "csrf_token" | PASSWORD_FIELD | CODE_FIELD));
+        Some(Self { action, fields })
+    }
+}
+
+pub(crate) struct ConfirmPage<'a> {
+    state: &'a AppState,
+    session: &'a Session,
+    admin: &'a Admin,
+    pending: PendingAction,
+    has_passkeys: bool,
+}
+
+impl<'a> ConfirmPage<'a> {
+    pub(crate) async fn new(
+        state: &'a AppState,
+        session: &'a Session,
+        admin: &'a Admin,
+        pending: PendingAction,
+    ) -> Self {
+        // A lookup error only hides the passkey button
+        let has_passkeys = repository::find_passkeys_by_admin(&state.db, admin.id)
+            .await
+            .is_ok_and(|passkeys| !passkeys.is_empty());
+        Self { state, session, admin, pending, has_passkeys }
+    }
+
+    // Admins with two-factor confirm with a code instead of their password
+    fn uses_code(&self) -> bool {
+        self.admin.has_two_factor() && self.state.two_factor.is_some()
+    }
+
+    // Single sign-on stands in for the password only: as at login, it does
+    // not replace a local second factor
+    pub(crate) fn sso_label(&self) -> Option<String> {
+        match &self.state.oidc {
+            Some(oidc) if !self.uses_code() => Some(oidc.config.button_label.clone()),
+            _ => None,
+        }
+    }
+
+    pub(crate) async fn render(&self, admin_user: AdminUser, error: Option<String>) -> Response {
+        self.template(admin_user, error, false).await.into_response()
+    }
+
+    // Shown on return from the identity provider: the window is open, so the
+    // form only needs to be re-posted
+    pub(crate) async fn render_confirmed(&self, admin_user: AdminUser) -> Response {
+        self.template(admin_user, None, true).await.into_response()
+    }
+
+    async fn template(
+        &self,
+        admin_user: AdminUser,
+        error: Option<String>,
+        confirmed: bool,
+    ) -> AdminReauthTemplate {
+        AdminReauthTemplate {
+            action: self.pending.action.clone(),
+            fields: self.pending.fields.clone(),
+            two_factor_enabled: self.uses_code(),
+            passkeys_enabled: self.has_passkeys,
+            sso_label: self.sso_label(),
+            confirmed,
+            window_minutes: self.state.reauth_window_seconds / 60,
+            error,
+            current_admin: Some(admin_user),
+            csrf_token: csrf::ensure_token(self.session).await,
+            base_path: self.state.base_path.clone(),
+        }
+    }
+}
+
+fn error_page(state: &AppState, admin_user: &AdminUser, code: StatusCode, message: &str) -> Response {
+    let template = AdminErrorTemplate {
+        error_code: code.as_u16(),
+        error_message: message.to_string(),
+        current_admin: Some(admin_user.clone()),
+        base_path: state.base_path.clone(),
+    };
+    (code, template).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(name: &str, value: &str) -> (String, String) {
+        (name.to_string(), value.to_string())
+    }
+
+    #[test]
+    fn pending_action_keeps_only_the_original_fields() {
+        let fields = vec![
+            field("csrf_token", "token"),
+            field(ACTION_FIELD, "/admin/users/3/delete"),
+            field("confirm", "yes"),
+            field(PASSWORD_FIELD, "secret"),
+            field(CODE_FIELD, "123456"),
+        ];
+        let pending = PendingAction::from_form(fields).unwrap();
+        assert_eq!(pending.action, "/admin/users/3/delete");
+        assert_eq!(pending.fields, vec![field("confirm", "yes")]);
+    }
+
+    #[test]
+    fn pending_action_needs_a_local_path() {
+        assert!(PendingAction::from_form(vec![field("confirm", "yes")]).is_none());
+        for action in ["https://evil.example/", "//evil.example/", "/\\evil.example/", "users/3/delete"] {
+            assert!(PendingAction::from_form(vec![field(ACTION_FIELD, action)]).is_none(), "{}", action);
+        }
+    }
+}
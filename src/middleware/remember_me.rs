@@ -0,0 +1,130 @@
+use axum::{
+    extract::{Request, State},
+    http::{HeaderValue, header::SET_COOKIE},
+    middleware::Next,
+    response::Response,
+};
+use axum_extra::extract::cookie::{Cookie, CookieJar};
+use tower_sessions::Session;
+
+use crate::controllers::auth_controller::{AdminUser, PendingTwoFactor};
+use crate::controllers::request_info::{ClientIp, UserAgent};
+use crate::repository;
+use crate::services::admin_sessions;
+use crate::middleware::login_notice;
+use crate::services::audit::{self, AuditEvent};
+use crate::services::login_history::{self, LoginAttempt, LoginMethod};
+use crate::services::remember_me::{COOKIE_NAME, TokenCheck};
+use crate::state::AppState;
+
+// Logs an admin back in from the remember-me cookie when their session has
+// expired, before the AdminUser extractor runs. Sets the rotated cookie (or
+// clears a bad one) on the response.
+pub async fn restore_admin_session(
+    State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    UserAgent(user_agent): UserAgent,
+    request: Request,
+    next: Next,
+) -> Response {
+    let cookie_value = CookieJar::from_headers(request.headers())
+        .get(COOKIE_NAME)
+        .map(|cookie| cookie.value().to_string());
+    let session = request.extensions().get::<Session>().cloned();
+    let (Some(cookie_value), Some(session)) = (cookie_value, session) else {
+        return next.run(request).await;
+    };
+    if !request.uri().path().starts_with(state.base_path.as_str())
+        || AdminUser::from_session(&session).await.is_some()
+        || PendingTwoFactor::from_session(&session).await.is_some()
+    {
+        return next.run(request).await;
+    }
+
+    let set_cookie = restore(&state, &session, &cookie_value, client_ip, &user_agent).await;
+    let mut response = next.run(request).await;
+    if let Some(cookie) = set_cookie
+        && let Ok(value) = HeaderValue::from_str(&cookie.to_string())
+    {
+        response.headers_mut().append(SET_COOKIE, value);
+    }
+    response
+}
+
+// Returns the cookie to send back: a rotated token, a removal, or nothing
+async fn restore(
+    state: &AppState,
+    session: &Session,
+    cookie_value: &str,
+    client_ip: std::net::IpAddr,
+    user_agent: &str,
+) -> Option<Cookie<'static>> {
+    let remember_me = &state.remember_me;
+    if !remember_me.enabled() {
+        return Some(remember_me.removal_cookie());
+    }
+    let check = match remember_me.check(&state.db, cookie_value).await {
+        Ok(check) => check,
+        Err(e) => {
+            tracing::error!("Failed to check remember-me token: {:?}", e);
+            return None;
+        }
+    };
+
+    let (token, current_hash) = match check {
+        TokenCheck::Valid(token, current_hash) => (token, Some(current_hash)),
+        TokenCheck::Superseded(token) => (token, None),
+        TokenCheck::Invalid => return Some(remember_me.removal_cookie()),
+        TokenCheck::Stolen(token) => {
+            tracing::warn!(
+                "Remember-me token reused after rotation for admin {} from {}; revoking all tokens and sessions",
+                token.username,
+                client_ip
+            );
+            if let Err(e) = repository::delete_remember_tokens_by_admin(&state.db, token.admin_id).await {
+                tracing::error!("Failed to revoke remember-me tokens: {:?}", e);
+            }
+            if let Err(e) = admin_sessions::revoke_all(&state.redis, token.admin_id).await {
+                tracing::error!("Failed to revoke admin sessions: {}", e);
+            }
+            let actor = AdminUser::new(token.admin_id, token.username.clone());
+            let event = AuditEvent::new("remember_token_theft", "admin", token.admin_id);
+            audit::record(&state.db, &actor, client_ip, event).await;
+            let attempt = LoginAttempt::new(LoginMethod::RememberMe, &token.username, client_ip, user_agent)
+                .for_admin(token.admin_id);
+            login_history::record_failure(&state.db, state.geoip.as_ref(), &attempt, "token_reused").await;
+            return Some(remember_me.removal_cookie());
+        }
+    };
+
+    let admin_user = AdminUser::new(token.admin_id, token.username.clone());
+    let session_ref = match admin_user
+        .login(session, state, &client_ip.to_string(), user_agent)
+        .await
+    {
+        Ok(session_ref) => session_ref,
+        Err(e) => {
+            tracing::error!("Failed to restore admin session: {:?}", e);
+            return None;
+        }
+    };
+    if let Err(e) = session.cycle_id().await {
+        tracing::error!("Failed to cycle session ID: {:?}", e);
+    }
+    tracing::info!("Admin session restored from remember-me token: {}", token.username);
+    let attempt = LoginAttempt::new(LoginMethod::RememberMe, &token.username, client_ip, user_agent)
+        .for_admin(token.admin_id);
+    if let Some(notice) = login_history::record_success(&state.db, state.geoip.as_ref(), &attempt).await {
+        login_notice::set(session, notice).await;
+    }
+
+    // A concurrent request that already rotated the token has sent the new cookie
+    let current_hash = current_hash?;
+    match remember_me.rotate(&state.db, &token, &current_hash, &session_ref).await {
+        Ok(cookie) => cookie,
+        Err(e) => {
+            tracing::error!("Failed to rotate remember-me token: {:?}", e);
+            None
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use axum::{
+    extract::{Request, State},
+    http::{HeaderName, HeaderValue, header},
+    middleware::Next,
+    response::Response,
+};
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use rand::RngCore;
+
+pub const CSP_REPORT_PATH: &str = "/csp-report";
+
+// Hosts the page templates load Bootstrap, jQuery, SweetAlert2 and DataTables from
+const CDN_SOURCES: &str = "https://cdn.jsdelivr.net https://code.jquery.com https://cdn.datatables.net";
+
+tokio::task_local! {
+    static CSP_NONCE: String;
+}
+
+// Nonce of the request being handled, for `<script nonce="...">` in templates:
+// `{{ crate::middleware::security_headers::csp_nonce() }}`. Empty outside the layer.
+pub fn csp_nonce() -> String {
+    CSP_NONCE.try_with(Clone::clone).unwrap_or_default()
+}
+
+// Headers added to every response. The policy is fixed apart from the nonce,
+// which is generated per request and only allows inline scripts that carry it.
+#[derive(Debug, Clone)]
+pub struct SecurityHeadersConfig {
+    // Send Content-Security-Policy-Report-Only instead of enforcing
+    pub report_only: bool,
+    // Strict-Transport-Security max-age; 0 leaves the header out
+    pub hsts_max_age: u64,
+}
+
+impl SecurityHeadersConfig {
+    pub fn from_env() -> Self {
+        let production = std::env::var("APP_ENV").is_ok_and(|v| v == "production");
+        let report_only = std::env::var("CSP_REPORT_ONLY")
+            .is_ok_and(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes"));
+        // HSTS is only meaningful behind HTTPS, so it defaults to on in production
+        let hsts_max_age = std::env::var("HSTS_MAX_AGE")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(if production { 31_536_000 } else { 0 });
+        Self { report_only, hsts_max_age }
+    }
+
+    fn policy(&self, nonce: &str) -> String {
+        // Styles stay 'unsafe-inline': the templates use style attributes and
+        // SweetAlert2 injects its stylesheet at runtime
+        [
+            "default-src 'self'".to_string(),
+            format!("script-src 'self' 'nonce-{}' {}", nonce, CDN_SOURCES),
+            format!("style-src 'self' 'unsafe-inline' {}", CDN_SOURCES),
+            format!("font-src 'self' data: {}", CDN_SOURCES),
+            "img-src 'self' data:".to_string(),
+            "connect-src 'self'".to_string(),
+            "object-src 'none'".to_string(),
+            "base-uri 'self'".to_string(),
+            "form-action 'self'".to_string(),
+            "frame-ancestors 'none'".to_string(),
+            format!("report-uri {}", CSP_REPORT_PATH),
+        ]
+        .join("; ")
+    }
+}
+
+fn generate_nonce() -> String {
+    let mut bytes = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    STANDARD.encode(bytes)
+}
+
+pub async fn security_headers(
+    State(config): State<SecurityHeadersConfig>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let nonce = generate_nonce();
+    let mut response = CSP_NONCE.scope(nonce.clone(), next.run(request)).await;
+
+    let csp_header = if config.report_only {
+        header::CONTENT_SECURITY_POLICY_REPORT_ONLY
+    } else {
+        header::CONTENT_SECURITY_POLICY
+    };
+    let mut headers: Vec<(HeaderName, String)> = vec![
+        (csp_header, config.policy(&nonce)),
+        (header::X_FRAME_OPTIONS, "DENY".to_string()),
+        (header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+        (header::REFERRER_POLICY, "strict-origin-when-cross-origin".to_string()),
+        (
+            HeaderName::from_static("permissions-policy"),
+            "camera=(), microphone=(), geolocation=(), payment=(), usb=()".to_string(),
+        ),
+    ];
+    if config.hsts_max_age > 0 {
+        headers.push((
+            header::STRICT_TRANSPORT_SECURITY,
+            format!("max-age={}; includeSubDomains", config.hsts_max_age),
+        ));
+    }
+
+    // Handlers that set one of these headers themselves keep their value
+    let response_headers = response.headers_mut();
+    for (name, value) in headers {
+        if let Ok(value) = HeaderValue::from_str(&value) {
+            response_headers.entry(name).or_insert(value);
+        }
+    }
+    response
+}
@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use time::OffsetDateTime;
+use time::{OffsetDateTime, Time};
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct Admin {
@@ -7,10 +7,104 @@ pub struct Admin {
     pub username: String,
     pub email: String,
     pub password_hash: String,
+    #[serde(skip_serializing)]
+    pub totp_secret_encrypted: Option<String>,
     #[serde(serialize_with = "crate::utils::serialize_datetime_option")]
+    pub totp_enabled_at: Option<OffsetDateTime>,
+    // Disabled admins cannot sign in and their sessions are revoked
+    #[serde(serialize_with = "crate::utils::serialize_datetime_option")]
+    pub disabled_at: Option<OffsetDateTime>,
+    #[serde(serialize_with = "crate::utils::serialize_datetime_option")]
+    pub created_at: Option<OffsetDateTime>,
+}
+
+impl Admin {
+    pub fn has_two_factor(&self) -> bool {
+        self.totp_enabled_at.is_some() && self.totp_secret_encrypted.is_some()
+    }
+
+    pub fn is_disabled(&self) -> bool {
+        self.disabled_at.is_some()
+    }
+}
+
+// Row of the admin accounts list
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct AdminListRow {
+    pub id: i32,
+    pub username: String,
+    pub email: String,
+    // Comma-separated role names
+    pub roles: Option<String>,
+    pub totp_enabled_at: Option<OffsetDateTime>,
+    pub disabled_at: Option<OffsetDateTime>,
     pub created_at: Option<OffsetDateTime>,
 }
 
+impl AdminListRow {
+    pub fn has_two_factor(&self) -> bool {
+        self.totp_enabled_at.is_some()
+    }
+
+    pub fn is_disabled(&self) -> bool {
+        self.disabled_at.is_some()
+    }
+
+    pub fn roles_display(&self) -> &str {
+        self.roles.as_deref().unwrap_or("none")
+    }
+
+    pub fn created_at_display(&self) -> String {
+        self.created_at.as_ref().map(crate::utils::format_datetime).unwrap_or_default()
+    }
+}
+
+// Role offered in the admin create and edit forms
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct RoleOption {
+    pub name: String,
+    pub description: Option<String>,
+}
+
+// Admin account management forms
+#[derive(Debug, Deserialize, validator::Validate)]
+pub struct CreateAdminForm {
+    #[validate(length(min = 1, max = 50))]
+    pub username: String,
+    #[validate(email, length(max = 100))]
+    pub email: String,
+    #[validate(length(min = 1))]
+    pub role: String,
+    // Strength rules live in services::password_policy
+    #[validate(length(min = 1))]
+    pub password: String,
+}
+
+#[derive(Debug, Deserialize, validator::Validate)]
+pub struct UpdateAdminForm {
+    #[validate(length(min = 1, max = 50))]
+    pub username: String,
+    #[validate(email, length(max = 100))]
+    pub email: String,
+    #[validate(length(min = 1))]
+    pub role: String,
+}
+
+#[derive(Debug, Deserialize, validator::Validate)]
+pub struct AdminPasswordForm {
+    #[validate(length(min = 1))]
+    pub password: String,
+    #[validate(must_match(other = "password"))]
+    pub password_confirm: String,
+}
+
+// Email change on the admin's own profile
+#[derive(Debug, Deserialize, validator::Validate)]
+pub struct AdminEmailForm {
+    #[validate(email, length(max = 100))]
+    pub email: String,
+}
+
 #[derive(Debug, Serialize, Clone)]
 pub struct AdminStateRow {
     pub id: i32,
@@ -19,5 +113,69 @@ pub struct AdminStateRow {
     pub name: String,
 }
 
+// Second login step and 2FA management forms
+#[derive(Debug, Deserialize, validator::Validate)]
+pub struct TwoFactorCodeForm {
+    #[validate(length(min = 6, max = 32))]
+    pub code: String,
+}
 
+// Forgot / reset password forms
+#[derive(Debug, Deserialize, validator::Validate)]
+pub struct ForgotPasswordForm {
+    #[validate(email)]
+    pub email: String,
+}
 
+#[derive(Debug, Deserialize)]
+pub struct ResetPasswordQuery {
+    pub token: Option<String>,
+}
+
+// Redirect back from the OpenID Connect provider
+#[derive(Debug, Deserialize)]
+pub struct OidcCallbackQuery {
+    pub code: Option<String>,
+    pub state: Option<String>,
+    pub error: Option<String>,
+    pub error_description: Option<String>,
+}
+
+#[derive(Debug, Deserialize, validator::Validate)]
+pub struct ResetPasswordForm {
+    #[validate(length(min = 1))]
+    pub token: String,
+    // Strength rules live in services::password_policy
+    #[validate(length(min = 1))]
+    pub password: String,
+    #[validate(must_match(other = "password"))]
+    pub password_confirm: String,
+}
+
+// Per-admin access override (admin_access_policies); None means unrestricted
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+pub struct AdminAccessPolicy {
+    pub admin_id: i32,
+    // Comma-separated CIDR ranges replacing ADMIN_IP_ALLOWLIST for this admin
+    pub ip_allowlist: Option<String>,
+    // Comma-separated weekdays, e.g. "mon,tue,wed,thu,fri"
+    pub allowed_days: Option<String>,
+    pub allowed_from: Option<Time>,
+    pub allowed_until: Option<Time>,
+    // Offset the days and hours are given in
+    pub utc_offset_minutes: i16,
+}
+
+// Remember-me token looked up by the selector in the cookie
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct AdminRememberToken {
+    pub id: i32,
+    pub admin_id: i32,
+    pub username: String,
+    pub selector: String,
+    pub validator_hash: String,
+    // Validator replaced by the last rotation, accepted briefly for concurrent requests
+    pub previous_validator_hash: Option<String>,
+    pub rotated_at: Option<OffsetDateTime>,
+    pub expires_at: OffsetDateTime,
+}
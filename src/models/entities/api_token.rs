@@ -0,0 +1,73 @@
+use serde::Deserialize;
+use time::OffsetDateTime;
+
+use crate::utils::format_datetime;
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ApiToken {
+    pub id: i32,
+    pub name: String,
+    pub token_prefix: String,
+    // Space-separated permission names, e.g. "users.view geo.view"
+    pub scopes: String,
+    pub expires_at: OffsetDateTime,
+    pub last_used_at: Option<OffsetDateTime>,
+    pub revoked_at: Option<OffsetDateTime>,
+    pub created_at: OffsetDateTime,
+}
+
+impl ApiToken {
+    pub fn scope_list(&self) -> Vec<&str> {
+        self.scopes.split_whitespace().collect()
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.revoked_at.is_none() && self.expires_at > OffsetDateTime::now_utc()
+    }
+
+    pub fn status(&self) -> &'static str {
+        if self.revoked_at.is_some() {
+            "Revoked"
+        } else if self.expires_at <= OffsetDateTime::now_utc() {
+            "Expired"
+        } else {
+            "Active"
+        }
+    }
+
+    pub fn created_at_display(&self) -> String {
+        format_datetime(&self.created_at)
+    }
+
+    pub fn expires_at_display(&self) -> String {
+        format_datetime(&self.expires_at)
+    }
+
+    pub fn last_used_at_display(&self) -> String {
+        self.last_used_at
+            .as_ref()
+            .map(format_datetime)
+            .unwrap_or_else(|| "Never".to_string())
+    }
+}
+
+// Token resolved from a bearer header
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ApiTokenOwner {
+    pub token_id: i32,
+    pub admin_id: i32,
+    pub username: String,
+    pub scopes: String,
+}
+
+// Scopes come from repeated checkboxes, so this is read with axum_extra's Form
+// (axum's Form cannot collect repeated keys)
+#[derive(Debug, Deserialize, validator::Validate)]
+pub struct CreateApiTokenForm {
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+    #[serde(default)]
+    pub scopes: Vec<String>,
+    #[validate(range(min = 1, max = 365))]
+    pub expires_in_days: i64,
+}
@@ -0,0 +1,122 @@
+use serde::{Deserialize, Serialize};
+use time::{Date, OffsetDateTime};
+use time::macros::format_description;
+
+use crate::utils::format_datetime;
+
+// Entity types written to audit_log.entity_type
+pub const AUDIT_ENTITY_TYPES: [&str; 7] =
+    ["user", "country", "state", "admin", "admin_session", "api_token", "passkey"];
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct AuditLogEntry {
+    pub id: i64,
+    pub admin_id: Option<i32>,
+    pub admin_username: String,
+    pub action: String,
+    pub entity_type: String,
+    pub entity_id: Option<String>,
+    pub before_json: Option<String>,
+    pub after_json: Option<String>,
+    pub ip: String,
+    #[serde(serialize_with = "crate::utils::serialize_datetime")]
+    pub created_at: OffsetDateTime,
+}
+
+impl AuditLogEntry {
+    pub fn created_at_display(&self) -> String {
+        format_datetime(&self.created_at)
+    }
+
+    pub fn has_changes(&self) -> bool {
+        self.before_json.is_some() || self.after_json.is_some()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NewAuditLog {
+    pub admin_id: i32,
+    pub admin_username: String,
+    pub action: String,
+    pub entity_type: String,
+    pub entity_id: Option<String>,
+    pub before_json: Option<String>,
+    pub after_json: Option<String>,
+    pub ip: String,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct AdminOption {
+    pub id: i32,
+    pub username: String,
+}
+
+// Raw filter inputs; empty form fields arrive as empty strings
+#[derive(Debug, Default, Deserialize)]
+pub struct AuditLogQuery {
+    pub admin_id: Option<String>,
+    pub entity_type: Option<String>,
+    pub entity_id: Option<String>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub page: Option<u32>,
+}
+
+// Validated filters; invalid inputs are dropped rather than rejected
+#[derive(Debug, Default, Clone)]
+pub struct AuditLogFilter {
+    pub admin_id: Option<i32>,
+    pub entity_type: Option<String>,
+    pub entity_id: Option<String>,
+    pub from: Option<Date>,
+    pub to: Option<Date>,
+}
+
+impl AuditLogQuery {
+    pub fn filter(&self) -> AuditLogFilter {
+        let non_empty = |value: &Option<String>| {
+            value
+                .as_deref()
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .map(str::to_string)
+        };
+        let parse_date = |value: &Option<String>| {
+            Date::parse(&non_empty(value)?, format_description!("[year]-[month]-[day]")).ok()
+        };
+
+        AuditLogFilter {
+            admin_id: non_empty(&self.admin_id).and_then(|v| v.parse().ok()),
+            entity_type: non_empty(&self.entity_type)
+                .filter(|v| AUDIT_ENTITY_TYPES.contains(&v.as_str())),
+            entity_id: non_empty(&self.entity_id)
+                .filter(|v| v.len() <= 64 && v.chars().all(|c| c.is_ascii_alphanumeric())),
+            from: parse_date(&self.from),
+            to: parse_date(&self.to),
+        }
+    }
+}
+
+impl AuditLogFilter {
+    // Query string for links (pagination, CSV) that keep the current filters.
+    // Every value has been validated above, so none needs escaping.
+    pub fn to_query_string(&self) -> String {
+        let mut params = Vec::new();
+        if let Some(admin_id) = self.admin_id {
+            params.push(format!("admin_id={}", admin_id));
+        }
+        if let Some(entity_type) = &self.entity_type {
+            params.push(format!("entity_type={}", entity_type));
+        }
+        if let Some(entity_id) = &self.entity_id {
+            params.push(format!("entity_id={}", entity_id));
+        }
+        if let Some(from) = self.from {
+            params.push(format!("from={}", from));
+        }
+        if let Some(to) = self.to {
+            params.push(format!("to={}", to));
+        }
+        params.join("&")
+    }
+}
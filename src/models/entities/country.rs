@@ -11,8 +11,6 @@ pub struct Country {
 pub struct CountryForm {
     #[validate(length(min = 1))]
     pub name: String,
-    #[validate(length(min = 1))]
-    pub csrf_token: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -12,6 +12,19 @@ pub struct DatatableParams {
     pub order_column: Option<i32>,
     #[serde(rename = "order[0][dir]")]
     pub order_dir: Option<String>,
+    // "verified" or "unverified"; anything else shows all users
+    pub verified: Option<String>,
+    // A UserStatus name; anything else shows all users
+    pub status: Option<String>,
+}
+
+// Parses the users table's verified filter
+pub fn parse_verified_filter(value: Option<&str>) -> Option<bool> {
+    match value {
+        Some("verified") => Some(true),
+        Some("unverified") => Some(false),
+        _ => None,
+    }
 }
 
 // DataTables response format
@@ -62,6 +75,8 @@ pub struct PaginationParams {
     pub offset: i64,
     pub limit: i64,
     pub search: Option<String>,
+    pub verified: Option<bool>,
+    pub status: Option<super::user_status::UserStatus>,
     pub order_column: String,
     pub order_direction: String,
 }
@@ -6,12 +6,6 @@ pub struct LoginForm {
     pub username: String,
     #[validate(length(min = 6))]
     pub password: String,
-    #[validate(length(min = 1))]
-    pub csrf_token: String,
-}
-
-#[derive(Debug, Deserialize, validator::Validate)]
-pub struct CsrfOnlyForm {
-    #[validate(length(min = 1))]
-    pub csrf_token: String,
+    // "remember me" checkbox on the admin login page
+    pub remember_me: Option<String>,
 }
\ No newline at end of file
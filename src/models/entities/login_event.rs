@@ -0,0 +1,53 @@
+use time::OffsetDateTime;
+
+use crate::utils::format_datetime;
+
+// Row of an admin's login history
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct AdminLoginEvent {
+    pub id: i64,
+    pub method: String,
+    pub success: bool,
+    pub reason: Option<String>,
+    pub ip: String,
+    pub user_agent: String,
+    pub country_code: Option<String>,
+    pub new_ip: bool,
+    pub new_country: bool,
+    pub created_at: OffsetDateTime,
+}
+
+impl AdminLoginEvent {
+    pub fn created_at_display(&self) -> String {
+        format_datetime(&self.created_at)
+    }
+
+    pub fn method_display(&self) -> &str {
+        match self.method.as_str() {
+            "password" => "Password",
+            "two_factor" => "Two-factor code",
+            "passkey" => "Passkey",
+            "sso" => "Single sign-on",
+            "remember_me" => "Remembered device",
+            other => other,
+        }
+    }
+
+    pub fn country_display(&self) -> &str {
+        self.country_code.as_deref().unwrap_or("-")
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NewAdminLoginEvent {
+    pub admin_id: Option<i32>,
+    pub username: String,
+    pub method: &'static str,
+    pub success: bool,
+    pub reason: Option<&'static str>,
+    pub ip: String,
+    pub user_agent: String,
+    pub country_code: Option<String>,
+    pub new_ip: bool,
+    pub new_country: bool,
+}
@@ -1,18 +1,43 @@
 pub mod admin;
+pub mod api_token;
+pub mod audit;
 pub mod country;
 pub mod datatable;
 pub mod forms;
+pub mod login_event;
+pub mod passkey;
+pub mod password;
 pub mod queries;
 pub mod state;
+pub mod trash;
 pub mod user;
+pub mod user_status;
 
-pub use admin::{Admin, AdminStateRow};
+pub use admin::{
+    Admin, AdminAccessPolicy, AdminEmailForm, AdminListRow, AdminPasswordForm, AdminRememberToken,
+    AdminStateRow, CreateAdminForm, ForgotPasswordForm, OidcCallbackQuery, ResetPasswordForm,
+    ResetPasswordQuery, RoleOption, TwoFactorCodeForm, UpdateAdminForm,
+};
+pub use api_token::{ApiToken, ApiTokenOwner, CreateApiTokenForm};
+pub use audit::{
+    AdminOption, AuditLogEntry, AuditLogFilter, AuditLogQuery, NewAuditLog, AUDIT_ENTITY_TYPES,
+};
 pub use country::{Country, CountryForm, CountryOption};
 pub use datatable::{
     DataTablesOrder, DataTablesRequest, DataTablesResponseLegacy, DataTablesSearch,
-    DatatableParams, DatatableResponse, PaginationParams, UserRow,
+    DatatableParams, DatatableResponse, PaginationParams, UserRow, parse_verified_filter,
 };
-pub use forms::{CsrfOnlyForm, LoginForm};
-pub use queries::PdfExportParams;
+pub use forms::LoginForm;
+pub use login_event::{AdminLoginEvent, NewAdminLoginEvent};
+pub use passkey::{
+    Passkey, PasskeyCredential, PasskeyLoginRequest, PasskeyRegistrationRequest, RenamePasskeyForm,
+};
+pub use password::PasswordOwner;
+pub use queries::{PdfExportParams, SignedPdfExportQuery};
 pub use state::{State, StateForm, StateOption, StateWithCountry, StatesQuery};
-pub use user::{CreateUserForm, UpdateUserForm, User, UserView};
+pub use trash::{PurgeCounts, TrashItem, TrashKind, TrashSection};
+pub use user::{
+    ChangePasswordForm, CreateUserForm, ProfileForm, RegisterForm, UpdateUserForm, User, UserView,
+    VerifyEmailQuery,
+};
+pub use user_status::{UserStatus, UserStatusChange, UserStatusForm};
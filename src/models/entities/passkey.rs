@@ -0,0 +1,69 @@
+use serde::Deserialize;
+use time::OffsetDateTime;
+
+use crate::utils::format_datetime;
+
+// Passkey as listed on the admin's passkeys page
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct Passkey {
+    pub id: i32,
+    pub name: String,
+    pub credential_id: Vec<u8>,
+    pub sign_count: u32,
+    pub last_used_at: Option<OffsetDateTime>,
+    pub created_at: OffsetDateTime,
+}
+
+impl Passkey {
+    pub fn created_at_display(&self) -> String {
+        format_datetime(&self.created_at)
+    }
+
+    pub fn last_used_at_display(&self) -> String {
+        self.last_used_at
+            .as_ref()
+            .map(format_datetime)
+            .unwrap_or_else(|| "Never".to_string())
+    }
+}
+
+// Stored credential looked up during a passkey login
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct PasskeyCredential {
+    pub id: i32,
+    pub admin_id: i32,
+    pub username: String,
+    pub public_key: Vec<u8>,
+    pub algorithm: i32,
+    pub sign_count: u32,
+}
+
+// Body of the registration request sent by static/js/passkeys.js; binary
+// fields are base64url
+#[derive(Debug, Deserialize, validator::Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct PasskeyRegistrationRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+    pub client_data_json: String,
+    pub attestation_object: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PasskeyLoginRequest {
+    pub raw_id: String,
+    pub client_data_json: String,
+    pub authenticator_data: String,
+    pub signature: String,
+    pub user_handle: Option<String>,
+    // "remember me" checkbox on the login page
+    #[serde(default)]
+    pub remember_me: bool,
+}
+
+#[derive(Debug, Deserialize, validator::Validate)]
+pub struct RenamePasskeyForm {
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+}
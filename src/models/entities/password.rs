@@ -0,0 +1,22 @@
+// Account a password belongs to; admins and users share password_history
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PasswordOwner {
+    Admin(i32),
+    User(i32),
+}
+
+impl PasswordOwner {
+    // Value of password_history.account_type
+    pub fn account_type(&self) -> &'static str {
+        match self {
+            PasswordOwner::Admin(_) => "admin",
+            PasswordOwner::User(_) => "user",
+        }
+    }
+
+    pub fn account_id(&self) -> i32 {
+        match self {
+            PasswordOwner::Admin(id) | PasswordOwner::User(id) => *id,
+        }
+    }
+}
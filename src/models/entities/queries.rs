@@ -1,8 +1,56 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct PdfExportParams {
     pub search: Option<String>,
+    pub verified: Option<String>,
+    pub status: Option<String>,
     pub order_column: Option<String>,
     pub order_direction: Option<String>,
 }
+
+impl PdfExportParams {
+    // Empty fields mean the same as missing ones
+    pub fn normalized(self) -> Self {
+        let keep = |value: Option<String>| value.filter(|v| !v.trim().is_empty());
+        Self {
+            search: keep(self.search),
+            verified: keep(self.verified),
+            status: keep(self.status),
+            order_column: keep(self.order_column),
+            order_direction: keep(self.order_direction),
+        }
+    }
+}
+
+// Query string of a shared PDF export link (services::export_links)
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SignedPdfExportQuery {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub search: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verified: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order_column: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order_direction: Option<String>,
+    // Admin who created the link
+    pub admin: i32,
+    pub expires: i64,
+    pub signature: String,
+}
+
+impl SignedPdfExportQuery {
+    pub fn params(&self) -> PdfExportParams {
+        PdfExportParams {
+            search: self.search.clone(),
+            verified: self.verified.clone(),
+            status: self.status.clone(),
+            order_column: self.order_column.clone(),
+            order_direction: self.order_direction.clone(),
+        }
+        .normalized()
+    }
+}
@@ -13,8 +13,6 @@ pub struct StateForm {
     pub country_id: i32,
     #[validate(length(min = 1))]
     pub name: String,
-    #[validate(length(min = 1))]
-    pub csrf_token: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
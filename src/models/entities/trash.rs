@@ -0,0 +1,108 @@
+use serde::Deserialize;
+use time::OffsetDateTime;
+
+use crate::utils::format_datetime;
+
+// Kinds of rows that are soft-deleted (`deleted_at`); the path segment of
+// the Trash routes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TrashKind {
+    Users,
+    Countries,
+    States,
+}
+
+impl TrashKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Users => "users",
+            Self::Countries => "countries",
+            Self::States => "states",
+        }
+    }
+
+    // Audit entity type
+    pub fn entity_type(&self) -> &'static str {
+        match self {
+            Self::Users => "user",
+            Self::Countries => "country",
+            Self::States => "state",
+        }
+    }
+
+    // Restoring and purging need the same permission as deleting
+    pub fn permission(&self) -> &'static str {
+        match self {
+            Self::Users => "users.delete",
+            Self::Countries | Self::States => "geo.delete",
+        }
+    }
+}
+
+// Row of the Trash page
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct TrashItem {
+    pub id: i32,
+    pub name: String,
+    // Email of a user, country of a state
+    pub detail: Option<String>,
+    pub deleted_at: OffsetDateTime,
+}
+
+impl TrashItem {
+    pub fn deleted_at_display(&self) -> String {
+        format_datetime(&self.deleted_at)
+    }
+
+    pub fn detail_display(&self) -> &str {
+        self.detail.as_deref().unwrap_or("-")
+    }
+}
+
+// One table of the Trash page
+#[derive(Debug, Clone)]
+pub struct TrashSection {
+    pub kind: TrashKind,
+    pub items: Vec<TrashItem>,
+}
+
+impl TrashSection {
+    pub fn path(&self) -> &'static str {
+        self.kind.as_str()
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self.kind {
+            TrashKind::Users => "Users",
+            TrashKind::Countries => "Countries",
+            TrashKind::States => "States",
+        }
+    }
+
+    pub fn detail_label(&self) -> &'static str {
+        match self.kind {
+            TrashKind::Users => "Email",
+            TrashKind::Countries => "",
+            TrashKind::States => "Country",
+        }
+    }
+
+    pub fn has_detail(&self) -> bool {
+        self.kind != TrashKind::Countries
+    }
+}
+
+// Rows removed by one auto-purge run
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PurgeCounts {
+    pub users: u64,
+    pub states: u64,
+    pub countries: u64,
+}
+
+impl PurgeCounts {
+    pub fn total(&self) -> u64 {
+        self.users + self.states + self.countries
+    }
+}
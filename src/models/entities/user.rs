@@ -1,17 +1,30 @@
 use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
 
+use super::user_status::UserStatus;
+
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct User {
     pub id: i32,
     pub username: String,
     pub email: String,
+    // NULL until the user follows a verification link; reset when the email changes
+    #[serde(serialize_with = "crate::utils::serialize_datetime_option")]
+    pub email_verified_at: Option<OffsetDateTime>,
     pub password_hash: String,
     #[serde(serialize_with = "crate::utils::serialize_datetime")]
     pub created_at: OffsetDateTime,
     pub address: Option<String>,
     pub country_id: Option<i32>,
     pub state_id: Option<i32>,
+    // Lifecycle state, see UserStatus
+    pub status: String,
+}
+
+impl User {
+    pub fn status(&self) -> UserStatus {
+        UserStatus::parse(&self.status).unwrap_or(UserStatus::Active)
+    }
 }
 
 // View data structures
@@ -20,9 +33,11 @@ pub struct UserView {
     pub id: u32,
     pub name: String,
     pub email: String,
+    pub email_verified_at: Option<String>,
     pub address: Option<String>,
     pub country: Option<String>,
     pub state: Option<String>,
+    pub status: UserStatus,
 }
 
 #[derive(Debug, Deserialize, validator::Validate)]
@@ -31,7 +46,7 @@ pub struct CreateUserForm {
     pub username: String,
     #[validate(email)]
     pub email: String,
-    #[validate(length(min = 6))]
+    // Strength rules live in services::password_policy
     pub password: String,
     #[validate(length(min = 1))]
     pub address: String,
@@ -39,8 +54,6 @@ pub struct CreateUserForm {
     pub country_id: i32,
     #[validate(range(min = 1))]
     pub state_id: i32,
-    #[validate(length(min = 1))]
-    pub csrf_token: String,
 }
 
 #[derive(Debug, Deserialize, validator::Validate)]
@@ -57,8 +70,6 @@ pub struct UpdateUserForm {
     pub state_id: i32,
     #[serde(default)]
     pub new_password: String,
-    #[validate(length(min = 1))]
-    pub csrf_token: String,
 }
 
 #[derive(Debug, Deserialize, validator::Validate)]
@@ -67,8 +78,33 @@ pub struct RegisterForm {
     pub username: String,
     #[validate(email)]
     pub email: String,
-    #[validate(length(min = 6))]
+    // Strength rules live in services::password_policy
     pub password: String,
+}
+
+// Query of the emailed verification link
+#[derive(Debug, Deserialize)]
+pub struct VerifyEmailQuery {
+    pub token: Option<String>,
+}
+
+// Public self-service profile forms
+#[derive(Debug, Deserialize, validator::Validate)]
+pub struct ProfileForm {
+    #[validate(length(min = 1, max = 255))]
+    pub address: String,
+    #[validate(range(min = 1))]
+    pub country_id: i32,
+    #[validate(range(min = 1))]
+    pub state_id: i32,
+}
+
+#[derive(Debug, Deserialize, validator::Validate)]
+pub struct ChangePasswordForm {
     #[validate(length(min = 1))]
-    pub csrf_token: String,
+    pub current_password: String,
+    // Strength rules live in services::password_policy
+    pub new_password: String,
+    #[validate(must_match(other = "new_password"))]
+    pub new_password_confirm: String,
 }
@@ -0,0 +1,189 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::utils::format_datetime;
+
+// Lifecycle of a user account (users.status). New accounts start pending and
+// become active once their email is verified; the other moves are made by
+// admins and must follow TRANSITIONS. Suspended is a sanction; locked is a
+// security hold (e.g. a suspected takeover) lifted by support once the owner
+// is confirmed. Neither can sign in, and each gets its own message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UserStatus {
+    Pending,
+    Active,
+    Suspended,
+    Locked,
+    Closed,
+}
+
+// Allowed (from, to) moves. Closed is final.
+const TRANSITIONS: &[(UserStatus, UserStatus)] = &[
+    (UserStatus::Pending, UserStatus::Active),
+    (UserStatus::Pending, UserStatus::Closed),
+    (UserStatus::Active, UserStatus::Suspended),
+    (UserStatus::Active, UserStatus::Locked),
+    (UserStatus::Active, UserStatus::Closed),
+    (UserStatus::Suspended, UserStatus::Active),
+    (UserStatus::Suspended, UserStatus::Closed),
+    (UserStatus::Locked, UserStatus::Active),
+    (UserStatus::Locked, UserStatus::Closed),
+];
+
+impl UserStatus {
+    pub const ALL: [UserStatus; 5] = [
+        UserStatus::Pending,
+        UserStatus::Active,
+        UserStatus::Suspended,
+        UserStatus::Locked,
+        UserStatus::Closed,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Active => "active",
+            Self::Suspended => "suspended",
+            Self::Locked => "locked",
+            Self::Closed => "closed",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|status| status.as_str() == value)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Pending => "Pending",
+            Self::Active => "Active",
+            Self::Suspended => "Suspended",
+            Self::Locked => "Locked",
+            Self::Closed => "Closed",
+        }
+    }
+
+    // Bootstrap badge class
+    pub fn badge_class(&self) -> &'static str {
+        match self {
+            Self::Pending => "bg-secondary",
+            Self::Active => "bg-success",
+            Self::Suspended => "bg-warning text-dark",
+            Self::Locked => "bg-info text-dark",
+            Self::Closed => "bg-dark",
+        }
+    }
+
+    pub fn can_transition_to(&self, to: UserStatus) -> bool {
+        TRANSITIONS.contains(&(*self, to))
+    }
+
+    pub fn transitions(&self) -> Vec<UserStatus> {
+        TRANSITIONS.iter().filter(|(from, _)| from == self).map(|(_, to)| *to).collect()
+    }
+
+    // Why the user login refuses this account, if it does
+    pub fn sign_in_refusal(&self) -> Option<&'static str> {
+        match self {
+            Self::Suspended => Some("This account has been suspended."),
+            Self::Locked => Some("This account is locked. Please contact support to unlock it."),
+            Self::Closed => Some("This account has been closed."),
+            _ => None,
+        }
+    }
+}
+
+// Status change submitted from the user detail page
+#[derive(Debug, Deserialize, validator::Validate)]
+pub struct UserStatusForm {
+    pub status: String,
+    #[validate(length(min = 1, max = 255))]
+    pub reason: String,
+}
+
+// Row of user_status_history
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct UserStatusChange {
+    pub id: i64,
+    pub from_status: String,
+    pub to_status: String,
+    pub reason: String,
+    // NULL for automatic changes (email verification)
+    pub admin_username: Option<String>,
+    pub created_at: OffsetDateTime,
+}
+
+impl UserStatusChange {
+    pub fn created_at_display(&self) -> String {
+        format_datetime(&self.created_at)
+    }
+
+    pub fn from_label(&self) -> &str {
+        UserStatus::parse(&self.from_status).map(|s| s.label()).unwrap_or(&self.from_status)
+    }
+
+    pub fn to_label(&self) -> &str {
+        UserStatus::parse(&self.to_status).map(|s| s.label()).unwrap_or(&self.to_status)
+    }
+
+    pub fn changed_by(&self) -> &str {
+        self.admin_username.as_deref().unwrap_or("system")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use UserStatus::*;
+
+    #[test]
+    fn allows_exactly_the_listed_moves() {
+        let allowed = [
+            (Pending, Active),
+            (Pending, Closed),
+            (Active, Suspended),
+            (Active, Locked),
+            (Active, Closed),
+            (Suspended, Active),
+            (Suspended, Closed),
+            (Locked, Active),
+            (Locked, Closed),
+        ];
+        for from in UserStatus::ALL {
+            for to in UserStatus::ALL {
+                let expected = allowed.contains(&(from, to));
+                assert_eq!(from.can_transition_to(to), expected, "{:?} -> {:?}", from, to);
+            }
+        }
+        assert_eq!(Active.transitions(), [Suspended, Locked, Closed]);
+    }
+
+    #[test]
+    fn refuses_moves_outside_the_table() {
+        assert!(!Closed.can_transition_to(Active));
+        assert!(!Pending.can_transition_to(Suspended));
+        assert!(!Locked.can_transition_to(Suspended));
+        assert!(Closed.transitions().is_empty());
+    }
+
+    #[test]
+    fn refuses_sign_in_unless_pending_or_active() {
+        assert_eq!(Pending.sign_in_refusal(), None);
+        assert_eq!(Active.sign_in_refusal(), None);
+        assert_eq!(Suspended.sign_in_refusal(), Some("This account has been suspended."));
+        assert_eq!(
+            Locked.sign_in_refusal(),
+            Some("This account is locked. Please contact support to unlock it.")
+        );
+        assert_eq!(Closed.sign_in_refusal(), Some("This account has been closed."));
+    }
+
+    #[test]
+    fn parses_its_own_names() {
+        for status in UserStatus::ALL {
+            assert_eq!(UserStatus::parse(status.as_str()), Some(status));
+        }
+        assert_eq!(UserStatus::parse("Active"), None);
+    }
+}
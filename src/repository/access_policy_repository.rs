@@ -0,0 +1,80 @@
+use sqlx::MySqlPool;
+
+use crate::models::entities::AdminAccessPolicy;
+
+pub async fn find_admin_access_policy(
+    pool: &MySqlPool,
+    admin_id: i32,
+) -> Result<Option<AdminAccessPolicy>, sqlx::Error> {
+    sqlx::query_as::<_, AdminAccessPolicy>(
+        "SELECT admin_id, ip_allowlist, allowed_days, allowed_from, allowed_until, utc_offset_minutes
+         FROM admin_access_policies
+         WHERE admin_id = ?",
+    )
+    .bind(admin_id)
+    .fetch_optional(pool)
+    .await
+}
+
+// Every per-admin IP allowlist, for requests made before signing in
+pub async fn find_admin_ip_allowlists(pool: &MySqlPool) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar::<_, String>(
+        "SELECT ip_allowlist FROM admin_access_policies WHERE ip_allowlist IS NOT NULL",
+    )
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn upsert_admin_access_policy(
+    pool: &MySqlPool,
+    policy: &AdminAccessPolicy,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO admin_access_policies
+             (admin_id, ip_allowlist, allowed_days, allowed_from, allowed_until, utc_offset_minutes)
+         VALUES (?, ?, ?, ?, ?, ?)
+         ON DUPLICATE KEY UPDATE
+             ip_allowlist = VALUES(ip_allowlist), allowed_days = VALUES(allowed_days),
+             allowed_from = VALUES(allowed_from), allowed_until = VALUES(allowed_until),
+             utc_offset_minutes = VALUES(utc_offset_minutes)",
+    )
+    .bind(policy.admin_id)
+    .bind(&policy.ip_allowlist)
+    .bind(&policy.allowed_days)
+    .bind(policy.allowed_from)
+    .bind(policy.allowed_until)
+    .bind(policy.utc_offset_minutes)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn delete_admin_access_policy(pool: &MySqlPool, admin_id: i32) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM admin_access_policies WHERE admin_id = ?")
+        .bind(admin_id)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn record_admin_access_denial(
+    pool: &MySqlPool,
+    admin_id: Option<i32>,
+    ip: &str,
+    path: &str,
+    reason: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO admin_access_denials (admin_id, ip, path, reason) VALUES (?, ?, ?, ?)",
+    )
+    .bind(admin_id)
+    .bind(ip)
+    .bind(path)
+    .bind(reason)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
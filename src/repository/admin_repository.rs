@@ -1,12 +1,17 @@
-use sqlx::MySqlPool;
-use crate::models::entities::Admin;
+use std::collections::HashSet;
+
+use sqlx::{MySql, MySqlPool, Transaction};
+use crate::models::entities::{Admin, AdminListRow, RoleOption};
+
+// Role that must stay held by at least one enabled admin
+pub const SUPER_ADMIN_ROLE: &str = "super-admin";
 
 pub async fn find_admin_by_username(
     pool: &MySqlPool,
     username: &str,
 ) -> Result<Option<Admin>, sqlx::Error> {
     let admin = sqlx::query_as::<_, Admin>(
-        "SELECT id, username, email, password_hash, created_at FROM admins WHERE username = ?",
+        "SELECT id, username, email, password_hash, totp_secret_encrypted, totp_enabled_at, disabled_at, created_at FROM admins WHERE username = ?",
     )
     .bind(username)
     .fetch_optional(pool)
@@ -20,7 +25,7 @@ pub async fn find_admin_by_id(
     admin_id: i32,
 ) -> Result<Option<Admin>, sqlx::Error> {
     let admin = sqlx::query_as::<_, Admin>(
-        "SELECT id, username, email, password_hash, created_at FROM admins WHERE id = ?",
+        "SELECT id, username, email, password_hash, totp_secret_encrypted, totp_enabled_at, disabled_at, created_at FROM admins WHERE id = ?",
     )
     .bind(admin_id)
     .fetch_optional(pool)
@@ -28,3 +33,430 @@ pub async fn find_admin_by_id(
 
     Ok(admin)
 }
+
+pub async fn find_admin_by_email(
+    pool: &MySqlPool,
+    email: &str,
+) -> Result<Option<Admin>, sqlx::Error> {
+    let admin = sqlx::query_as::<_, Admin>(
+        "SELECT id, username, email, password_hash, totp_secret_encrypted, totp_enabled_at, disabled_at, created_at FROM admins WHERE email = ?",
+    )
+    .bind(email)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(admin)
+}
+
+pub async fn update_admin_password(
+    pool: &MySqlPool,
+    admin_id: i32,
+    password_hash: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE admins SET password_hash = ? WHERE id = ?")
+        .bind(password_hash)
+        .bind(admin_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn update_admin_email(pool: &MySqlPool, admin_id: i32, email: &str) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE admins SET email = ? WHERE id = ?")
+        .bind(email)
+        .bind(admin_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+// Creates an admin holding one role (by name). Fails with RowNotFound, and
+// creates nothing, when the role does not exist.
+pub async fn create_admin_with_role(
+    pool: &MySqlPool,
+    username: &str,
+    email: &str,
+    password_hash: &str,
+    role: &str,
+) -> Result<i32, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let admin_id = sqlx::query("INSERT INTO admins (username, email, password_hash) VALUES (?, ?, ?)")
+        .bind(username)
+        .bind(email)
+        .bind(password_hash)
+        .execute(&mut *tx)
+        .await?
+        .last_insert_id() as i32;
+
+    let granted = sqlx::query(
+        "INSERT INTO admin_roles (admin_id, role_id) SELECT ?, id FROM roles WHERE name = ?",
+    )
+    .bind(admin_id)
+    .bind(role)
+    .execute(&mut *tx)
+    .await?;
+    if granted.rows_affected() == 0 {
+        tx.rollback().await?;
+        return Err(sqlx::Error::RowNotFound);
+    }
+
+    tx.commit().await?;
+    Ok(admin_id)
+}
+
+// All admins with their roles, for the admin accounts page
+pub async fn list_admins(pool: &MySqlPool) -> Result<Vec<AdminListRow>, sqlx::Error> {
+    sqlx::query_as::<_, AdminListRow>(
+        "SELECT a.id, a.username, a.email,
+                GROUP_CONCAT(r.name ORDER BY r.name SEPARATOR ', ') AS roles,
+                a.totp_enabled_at, a.disabled_at, a.created_at
+         FROM admins a
+         LEFT JOIN admin_roles ar ON ar.admin_id = a.id
+         LEFT JOIN roles r ON r.id = ar.role_id
+         GROUP BY a.id, a.username, a.email, a.totp_enabled_at, a.disabled_at, a.created_at
+         ORDER BY a.username",
+    )
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn find_roles(pool: &MySqlPool) -> Result<Vec<RoleOption>, sqlx::Error> {
+    sqlx::query_as::<_, RoleOption>("SELECT name, description FROM roles ORDER BY name")
+        .fetch_all(pool)
+        .await
+}
+
+// Role names held by one admin
+pub async fn find_admin_roles(pool: &MySqlPool, admin_id: i32) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar::<_, String>(
+        "SELECT r.name FROM admin_roles ar JOIN roles r ON r.id = ar.role_id
+         WHERE ar.admin_id = ? ORDER BY r.name",
+    )
+    .bind(admin_id)
+    .fetch_all(pool)
+    .await
+}
+
+// True when `admin_id` is the only enabled admin holding SUPER_ADMIN_ROLE.
+// Locks those rows so concurrent changes cannot both pass the check.
+async fn is_last_active_super_admin(
+    tx: &mut Transaction<'_, MySql>,
+    admin_id: i32,
+) -> Result<bool, sqlx::Error> {
+    let holders: Vec<i32> = sqlx::query_scalar(
+        "SELECT a.id FROM admins a
+         JOIN admin_roles ar ON ar.admin_id = a.id
+         JOIN roles r ON r.id = ar.role_id
+         WHERE r.name = ? AND a.disabled_at IS NULL
+         FOR UPDATE",
+    )
+    .bind(SUPER_ADMIN_ROLE)
+    .fetch_all(&mut **tx)
+    .await?;
+
+    Ok(holders == [admin_id])
+}
+
+// Updates username, email and the (single) role. Returns false, changing
+// nothing, when it would take the role from the last active super-admin.
+// Fails with RowNotFound when the role does not exist.
+pub async fn update_admin(
+    pool: &MySqlPool,
+    admin_id: i32,
+    username: &str,
+    email: &str,
+    role: &str,
+) -> Result<bool, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    if role != SUPER_ADMIN_ROLE && is_last_active_super_admin(&mut tx, admin_id).await? {
+        tx.rollback().await?;
+        return Ok(false);
+    }
+
+    sqlx::query("UPDATE admins SET username = ?, email = ? WHERE id = ?")
+        .bind(username)
+        .bind(email)
+        .bind(admin_id)
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query("DELETE FROM admin_roles WHERE admin_id = ?")
+        .bind(admin_id)
+        .execute(&mut *tx)
+        .await?;
+    let granted = sqlx::query(
+        "INSERT INTO admin_roles (admin_id, role_id) SELECT ?, id FROM roles WHERE name = ?",
+    )
+    .bind(admin_id)
+    .bind(role)
+    .execute(&mut *tx)
+    .await?;
+    if granted.rows_affected() == 0 {
+        tx.rollback().await?;
+        return Err(sqlx::Error::RowNotFound);
+    }
+
+    tx.commit().await?;
+    Ok(true)
+}
+
+// Disables or re-enables sign-in. Returns false, changing nothing, when it
+// would disable the last active super-admin.
+pub async fn set_admin_disabled(
+    pool: &MySqlPool,
+    admin_id: i32,
+    disabled: bool,
+) -> Result<bool, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    if disabled && is_last_active_super_admin(&mut tx, admin_id).await? {
+        tx.rollback().await?;
+        return Ok(false);
+    }
+
+    let sql = if disabled {
+        "UPDATE admins SET disabled_at = COALESCE(disabled_at, CURRENT_TIMESTAMP) WHERE id = ?"
+    } else {
+        "UPDATE admins SET disabled_at = NULL WHERE id = ?"
+    };
+    sqlx::query(sql).bind(admin_id).execute(&mut *tx).await?;
+
+    tx.commit().await?;
+    Ok(true)
+}
+
+// Returns false, deleting nothing, for the last active super-admin. Rows
+// owned by the admin go with it (ON DELETE CASCADE); audit rows keep the
+// username.
+pub async fn delete_admin(pool: &MySqlPool, admin_id: i32) -> Result<bool, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    if is_last_active_super_admin(&mut tx, admin_id).await? {
+        tx.rollback().await?;
+        return Ok(false);
+    }
+
+    sqlx::query("DELETE FROM admins WHERE id = ?")
+        .bind(admin_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(true)
+}
+
+// Permission names granted through all of the admin's roles
+pub async fn find_admin_permissions(
+    pool: &MySqlPool,
+    admin_id: i32,
+) -> Result<HashSet<String>, sqlx::Error> {
+    let rows: Vec<(String,)> = sqlx::query_as(
+        "SELECT DISTINCT p.name
+         FROM admin_roles ar
+         JOIN role_permissions rp ON rp.role_id = ar.role_id
+         JOIN permissions p ON p.id = rp.permission_id
+         WHERE ar.admin_id = ?",
+    )
+    .bind(admin_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(|(name,)| name).collect())
+}
+
+// Stores the encrypted TOTP secret and replaces any previous recovery codes;
+// returns false if two-factor authentication is already enabled
+pub async fn enable_admin_totp(
+    pool: &MySqlPool,
+    admin_id: i32,
+    secret_encrypted: &str,
+    recovery_code_hashes: &[String],
+) -> Result<bool, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let updated = sqlx::query(
+        "UPDATE admins SET totp_secret_encrypted = ?, totp_enabled_at = CURRENT_TIMESTAMP \
+         WHERE id = ? AND totp_enabled_at IS NULL",
+    )
+    .bind(secret_encrypted)
+    .bind(admin_id)
+    .execute(&mut *tx)
+    .await?;
+    if updated.rows_affected() == 0 {
+        tx.rollback().await?;
+        return Ok(false);
+    }
+
+    store_recovery_codes(&mut tx, admin_id, recovery_code_hashes).await?;
+
+    tx.commit().await?;
+    Ok(true)
+}
+
+pub async fn disable_admin_totp(pool: &MySqlPool, admin_id: i32) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        "UPDATE admins SET totp_secret_encrypted = NULL, totp_enabled_at = NULL WHERE id = ?",
+    )
+    .bind(admin_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query("DELETE FROM admin_recovery_codes WHERE admin_id = ?")
+        .bind(admin_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(())
+}
+
+pub async fn replace_admin_recovery_codes(
+    pool: &MySqlPool,
+    admin_id: i32,
+    recovery_code_hashes: &[String],
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    store_recovery_codes(&mut tx, admin_id, recovery_code_hashes).await?;
+
+    tx.commit().await?;
+    Ok(())
+}
+
+async fn store_recovery_codes(
+    tx: &mut Transaction<'_, MySql>,
+    admin_id: i32,
+    recovery_code_hashes: &[String],
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM admin_recovery_codes WHERE admin_id = ?")
+        .bind(admin_id)
+        .execute(&mut **tx)
+        .await?;
+
+    for code_hash in recovery_code_hashes {
+        sqlx::query("INSERT INTO admin_recovery_codes (admin_id, code_hash) VALUES (?, ?)")
+            .bind(admin_id)
+            .bind(code_hash)
+            .execute(&mut **tx)
+            .await?;
+    }
+
+    Ok(())
+}
+
+// Marks a recovery code as used; returns false if it was unknown or already used
+pub async fn consume_admin_recovery_code(
+    pool: &MySqlPool,
+    admin_id: i32,
+    code_hash: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE admin_recovery_codes SET used_at = CURRENT_TIMESTAMP
+         WHERE admin_id = ? AND code_hash = ? AND used_at IS NULL",
+    )
+    .bind(admin_id)
+    .bind(code_hash)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() == 1)
+}
+
+pub async fn count_unused_recovery_codes(
+    pool: &MySqlPool,
+    admin_id: i32,
+) -> Result<i64, sqlx::Error> {
+    let (count,): (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM admin_recovery_codes WHERE admin_id = ? AND used_at IS NULL",
+    )
+    .bind(admin_id)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(count)
+}
+
+// Stores a new reset token; any earlier unused tokens for the admin stop working
+pub async fn create_admin_password_reset(
+    pool: &MySqlPool,
+    admin_id: i32,
+    token_hash: &str,
+    ttl_minutes: i64,
+    requested_ip: &str,
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        "UPDATE admin_password_resets SET used_at = CURRENT_TIMESTAMP WHERE admin_id = ? AND used_at IS NULL",
+    )
+    .bind(admin_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        "INSERT INTO admin_password_resets (admin_id, token_hash, expires_at, requested_ip)
+         VALUES (?, ?, DATE_ADD(CURRENT_TIMESTAMP, INTERVAL ? MINUTE), ?)",
+    )
+    .bind(admin_id)
+    .bind(token_hash)
+    .bind(ttl_minutes)
+    .bind(requested_ip)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(())
+}
+
+// Returns (reset id, admin id) for an unused, unexpired token
+pub async fn find_valid_admin_password_reset(
+    pool: &MySqlPool,
+    token_hash: &str,
+) -> Result<Option<(i32, i32)>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT id, admin_id FROM admin_password_resets
+         WHERE token_hash = ? AND used_at IS NULL AND expires_at > CURRENT_TIMESTAMP",
+    )
+    .bind(token_hash)
+    .fetch_optional(pool)
+    .await
+}
+
+// Consumes the token and sets the new password; returns false if the token was already used
+pub async fn complete_admin_password_reset(
+    pool: &MySqlPool,
+    reset_id: i32,
+    admin_id: i32,
+    password_hash: &str,
+) -> Result<bool, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let consumed = sqlx::query(
+        "UPDATE admin_password_resets SET used_at = CURRENT_TIMESTAMP
+         WHERE id = ? AND admin_id = ? AND used_at IS NULL AND expires_at > CURRENT_TIMESTAMP",
+    )
+    .bind(reset_id)
+    .bind(admin_id)
+    .execute(&mut *tx)
+    .await?;
+
+    if consumed.rows_affected() != 1 {
+        tx.rollback().await?;
+        return Ok(false);
+    }
+
+    sqlx::query("UPDATE admins SET password_hash = ? WHERE id = ?")
+        .bind(password_hash)
+        .bind(admin_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(true)
+}
@@ -0,0 +1,93 @@
+use sqlx::MySqlPool;
+
+use crate::models::entities::{ApiToken, ApiTokenOwner};
+
+// last_used_at is written at most this often per token
+const LAST_USED_RESOLUTION_SECONDS: i64 = 60;
+
+pub async fn create_api_token(
+    pool: &MySqlPool,
+    admin_id: i32,
+    name: &str,
+    token_prefix: &str,
+    token_hash: &str,
+    scopes: &str,
+    expires_in_days: i64,
+) -> Result<i32, sqlx::Error> {
+    let result = sqlx::query(
+        "INSERT INTO api_tokens (admin_id, name, token_prefix, token_hash, scopes, expires_at)
+         VALUES (?, ?, ?, ?, ?, DATE_ADD(CURRENT_TIMESTAMP, INTERVAL ? DAY))",
+    )
+    .bind(admin_id)
+    .bind(name)
+    .bind(token_prefix)
+    .bind(token_hash)
+    .bind(scopes)
+    .bind(expires_in_days)
+    .execute(pool)
+    .await?;
+
+    Ok(result.last_insert_id() as i32)
+}
+
+pub async fn find_api_tokens_by_admin(
+    pool: &MySqlPool,
+    admin_id: i32,
+) -> Result<Vec<ApiToken>, sqlx::Error> {
+    sqlx::query_as::<_, ApiToken>(
+        "SELECT id, name, token_prefix, scopes, expires_at, last_used_at, revoked_at, created_at
+         FROM api_tokens WHERE admin_id = ? ORDER BY created_at DESC, id DESC",
+    )
+    .bind(admin_id)
+    .fetch_all(pool)
+    .await
+}
+
+// Returns false if the token does not belong to the admin or is already revoked
+pub async fn revoke_api_token(
+    pool: &MySqlPool,
+    admin_id: i32,
+    token_id: i32,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE api_tokens SET revoked_at = CURRENT_TIMESTAMP
+         WHERE id = ? AND admin_id = ? AND revoked_at IS NULL",
+    )
+    .bind(token_id)
+    .bind(admin_id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() == 1)
+}
+
+// Owner of an unrevoked, unexpired token
+pub async fn find_active_api_token(
+    pool: &MySqlPool,
+    token_hash: &str,
+) -> Result<Option<ApiTokenOwner>, sqlx::Error> {
+    sqlx::query_as::<_, ApiTokenOwner>(
+        "SELECT t.id AS token_id, t.admin_id, a.username, t.scopes
+         FROM api_tokens t
+         JOIN admins a ON a.id = t.admin_id
+         WHERE t.token_hash = ? AND t.revoked_at IS NULL AND t.expires_at > CURRENT_TIMESTAMP
+           AND a.disabled_at IS NULL",
+    )
+    .bind(token_hash)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn touch_api_token(pool: &MySqlPool, token_id: i32) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE api_tokens SET last_used_at = CURRENT_TIMESTAMP
+         WHERE id = ?
+           AND (last_used_at IS NULL OR last_used_at < DATE_SUB(CURRENT_TIMESTAMP, INTERVAL ? SECOND))",
+    )
+    .bind(token_id)
+    .bind(LAST_USED_RESOLUTION_SECONDS)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
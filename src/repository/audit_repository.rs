@@ -0,0 +1,82 @@
+use sqlx::{MySql, MySqlPool, QueryBuilder};
+use time::Duration;
+
+use crate::models::entities::{AdminOption, AuditLogEntry, AuditLogFilter, NewAuditLog};
+
+pub async fn insert_audit_log(pool: &MySqlPool, entry: &NewAuditLog) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO audit_log
+            (admin_id, admin_username, action, entity_type, entity_id, before_json, after_json, ip)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(entry.admin_id)
+    .bind(&entry.admin_username)
+    .bind(&entry.action)
+    .bind(&entry.entity_type)
+    .bind(&entry.entity_id)
+    .bind(&entry.before_json)
+    .bind(&entry.after_json)
+    .bind(&entry.ip)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+fn push_filters(builder: &mut QueryBuilder<'_, MySql>, filter: &AuditLogFilter) {
+    builder.push(" WHERE 1 = 1");
+    if let Some(admin_id) = filter.admin_id {
+        builder.push(" AND admin_id = ").push_bind(admin_id);
+    }
+    if let Some(entity_type) = &filter.entity_type {
+        builder.push(" AND entity_type = ").push_bind(entity_type.clone());
+    }
+    if let Some(entity_id) = &filter.entity_id {
+        builder.push(" AND entity_id = ").push_bind(entity_id.clone());
+    }
+    if let Some(from) = filter.from {
+        builder.push(" AND created_at >= ").push_bind(from);
+    }
+    // `to` is inclusive of the whole day
+    if let Some(to) = filter.to {
+        builder.push(" AND created_at < ").push_bind(to + Duration::days(1));
+    }
+}
+
+pub async fn count_audit_logs(
+    pool: &MySqlPool,
+    filter: &AuditLogFilter,
+) -> Result<i64, sqlx::Error> {
+    let mut builder = QueryBuilder::<MySql>::new("SELECT COUNT(*) FROM audit_log");
+    push_filters(&mut builder, filter);
+    let (count,): (i64,) = builder.build_query_as().fetch_one(pool).await?;
+    Ok(count)
+}
+
+// Newest first
+pub async fn find_audit_logs(
+    pool: &MySqlPool,
+    filter: &AuditLogFilter,
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<AuditLogEntry>, sqlx::Error> {
+    let mut builder = QueryBuilder::<MySql>::new(
+        "SELECT id, admin_id, admin_username, action, entity_type, entity_id,
+                CAST(before_json AS CHAR) AS before_json, CAST(after_json AS CHAR) AS after_json,
+                ip, created_at
+         FROM audit_log",
+    );
+    push_filters(&mut builder, filter);
+    builder
+        .push(" ORDER BY created_at DESC, id DESC LIMIT ")
+        .push_bind(limit)
+        .push(" OFFSET ")
+        .push_bind(offset);
+    builder.build_query_as::<AuditLogEntry>().fetch_all(pool).await
+}
+
+pub async fn find_admin_options(pool: &MySqlPool) -> Result<Vec<AdminOption>, sqlx::Error> {
+    sqlx::query_as::<_, AdminOption>("SELECT id, username FROM admins ORDER BY username")
+        .fetch_all(pool)
+        .await
+}
@@ -2,7 +2,7 @@ use sqlx::MySqlPool;
 use crate::models::entities::Country;
 
 pub async fn get_countries(pool: &MySqlPool) -> Result<Vec<Country>, sqlx::Error> {
-    let rows = sqlx::query_as::<_, Country>("SELECT id, name FROM countries ORDER BY name ASC")
+    let rows = sqlx::query_as::<_, Country>("SELECT id, name FROM countries WHERE deleted_at IS NULL ORDER BY name ASC")
         .fetch_all(pool)
         .await?;
     Ok(rows)
@@ -13,7 +13,7 @@ pub async fn get_country_by_id(
     country_id: i32,
 ) -> Result<Option<Country>, sqlx::Error> {
     let row = sqlx::query_as::<_, Country>(
-        "SELECT id, name FROM countries WHERE id = ?",
+        "SELECT id, name FROM countries WHERE id = ? AND deleted_at IS NULL",
     )
     .bind(country_id)
     .fetch_optional(pool)
@@ -40,10 +40,14 @@ pub async fn update_country(
     Ok(())
 }
 
+// Moves the country to the trash (see trash_repository)
 pub async fn delete_country(pool: &MySqlPool, country_id: i32) -> Result<(), sqlx::Error> {
-    sqlx::query!("DELETE FROM countries WHERE id = ?", country_id)
-        .execute(pool)
-        .await?;
+    sqlx::query!(
+        "UPDATE countries SET deleted_at = CURRENT_TIMESTAMP WHERE id = ? AND deleted_at IS NULL",
+        country_id
+    )
+    .execute(pool)
+    .await?;
     Ok(())
 }
 
@@ -52,7 +56,7 @@ pub async fn count_states_by_country_id(
     country_id: i32,
 ) -> Result<i64, sqlx::Error> {
     let result = sqlx::query!(
-        "SELECT COUNT(*) as count FROM states WHERE country_id = ?",
+        "SELECT COUNT(*) as count FROM states WHERE country_id = ? AND deleted_at IS NULL",
         country_id
     )
     .fetch_one(pool)
@@ -65,7 +69,7 @@ pub async fn count_users_by_country_id(
     country_id: i32,
 ) -> Result<i64, sqlx::Error> {
     let result = sqlx::query!(
-        "SELECT COUNT(*) as count FROM users WHERE country_id = ?",
+        "SELECT COUNT(*) as count FROM users WHERE country_id = ? AND deleted_at IS NULL",
         country_id
     )
     .fetch_one(pool)
@@ -0,0 +1,68 @@
+use sqlx::MySqlPool;
+
+use crate::models::entities::{AdminLoginEvent, NewAdminLoginEvent};
+
+pub async fn record_admin_login_event(
+    pool: &MySqlPool,
+    event: &NewAdminLoginEvent,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO admin_login_events
+             (admin_id, username, method, success, reason, ip, user_agent, country_code, new_ip, new_country)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(event.admin_id)
+    .bind(&event.username)
+    .bind(event.method)
+    .bind(event.success)
+    .bind(event.reason)
+    .bind(&event.ip)
+    .bind(&event.user_agent)
+    .bind(&event.country_code)
+    .bind(event.new_ip)
+    .bind(event.new_country)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// Successful sign-ins of the admin so far: in total, from `ip`, and from
+// `country_code`
+pub async fn count_admin_successful_logins(
+    pool: &MySqlPool,
+    admin_id: i32,
+    ip: &str,
+    country_code: Option<&str>,
+) -> Result<(i64, i64, i64), sqlx::Error> {
+    sqlx::query_as::<_, (i64, i64, i64)>(
+        "SELECT COUNT(*),
+                COUNT(CASE WHEN ip = ? THEN 1 END),
+                COUNT(CASE WHEN country_code = ? THEN 1 END)
+         FROM admin_login_events
+         WHERE admin_id = ? AND success = TRUE",
+    )
+    .bind(ip)
+    .bind(country_code)
+    .bind(admin_id)
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn find_recent_admin_login_events(
+    pool: &MySqlPool,
+    admin_id: i32,
+    limit: i64,
+) -> Result<Vec<AdminLoginEvent>, sqlx::Error> {
+    sqlx::query_as::<_, AdminLoginEvent>(
+        "SELECT id, method, success, reason, ip, user_agent, country_code, new_ip, new_country, created_at
+         FROM admin_login_events
+         WHERE admin_id = ?
+         ORDER BY created_at DESC, id DESC
+         LIMIT ?",
+    )
+    .bind(admin_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
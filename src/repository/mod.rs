@@ -1,11 +1,29 @@
 // Repository modules
 pub mod user_repository;
 pub mod admin_repository;
+pub mod access_policy_repository;
+pub mod api_token_repository;
+pub mod audit_repository;
 pub mod country_repository;
+pub mod login_event_repository;
+pub mod passkey_repository;
+pub mod password_history_repository;
+pub mod remember_token_repository;
 pub mod state_repository;
+pub mod trash_repository;
+pub mod user_status_repository;
 
 // Re-export commonly used repository functions
 pub use user_repository::*;
 pub use admin_repository::*;
+pub use access_policy_repository::*;
+pub use api_token_repository::*;
+pub use audit_repository::*;
 pub use country_repository::*;
+pub use login_event_repository::*;
+pub use passkey_repository::*;
+pub use password_history_repository::*;
+pub use remember_token_repository::*;
 pub use state_repository::*;
+pub use trash_repository::*;
+pub use user_status_repository::*;
@@ -0,0 +1,111 @@
+use sqlx::MySqlPool;
+
+use crate::models::entities::{Passkey, PasskeyCredential};
+
+pub async fn create_passkey(
+    pool: &MySqlPool,
+    admin_id: i32,
+    name: &str,
+    credential_id: &[u8],
+    public_key: &[u8],
+    algorithm: i64,
+    sign_count: u32,
+) -> Result<i32, sqlx::Error> {
+    let result = sqlx::query(
+        "INSERT INTO admin_passkeys
+             (admin_id, name, credential_id, credential_hash, public_key, algorithm, sign_count)
+         VALUES (?, ?, ?, SHA2(?, 256), ?, ?, ?)",
+    )
+    .bind(admin_id)
+    .bind(name)
+    .bind(credential_id)
+    .bind(credential_id)
+    .bind(public_key)
+    .bind(algorithm)
+    .bind(sign_count)
+    .execute(pool)
+    .await?;
+
+    Ok(result.last_insert_id() as i32)
+}
+
+pub async fn find_passkeys_by_admin(
+    pool: &MySqlPool,
+    admin_id: i32,
+) -> Result<Vec<Passkey>, sqlx::Error> {
+    sqlx::query_as::<_, Passkey>(
+        "SELECT id, name, credential_id, sign_count, last_used_at, created_at
+         FROM admin_passkeys WHERE admin_id = ? ORDER BY created_at DESC, id DESC",
+    )
+    .bind(admin_id)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn find_passkey_by_credential_id(
+    pool: &MySqlPool,
+    credential_id: &[u8],
+) -> Result<Option<PasskeyCredential>, sqlx::Error> {
+    sqlx::query_as::<_, PasskeyCredential>(
+        "SELECT p.id, p.admin_id, a.username, p.public_key, p.algorithm, p.sign_count
+         FROM admin_passkeys p
+         JOIN admins a ON a.id = p.admin_id
+         WHERE p.credential_hash = SHA2(?, 256)
+           AND a.disabled_at IS NULL",
+    )
+    .bind(credential_id)
+    .fetch_optional(pool)
+    .await
+}
+
+// Stores the new sign count. Returns false if another login already moved the
+// counter past `previous`, so a replayed or cloned assertion cannot win a race.
+pub async fn record_passkey_use(
+    pool: &MySqlPool,
+    passkey_id: i32,
+    previous: u32,
+    sign_count: u32,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE admin_passkeys SET sign_count = ?, last_used_at = CURRENT_TIMESTAMP
+         WHERE id = ? AND sign_count = ?",
+    )
+    .bind(sign_count)
+    .bind(passkey_id)
+    .bind(previous)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() == 1)
+}
+
+// Returns false if the passkey does not belong to the admin
+pub async fn rename_passkey(
+    pool: &MySqlPool,
+    admin_id: i32,
+    passkey_id: i32,
+    name: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("UPDATE admin_passkeys SET name = ? WHERE id = ? AND admin_id = ?")
+        .bind(name)
+        .bind(passkey_id)
+        .bind(admin_id)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() == 1)
+}
+
+pub async fn delete_passkey(
+    pool: &MySqlPool,
+    admin_id: i32,
+    passkey_id: i32,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM admin_passkeys WHERE id = ? AND admin_id = ?")
+        .bind(passkey_id)
+        .bind(admin_id)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() == 1)
+}
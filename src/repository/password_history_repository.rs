@@ -0,0 +1,62 @@
+use sqlx::MySqlPool;
+
+use crate::models::PasswordOwner;
+
+// Most recent password hashes of the account, newest first
+pub async fn find_password_history(
+    pool: &MySqlPool,
+    owner: PasswordOwner,
+    limit: u32,
+) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT password_hash FROM password_history
+         WHERE account_type = ? AND account_id = ?
+         ORDER BY id DESC LIMIT ?",
+    )
+    .bind(owner.account_type())
+    .bind(owner.account_id())
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
+
+// Appends a hash and drops everything but the newest `keep` rows for the account
+pub async fn insert_password_history(
+    pool: &MySqlPool,
+    owner: PasswordOwner,
+    password_hash: &str,
+    keep: u32,
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        "INSERT INTO password_history (account_type, account_id, password_hash) VALUES (?, ?, ?)",
+    )
+    .bind(owner.account_type())
+    .bind(owner.account_id())
+    .bind(password_hash)
+    .execute(&mut *tx)
+    .await?;
+
+    // MySQL cannot select from the table being deleted from, hence the derived table
+    sqlx::query(
+        "DELETE FROM password_history
+         WHERE account_type = ? AND account_id = ? AND id NOT IN (
+             SELECT id FROM (
+                 SELECT id FROM password_history
+                 WHERE account_type = ? AND account_id = ?
+                 ORDER BY id DESC LIMIT ?
+             ) AS newest
+         )",
+    )
+    .bind(owner.account_type())
+    .bind(owner.account_id())
+    .bind(owner.account_type())
+    .bind(owner.account_id())
+    .bind(keep)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(())
+}
@@ -0,0 +1,129 @@
+use sqlx::MySqlPool;
+
+use crate::models::entities::AdminRememberToken;
+
+pub async fn create_remember_token(
+    pool: &MySqlPool,
+    admin_id: i32,
+    selector: &str,
+    validator_hash: &str,
+    session_ref: &str,
+    lifetime_days: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO admin_remember_tokens (admin_id, selector, validator_hash, session_ref, expires_at)
+         VALUES (?, ?, ?, ?, DATE_ADD(CURRENT_TIMESTAMP, INTERVAL ? DAY))",
+    )
+    .bind(admin_id)
+    .bind(selector)
+    .bind(validator_hash)
+    .bind(session_ref)
+    .bind(lifetime_days)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// Unexpired token for a selector
+pub async fn find_remember_token(
+    pool: &MySqlPool,
+    selector: &str,
+) -> Result<Option<AdminRememberToken>, sqlx::Error> {
+    sqlx::query_as::<_, AdminRememberToken>(
+        "SELECT t.id, t.admin_id, a.username, t.selector, t.validator_hash, t.previous_validator_hash,
+                t.rotated_at, t.expires_at
+         FROM admin_remember_tokens t
+         JOIN admins a ON a.id = t.admin_id
+         WHERE t.selector = ? AND t.expires_at > CURRENT_TIMESTAMP
+           AND a.disabled_at IS NULL",
+    )
+    .bind(selector)
+    .fetch_optional(pool)
+    .await
+}
+
+// Replaces the validator. Returns false if another request rotated the token
+// first (the stored hash no longer matches `current_hash`).
+pub async fn rotate_remember_token(
+    pool: &MySqlPool,
+    token_id: i32,
+    current_hash: &str,
+    new_hash: &str,
+    session_ref: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE admin_remember_tokens
+         SET previous_validator_hash = validator_hash, validator_hash = ?,
+             rotated_at = CURRENT_TIMESTAMP, session_ref = ?
+         WHERE id = ? AND validator_hash = ?",
+    )
+    .bind(new_hash)
+    .bind(session_ref)
+    .bind(token_id)
+    .bind(current_hash)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() == 1)
+}
+
+pub async fn update_remember_token_session(
+    pool: &MySqlPool,
+    token_id: i32,
+    session_ref: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE admin_remember_tokens SET session_ref = ? WHERE id = ?")
+        .bind(session_ref)
+        .bind(token_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn delete_remember_token(pool: &MySqlPool, selector: &str) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM admin_remember_tokens WHERE selector = ?")
+        .bind(selector)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+// Token that restored (or was issued with) a given admin session
+pub async fn delete_remember_tokens_by_session(
+    pool: &MySqlPool,
+    admin_id: i32,
+    session_ref: &str,
+) -> Result<u64, sqlx::Error> {
+    let result =
+        sqlx::query("DELETE FROM admin_remember_tokens WHERE admin_id = ? AND session_ref = ?")
+            .bind(admin_id)
+            .bind(session_ref)
+            .execute(pool)
+            .await?;
+
+    Ok(result.rows_affected())
+}
+
+pub async fn delete_remember_tokens_by_admin(
+    pool: &MySqlPool,
+    admin_id: i32,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM admin_remember_tokens WHERE admin_id = ?")
+        .bind(admin_id)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected())
+}
+
+pub async fn delete_expired_remember_tokens(pool: &MySqlPool) -> Result<u64, sqlx::Error> {
+    let result =
+        sqlx::query("DELETE FROM admin_remember_tokens WHERE expires_at <= CURRENT_TIMESTAMP")
+            .execute(pool)
+            .await?;
+
+    Ok(result.rows_affected())
+}
@@ -6,7 +6,7 @@ pub async fn get_states_by_country(
     country_id: i32,
 ) -> Result<Vec<State>, sqlx::Error> {
     let rows = sqlx::query_as::<_, State>(
-        "SELECT id, country_id, name FROM states WHERE country_id = ? ORDER BY name ASC",
+        "SELECT id, country_id, name FROM states WHERE country_id = ? AND deleted_at IS NULL ORDER BY name ASC",
     )
     .bind(country_id)
     .fetch_all(pool)
@@ -19,7 +19,7 @@ pub async fn get_state_by_id(
     state_id: i32,
 ) -> Result<Option<State>, sqlx::Error> {
     let row = sqlx::query_as::<_, State>(
-        "SELECT id, country_id, name FROM states WHERE id = ?",
+        "SELECT id, country_id, name FROM states WHERE id = ? AND deleted_at IS NULL",
     )
     .bind(state_id)
     .fetch_optional(pool)
@@ -34,6 +34,7 @@ pub async fn get_states_with_countries(
         "SELECT s.id, s.country_id, s.name, c.name as country_name
          FROM states s
          JOIN countries c ON c.id = s.country_id
+         WHERE s.deleted_at IS NULL AND c.deleted_at IS NULL
          ORDER BY c.name ASC, s.name ASC",
     )
     .fetch_all(pool)
@@ -71,10 +72,14 @@ pub async fn update_state(
     Ok(())
 }
 
+// Moves the state to the trash (see trash_repository)
 pub async fn delete_state(pool: &MySqlPool, state_id: i32) -> Result<(), sqlx::Error> {
-    sqlx::query!("DELETE FROM states WHERE id = ?", state_id)
-        .execute(pool)
-        .await?;
+    sqlx::query!(
+        "UPDATE states SET deleted_at = CURRENT_TIMESTAMP WHERE id = ? AND deleted_at IS NULL",
+        state_id
+    )
+    .execute(pool)
+    .await?;
     Ok(())
 }
 
@@ -83,7 +88,7 @@ pub async fn count_users_by_state_id(
     state_id: i32,
 ) -> Result<i64, sqlx::Error> {
     let result = sqlx::query!(
-        "SELECT COUNT(*) as count FROM users WHERE state_id = ?",
+        "SELECT COUNT(*) as count FROM users WHERE state_id = ? AND deleted_at IS NULL",
         state_id
     )
     .fetch_one(pool)
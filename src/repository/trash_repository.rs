@@ -0,0 +1,104 @@
+use sqlx::MySqlPool;
+
+use crate::models::entities::{PurgeCounts, TrashItem, TrashKind};
+
+// Soft-deleted users, countries and states. The delete_* functions of the
+// other repositories only set `deleted_at`; rows leave the database here.
+
+pub async fn find_trash_items(pool: &MySqlPool, kind: TrashKind) -> Result<Vec<TrashItem>, sqlx::Error> {
+    let sql = match kind {
+        TrashKind::Users => {
+            "SELECT id, username AS name, email AS detail, deleted_at
+             FROM users WHERE deleted_at IS NOT NULL ORDER BY deleted_at DESC"
+        }
+        TrashKind::Countries => {
+            "SELECT id, name, CAST(NULL AS CHAR) AS detail, deleted_at
+             FROM countries WHERE deleted_at IS NOT NULL ORDER BY deleted_at DESC"
+        }
+        TrashKind::States => {
+            "SELECT s.id, s.name, c.name AS detail, s.deleted_at
+             FROM states s
+             LEFT JOIN countries c ON c.id = s.country_id
+             WHERE s.deleted_at IS NOT NULL ORDER BY s.deleted_at DESC"
+        }
+    };
+    sqlx::query_as::<_, TrashItem>(sql).fetch_all(pool).await
+}
+
+// Moves a row out of the trash; returns false if it was not in it. A user or
+// state whose country (or state) is itself in the trash is not restored.
+pub async fn restore_trash_item(pool: &MySqlPool, kind: TrashKind, id: i32) -> Result<bool, sqlx::Error> {
+    let sql = match kind {
+        TrashKind::Users => {
+            "UPDATE users u SET u.deleted_at = NULL
+             WHERE u.id = ? AND u.deleted_at IS NOT NULL
+               AND NOT EXISTS (SELECT 1 FROM countries c WHERE c.id = u.country_id AND c.deleted_at IS NOT NULL)
+               AND NOT EXISTS (SELECT 1 FROM states s WHERE s.id = u.state_id AND s.deleted_at IS NOT NULL)"
+        }
+        TrashKind::Countries => "UPDATE countries SET deleted_at = NULL WHERE id = ? AND deleted_at IS NOT NULL",
+        TrashKind::States => {
+            "UPDATE states s SET s.deleted_at = NULL
+             WHERE s.id = ? AND s.deleted_at IS NOT NULL
+               AND NOT EXISTS (SELECT 1 FROM countries c WHERE c.id = s.country_id AND c.deleted_at IS NOT NULL)"
+        }
+    };
+    let result = sqlx::query(sql).bind(id).execute(pool).await?;
+    Ok(result.rows_affected() > 0)
+}
+
+// Deletes a row in the trash for good; returns false if it was not in the
+// trash. Countries and states still referenced by any row, trashed or not,
+// are kept so nothing points at a missing id.
+pub async fn purge_trash_item(pool: &MySqlPool, kind: TrashKind, id: i32) -> Result<bool, sqlx::Error> {
+    let sql = match kind {
+        TrashKind::Users => "DELETE FROM users WHERE id = ? AND deleted_at IS NOT NULL",
+        TrashKind::Countries => {
+            "DELETE FROM countries
+             WHERE id = ? AND deleted_at IS NOT NULL
+               AND NOT EXISTS (SELECT 1 FROM states s WHERE s.country_id = countries.id)
+               AND NOT EXISTS (SELECT 1 FROM users u WHERE u.country_id = countries.id)"
+        }
+        TrashKind::States => {
+            "DELETE FROM states
+             WHERE id = ? AND deleted_at IS NOT NULL
+               AND NOT EXISTS (SELECT 1 FROM users u WHERE u.state_id = states.id)"
+        }
+    };
+    let result = sqlx::query(sql).bind(id).execute(pool).await?;
+    Ok(result.rows_affected() > 0)
+}
+
+// Deletes everything that has been in the trash for more than `days` days.
+// Users go first so their states and countries can follow in the same run.
+pub async fn purge_trash_older_than(pool: &MySqlPool, days: u32) -> Result<PurgeCounts, sqlx::Error> {
+    let users = sqlx::query(
+        "DELETE FROM users WHERE deleted_at < CURRENT_TIMESTAMP - INTERVAL ? DAY",
+    )
+    .bind(days)
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    let states = sqlx::query(
+        "DELETE FROM states
+         WHERE deleted_at < CURRENT_TIMESTAMP - INTERVAL ? DAY
+           AND NOT EXISTS (SELECT 1 FROM users u WHERE u.state_id = states.id)",
+    )
+    .bind(days)
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    let countries = sqlx::query(
+        "DELETE FROM countries
+         WHERE deleted_at < CURRENT_TIMESTAMP - INTERVAL ? DAY
+           AND NOT EXISTS (SELECT 1 FROM states s WHERE s.country_id = countries.id)
+           AND NOT EXISTS (SELECT 1 FROM users u WHERE u.country_id = countries.id)",
+    )
+    .bind(days)
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    Ok(PurgeCounts { users, states, countries })
+}
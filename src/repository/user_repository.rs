@@ -1,20 +1,17 @@
 use sqlx::MySqlPool;
-use crate::models::entities::{User, PaginationParams};
+use crate::models::entities::{User, PaginationParams, UserStatus};
 
 pub async fn create_user(
     pool: &MySqlPool,
     username: &str,
     email: &str,
-    password: &str,
+    password_hash: &str,
     address: Option<&str>,
     country_id: Option<i32>,
     state_id: Option<i32>,
 ) -> Result<i32, sqlx::Error> {
-    let password_hash = bcrypt::hash(password, bcrypt::DEFAULT_COST)
-        .map_err(|e| sqlx::Error::Protocol(format!("Password hashing failed: {}", e)))?;
-
     let result = sqlx::query(
-        "INSERT INTO users (username, email, password_hash, address, country_id, state_id) VALUES (?, ?, ?, ?, ?, ?)",
+        "INSERT INTO users (username, email, password_hash, address, country_id, state_id, status) VALUES (?, ?, ?, ?, ?, ?, ?)",
     )
     .bind(username)
     .bind(email)
@@ -22,6 +19,7 @@ pub async fn create_user(
     .bind(address)
     .bind(country_id)
     .bind(state_id)
+    .bind(UserStatus::Pending.as_str())
     .execute(pool)
     .await?;
 
@@ -33,7 +31,7 @@ pub async fn find_user_by_username(
     username: &str,
 ) -> Result<Option<User>, sqlx::Error> {
     let user = sqlx::query_as::<_, User>(
-        "SELECT id, username, email, password_hash, created_at, address, country_id, state_id FROM users WHERE username = ?",
+        "SELECT id, username, email, email_verified_at, password_hash, created_at, address, country_id, state_id, status FROM users WHERE username = ? AND deleted_at IS NULL",
     )
     .bind(username)
     .fetch_optional(pool)
@@ -47,7 +45,7 @@ pub async fn find_user_by_id(
     user_id: i32,
 ) -> Result<Option<User>, sqlx::Error> {
     let user = sqlx::query_as::<_, User>(
-        "SELECT id, username, email, password_hash, created_at, address, country_id, state_id FROM users WHERE id = ?",
+        "SELECT id, username, email, email_verified_at, password_hash, created_at, address, country_id, state_id, status FROM users WHERE id = ? AND deleted_at IS NULL",
     )
     .bind(user_id)
     .fetch_optional(pool)
@@ -56,22 +54,33 @@ pub async fn find_user_by_id(
     Ok(user)
 }
 
-pub async fn update_password(
+// Status of a user that has not been deleted; checked on every request made
+// with a user session
+pub async fn find_user_status(
     pool: &MySqlPool,
     user_id: i32,
-    new_password: &str,
-) -> Result<(), sqlx::Error> {
-    let password_hash = bcrypt::hash(new_password, bcrypt::DEFAULT_COST)
-        .map_err(|e| sqlx::Error::Protocol(format!("Password hashing failed: {}", e)))?;
-
-    sqlx::query!(
-        "UPDATE users SET password_hash = ? WHERE id = ?",
-        password_hash,
-        user_id
+) -> Result<Option<UserStatus>, sqlx::Error> {
+    let status = sqlx::query_scalar::<_, String>(
+        "SELECT status FROM users WHERE id = ? AND deleted_at IS NULL",
     )
-    .execute(pool)
+    .bind(user_id)
+    .fetch_optional(pool)
     .await?;
 
+    Ok(status.map(|status| UserStatus::parse(&status).unwrap_or(UserStatus::Active)))
+}
+
+pub async fn update_password(
+    pool: &MySqlPool,
+    user_id: i32,
+    password_hash: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE users SET password_hash = ? WHERE id = ?")
+        .bind(password_hash)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+
     Ok(())
 }
 
@@ -84,44 +93,108 @@ pub async fn update_user(
     country_id: Option<i32>,
     state_id: Option<i32>,
 ) -> Result<(), sqlx::Error> {
-    sqlx::query!(
-        "UPDATE users SET username = ?, email = ?, address = ?, country_id = ?, state_id = ? WHERE id = ?",
-        username,
-        email,
-        address,
-        country_id,
-        state_id,
-        user_id
+    // A new address has to be verified again. MySQL applies SET assignments
+    // left to right, so the comparison sees the old email.
+    sqlx::query(
+        "UPDATE users SET email_verified_at = IF(email = ?, email_verified_at, NULL), \
+         username = ?, email = ?, address = ?, country_id = ?, state_id = ? WHERE id = ?",
     )
+    .bind(email)
+    .bind(username)
+    .bind(email)
+    .bind(address)
+    .bind(country_id)
+    .bind(state_id)
+    .bind(user_id)
     .execute(pool)
     .await?;
 
     Ok(())
 }
 
-pub async fn delete_user(pool: &MySqlPool, user_id: i32) -> Result<(), sqlx::Error> {
-    sqlx::query!("DELETE FROM users WHERE id = ?", user_id)
+// Marks the user's current email as verified; returns false if it already was
+pub async fn mark_email_verified(pool: &MySqlPool, user_id: i32) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE users SET email_verified_at = CURRENT_TIMESTAMP WHERE id = ? AND email_verified_at IS NULL",
+    )
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+// Self-service profile update (address fields only)
+pub async fn update_user_profile(
+    pool: &MySqlPool,
+    user_id: i32,
+    address: &str,
+    country_id: i32,
+    state_id: i32,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE users SET address = ?, country_id = ?, state_id = ? WHERE id = ?")
+        .bind(address)
+        .bind(country_id)
+        .bind(state_id)
+        .bind(user_id)
         .execute(pool)
         .await?;
+
     Ok(())
 }
 
-// Get total count of users
+// Moves the user to the trash (see trash_repository)
+pub async fn delete_user(pool: &MySqlPool, user_id: i32) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "UPDATE users SET deleted_at = CURRENT_TIMESTAMP WHERE id = ? AND deleted_at IS NULL",
+        user_id
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// Get total count of users (not in the trash)
 pub async fn get_users_count(pool: &MySqlPool) -> Result<i64, sqlx::Error> {
-    let result = sqlx::query!("SELECT COUNT(*) as count FROM users")
+    let result = sqlx::query!("SELECT COUNT(*) as count FROM users WHERE deleted_at IS NULL")
         .fetch_one(pool)
         .await?;
 
     Ok(result.count)
 }
 
-// Get users with pagination and sorting
-pub async fn get_users_paginated(
-    pool: &MySqlPool,
-    params: &PaginationParams,
-) -> Result<Vec<User>, sqlx::Error> {
+// WHERE clause for the users datatable, its filtered count and the PDF export.
+// Returns the clause and the LIKE pattern to bind twice when searching.
+// Users in the trash are always left out.
+fn user_filter_clause(
+    search: &Option<String>,
+    verified: Option<bool>,
+    status: Option<UserStatus>,
+) -> (String, Option<String>) {
+    let mut conditions = vec!["deleted_at IS NULL".to_string()];
+    let pattern = search
+        .as_ref()
+        .filter(|s| !s.is_empty())
+        .map(|s| format!("%{}%", s));
+    if pattern.is_some() {
+        conditions.push("(username LIKE ? OR email LIKE ?)".to_string());
+    }
+    match verified {
+        Some(true) => conditions.push("email_verified_at IS NOT NULL".to_string()),
+        Some(false) => conditions.push("email_verified_at IS NULL".to_string()),
+        None => {}
+    }
+    // as_str() is one of a fixed set of words, so it can go in the SQL as is
+    if let Some(status) = status {
+        conditions.push(format!("status = '{}'", status.as_str()));
+    }
+
+    let clause = format!("WHERE {}", conditions.join(" AND "));
+    (clause, pattern)
+}
+
+fn sanitize_order(order_column: &str, order_direction: &str) -> (&'static str, &'static str) {
     // Validate and sanitize order column to prevent SQL injection
-    let order_column = match params.order_column.as_str() {
+    let order_column = match order_column {
         "id" => "id",
         "username" => "username",
         "email" => "email",
@@ -129,160 +202,82 @@ pub async fn get_users_paginated(
         _ => "id", // default
     };
 
-    let order_direction = match params.order_direction.as_str() {
+    let order_direction = match order_direction {
         "asc" | "ASC" => "ASC",
         "desc" | "DESC" => "DESC",
         _ => "DESC", // default
     };
+    (order_column, order_direction)
+}
 
-    let query_str = if let Some(search) = &params.search {
-        if !search.is_empty() {
-            format!(
-                "SELECT id, username, email, password_hash, created_at, address, country_id, state_id 
-                 FROM users 
-                 WHERE username LIKE ? OR email LIKE ?
-                 ORDER BY {} {} 
-                 LIMIT ? OFFSET ?",
-                order_column, order_direction
-            )
-        } else {
-            format!(
-                "SELECT id, username, email, password_hash, created_at, address, country_id, state_id 
-                 FROM users 
-                 ORDER BY {} {} 
-                 LIMIT ? OFFSET ?",
-                order_column, order_direction
-            )
-        }
-    } else {
-        format!(
-            "SELECT id, username, email, password_hash, created_at, address, country_id, state_id 
-             FROM users 
-             ORDER BY {} {} 
-             LIMIT ? OFFSET ?",
-            order_column, order_direction
-        )
-    };
+// Get users with pagination and sorting
+pub async fn get_users_paginated(
+    pool: &MySqlPool,
+    params: &PaginationParams,
+) -> Result<Vec<User>, sqlx::Error> {
+    let (order_column, order_direction) =
+        sanitize_order(&params.order_column, &params.order_direction);
+    let (clause, pattern) = user_filter_clause(&params.search, params.verified, params.status);
 
-    let users = if let Some(search) = &params.search {
-        if !search.is_empty() {
-            let search_pattern = format!("%{}%", search);
-            sqlx::query_as::<_, User>(&query_str)
-                .bind(&search_pattern)
-                .bind(&search_pattern)
-                .bind(params.limit)
-                .bind(params.offset)
-                .fetch_all(pool)
-                .await?
-        } else {
-            sqlx::query_as::<_, User>(&query_str)
-                .bind(params.limit)
-                .bind(params.offset)
-                .fetch_all(pool)
-                .await?
-        }
-    } else {
-        sqlx::query_as::<_, User>(&query_str)
-            .bind(params.limit)
-            .bind(params.offset)
-            .fetch_all(pool)
-            .await?
-    };
+    let query_str = format!(
+        "SELECT id, username, email, email_verified_at, password_hash, created_at, address, country_id, state_id, status
+         FROM users
+         {}
+         ORDER BY {} {}
+         LIMIT ? OFFSET ?",
+        clause, order_column, order_direction
+    );
 
-    Ok(users)
+    let mut query = sqlx::query_as::<_, User>(&query_str);
+    if let Some(pattern) = &pattern {
+        query = query.bind(pattern).bind(pattern);
+    }
+    query
+        .bind(params.limit)
+        .bind(params.offset)
+        .fetch_all(pool)
+        .await
 }
 
 pub async fn get_users_for_export(
     pool: &MySqlPool,
     search: &Option<String>,
+    verified: Option<bool>,
+    status: Option<UserStatus>,
     order_column: &str,
     order_direction: &str,
 ) -> Result<Vec<User>, sqlx::Error> {
-    // Validate and sanitize order column to prevent SQL injection
-    let order_column = match order_column {
-        "id" => "id",
-        "username" => "username",
-        "email" => "email",
-        "created_at" => "created_at",
-        _ => "id",
-    };
-
-    let order_direction = match order_direction {
-        "asc" | "ASC" => "ASC",
-        "desc" | "DESC" => "DESC",
-        _ => "DESC",
-    };
+    let (order_column, order_direction) = sanitize_order(order_column, order_direction);
+    let (clause, pattern) = user_filter_clause(search, verified, status);
 
-    let query_str = if let Some(search) = search {
-        if !search.is_empty() {
-            format!(
-                "SELECT id, username, email, password_hash, created_at, address, country_id, state_id 
-                 FROM users 
-                 WHERE username LIKE ? OR email LIKE ?
-                 ORDER BY {} {}",
-                order_column, order_direction
-            )
-        } else {
-            format!(
-                "SELECT id, username, email, password_hash, created_at, address, country_id, state_id 
-                 FROM users 
-                 ORDER BY {} {}",
-                order_column, order_direction
-            )
-        }
-    } else {
-        format!(
-            "SELECT id, username, email, password_hash, created_at, address, country_id, state_id 
-             FROM users 
-             ORDER BY {} {}",
-            order_column, order_direction
-        )
-    };
+    let query_str = format!(
+        "SELECT id, username, email, email_verified_at, password_hash, created_at, address, country_id, state_id, status
+         FROM users
+         {}
+         ORDER BY {} {}",
+        clause, order_column, order_direction
+    );
 
-    let users = if let Some(search) = search {
-        if !search.is_empty() {
-            let search_pattern = format!("%{}%", search);
-            sqlx::query_as::<_, User>(&query_str)
-                .bind(&search_pattern)
-                .bind(&search_pattern)
-                .fetch_all(pool)
-                .await?
-        } else {
-            sqlx::query_as::<_, User>(&query_str)
-                .fetch_all(pool)
-                .await?
-        }
-    } else {
-        sqlx::query_as::<_, User>(&query_str)
-            .fetch_all(pool)
-            .await?
-    };
-
-    Ok(users)
+    let mut query = sqlx::query_as::<_, User>(&query_str);
+    if let Some(pattern) = &pattern {
+        query = query.bind(pattern).bind(pattern);
+    }
+    query.fetch_all(pool).await
 }
 
-// Get count of filtered users (for search)
+// Get count of filtered users (for search and the verified and status filters)
 pub async fn get_filtered_users_count(
     pool: &MySqlPool,
     search: &Option<String>,
+    verified: Option<bool>,
+    status: Option<UserStatus>,
 ) -> Result<i64, sqlx::Error> {
-    let count = if let Some(search_term) = search {
-        if !search_term.is_empty() {
-            let search_pattern = format!("%{}%", search_term);
-            let result = sqlx::query!(
-                "SELECT COUNT(*) as count FROM users WHERE username LIKE ? OR email LIKE ?",
-                search_pattern,
-                search_pattern
-            )
-            .fetch_one(pool)
-            .await?;
-            result.count
-        } else {
-            get_users_count(pool).await?
-        }
-    } else {
-        get_users_count(pool).await?
-    };
+    let (clause, pattern) = user_filter_clause(search, verified, status);
+    let query_str = format!("SELECT COUNT(*) FROM users {}", clause);
 
-    Ok(count)
+    let mut query = sqlx::query_scalar::<_, i64>(&query_str);
+    if let Some(pattern) = &pattern {
+        query = query.bind(pattern).bind(pattern);
+    }
+    query.fetch_one(pool).await
 }
@@ -0,0 +1,62 @@
+use sqlx::MySqlPool;
+
+use crate::models::entities::{UserStatus, UserStatusChange};
+
+// Moves the user from `from` to `to` and records the change; returns false
+// when the user is gone or no longer in `from` (a concurrent change won).
+// Callers check the move against UserStatus::can_transition_to first.
+pub async fn change_user_status(
+    pool: &MySqlPool,
+    user_id: i32,
+    from: UserStatus,
+    to: UserStatus,
+    reason: &str,
+    admin: Option<(i32, &str)>,
+) -> Result<bool, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let updated = sqlx::query(
+        "UPDATE users SET status = ? WHERE id = ? AND status = ? AND deleted_at IS NULL",
+    )
+    .bind(to.as_str())
+    .bind(user_id)
+    .bind(from.as_str())
+    .execute(&mut *tx)
+    .await?;
+    if updated.rows_affected() == 0 {
+        tx.rollback().await?;
+        return Ok(false);
+    }
+
+    sqlx::query(
+        "INSERT INTO user_status_history (user_id, from_status, to_status, reason, admin_id, admin_username) \
+         VALUES (?, ?, ?, ?, ?, ?)",
+    )
+    .bind(user_id)
+    .bind(from.as_str())
+    .bind(to.as_str())
+    .bind(reason)
+    .bind(admin.map(|(id, _)| id))
+    .bind(admin.map(|(_, username)| username))
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(true)
+}
+
+// Newest first
+pub async fn find_user_status_history(
+    pool: &MySqlPool,
+    user_id: i32,
+) -> Result<Vec<UserStatusChange>, sqlx::Error> {
+    sqlx::query_as::<_, UserStatusChange>(
+        "SELECT id, from_status, to_status, reason, admin_username, created_at
+         FROM user_status_history
+         WHERE user_id = ?
+         ORDER BY created_at DESC, id DESC",
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+}
@@ -1,13 +1,94 @@
-use axum::{Router, routing::{get, post}};
+use axum::{Router, middleware::from_fn_with_state, routing::{get, post}};
 
 use crate::controllers::page_controller;
+use crate::middleware::reauth::require_reauth;
 use crate::state::AppState;
 
-pub fn routes() -> Router<AppState> {
+pub fn routes(state: &AppState) -> Router<AppState> {
+    // Sudo mode for destructive actions: a recent sign-in or re-authentication
+    let reauth = from_fn_with_state(state.clone(), require_reauth);
+
     Router::new()
         .route("/", get(page_controller::admin_index))
         .route("/dashboard", get(page_controller::admin_dashboard))
         .route("/logout", get(page_controller::admin_logout))
+        .route(
+            "/profile",
+            get(page_controller::admin_profile_page).post(page_controller::admin_profile_submit),
+        )
+        .route("/profile/password", post(page_controller::admin_profile_password_submit))
+        .route("/security", get(page_controller::admin_security_page))
+        .route("/security/2fa/setup", post(page_controller::admin_totp_setup_submit))
+        .route("/security/2fa/enable", post(page_controller::admin_totp_enable_submit))
+        .route("/security/2fa/disable", post(page_controller::admin_totp_disable_submit))
+        .route(
+            "/security/2fa/recovery-codes",
+            post(page_controller::admin_recovery_codes_submit),
+        )
+        .route(
+            "/security/api-tokens",
+            get(page_controller::admin_api_tokens_page)
+                .post(page_controller::admin_api_token_create_submit),
+        )
+        .route(
+            "/security/api-tokens/:id/revoke",
+            post(page_controller::admin_api_token_revoke_submit),
+        )
+        .route("/security/passkeys", get(page_controller::admin_passkeys_page))
+        .route(
+            "/security/passkeys/options",
+            post(page_controller::admin_passkey_options_submit),
+        )
+        .route(
+            "/security/passkeys/register",
+            post(page_controller::admin_passkey_register_submit),
+        )
+        .route(
+            "/security/passkeys/:id/rename",
+            post(page_controller::admin_passkey_rename_submit),
+        )
+        .route(
+            "/security/passkeys/:id/delete",
+            post(page_controller::admin_passkey_delete_submit),
+        )
+        // Ways to confirm on the sudo-mode page other than the password
+        .route(
+            "/reauth/passkey/options",
+            post(page_controller::admin_passkey_reauth_options),
+        )
+        .route("/reauth/passkey", post(page_controller::admin_passkey_reauth_submit))
+        .route("/reauth/oidc", post(page_controller::admin_oidc_reauth_submit))
+        .route("/security/sessions", get(page_controller::admin_sessions_page))
+        .route(
+            "/security/sessions/revoke-all",
+            post(page_controller::admin_sessions_revoke_all_submit),
+        )
+        .route(
+            "/security/sessions/:session_ref/revoke",
+            post(page_controller::admin_session_revoke_submit),
+        )
+        .route(
+            "/admins",
+            get(page_controller::admin_admins_list).post(page_controller::admin_admin_create_submit),
+        )
+        .route("/admins/new", get(page_controller::admin_admin_create_page))
+        .route("/admins/:id", post(page_controller::admin_admin_edit_submit))
+        .route("/admins/:id/edit", get(page_controller::admin_admin_edit_page))
+        .route(
+            "/admins/:id/password",
+            post(page_controller::admin_admin_password_submit).route_layer(reauth.clone()),
+        )
+        .route(
+            "/admins/:id/disable",
+            post(page_controller::admin_admin_disable_submit).route_layer(reauth.clone()),
+        )
+        .route("/admins/:id/enable", post(page_controller::admin_admin_enable_submit))
+        .route(
+            "/admins/:id/delete",
+            post(page_controller::admin_admin_delete_submit).route_layer(reauth.clone()),
+        )
+        .route("/audit", get(page_controller::admin_audit_log_page))
+        .route("/audit/export.csv", get(page_controller::admin_audit_log_csv))
         .route(
             "/countries",
             get(page_controller::admin_countries_list)
@@ -16,7 +97,10 @@ pub fn routes() -> Router<AppState> {
         .route("/countries/new", get(page_controller::admin_country_create_page))
         .route("/countries/:id", post(page_controller::admin_country_edit_submit))
         .route("/countries/:id/edit", get(page_controller::admin_country_edit_page))
-        .route("/countries/:id/delete", post(page_controller::admin_country_delete))
+        .route(
+            "/countries/:id/delete",
+            post(page_controller::admin_country_delete).route_layer(reauth.clone()),
+        )
         .route(
             "/states",
             get(page_controller::admin_states_list)
@@ -27,17 +111,41 @@ pub fn routes() -> Router<AppState> {
         .route("/geo/states", get(page_controller::admin_states_api))
         .route("/states/:id", post(page_controller::admin_state_edit_submit))
         .route("/states/:id/edit", get(page_controller::admin_state_edit_page))
-        .route("/states/:id/delete", post(page_controller::admin_state_delete))
+        .route(
+            "/states/:id/delete",
+            post(page_controller::admin_state_delete).route_layer(reauth.clone()),
+        )
         .route(
             "/users",
             get(page_controller::users_list).post(page_controller::user_create_submit),
         )
         .route("/users/print", get(page_controller::admin_users_pdf))
+        .route("/users/print/link", post(page_controller::admin_users_pdf_link))
+        // Authorized by the link's signature, not the session
+        .route("/users/print/shared", get(page_controller::admin_users_pdf_shared))
         .route("/users/new", get(page_controller::user_create_page))
         .route(
             "/users/:id",
             get(page_controller::user_detail).post(page_controller::user_edit_submit),
         )
         .route("/users/:id/edit", get(page_controller::user_edit_page))
-        .route("/users/:id/delete", post(page_controller::user_delete))
+        .route(
+            "/users/:id/delete",
+            post(page_controller::user_delete).route_layer(reauth.clone()),
+        )
+        .route(
+            "/users/:id/resend-verification",
+            post(page_controller::user_resend_verification_submit),
+        )
+        .route("/users/:id/impersonate", post(page_controller::user_impersonate_submit))
+        .route("/users/:id/status", post(page_controller::user_status_submit))
+        .route("/trash", get(page_controller::admin_trash_page))
+        .route(
+            "/trash/:kind/:id/restore",
+            post(page_controller::admin_trash_restore_submit),
+        )
+        .route(
+            "/trash/:kind/:id/purge",
+            post(page_controller::admin_trash_purge_submit).route_layer(reauth.clone()),
+        )
 }
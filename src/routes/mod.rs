@@ -1,7 +1,13 @@
-use axum::Router;
+use axum::{Router, middleware::from_fn_with_state};
 use tower_http::{services::ServeDir, trace::TraceLayer};
 use tower_sessions::SessionManagerLayer;
 use tower_sessions_redis_store::{fred::prelude::RedisPool, RedisStore};
+use crate::middleware::admin_access::enforce_admin_access;
+use crate::middleware::client_ip::resolve_client_ip;
+use crate::middleware::csrf::{CsrfConfig, csrf_protect};
+use crate::middleware::login_notice::show_login_notice;
+use crate::middleware::remember_me::restore_admin_session;
+use crate::middleware::security_headers::{CSP_REPORT_PATH, security_headers};
 use crate::state::AppState;
 
 mod admin;
@@ -13,14 +19,28 @@ pub fn app(
     session_layer: SessionManagerLayer<RedisStore<RedisPool>>,
 ) -> Router {
     let base_path = state.base_path.clone();
+    // Bearer-token requests are skipped by the layer itself; list any other
+    // cookie-less endpoints here
+    let csrf = CsrfConfig::new(&base_path).exempt(CSP_REPORT_PATH);
+    let headers = state.security_headers.clone();
+    let proxies = state.trusted_proxies.clone();
 
     Router::new()
         .merge(public::routes(&base_path))
-        .nest(&base_path, admin::routes())
+        .nest(&base_path, admin::routes(&state))
         .nest("/api", api::routes())
         .nest_service("/static", ServeDir::new("static"))
         .fallback(crate::controllers::page_controller::handle_404)
+        // Must run after restore_admin_session, which can sign an admin in
+        .layer(from_fn_with_state(base_path.clone(), show_login_notice))
+        .layer(from_fn_with_state(csrf, csrf_protect))
+        // IP allowlists and allowed hours for everything under base_path
+        .layer(from_fn_with_state(state.clone(), enforce_admin_access))
+        // Needs the session, and must run before the CSRF check and extractors
+        .layer(from_fn_with_state(state.clone(), restore_admin_session))
         .layer(TraceLayer::new_for_http())
         .layer(session_layer)
+        .layer(from_fn_with_state(headers, security_headers))
+        .layer(from_fn_with_state(proxies, resolve_client_ip))
         .with_state(state)
 }
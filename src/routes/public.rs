@@ -1,14 +1,66 @@
-use axum::{Router, routing::get};
+use axum::{Router, extract::DefaultBodyLimit, routing::{get, post}};
 
 use crate::controllers::page_controller;
+use crate::middleware::security_headers::CSP_REPORT_PATH;
 use crate::state::AppState;
 
 pub fn routes(base_path: &str) -> Router<AppState> {
     Router::new()
         .route("/", get(page_controller::index))
         .route("/logout", get(page_controller::logout))
+        .route(
+            CSP_REPORT_PATH,
+            post(page_controller::csp_report_submit).layer(DefaultBodyLimit::max(64 * 1024)),
+        )
+        .route("/impersonation/stop", post(page_controller::impersonation_stop_submit))
+        .route(
+            "/login",
+            get(page_controller::user_login_page).post(page_controller::user_login_submit),
+        )
+        .route(
+            "/register",
+            get(page_controller::user_register_page).post(page_controller::user_register_submit),
+        )
+        .route(
+            "/profile",
+            get(page_controller::user_profile_page).post(page_controller::user_profile_submit),
+        )
+        .route("/profile/password", post(page_controller::user_password_submit))
+        .route("/verify-email", get(page_controller::verify_email_page))
+        .route("/geo/states", get(page_controller::user_states_api))
         .route(
             &format!("{}/login", base_path),
             get(page_controller::admin_login_page).post(page_controller::admin_login_submit),
         )
+        .route(
+            &format!("{}/login/passkey/options", base_path),
+            post(page_controller::admin_passkey_login_options),
+        )
+        .route(
+            &format!("{}/login/passkey", base_path),
+            post(page_controller::admin_passkey_login_submit),
+        )
+        .route(
+            &format!("{}/login/oidc", base_path),
+            get(page_controller::admin_oidc_login),
+        )
+        .route(
+            &format!("{}/login/oidc/callback", base_path),
+            get(page_controller::admin_oidc_callback),
+        )
+        .route(
+            &format!("{}/login/2fa", base_path),
+            get(page_controller::admin_login_2fa_page)
+                .post(page_controller::admin_login_2fa_submit),
+        )
+        .route(
+            &format!("{}/forgot-password", base_path),
+            get(page_controller::admin_forgot_password_page)
+                .post(page_controller::admin_forgot_password_submit),
+        )
+        .route(
+            &format!("{}/reset-password", base_path),
+            get(page_controller::admin_reset_password_page)
+                .post(page_controller::admin_reset_password_submit),
+        )
 }
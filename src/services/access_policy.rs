@@ -0,0 +1,362 @@
+use std::net::IpAddr;
+
+use ipnet::IpNet;
+use time::{OffsetDateTime, Time, UtcOffset, Weekday};
+
+use crate::models::AdminAccessPolicy;
+
+const WEEKDAYS: [(&str, Weekday); 7] = [
+    ("mon", Weekday::Monday),
+    ("tue", Weekday::Tuesday),
+    ("wed", Weekday::Wednesday),
+    ("thu", Weekday::Thursday),
+    ("fri", Weekday::Friday),
+    ("sat", Weekday::Saturday),
+    ("sun", Weekday::Sunday),
+];
+
+// Why a request to the admin area was refused
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessDenial {
+    IpNotAllowed,
+    OutsideAllowedHours,
+}
+
+impl AccessDenial {
+    // Stored in admin_access_denials.reason
+    pub fn reason(&self) -> &'static str {
+        match self {
+            Self::IpNotAllowed => "ip_not_allowed",
+            Self::OutsideAllowedHours => "outside_allowed_hours",
+        }
+    }
+
+    pub fn message(&self) -> &'static str {
+        match self {
+            Self::IpNotAllowed => "Access to the admin area is not allowed from your network.",
+            Self::OutsideAllowedHours => "Your account cannot access the admin area at this time.",
+        }
+    }
+}
+
+// Addresses allowed to reach the admin area (ADMIN_IP_ALLOWLIST). Empty means
+// any address; admins with their own list in admin_access_policies use that
+// list instead once they are signed in.
+#[derive(Debug, Clone, Default)]
+pub struct AccessPolicyConfig {
+    pub allowlist: Vec<IpNet>,
+}
+
+impl AccessPolicyConfig {
+    pub fn from_env() -> Result<Self, String> {
+        let allowlist = std::env::var("ADMIN_IP_ALLOWLIST").unwrap_or_default();
+        let allowlist =
+            parse_cidrs(&allowlist).map_err(|e| format!("Invalid ADMIN_IP_ALLOWLIST: {}", e))?;
+        Ok(Self { allowlist })
+    }
+
+    pub fn allows_ip(&self, ip: IpAddr) -> bool {
+        self.allowlist.is_empty() || contains(&self.allowlist, ip)
+    }
+
+    // Checks a signed-in admin against their override, or the global list
+    pub fn check_admin(
+        &self,
+        ip: IpAddr,
+        policy: Option<&AdminAccessPolicy>,
+        now: OffsetDateTime,
+    ) -> Result<(), AccessDenial> {
+        let Some(policy) = policy else {
+            return if self.allows_ip(ip) { Ok(()) } else { Err(AccessDenial::IpNotAllowed) };
+        };
+        let ip_allowed = match policy.ip_allowlist.as_deref() {
+            Some(list) => list_allows(list, ip),
+            None => self.allows_ip(ip),
+        };
+        if !ip_allowed {
+            return Err(AccessDenial::IpNotAllowed);
+        }
+        if !within_allowed_hours(policy, now) {
+            return Err(AccessDenial::OutsideAllowedHours);
+        }
+        Ok(())
+    }
+}
+
+fn contains(ranges: &[IpNet], ip: IpAddr) -> bool {
+    // IPv4 clients of a dual-stack listener arrive as ::ffff:a.b.c.d
+    let ip = match ip {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(ip),
+        IpAddr::V4(_) => ip,
+    };
+    ranges.iter().any(|range| range.contains(&ip))
+}
+
+// Whether a stored comma-separated list contains `ip`. Lists are validated
+// when saved; an unparsable one allows nothing.
+pub fn list_allows(list: &str, ip: IpAddr) -> bool {
+    parse_cidrs(list).is_ok_and(|ranges| contains(&ranges, ip))
+}
+
+// Comma-separated CIDR ranges; a bare address is a single-host range
+pub fn parse_cidrs(value: &str) -> Result<Vec<IpNet>, String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            part.parse::<IpNet>()
+                .or_else(|_| part.parse::<IpAddr>().map(IpNet::from))
+                .map(|range| range.trunc())
+                .map_err(|_| format!("'{}' is not an IP address or CIDR range", part))
+        })
+        .collect()
+}
+
+// Comma-separated weekday names or ranges: "mon-fri", "mon,wed,sat-sun"
+pub fn parse_days(value: &str) -> Result<Vec<Weekday>, String> {
+    let index = |name: &str| {
+        let name = name.trim().to_ascii_lowercase();
+        WEEKDAYS
+            .iter()
+            .position(|(short, _)| name.starts_with(short))
+            .ok_or_else(|| format!("'{}' is not a weekday", name))
+    };
+    let mut days = Vec::new();
+    for part in value.split(',').map(str::trim).filter(|part| !part.is_empty()) {
+        let (first, last) = match part.split_once('-') {
+            Some((first, last)) => (index(first)?, index(last)?),
+            None => (index(part)?, index(part)?),
+        };
+        if first > last {
+            return Err(format!("'{}' must run from an earlier to a later weekday", part));
+        }
+        for (_, day) in &WEEKDAYS[first..=last] {
+            if !days.contains(day) {
+                days.push(*day);
+            }
+        }
+    }
+    if days.is_empty() {
+        return Err("at least one weekday is required".to_string());
+    }
+    Ok(days)
+}
+
+// Stored form of parse_days, e.g. "mon,tue,wed"
+pub fn format_days(days: &[Weekday]) -> String {
+    WEEKDAYS
+        .iter()
+        .filter(|(_, day)| days.contains(day))
+        .map(|(short, _)| *short)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+// "HH:MM-HH:MM"; a start later than the end spans midnight
+pub fn parse_hours(value: &str) -> Result<(Time, Time), String> {
+    let parse_time = |part: &str| {
+        let (hour, minute) = part.trim().split_once(':').ok_or_else(|| format!("'{}' is not HH:MM", part))?;
+        let hour = hour.parse::<u8>().map_err(|_| format!("'{}' is not HH:MM", part))?;
+        let minute = minute.parse::<u8>().map_err(|_| format!("'{}' is not HH:MM", part))?;
+        Time::from_hms(hour, minute, 0).map_err(|_| format!("'{}' is not a valid time", part))
+    };
+    let (from, until) = value
+        .split_once('-')
+        .ok_or_else(|| "hours must look like 08:00-18:00".to_string())?;
+    let (from, until) = (parse_time(from)?, parse_time(until)?);
+    if from == until {
+        return Err("start and end of the allowed hours must differ".to_string());
+    }
+    Ok((from, until))
+}
+
+// "+07:00", "-05:30" or "0", as minutes east of UTC
+pub fn parse_utc_offset(value: &str) -> Result<i16, String> {
+    let value = value.trim();
+    if value == "0" || value.eq_ignore_ascii_case("utc") {
+        return Ok(0);
+    }
+    let invalid = || format!("'{}' is not a UTC offset like +07:00", value);
+    let (sign, rest) = match value.as_bytes().first() {
+        Some(b'+') => (1, &value[1..]),
+        Some(b'-') => (-1, &value[1..]),
+        _ => return Err(invalid()),
+    };
+    let (hours, minutes) = rest.split_once(':').unwrap_or((rest, "0"));
+    let hours = hours.parse::<i8>().map_err(|_| invalid())?;
+    let minutes = minutes.parse::<i8>().map_err(|_| invalid())?;
+    let offset = UtcOffset::from_hms(sign * hours, sign * minutes, 0).map_err(|_| invalid())?;
+    Ok((offset.whole_seconds() / 60) as i16)
+}
+
+// Days are matched against the date the window starts on, so an overnight
+// window from Friday 22:00 still allows Saturday 01:00
+fn within_allowed_hours(policy: &AdminAccessPolicy, now: OffsetDateTime) -> bool {
+    let offset = UtcOffset::from_whole_seconds(i32::from(policy.utc_offset_minutes) * 60)
+        .unwrap_or(UtcOffset::UTC);
+    let local = now.to_offset(offset);
+    let time = local.time();
+
+    let day = match (policy.allowed_from, policy.allowed_until) {
+        (Some(from), Some(until)) => {
+            let inside = if from < until {
+                time >= from && time < until
+            } else {
+                time >= from || time < until
+            };
+            if !inside {
+                return false;
+            }
+            if from > until && time < until {
+                local.weekday().previous()
+            } else {
+                local.weekday()
+            }
+        }
+        _ => local.weekday(),
+    };
+
+    match policy.allowed_days.as_deref() {
+        Some(days) => parse_days(days).is_ok_and(|days| days.contains(&day)),
+        None => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::{datetime, time};
+
+    use super::*;
+
+    fn ip(value: &str) -> IpAddr {
+        value.parse().unwrap()
+    }
+
+    fn policy() -> AdminAccessPolicy {
+        AdminAccessPolicy {
+            admin_id: 1,
+            ip_allowlist: None,
+            allowed_days: None,
+            allowed_from: None,
+            allowed_until: None,
+            utc_offset_minutes: 0,
+        }
+    }
+
+    #[test]
+    fn parses_cidrs_and_bare_addresses() {
+        let ranges = parse_cidrs(" 10.1.2.3/8, 192.168.0.7 ,2001:db8::/32,").unwrap();
+        let ranges: Vec<String> = ranges.iter().map(ToString::to_string).collect();
+        assert_eq!(ranges, ["10.0.0.0/8", "192.168.0.7/32", "2001:db8::/32"]);
+
+        assert!(parse_cidrs("").unwrap().is_empty());
+        assert!(parse_cidrs("10.0.0.0/8, example.com").is_err());
+        assert!(parse_cidrs("10.0.0.0/33").is_err());
+    }
+
+    #[test]
+    fn matches_ipv4_and_ipv6_ranges() {
+        let list = "203.0.113.0/24, 2001:db8::/32";
+        assert!(list_allows(list, ip("203.0.113.200")));
+        assert!(!list_allows(list, ip("203.0.114.1")));
+        assert!(list_allows(list, ip("2001:db8:1::5")));
+        assert!(!list_allows(list, ip("2001:db9::5")));
+        // IPv4 client seen through a dual-stack listener
+        assert!(list_allows(list, ip("::ffff:203.0.113.9")));
+        assert!(!list_allows("not-an-ip", ip("203.0.113.9")));
+    }
+
+    #[test]
+    fn empty_global_allowlist_allows_any_address() {
+        let config = AccessPolicyConfig::default();
+        assert!(config.allows_ip(ip("198.51.100.1")));
+        assert!(config.allows_ip(ip("2001:db8::1")));
+        assert_eq!(config.check_admin(ip("198.51.100.1"), None, datetime!(2024-03-01 12:00 UTC)), Ok(()));
+    }
+
+    #[test]
+    fn admin_list_replaces_global_list() {
+        let config = AccessPolicyConfig { allowlist: parse_cidrs("10.0.0.0/8").unwrap() };
+        let now = datetime!(2024-03-01 12:00 UTC);
+        assert_eq!(config.check_admin(ip("10.1.1.1"), None, now), Ok(()));
+        assert_eq!(config.check_admin(ip("198.51.100.1"), None, now), Err(AccessDenial::IpNotAllowed));
+
+        let own_list = AdminAccessPolicy { ip_allowlist: Some("198.51.100.0/24".to_string()), ..policy() };
+        assert_eq!(config.check_admin(ip("198.51.100.1"), Some(&own_list), now), Ok(()));
+        assert_eq!(config.check_admin(ip("10.1.1.1"), Some(&own_list), now), Err(AccessDenial::IpNotAllowed));
+
+        // A policy without its own list keeps the global one
+        let hours_only = policy();
+        assert_eq!(config.check_admin(ip("198.51.100.1"), Some(&hours_only), now), Err(AccessDenial::IpNotAllowed));
+    }
+
+    #[test]
+    fn daytime_window() {
+        let config = AccessPolicyConfig::default();
+        let office = AdminAccessPolicy {
+            allowed_days: Some("mon-fri".to_string()),
+            allowed_from: Some(time!(08:00)),
+            allowed_until: Some(time!(18:00)),
+            ..policy()
+        };
+        let check = |now| config.check_admin(ip("10.0.0.1"), Some(&office), now);
+        // 2024-03-01 is a Friday
+        assert_eq!(check(datetime!(2024-03-01 08:00 UTC)), Ok(()));
+        assert_eq!(check(datetime!(2024-03-01 17:59 UTC)), Ok(()));
+        assert_eq!(check(datetime!(2024-03-01 18:00 UTC)), Err(AccessDenial::OutsideAllowedHours));
+        assert_eq!(check(datetime!(2024-03-02 12:00 UTC)), Err(AccessDenial::OutsideAllowedHours));
+    }
+
+    #[test]
+    fn overnight_window_belongs_to_the_day_it_starts() {
+        let config = AccessPolicyConfig::default();
+        let night_shift = AdminAccessPolicy {
+            allowed_days: Some("fri".to_string()),
+            allowed_from: Some(time!(22:00)),
+            allowed_until: Some(time!(06:00)),
+            ..policy()
+        };
+        let check = |now| config.check_admin(ip("10.0.0.1"), Some(&night_shift), now);
+        assert_eq!(check(datetime!(2024-03-01 23:00 UTC)), Ok(()));
+        assert_eq!(check(datetime!(2024-03-02 01:00 UTC)), Ok(()));
+        assert_eq!(check(datetime!(2024-03-02 06:00 UTC)), Err(AccessDenial::OutsideAllowedHours));
+        assert_eq!(check(datetime!(2024-03-01 12:00 UTC)), Err(AccessDenial::OutsideAllowedHours));
+        // Thursday night's window is not allowed
+        assert_eq!(check(datetime!(2024-03-01 01:00 UTC)), Err(AccessDenial::OutsideAllowedHours));
+        assert_eq!(check(datetime!(2024-03-02 23:00 UTC)), Err(AccessDenial::OutsideAllowedHours));
+    }
+
+    #[test]
+    fn window_uses_admin_offset() {
+        let config = AccessPolicyConfig::default();
+        let evening = AdminAccessPolicy {
+            allowed_days: Some("fri".to_string()),
+            allowed_from: Some(time!(22:00)),
+            allowed_until: Some(time!(23:30)),
+            utc_offset_minutes: 7 * 60,
+            ..policy()
+        };
+        let check = |now| config.check_admin(ip("10.0.0.1"), Some(&evening), now);
+        // 15:30 UTC is 22:30 on Friday at +07:00
+        assert_eq!(check(datetime!(2024-03-01 15:30 UTC)), Ok(()));
+        assert_eq!(check(datetime!(2024-03-01 22:30 UTC)), Err(AccessDenial::OutsideAllowedHours));
+    }
+
+    #[test]
+    fn parses_days_hours_and_offsets() {
+        let days = parse_days("mon-wed, fri,Saturday").unwrap();
+        assert_eq!(format_days(&days), "mon,tue,wed,fri,sat");
+        assert!(parse_days("fri-mon").is_err());
+        assert!(parse_days("").is_err());
+
+        assert_eq!(parse_hours("22:00-06:00"), Ok((time!(22:00), time!(06:00))));
+        assert!(parse_hours("08:00-08:00").is_err());
+        assert!(parse_hours("25:00-06:00").is_err());
+
+        assert_eq!(parse_utc_offset("+07:00"), Ok(420));
+        assert_eq!(parse_utc_offset("-05:30"), Ok(-330));
+        assert_eq!(parse_utc_offset("UTC"), Ok(0));
+        assert!(parse_utc_offset("07:00").is_err());
+    }
+}
@@ -0,0 +1,216 @@
+use rand::{Rng, distributions::Alphanumeric};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use time::macros::format_description;
+use tower_sessions_redis_store::fred::prelude::{KeysInterface, RedisPool, SetsInterface};
+use tower_sessions_redis_store::fred::types::Expiration;
+
+// Per-admin index of live sessions.
+//
+// Key layout:
+//   admin:session:{ref}      JSON AdminSessionRecord, expires with the session
+//   admin:sessions:{admin}   set of refs belonging to the admin
+//
+// `ref` is a random handle stored in the admin's session at login. The
+// AdminUser extractor rejects a session whose record is gone, so deleting
+// the record (revoke) signs that session out on its next request. Revoking
+// also deletes the underlying RedisStore session when its id is known.
+
+// How often last_seen is written back; avoids a Redis write on every request
+const TOUCH_INTERVAL_SECONDS: i64 = 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdminSessionRecord {
+    pub session_ref: String,
+    pub admin_id: i32,
+    // tower-sessions id; unknown until the session is first saved
+    pub session_id: Option<String>,
+    pub ip: String,
+    pub user_agent: String,
+    pub created_at: i64,
+    pub last_seen: i64,
+}
+
+impl AdminSessionRecord {
+    pub fn created_at_display(&self) -> String {
+        format_timestamp(self.created_at)
+    }
+
+    pub fn last_seen_display(&self) -> String {
+        format_timestamp(self.last_seen)
+    }
+}
+
+fn format_timestamp(timestamp: i64) -> String {
+    let format = format_description!("[day]-[month]-[year] [hour]:[minute]:[second] UTC");
+    OffsetDateTime::from_unix_timestamp(timestamp)
+        .ok()
+        .and_then(|dt| dt.format(format).ok())
+        .unwrap_or_else(|| "-".to_string())
+}
+
+fn record_key(session_ref: &str) -> String {
+    format!("admin:session:{}", session_ref)
+}
+
+fn index_key(admin_id: i32) -> String {
+    format!("admin:sessions:{}", admin_id)
+}
+
+fn now() -> i64 {
+    OffsetDateTime::now_utc().unix_timestamp()
+}
+
+async fn save(redis: &RedisPool, record: &AdminSessionRecord, ttl_seconds: i64) -> Result<(), String> {
+    let json = serde_json::to_string(record).map_err(|e| e.to_string())?;
+    redis
+        .set::<(), _, _>(
+            record_key(&record.session_ref),
+            json,
+            Some(Expiration::EX(ttl_seconds)),
+            None,
+            false,
+        )
+        .await
+        .map_err(|e| format!("{:?}", e))
+}
+
+async fn load(redis: &RedisPool, session_ref: &str) -> Result<Option<AdminSessionRecord>, String> {
+    let json: Option<String> = redis
+        .get(record_key(session_ref))
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+    Ok(json.and_then(|json| serde_json::from_str(&json).ok()))
+}
+
+// Records a new login and returns the ref to keep in the session
+pub async fn register(
+    redis: &RedisPool,
+    admin_id: i32,
+    ip: &str,
+    user_agent: &str,
+    ttl_seconds: i64,
+) -> Result<String, String> {
+    let session_ref: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(32)
+        .map(char::from)
+        .collect();
+    let record = AdminSessionRecord {
+        session_ref: session_ref.clone(),
+        admin_id,
+        session_id: None,
+        ip: ip.to_string(),
+        user_agent: user_agent.chars().take(255).collect(),
+        created_at: now(),
+        last_seen: now(),
+    };
+
+    save(redis, &record, ttl_seconds).await?;
+    redis
+        .sadd::<(), _, _>(index_key(admin_id), session_ref.clone())
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+    Ok(session_ref)
+}
+
+// Returns false when the session has been revoked or expired
+pub async fn touch(
+    redis: &RedisPool,
+    session_ref: &str,
+    admin_id: i32,
+    session_id: Option<String>,
+    ttl_seconds: i64,
+) -> bool {
+    let mut record = match load(redis, session_ref).await {
+        Ok(Some(record)) if record.admin_id == admin_id => record,
+        Ok(_) => return false,
+        Err(e) => {
+            // Sessions live in the same Redis; don't sign everyone out on a blip
+            tracing::error!("Failed to read admin session record: {}", e);
+            return true;
+        }
+    };
+
+    let id_changed = session_id.is_some() && record.session_id != session_id;
+    if id_changed || now() - record.last_seen >= TOUCH_INTERVAL_SECONDS {
+        if session_id.is_some() {
+            record.session_id = session_id;
+        }
+        record.last_seen = now();
+        if let Err(e) = save(redis, &record, ttl_seconds).await {
+            tracing::error!("Failed to update admin session record: {}", e);
+        }
+    }
+    true
+}
+
+// Active sessions, most recently used first. Expired refs are pruned from the index.
+pub async fn list(redis: &RedisPool, admin_id: i32) -> Vec<AdminSessionRecord> {
+    let refs: Vec<String> = redis.smembers(index_key(admin_id)).await.unwrap_or_default();
+    let mut records = Vec::with_capacity(refs.len());
+    for session_ref in refs {
+        match load(redis, &session_ref).await {
+            Ok(Some(record)) => records.push(record),
+            Ok(None) => {
+                let _: Result<(), _> = redis.srem(index_key(admin_id), session_ref).await;
+            }
+            Err(e) => tracing::error!("Failed to read admin session record: {}", e),
+        }
+    }
+    records.sort_by_key(|record| std::cmp::Reverse(record.last_seen));
+    records
+}
+
+// Signs out one session; returns false if it does not belong to the admin
+pub async fn revoke(redis: &RedisPool, admin_id: i32, session_ref: &str) -> Result<bool, String> {
+    let record = match load(redis, session_ref).await? {
+        Some(record) if record.admin_id == admin_id => record,
+        _ => return Ok(false),
+    };
+    delete_record(redis, &record).await?;
+    Ok(true)
+}
+
+// Signs the admin out of every session; returns how many were revoked
+pub async fn revoke_all(redis: &RedisPool, admin_id: i32) -> Result<usize, String> {
+    revoke_all_except(redis, admin_id, None).await
+}
+
+pub async fn revoke_all_except(
+    redis: &RedisPool,
+    admin_id: i32,
+    keep_ref: Option<&str>,
+) -> Result<usize, String> {
+    let mut revoked = 0;
+    for record in list(redis, admin_id).await {
+        if Some(record.session_ref.as_str()) == keep_ref {
+            continue;
+        }
+        delete_record(redis, &record).await?;
+        revoked += 1;
+    }
+    Ok(revoked)
+}
+
+// Drops the index entry on normal logout (the session itself is kept)
+pub async fn remove(redis: &RedisPool, admin_id: i32, session_ref: &str) {
+    let _: Result<(), _> = redis.del(record_key(session_ref)).await;
+    let _: Result<(), _> = redis.srem(index_key(admin_id), session_ref.to_string()).await;
+}
+
+async fn delete_record(redis: &RedisPool, record: &AdminSessionRecord) -> Result<(), String> {
+    let mut keys = vec![record_key(&record.session_ref)];
+    // RedisStore keys sessions by their id
+    if let Some(session_id) = &record.session_id {
+        keys.push(session_id.clone());
+    }
+    redis
+        .del::<(), _>(keys)
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+    redis
+        .srem::<(), _, _>(index_key(record.admin_id), record.session_ref.clone())
+        .await
+        .map_err(|e| format!("{:?}", e))
+}
@@ -0,0 +1,50 @@
+use axum::http::{HeaderMap, header};
+use rand::{Rng, distributions::Alphanumeric};
+
+// Same SHA-256 hex digest as reset tokens
+pub use crate::services::password_reset::hash_token;
+
+// Prefix makes leaked tokens easy to recognise in logs and secret scanners
+const TOKEN_PREFIX: &str = "aat_";
+// Characters of the token shown in the UI to tell tokens apart
+const DISPLAY_PREFIX_LEN: usize = 12;
+
+// Lifetimes offered when minting a token
+pub const EXPIRY_OPTIONS_DAYS: [i64; 4] = [7, 30, 90, 365];
+pub const DEFAULT_EXPIRY_DAYS: i64 = 30;
+
+pub struct NewToken {
+    // Shown to the admin once, never stored
+    pub token: String,
+    pub display_prefix: String,
+    pub hash: String,
+}
+
+pub fn generate_token() -> NewToken {
+    let random: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(40)
+        .map(char::from)
+        .collect();
+    let token = format!("{}{}", TOKEN_PREFIX, random);
+    NewToken {
+        display_prefix: token.chars().take(DISPLAY_PREFIX_LEN).collect(),
+        hash: hash_token(&token),
+        token,
+    }
+}
+
+// Bearer values that cannot be ours are rejected without a database lookup
+pub fn looks_like_token(value: &str) -> bool {
+    value.starts_with(TOKEN_PREFIX) && value.len() <= 64
+}
+
+// Token of an `Authorization: Bearer <token>` header. The scheme is matched
+// case-insensitively (RFC 7235); used by both the CSRF check and BearerToken
+// so they agree on which requests are token-authenticated.
+pub fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    let value = headers.get(header::AUTHORIZATION)?.to_str().ok()?;
+    let (scheme, token) = value.trim_start().split_once(' ')?;
+    let token = token.trim();
+    (scheme.eq_ignore_ascii_case("bearer") && !token.is_empty()).then_some(token)
+}
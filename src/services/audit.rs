@@ -0,0 +1,145 @@
+use serde_json::{Value, json};
+use sqlx::MySqlPool;
+use std::net::IpAddr;
+
+use crate::controllers::auth_controller::AdminUser;
+use crate::models::{Admin, Country, NewAuditLog, State, User};
+use crate::repository;
+
+// Entities whose changes are recorded with before/after snapshots
+pub trait Auditable {
+    const ENTITY_TYPE: &'static str;
+    fn audit_id(&self) -> String;
+    // Must not include secrets such as password hashes
+    fn audit_snapshot(&self) -> Value;
+}
+
+impl Auditable for User {
+    const ENTITY_TYPE: &'static str = "user";
+
+    fn audit_id(&self) -> String {
+        self.id.to_string()
+    }
+
+    fn audit_snapshot(&self) -> Value {
+        json!({
+            "username": self.username,
+            "email": self.email,
+            "address": self.address,
+            "country_id": self.country_id,
+            "state_id": self.state_id,
+            "status": self.status,
+        })
+    }
+}
+
+impl Auditable for Admin {
+    const ENTITY_TYPE: &'static str = "admin";
+
+    fn audit_id(&self) -> String {
+        self.id.to_string()
+    }
+
+    fn audit_snapshot(&self) -> Value {
+        json!({ "username": self.username, "email": self.email, "disabled": self.is_disabled() })
+    }
+}
+
+impl Auditable for Country {
+    const ENTITY_TYPE: &'static str = "country";
+
+    fn audit_id(&self) -> String {
+        self.id.to_string()
+    }
+
+    fn audit_snapshot(&self) -> Value {
+        json!({ "name": self.name })
+    }
+}
+
+impl Auditable for State {
+    const ENTITY_TYPE: &'static str = "state";
+
+    fn audit_id(&self) -> String {
+        self.id.to_string()
+    }
+
+    fn audit_snapshot(&self) -> Value {
+        json!({ "country_id": self.country_id, "name": self.name })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AuditEvent {
+    pub action: &'static str,
+    pub entity_type: &'static str,
+    pub entity_id: Option<String>,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+impl AuditEvent {
+    pub fn new(action: &'static str, entity_type: &'static str, entity_id: impl ToString) -> Self {
+        Self {
+            action,
+            entity_type,
+            entity_id: Some(entity_id.to_string()),
+            before: None,
+            after: None,
+        }
+    }
+
+    pub fn created<T: Auditable>(after: &T) -> Self {
+        Self::new("create", T::ENTITY_TYPE, after.audit_id()).with_after(after.audit_snapshot())
+    }
+
+    // `before` is None when the previous state could not be loaded
+    pub fn updated<T: Auditable>(before: Option<&T>, after: &T) -> Self {
+        let event = Self::new("update", T::ENTITY_TYPE, after.audit_id());
+        match before {
+            Some(before) => event.with_before(before.audit_snapshot()),
+            None => event,
+        }
+        .with_after(after.audit_snapshot())
+    }
+
+    pub fn deleted<T: Auditable>(before: &T) -> Self {
+        Self::new("delete", T::ENTITY_TYPE, before.audit_id()).with_before(before.audit_snapshot())
+    }
+
+    pub fn with_before(mut self, before: Value) -> Self {
+        self.before = Some(before);
+        self
+    }
+
+    pub fn with_after(mut self, after: Value) -> Self {
+        self.after = Some(after);
+        self
+    }
+}
+
+// Writes one audit_log row. Failures are logged, not returned: the change
+// itself has already been committed by the time this runs.
+pub async fn record(db: &MySqlPool, actor: &AdminUser, ip: IpAddr, event: AuditEvent) {
+    let entry = NewAuditLog {
+        admin_id: actor.id,
+        admin_username: actor.username.clone(),
+        action: event.action.to_string(),
+        entity_type: event.entity_type.to_string(),
+        entity_id: event.entity_id,
+        before_json: event.before.map(|v| v.to_string()),
+        after_json: event.after.map(|v| v.to_string()),
+        ip: ip.to_string(),
+    };
+
+    if let Err(e) = repository::insert_audit_log(db, &entry).await {
+        tracing::error!(
+            "Failed to write audit log ({} {} {:?} by {}): {:?}",
+            entry.action,
+            entry.entity_type,
+            entry.entity_id,
+            entry.admin_username,
+            e
+        );
+    }
+}
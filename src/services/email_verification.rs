@@ -0,0 +1,186 @@
+use base64::{Engine, engine::general_purpose::{STANDARD as BASE64, URL_SAFE_NO_PAD}};
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use sha2::Sha256;
+use time::OffsetDateTime;
+
+type HmacSha256 = Hmac<Sha256>;
+
+// Verification links are signed, not stored: a token is
+// `{user_id}.{expires}.{signature}` where the signature covers the user id,
+// the expiry and the email address it was sent to. Changing the address
+// therefore invalidates links sent to the old one.
+#[derive(Clone)]
+pub struct EmailVerificationConfig {
+    key: Vec<u8>,
+    pub ttl_hours: i64,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum TokenError {
+    Malformed,
+    Expired,
+    BadSignature,
+}
+
+impl EmailVerificationConfig {
+    // Reads EMAIL_VERIFICATION_KEY (base64, at least 32 bytes) and
+    // EMAIL_VERIFICATION_TTL_HOURS. Without a key a random one is used, so
+    // links stop working when the process restarts.
+    pub fn from_env() -> Result<Self, String> {
+        let ttl_hours = std::env::var("EMAIL_VERIFICATION_TTL_HOURS")
+            .ok()
+            .and_then(|v| v.parse::<i64>().ok())
+            .filter(|v| *v > 0)
+            .unwrap_or(48);
+
+        let key = match std::env::var("EMAIL_VERIFICATION_KEY") {
+            Ok(raw) if !raw.trim().is_empty() => {
+                let key = BASE64
+                    .decode(raw.trim())
+                    .map_err(|e| format!("EMAIL_VERIFICATION_KEY is not valid base64: {}", e))?;
+                if key.len() < 32 {
+                    return Err("EMAIL_VERIFICATION_KEY must decode to at least 32 bytes".to_string());
+                }
+                key
+            }
+            _ => {
+                tracing::warn!(
+                    "EMAIL_VERIFICATION_KEY not set; verification links are invalidated on restart"
+                );
+                let mut key = vec![0u8; 32];
+                rand::thread_rng().fill_bytes(&mut key);
+                key
+            }
+        };
+
+        Ok(Self { key, ttl_hours })
+    }
+
+    fn mac(&self, user_id: i32, email: &str, expires: i64) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(&self.key).expect("HMAC accepts any key length");
+        mac.update(format!("email-verify:{}:{}:{}", user_id, email.to_lowercase(), expires).as_bytes());
+        mac
+    }
+
+    pub fn generate_token(&self, user_id: i32, email: &str) -> String {
+        let expires = OffsetDateTime::now_utc().unix_timestamp() + self.ttl_hours * 3600;
+        let signature = self.mac(user_id, email, expires).finalize().into_bytes();
+        format!("{}.{}.{}", user_id, expires, URL_SAFE_NO_PAD.encode(signature))
+    }
+
+    // The user id a token claims, before the signature is checked; needed to
+    // look up the address the signature should cover
+    pub fn token_user_id(token: &str) -> Result<i32, TokenError> {
+        token
+            .trim()
+            .split('.')
+            .next()
+            .and_then(|id| id.parse().ok())
+            .ok_or(TokenError::Malformed)
+    }
+
+    // Checks the signature against the user's current email and the expiry
+    pub fn verify_token(&self, token: &str, user_id: i32, email: &str) -> Result<(), TokenError> {
+        let mut parts = token.trim().split('.');
+        let (Some(id), Some(expires), Some(signature), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(TokenError::Malformed);
+        };
+        let id: i32 = id.parse().map_err(|_| TokenError::Malformed)?;
+        let expires: i64 = expires.parse().map_err(|_| TokenError::Malformed)?;
+        let signature = URL_SAFE_NO_PAD
+            .decode(signature)
+            .map_err(|_| TokenError::Malformed)?;
+        if id != user_id {
+            return Err(TokenError::BadSignature);
+        }
+
+        self.mac(user_id, email, expires)
+            .verify_slice(&signature)
+            .map_err(|_| TokenError::BadSignature)?;
+        if OffsetDateTime::now_utc().unix_timestamp() > expires {
+            return Err(TokenError::Expired);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(key: u8, ttl_hours: i64) -> EmailVerificationConfig {
+        EmailVerificationConfig { key: vec![key; 32], ttl_hours }
+    }
+
+    #[test]
+    fn accepts_valid_token() {
+        let config = config(7, 48);
+        let token = config.generate_token(12, "alice@example.com");
+        assert_eq!(EmailVerificationConfig::token_user_id(&token), Ok(12));
+        assert_eq!(config.verify_token(&token, 12, "alice@example.com"), Ok(()));
+    }
+
+    #[test]
+    fn rejects_expired_token() {
+        let config = config(7, -1);
+        let token = config.generate_token(12, "alice@example.com");
+        assert_eq!(config.verify_token(&token, 12, "alice@example.com"), Err(TokenError::Expired));
+    }
+
+    #[test]
+    fn rejects_token_after_email_change() {
+        let config = config(7, 48);
+        let token = config.generate_token(12, "alice@example.com");
+        assert_eq!(
+            config.verify_token(&token, 12, "alice@example.org"),
+            Err(TokenError::BadSignature)
+        );
+        assert_eq!(
+            config.verify_token(&token, 13, "alice@example.com"),
+            Err(TokenError::BadSignature)
+        );
+    }
+
+    #[test]
+    fn signs_lowercased_email() {
+        let config = config(7, 48);
+        let token = config.generate_token(12, "Alice@Example.COM");
+        assert_eq!(config.verify_token(&token, 12, "alice@example.com"), Ok(()));
+
+        let expires: i64 = token.split('.').nth(1).unwrap().parse().unwrap();
+        let mut mac = HmacSha256::new_from_slice(&[7; 32]).unwrap();
+        mac.update(format!("email-verify:12:alice@example.com:{}", expires).as_bytes());
+        let signature = URL_SAFE_NO_PAD.encode(mac.finalize().into_bytes());
+        assert_eq!(token, format!("12.{}.{}", expires, signature));
+    }
+
+    #[test]
+    fn rejects_tampered_signature() {
+        let config = config(7, 48);
+        let token = config.generate_token(12, "alice@example.com");
+        let (rest, signature) = token.rsplit_once('.').unwrap();
+        let mut signature = URL_SAFE_NO_PAD.decode(signature).unwrap();
+        signature[0] ^= 1;
+        let tampered = format!("{}.{}", rest, URL_SAFE_NO_PAD.encode(signature));
+        assert_eq!(
+            config.verify_token(&tampered, 12, "alice@example.com"),
+            Err(TokenError::BadSignature)
+        );
+        assert_eq!(
+            config.verify_token(&format!("{}.", rest), 12, "alice@example.com"),
+            Err(TokenError::BadSignature)
+        );
+    }
+
+    #[test]
+    fn rejects_token_after_key_rotation() {
+        let token = config(7, 48).generate_token(12, "alice@example.com");
+        assert_eq!(
+            config(8, 48).verify_token(&token, 12, "alice@example.com"),
+            Err(TokenError::BadSignature)
+        );
+    }
+}
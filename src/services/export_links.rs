@@ -0,0 +1,203 @@
+use base64::{Engine, engine::general_purpose::{STANDARD as BASE64, URL_SAFE_NO_PAD}};
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use sha2::Sha256;
+use time::OffsetDateTime;
+
+use crate::models::{PdfExportParams, SignedPdfExportQuery};
+
+type HmacSha256 = Hmac<Sha256>;
+
+// Shareable PDF export links are signed, not stored: the signature covers
+// the issuing admin, the expiry and every export parameter, so none of them
+// can be changed. Rotating EXPORT_LINK_KEY revokes all outstanding links.
+#[derive(Clone)]
+pub struct ExportLinkConfig {
+    key: Vec<u8>,
+    pub ttl_minutes: i64,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum LinkError {
+    Expired,
+    BadSignature,
+}
+
+impl ExportLinkConfig {
+    // Reads EXPORT_LINK_KEY (base64, at least 32 bytes) and
+    // EXPORT_LINK_TTL_MINUTES. Without a key a random one is used, so links
+    // stop working when the process restarts.
+    pub fn from_env() -> Result<Self, String> {
+        let ttl_minutes = std::env::var("EXPORT_LINK_TTL_MINUTES")
+            .ok()
+            .and_then(|v| v.parse::<i64>().ok())
+            .filter(|v| *v > 0)
+            .unwrap_or(60);
+
+        let key = match std::env::var("EXPORT_LINK_KEY") {
+            Ok(raw) if !raw.trim().is_empty() => {
+                let key = BASE64
+                    .decode(raw.trim())
+                    .map_err(|e| format!("EXPORT_LINK_KEY is not valid base64: {}", e))?;
+                if key.len() < 32 {
+                    return Err("EXPORT_LINK_KEY must decode to at least 32 bytes".to_string());
+                }
+                key
+            }
+            _ => {
+                tracing::warn!("EXPORT_LINK_KEY not set; shared export links are invalidated on restart");
+                let mut key = vec![0u8; 32];
+                rand::thread_rng().fill_bytes(&mut key);
+                key
+            }
+        };
+
+        Ok(Self { key, ttl_minutes })
+    }
+
+    fn mac(&self, admin_id: i32, expires: i64, params: &PdfExportParams) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(&self.key).expect("HMAC accepts any key length");
+        // A JSON array keeps field boundaries unambiguous
+        let message = serde_json::json!([
+            "users-pdf",
+            admin_id,
+            expires,
+            params.search,
+            params.verified,
+            params.status,
+            params.order_column,
+            params.order_direction,
+        ]);
+        mac.update(message.to_string().as_bytes());
+        mac
+    }
+
+    // Query string parameters of a link to this export, valid for ttl_minutes
+    pub fn sign(&self, admin_id: i32, params: PdfExportParams) -> SignedPdfExportQuery {
+        let params = params.normalized();
+        let expires = OffsetDateTime::now_utc().unix_timestamp() + self.ttl_minutes * 60;
+        let signature = self.mac(admin_id, expires, &params).finalize().into_bytes();
+        SignedPdfExportQuery {
+            search: params.search,
+            verified: params.verified,
+            status: params.status,
+            order_column: params.order_column,
+            order_direction: params.order_direction,
+            admin: admin_id,
+            expires,
+            signature: URL_SAFE_NO_PAD.encode(signature),
+        }
+    }
+
+    // The export parameters of a link whose signature and expiry check out
+    pub fn verify(&self, query: &SignedPdfExportQuery) -> Result<PdfExportParams, LinkError> {
+        let params = query.params();
+        let signature = URL_SAFE_NO_PAD
+            .decode(query.signature.trim())
+            .map_err(|_| LinkError::BadSignature)?;
+        self.mac(query.admin, query.expires, &params)
+            .verify_slice(&signature)
+            .map_err(|_| LinkError::BadSignature)?;
+        if OffsetDateTime::now_utc().unix_timestamp() > query.expires {
+            return Err(LinkError::Expired);
+        }
+        Ok(params)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(ttl_minutes: i64) -> ExportLinkConfig {
+        ExportLinkConfig { key: vec![7; 32], ttl_minutes }
+    }
+
+    fn params() -> PdfExportParams {
+        PdfExportParams {
+            search: Some("alice".to_string()),
+            verified: Some("yes".to_string()),
+            status: Some("active".to_string()),
+            order_column: Some("2".to_string()),
+            order_direction: Some("desc".to_string()),
+        }
+    }
+
+    // The query as it arrives after a round trip through the URL
+    fn reparse(query: &SignedPdfExportQuery) -> SignedPdfExportQuery {
+        let url_query = serde_urlencoded::to_string(query).unwrap();
+        serde_urlencoded::from_str(&url_query).unwrap()
+    }
+
+    #[test]
+    fn accepts_valid_link() {
+        let config = config(60);
+        let query = reparse(&config.sign(3, params()));
+        let verified = config.verify(&query).unwrap();
+        assert_eq!(verified.search.as_deref(), Some("alice"));
+        assert_eq!(verified.status.as_deref(), Some("active"));
+        assert_eq!(verified.order_direction.as_deref(), Some("desc"));
+    }
+
+    #[test]
+    fn empty_parameters_match_missing_ones() {
+        let config = config(60);
+        let mut query = config.sign(3, PdfExportParams { search: Some(" ".to_string()), ..params() });
+        assert_eq!(query.search, None);
+        query.search = Some(String::new());
+        assert!(config.verify(&query).is_ok());
+    }
+
+    #[test]
+    fn rejects_tampered_signature() {
+        let config = config(60);
+        let mut query = config.sign(3, params());
+        let mut signature = URL_SAFE_NO_PAD.decode(&query.signature).unwrap();
+        signature[0] ^= 1;
+        query.signature = URL_SAFE_NO_PAD.encode(signature);
+        assert_eq!(config.verify(&query).unwrap_err(), LinkError::BadSignature);
+
+        query.signature = "not base64!".to_string();
+        assert_eq!(config.verify(&query).unwrap_err(), LinkError::BadSignature);
+    }
+
+    #[test]
+    fn rejects_tampered_expiry_admin_and_parameters() {
+        let config = config(60);
+        let signed = || config.sign(3, params());
+
+        let mut query = signed();
+        query.expires += 3600;
+        assert_eq!(config.verify(&query).unwrap_err(), LinkError::BadSignature);
+
+        let mut query = signed();
+        query.admin = 4;
+        assert_eq!(config.verify(&query).unwrap_err(), LinkError::BadSignature);
+
+        let mut query = signed();
+        query.search = Some("bob".to_string());
+        assert_eq!(config.verify(&query).unwrap_err(), LinkError::BadSignature);
+
+        let mut query = signed();
+        query.status = None;
+        assert_eq!(config.verify(&query).unwrap_err(), LinkError::BadSignature);
+
+        let mut query = signed();
+        query.order_direction = Some("asc".to_string());
+        assert_eq!(config.verify(&query).unwrap_err(), LinkError::BadSignature);
+    }
+
+    #[test]
+    fn rejects_link_signed_with_another_key() {
+        let query = config(60).sign(3, params());
+        let rotated = ExportLinkConfig { key: vec![8; 32], ttl_minutes: 60 };
+        assert_eq!(rotated.verify(&query).unwrap_err(), LinkError::BadSignature);
+    }
+
+    #[test]
+    fn rejects_expired_link() {
+        let config = config(-1);
+        let query = config.sign(3, params());
+        assert_eq!(config.verify(&query).unwrap_err(), LinkError::Expired);
+    }
+}
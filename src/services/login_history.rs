@@ -0,0 +1,154 @@
+use std::net::IpAddr;
+use std::sync::Arc;
+
+use maxminddb::{Reader, geoip2};
+use sqlx::MySqlPool;
+
+use crate::models::NewAdminLoginEvent;
+use crate::repository;
+
+// How an admin signed in (admin_login_events.method)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoginMethod {
+    Password,
+    // Second step after a password or SSO sign-in
+    TwoFactor,
+    Passkey,
+    Sso,
+    RememberMe,
+}
+
+impl LoginMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Password => "password",
+            Self::TwoFactor => "two_factor",
+            Self::Passkey => "passkey",
+            Self::Sso => "sso",
+            Self::RememberMe => "remember_me",
+        }
+    }
+}
+
+// Local MaxMind-format country database (GeoLite2/GeoIP2 Country or City),
+// read once at startup from GEOIP_DATABASE. Nothing is looked up online.
+#[derive(Clone)]
+pub struct GeoIp {
+    reader: Arc<Reader<Vec<u8>>>,
+}
+
+impl GeoIp {
+    pub fn from_env() -> Result<Option<Self>, String> {
+        let Some(path) = std::env::var("GEOIP_DATABASE").ok().filter(|path| !path.trim().is_empty())
+        else {
+            return Ok(None);
+        };
+        let reader = Reader::open_readfile(path.trim())
+            .map_err(|e| format!("Failed to open GEOIP_DATABASE {}: {}", path, e))?;
+        Ok(Some(Self { reader: Arc::new(reader) }))
+    }
+
+    pub fn database_type(&self) -> &str {
+        &self.reader.metadata.database_type
+    }
+
+    // ISO 3166 code, or None for private and unknown addresses
+    pub fn country_code(&self, ip: IpAddr) -> Option<String> {
+        let record = self.reader.lookup::<geoip2::Country>(ip).ok()?;
+        let code = record.country.or(record.registered_country)?.iso_code?;
+        Some(code.to_ascii_uppercase())
+    }
+}
+
+// One sign-in attempt to record
+#[derive(Debug, Clone)]
+pub struct LoginAttempt<'a> {
+    pub admin_id: Option<i32>,
+    pub username: &'a str,
+    pub method: LoginMethod,
+    pub ip: IpAddr,
+    pub user_agent: &'a str,
+}
+
+impl<'a> LoginAttempt<'a> {
+    pub fn new(method: LoginMethod, username: &'a str, ip: IpAddr, user_agent: &'a str) -> Self {
+        Self { admin_id: None, username, method, ip, user_agent }
+    }
+
+    pub fn for_admin(mut self, admin_id: i32) -> Self {
+        self.admin_id = Some(admin_id);
+        self
+    }
+
+    fn event(&self, success: bool, reason: Option<&'static str>, country_code: Option<String>) -> NewAdminLoginEvent {
+        NewAdminLoginEvent {
+            admin_id: self.admin_id,
+            username: self.username.chars().take(100).collect(),
+            method: self.method.as_str(),
+            success,
+            reason,
+            ip: self.ip.to_string(),
+            user_agent: self.user_agent.chars().take(255).collect(),
+            country_code,
+            new_ip: false,
+            new_country: false,
+        }
+    }
+}
+
+// Records a refused attempt; `reason` is a short code such as "invalid_password"
+pub async fn record_failure(
+    pool: &MySqlPool,
+    geoip: Option<&GeoIp>,
+    attempt: &LoginAttempt<'_>,
+    reason: &'static str,
+) {
+    let country_code = geoip.and_then(|geoip| geoip.country_code(attempt.ip));
+    let event = attempt.event(false, Some(reason), country_code);
+    if let Err(e) = repository::record_admin_login_event(pool, &event).await {
+        tracing::error!("Failed to record login event for {}: {:?}", attempt.username, e);
+    }
+}
+
+// Records a successful sign-in. Returns the notice to show the admin when it
+// came from an address or country none of their earlier sign-ins used; the
+// very first sign-in is not flagged.
+pub async fn record_success(
+    pool: &MySqlPool,
+    geoip: Option<&GeoIp>,
+    attempt: &LoginAttempt<'_>,
+) -> Option<String> {
+    let country_code = geoip.and_then(|geoip| geoip.country_code(attempt.ip));
+    let mut event = attempt.event(true, None, country_code);
+
+    if let Some(admin_id) = attempt.admin_id {
+        match repository::count_admin_successful_logins(pool, admin_id, &event.ip, event.country_code.as_deref())
+            .await
+        {
+            Ok((previous, from_ip, from_country)) if previous > 0 => {
+                event.new_ip = from_ip == 0;
+                event.new_country = event.country_code.is_some() && from_country == 0;
+            }
+            Ok(_) => {}
+            Err(e) => tracing::error!("Failed to load login history of admin {}: {:?}", admin_id, e),
+        }
+    }
+
+    if let Err(e) = repository::record_admin_login_event(pool, &event).await {
+        tracing::error!("Failed to record login event for {}: {:?}", attempt.username, e);
+    }
+    notice(&event)
+}
+
+fn notice(event: &NewAdminLoginEvent) -> Option<String> {
+    let source = match (event.new_ip, event.new_country, event.country_code.as_deref()) {
+        (_, true, Some(country)) => format!("a country you have not signed in from before ({}, {})", country, event.ip),
+        (true, _, _) => format!("an IP address you have not signed in from before ({})", event.ip),
+        _ => return None,
+    };
+    tracing::warn!("Admin {} signed in from {}", event.username, source);
+    Some(format!(
+        "This sign-in came from {}. If this was not you, change your password and sign out your other sessions.",
+        source
+    ))
+}
@@ -0,0 +1,249 @@
+use std::net::IpAddr;
+
+use tower_sessions_redis_store::fred::prelude::{KeysInterface, RedisPool};
+use tower_sessions_redis_store::fred::types::Expiration;
+
+// Failed-login tracking in Redis, keyed by username and by client IP.
+//
+// Key layout:
+//   login:fail:{subject}  failure counter, expires after the attempt window
+//   login:wait:{subject}  present while an exponential backoff delay is running
+//   login:lock:{subject}  present while the subject is locked out
+// where subject is `user:{lowercased admin username}`, `member:{lowercased username}`
+// for public accounts, or `ip:admin:{address}` / `ip:member:{address}`. Each
+// login form counts its own failures per IP, so failed public logins never
+// lock an address out of the admin login or the other way round.
+#[derive(Debug, Clone)]
+pub struct LoginThrottleConfig {
+    pub backoff_after: i64,
+    pub backoff_base_seconds: i64,
+    pub backoff_max_seconds: i64,
+    pub max_attempts_per_user: i64,
+    pub max_attempts_per_ip: i64,
+    pub lockout_seconds: i64,
+    pub window_seconds: i64,
+}
+
+impl LoginThrottleConfig {
+    pub fn from_env() -> Self {
+        fn read(name: &str, default: i64) -> i64 {
+            std::env::var(name)
+                .ok()
+                .and_then(|v| v.parse::<i64>().ok())
+                .filter(|v| *v > 0)
+                .unwrap_or(default)
+        }
+
+        Self {
+            backoff_after: read("LOGIN_BACKOFF_AFTER", 3),
+            backoff_base_seconds: read("LOGIN_BACKOFF_BASE_SECONDS", 2),
+            backoff_max_seconds: read("LOGIN_BACKOFF_MAX_SECONDS", 60),
+            max_attempts_per_user: read("LOGIN_MAX_ATTEMPTS", 10),
+            max_attempts_per_ip: read("LOGIN_MAX_ATTEMPTS_PER_IP", 50),
+            lockout_seconds: read("LOGIN_LOCKOUT_SECONDS", 900),
+            window_seconds: read("LOGIN_ATTEMPT_WINDOW_SECONDS", 900),
+        }
+    }
+
+    fn delay_for(&self, failures: i64) -> i64 {
+        let exponent = (failures - self.backoff_after).clamp(0, 20) as u32;
+        self.backoff_base_seconds
+            .saturating_mul(2_i64.saturating_pow(exponent))
+            .min(self.backoff_max_seconds)
+    }
+}
+
+// Which login form the username belongs to; admins and public users have separate counters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoginRealm {
+    Admin,
+    User,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThrottleStatus {
+    Allowed,
+    // Seconds until the next attempt is accepted
+    Backoff(i64),
+    // Seconds until the lockout ends
+    Locked(i64),
+}
+
+impl ThrottleStatus {
+    fn max(self, other: ThrottleStatus) -> ThrottleStatus {
+        match (self, other) {
+            (ThrottleStatus::Locked(a), ThrottleStatus::Locked(b)) => ThrottleStatus::Locked(a.max(b)),
+            (ThrottleStatus::Locked(a), _) | (_, ThrottleStatus::Locked(a)) => ThrottleStatus::Locked(a),
+            (ThrottleStatus::Backoff(a), ThrottleStatus::Backoff(b)) => ThrottleStatus::Backoff(a.max(b)),
+            (ThrottleStatus::Backoff(a), _) | (_, ThrottleStatus::Backoff(a)) => ThrottleStatus::Backoff(a),
+            _ => ThrottleStatus::Allowed,
+        }
+    }
+
+    // User-facing explanation, or None when the attempt is allowed
+    pub fn message(&self) -> Option<String> {
+        match self {
+            ThrottleStatus::Allowed => None,
+            ThrottleStatus::Backoff(seconds) => Some(format!(
+                "Too many failed attempts. Please wait {} before trying again.",
+                format_duration(*seconds)
+            )),
+            ThrottleStatus::Locked(seconds) => Some(format!(
+                "Sign-in is temporarily locked after too many failed attempts. Try again in {} or contact an administrator.",
+                format_duration(*seconds)
+            )),
+        }
+    }
+}
+
+fn format_duration(seconds: i64) -> String {
+    let seconds = seconds.max(1);
+    if seconds < 60 {
+        format!("{} second{}", seconds, if seconds == 1 { "" } else { "s" })
+    } else {
+        let minutes = (seconds + 59) / 60;
+        format!("{} minute{}", minutes, if minutes == 1 { "" } else { "s" })
+    }
+}
+
+fn user_subject(realm: LoginRealm, username: &str) -> String {
+    let prefix = match realm {
+        LoginRealm::Admin => "user",
+        LoginRealm::User => "member",
+    };
+    format!("{}:{}", prefix, username.trim().to_lowercase())
+}
+
+fn ip_subject(realm: LoginRealm, ip: IpAddr) -> String {
+    let realm = match realm {
+        LoginRealm::Admin => "admin",
+        LoginRealm::User => "member",
+    };
+    format!("ip:{}:{}", realm, ip)
+}
+
+async fn remaining(redis: &RedisPool, key: String) -> i64 {
+    redis.ttl::<i64, _>(key).await.unwrap_or(-2)
+}
+
+async fn subject_status(redis: &RedisPool, subject: &str) -> ThrottleStatus {
+    let locked = remaining(redis, format!("login:lock:{}", subject)).await;
+    if locked > 0 {
+        return ThrottleStatus::Locked(locked);
+    }
+    let wait = remaining(redis, format!("login:wait:{}", subject)).await;
+    if wait > 0 {
+        return ThrottleStatus::Backoff(wait);
+    }
+    ThrottleStatus::Allowed
+}
+
+async fn subject_failure(
+    redis: &RedisPool,
+    config: &LoginThrottleConfig,
+    subject: &str,
+    max_attempts: i64,
+) -> ThrottleStatus {
+    let fail_key = format!("login:fail:{}", subject);
+    let failures = match redis.incr::<i64, _>(fail_key.clone()).await {
+        Ok(count) => count,
+        Err(e) => {
+            tracing::error!("Failed to record login failure for {}: {:?}", subject, e);
+            return ThrottleStatus::Allowed;
+        }
+    };
+    if failures == 1 {
+        let _: Result<(), _> = redis.expire(fail_key.clone(), config.window_seconds).await;
+    }
+
+    if failures >= max_attempts {
+        let _: Result<(), _> = redis
+            .set(
+                format!("login:lock:{}", subject),
+                failures,
+                Some(Expiration::EX(config.lockout_seconds)),
+                None,
+                false,
+            )
+            .await;
+        let _: Result<(), _> = redis.del(fail_key).await;
+        tracing::warn!("Login locked out for {} after {} failures", subject, failures);
+        return ThrottleStatus::Locked(config.lockout_seconds);
+    }
+
+    if failures >= config.backoff_after {
+        let delay = config.delay_for(failures);
+        let _: Result<(), _> = redis
+            .set(
+                format!("login:wait:{}", subject),
+                failures,
+                Some(Expiration::EX(delay)),
+                None,
+                false,
+            )
+            .await;
+        return ThrottleStatus::Backoff(delay);
+    }
+
+    ThrottleStatus::Allowed
+}
+
+// Whether a login attempt for this username from this IP may proceed
+pub async fn check(
+    redis: &RedisPool,
+    realm: LoginRealm,
+    username: &str,
+    ip: IpAddr,
+) -> ThrottleStatus {
+    let user = subject_status(redis, &user_subject(realm, username)).await;
+    let ip = subject_status(redis, &ip_subject(realm, ip)).await;
+    user.max(ip)
+}
+
+pub async fn record_failure(
+    redis: &RedisPool,
+    config: &LoginThrottleConfig,
+    realm: LoginRealm,
+    username: &str,
+    ip: IpAddr,
+) -> ThrottleStatus {
+    let subject = user_subject(realm, username);
+    let user = subject_failure(redis, config, &subject, config.max_attempts_per_user).await;
+    let ip = subject_failure(redis, config, &ip_subject(realm, ip), config.max_attempts_per_ip).await;
+    user.max(ip)
+}
+
+// A successful login clears the username's counters; the IP counter is left to expire
+pub async fn record_success(redis: &RedisPool, realm: LoginRealm, username: &str) {
+    let subject = user_subject(realm, username);
+    let _: Result<(), _> = redis
+        .del(vec![
+            format!("login:fail:{}", subject),
+            format!("login:wait:{}", subject),
+        ])
+        .await;
+}
+
+async fn clear_subject(redis: &RedisPool, subject: &str) -> Result<i64, String> {
+    redis
+        .del::<i64, _>(vec![
+            format!("login:fail:{}", subject),
+            format!("login:wait:{}", subject),
+            format!("login:lock:{}", subject),
+        ])
+        .await
+        .map_err(|e| format!("{:?}", e))
+}
+
+// Operator unlock; returns the number of throttle keys removed
+pub async fn unlock_username(
+    redis: &RedisPool,
+    realm: LoginRealm,
+    username: &str,
+) -> Result<i64, String> {
+    clear_subject(redis, &user_subject(realm, username)).await
+}
+
+pub async fn unlock_ip(redis: &RedisPool, realm: LoginRealm, ip: IpAddr) -> Result<i64, String> {
+    clear_subject(redis, &ip_subject(realm, ip)).await
+}
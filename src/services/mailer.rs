@@ -0,0 +1,117 @@
+use lettre::{
+    AsyncFileTransport, AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+    message::{Mailbox, header::ContentType},
+    transport::smtp::authentication::Credentials,
+};
+
+// Outgoing mail. MAIL_TRANSPORT selects the backend:
+//   smtp  SMTP_HOST, SMTP_PORT, SMTP_USERNAME, SMTP_PASSWORD, SMTP_TLS (starttls | tls | off)
+//   file  writes .eml files to MAIL_DIR (default `mail`), for local development
+//   log   prints the message to the application log (default)
+#[derive(Clone)]
+enum MailTransport {
+    Smtp(AsyncSmtpTransport<Tokio1Executor>),
+    File(AsyncFileTransport<Tokio1Executor>),
+    Log,
+}
+
+#[derive(Clone)]
+pub struct Mailer {
+    from: Mailbox,
+    transport: MailTransport,
+}
+
+impl Mailer {
+    pub fn from_env() -> Result<Self, String> {
+        let from = std::env::var("MAIL_FROM")
+            .unwrap_or_else(|_| "Admin Console <no-reply@localhost>".to_string())
+            .parse::<Mailbox>()
+            .map_err(|e| format!("Invalid MAIL_FROM: {}", e))?;
+
+        let transport = match std::env::var("MAIL_TRANSPORT")
+            .unwrap_or_else(|_| "log".to_string())
+            .to_lowercase()
+            .as_str()
+        {
+            "smtp" => MailTransport::Smtp(smtp_from_env()?),
+            "file" => {
+                let dir = std::env::var("MAIL_DIR").unwrap_or_else(|_| "mail".to_string());
+                std::fs::create_dir_all(&dir)
+                    .map_err(|e| format!("Failed to create MAIL_DIR {}: {}", dir, e))?;
+                MailTransport::File(AsyncFileTransport::new(dir))
+            }
+            "log" => MailTransport::Log,
+            other => return Err(format!("Unknown MAIL_TRANSPORT: {}", other)),
+        };
+
+        Ok(Self { from, transport })
+    }
+
+    pub fn transport_name(&self) -> &'static str {
+        match self.transport {
+            MailTransport::Smtp(_) => "smtp",
+            MailTransport::File(_) => "file",
+            MailTransport::Log => "log",
+        }
+    }
+
+    // Sends a plain-text message
+    pub async fn send(&self, to: &str, subject: &str, body: String) -> Result<(), String> {
+        let to = to
+            .parse::<Mailbox>()
+            .map_err(|e| format!("Invalid recipient {}: {}", to, e))?;
+
+        if let MailTransport::Log = self.transport {
+            tracing::info!("Mail to {} — {}\n{}", to, subject, body);
+            return Ok(());
+        }
+
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(subject)
+            .header(ContentType::TEXT_PLAIN)
+            .body(body)
+            .map_err(|e| format!("Failed to build message: {}", e))?;
+
+        match &self.transport {
+            MailTransport::Smtp(smtp) => smtp
+                .send(message)
+                .await
+                .map(|_| ())
+                .map_err(|e| format!("SMTP send failed: {}", e)),
+            MailTransport::File(file) => file
+                .send(message)
+                .await
+                .map(|_| ())
+                .map_err(|e| format!("Failed to write mail file: {}", e)),
+            MailTransport::Log => Ok(()),
+        }
+    }
+}
+
+fn smtp_from_env() -> Result<AsyncSmtpTransport<Tokio1Executor>, String> {
+    let host = std::env::var("SMTP_HOST").map_err(|_| "SMTP_HOST must be set".to_string())?;
+    let tls = std::env::var("SMTP_TLS").unwrap_or_else(|_| "starttls".to_string());
+
+    let mut builder = match tls.to_lowercase().as_str() {
+        "starttls" => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&host)
+            .map_err(|e| format!("Invalid SMTP_HOST: {}", e))?,
+        "tls" => AsyncSmtpTransport::<Tokio1Executor>::relay(&host)
+            .map_err(|e| format!("Invalid SMTP_HOST: {}", e))?,
+        "off" => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&host),
+        other => return Err(format!("Unknown SMTP_TLS: {}", other)),
+    };
+
+    if let Some(port) = std::env::var("SMTP_PORT").ok().and_then(|p| p.parse::<u16>().ok()) {
+        builder = builder.port(port);
+    }
+    if let (Ok(username), Ok(password)) = (
+        std::env::var("SMTP_USERNAME"),
+        std::env::var("SMTP_PASSWORD"),
+    ) {
+        builder = builder.credentials(Credentials::new(username, password));
+    }
+
+    Ok(builder.build())
+}
@@ -0,0 +1,18 @@
+// Non-HTTP helpers shared by controllers (crypto, tokens, integrations)
+pub mod access_policy;
+pub mod admin_sessions;
+pub mod api_tokens;
+pub mod audit;
+pub mod email_verification;
+pub mod export_links;
+pub mod login_history;
+pub mod login_throttle;
+pub mod mailer;
+pub mod oidc;
+pub mod password_hash;
+pub mod password_policy;
+pub mod password_reset;
+pub mod remember_me;
+pub mod trash;
+pub mod two_factor;
+pub mod webauthn;
@@ -0,0 +1,510 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use rand::RngCore;
+use rsa::pkcs1v15::{Signature, VerifyingKey};
+use rsa::signature::Verifier;
+use rsa::{BigUint, RsaPublicKey};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use time::OffsetDateTime;
+use tokio::sync::RwLock;
+
+// Discovery document and keys are refetched after this long, or earlier when
+// a token names a key we have not seen (key rotation)
+const PROVIDER_CACHE_TTL: Duration = Duration::from_secs(3600);
+const HTTP_TIMEOUT: Duration = Duration::from_secs(10);
+// Allowed clock difference with the identity provider
+const CLOCK_SKEW_SECONDS: i64 = 60;
+// How long the browser has to come back from the identity provider
+pub const LOGIN_TTL_SECONDS: i64 = 600;
+
+// Settings for admin single sign-on through an OpenID Connect provider
+#[derive(Debug, Clone)]
+pub struct OidcConfig {
+    pub issuer: String,
+    pub client_id: String,
+    // Confidential clients authenticate with HTTP Basic; public clients rely on PKCE alone
+    pub client_secret: Option<String>,
+    pub redirect_url: String,
+    pub scopes: String,
+    // Text of the button on the admin login page
+    pub button_label: String,
+    // Create admins that do not exist yet, if their domain or groups allow it
+    pub auto_provision: bool,
+    pub allowed_domains: Vec<String>,
+    pub allowed_groups: Vec<String>,
+    pub groups_claim: String,
+    // Role granted to auto-provisioned admins
+    pub provision_role: String,
+}
+
+fn list_from_env(name: &str) -> Vec<String> {
+    std::env::var(name)
+        .unwrap_or_default()
+        .split(',')
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .collect()
+}
+
+impl OidcConfig {
+    // Returns Ok(None) when OIDC_ISSUER is not set, which hides single sign-on.
+    // `default_redirect_url` is the callback route on APP_PUBLIC_URL.
+    pub fn from_env(default_redirect_url: &str) -> Result<Option<Self>, String> {
+        let issuer = match std::env::var("OIDC_ISSUER") {
+            Ok(issuer) if !issuer.trim().is_empty() => issuer.trim().trim_end_matches('/').to_string(),
+            _ => return Ok(None),
+        };
+        let client_id = std::env::var("OIDC_CLIENT_ID")
+            .ok()
+            .filter(|v| !v.trim().is_empty())
+            .ok_or("OIDC_CLIENT_ID must be set when OIDC_ISSUER is set")?;
+        let client_secret = std::env::var("OIDC_CLIENT_SECRET")
+            .ok()
+            .filter(|v| !v.trim().is_empty());
+        let redirect_url = std::env::var("OIDC_REDIRECT_URL")
+            .ok()
+            .filter(|v| !v.trim().is_empty())
+            .unwrap_or_else(|| default_redirect_url.to_string());
+        let auto_provision = std::env::var("OIDC_AUTO_PROVISION")
+            .is_ok_and(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes"));
+        let allowed_domains: Vec<String> = list_from_env("OIDC_ALLOWED_DOMAINS")
+            .into_iter()
+            .map(|d| d.trim_start_matches('@').to_ascii_lowercase())
+            .collect();
+        let allowed_groups = list_from_env("OIDC_ALLOWED_GROUPS");
+        if auto_provision && allowed_domains.is_empty() && allowed_groups.is_empty() {
+            return Err(
+                "OIDC_AUTO_PROVISION needs OIDC_ALLOWED_DOMAINS or OIDC_ALLOWED_GROUPS".to_string(),
+            );
+        }
+
+        Ok(Some(Self {
+            issuer,
+            client_id: client_id.trim().to_string(),
+            client_secret,
+            redirect_url,
+            scopes: std::env::var("OIDC_SCOPES")
+                .unwrap_or_else(|_| "openid email profile".to_string()),
+            button_label: std::env::var("OIDC_BUTTON_LABEL")
+                .unwrap_or_else(|_| "Sign in with SSO".to_string()),
+            auto_provision,
+            allowed_domains,
+            allowed_groups,
+            groups_claim: std::env::var("OIDC_GROUPS_CLAIM").unwrap_or_else(|_| "groups".to_string()),
+            provision_role: std::env::var("OIDC_PROVISION_ROLE")
+                .unwrap_or_else(|_| "read-only".to_string()),
+        }))
+    }
+
+    // Whether an unknown identity may get an admin account. Every configured
+    // restriction must match: the email domain and/or one of the groups.
+    pub fn may_provision(&self, identity: &OidcIdentity) -> bool {
+        if !self.auto_provision {
+            return false;
+        }
+        let domain_ok = self.allowed_domains.is_empty()
+            || identity
+                .email
+                .rsplit_once('@')
+                .is_some_and(|(_, domain)| self.allowed_domains.contains(&domain.to_ascii_lowercase()));
+        let group_ok = self.allowed_groups.is_empty()
+            || identity.groups.iter().any(|g| self.allowed_groups.contains(g));
+        domain_ok && group_ok
+    }
+}
+
+// The subset of the discovery document the login flow uses
+#[derive(Debug, Clone, Deserialize)]
+struct ProviderMetadata {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Jwk {
+    kty: String,
+    kid: Option<String>,
+    alg: Option<String>,
+    n: Option<String>,
+    e: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JwkSet {
+    keys: Vec<Jwk>,
+}
+
+struct Provider {
+    metadata: ProviderMetadata,
+    keys: Vec<Jwk>,
+    fetched_at: Instant,
+}
+
+// Kept in the session between the redirect to the provider and the callback
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingOidcLogin {
+    pub state: String,
+    pub nonce: String,
+    pub code_verifier: String,
+    pub started_at: i64,
+}
+
+impl PendingOidcLogin {
+    pub fn is_expired(&self) -> bool {
+        OffsetDateTime::now_utc().unix_timestamp() - self.started_at > LOGIN_TTL_SECONDS
+    }
+}
+
+// Verified claims of a successful sign-in
+#[derive(Debug, Clone)]
+pub struct OidcIdentity {
+    pub subject: String,
+    pub email: String,
+    pub preferred_username: Option<String>,
+    pub groups: Vec<String>,
+    // When the user last authenticated at the provider
+    pub auth_time: Option<i64>,
+}
+
+impl OidcIdentity {
+    // Whether the provider authenticated the user again after `started_at`,
+    // as asked for by `begin_reauth`
+    pub fn authenticated_since(&self, started_at: i64) -> bool {
+        self.auth_time.is_some_and(|at| at + CLOCK_SKEW_SECONDS >= started_at)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Audience {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl Audience {
+    fn contains(&self, client_id: &str) -> bool {
+        match self {
+            Audience::One(aud) => aud == client_id,
+            Audience::Many(auds) => auds.iter().any(|aud| aud == client_id),
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Audience::One(_) => 1,
+            Audience::Many(auds) => auds.len(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct IdTokenClaims {
+    iss: String,
+    sub: String,
+    aud: Audience,
+    azp: Option<String>,
+    exp: i64,
+    nonce: Option<String>,
+    email: Option<String>,
+    email_verified: Option<bool>,
+    preferred_username: Option<String>,
+    auth_time: Option<i64>,
+    #[serde(flatten)]
+    extra: HashMap<String, Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JwtHeader {
+    alg: String,
+    kid: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    id_token: Option<String>,
+}
+
+fn random_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+// PKCE S256: base64url(SHA-256(verifier))
+pub fn code_challenge(code_verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()))
+}
+
+// Authorization code flow with PKCE against one provider. Discovery runs on
+// first use, so the app starts even while the provider is unreachable.
+#[derive(Clone)]
+pub struct OidcClient {
+    pub config: OidcConfig,
+    http: reqwest::Client,
+    provider: Arc<RwLock<Option<Provider>>>,
+}
+
+impl OidcClient {
+    pub fn new(config: OidcConfig) -> Result<Self, String> {
+        let http = reqwest::Client::builder()
+            .timeout(HTTP_TIMEOUT)
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .map_err(|e| format!("Failed to build OIDC HTTP client: {}", e))?;
+        Ok(Self {
+            config,
+            http,
+            provider: Arc::new(RwLock::new(None)),
+        })
+    }
+
+    async fn fetch_provider(&self) -> Result<Provider, String> {
+        let discovery_url = format!("{}/.well-known/openid-configuration", self.config.issuer);
+        let metadata: ProviderMetadata = self
+            .http
+            .get(&discovery_url)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| format!("OIDC discovery failed: {}", e))?
+            .json()
+            .await
+            .map_err(|e| format!("Invalid OIDC discovery document: {}", e))?;
+        if metadata.issuer.trim_end_matches('/') != self.config.issuer {
+            return Err(format!(
+                "OIDC discovery issuer {} does not match OIDC_ISSUER {}",
+                metadata.issuer, self.config.issuer
+            ));
+        }
+
+        let jwks: JwkSet = self
+            .http
+            .get(&metadata.jwks_uri)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| format!("Fetching OIDC keys failed: {}", e))?
+            .json()
+            .await
+            .map_err(|e| format!("Invalid OIDC key set: {}", e))?;
+
+        Ok(Provider {
+            metadata,
+            keys: jwks.keys,
+            fetched_at: Instant::now(),
+        })
+    }
+
+    // Cached metadata and keys; `refresh` forces a refetch
+    async fn provider(&self, refresh: bool) -> Result<(ProviderMetadata, Vec<Jwk>), String> {
+        if !refresh
+            && let Some(provider) = self.provider.read().await.as_ref()
+            && provider.fetched_at.elapsed() < PROVIDER_CACHE_TTL
+        {
+            return Ok((provider.metadata.clone(), provider.keys.clone()));
+        }
+        let provider = self.fetch_provider().await?;
+        let result = (provider.metadata.clone(), provider.keys.clone());
+        *self.provider.write().await = Some(provider);
+        Ok(result)
+    }
+
+    // Starts a sign-in: the URL to send the browser to and the state to keep
+    pub async fn begin_login(&self) -> Result<(String, PendingOidcLogin), String> {
+        self.begin(false).await
+    }
+
+    // Starts a sign-in that confirms an admin who is already signed in. The
+    // provider is asked to authenticate the user again instead of reusing its
+    // own session; check `OidcIdentity::authenticated_since` on return.
+    pub async fn begin_reauth(&self) -> Result<(String, PendingOidcLogin), String> {
+        self.begin(true).await
+    }
+
+    async fn begin(&self, reauth: bool) -> Result<(String, PendingOidcLogin), String> {
+        let (metadata, _) = self.provider(false).await?;
+        let pending = PendingOidcLogin {
+            state: random_token(),
+            nonce: random_token(),
+            code_verifier: random_token(),
+            started_at: OffsetDateTime::now_utc().unix_timestamp(),
+        };
+
+        let mut url = reqwest::Url::parse(&metadata.authorization_endpoint)
+            .map_err(|e| format!("Invalid authorization endpoint: {}", e))?;
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &self.config.client_id)
+            .append_pair("redirect_uri", &self.config.redirect_url)
+            .append_pair("scope", &self.config.scopes)
+            .append_pair("state", &pending.state)
+            .append_pair("nonce", &pending.nonce)
+            .append_pair("code_challenge", &code_challenge(&pending.code_verifier))
+            .append_pair("code_challenge_method", "S256");
+        if reauth {
+            url.query_pairs_mut()
+                .append_pair("prompt", "login")
+                .append_pair("max_age", "0");
+        }
+        Ok((url.to_string(), pending))
+    }
+
+    // Exchanges the authorization code and validates the returned ID token
+    pub async fn complete_login(
+        &self,
+        code: &str,
+        pending: &PendingOidcLogin,
+    ) -> Result<OidcIdentity, String> {
+        let (metadata, _) = self.provider(false).await?;
+
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", self.config.redirect_url.as_str()),
+            ("code_verifier", pending.code_verifier.as_str()),
+        ];
+        let mut request = self.http.post(&metadata.token_endpoint);
+        match &self.config.client_secret {
+            Some(secret) => request = request.basic_auth(&self.config.client_id, Some(secret)),
+            None => form.push(("client_id", self.config.client_id.as_str())),
+        }
+        let response = request
+            .form(&form)
+            .send()
+            .await
+            .map_err(|e| format!("OIDC token request failed: {}", e))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(format!("OIDC token endpoint returned {}: {}", status, body));
+        }
+        let tokens: TokenResponse = response
+            .json()
+            .await
+            .map_err(|e| format!("Invalid OIDC token response: {}", e))?;
+        let id_token = tokens.id_token.ok_or("OIDC token response has no id_token")?;
+
+        let claims = self.verify_id_token(&id_token, &metadata.issuer).await?;
+        self.identity_from_claims(claims, &pending.nonce)
+    }
+
+    async fn verify_id_token(&self, token: &str, issuer: &str) -> Result<IdTokenClaims, String> {
+        let mut parts = token.split('.');
+        let (Some(header_b64), Some(payload_b64), Some(signature_b64), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err("ID token is not a JWS".to_string());
+        };
+        let header: JwtHeader = URL_SAFE_NO_PAD
+            .decode(header_b64)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .ok_or("ID token header is invalid")?;
+        if header.alg != "RS256" {
+            return Err(format!("Unsupported ID token algorithm {}", header.alg));
+        }
+        let signature = URL_SAFE_NO_PAD
+            .decode(signature_b64)
+            .map_err(|_| "ID token signature is not base64url")?;
+        let signing_input = format!("{}.{}", header_b64, payload_b64);
+
+        let (_, keys) = self.provider(false).await?;
+        let key = match find_key(&keys, header.kid.as_deref()) {
+            Some(key) => key,
+            // Unknown key id: the provider may have rotated its keys
+            None => {
+                let (_, keys) = self.provider(true).await?;
+                find_key(&keys, header.kid.as_deref()).ok_or("No matching OIDC signing key")?
+            }
+        };
+        verify_rs256(&key, signing_input.as_bytes(), &signature)?;
+
+        let claims: IdTokenClaims = URL_SAFE_NO_PAD
+            .decode(payload_b64)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .ok_or("ID token claims are invalid")?;
+
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        if claims.iss.trim_end_matches('/') != issuer.trim_end_matches('/') {
+            return Err(format!("ID token issuer {} is not {}", claims.iss, issuer));
+        }
+        if !claims.aud.contains(&self.config.client_id) {
+            return Err("ID token audience does not include this client".to_string());
+        }
+        if claims.aud.len() > 1 && claims.azp.as_deref() != Some(self.config.client_id.as_str()) {
+            return Err("ID token authorized party is not this client".to_string());
+        }
+        if claims.exp + CLOCK_SKEW_SECONDS < now {
+            return Err("ID token has expired".to_string());
+        }
+        Ok(claims)
+    }
+
+    fn identity_from_claims(
+        &self,
+        claims: IdTokenClaims,
+        expected_nonce: &str,
+    ) -> Result<OidcIdentity, String> {
+        if claims.nonce.as_deref() != Some(expected_nonce) {
+            return Err("ID token nonce does not match".to_string());
+        }
+        let email = claims
+            .email
+            .filter(|e| !e.trim().is_empty())
+            .ok_or("ID token has no email claim")?;
+        // Providers that omit email_verified are trusted to only issue
+        // addresses they own; an explicit false is refused
+        if claims.email_verified == Some(false) {
+            return Err(format!("Email {} is not verified by the identity provider", email));
+        }
+
+        let groups = match claims.extra.get(&self.config.groups_claim) {
+            Some(Value::Array(items)) => items
+                .iter()
+                .filter_map(|v| v.as_str().map(str::to_string))
+                .collect(),
+            Some(Value::String(group)) => vec![group.clone()],
+            _ => Vec::new(),
+        };
+
+        Ok(OidcIdentity {
+            subject: claims.sub,
+            email: email.trim().to_string(),
+            preferred_username: claims.preferred_username,
+            groups,
+            auth_time: claims.auth_time,
+        })
+    }
+}
+
+fn find_key(keys: &[Jwk], kid: Option<&str>) -> Option<Jwk> {
+    keys.iter()
+        .filter(|k| k.kty == "RSA" && k.alg.as_deref().is_none_or(|alg| alg == "RS256"))
+        .find(|k| kid.is_none() || k.kid.as_deref() == kid)
+        .cloned()
+}
+
+fn verify_rs256(key: &Jwk, message: &[u8], signature: &[u8]) -> Result<(), String> {
+    let decode = |value: &Option<String>| {
+        value
+            .as_deref()
+            .and_then(|v| URL_SAFE_NO_PAD.decode(v).ok())
+            .map(|bytes| BigUint::from_bytes_be(&bytes))
+            .ok_or("OIDC signing key is incomplete")
+    };
+    let public_key = RsaPublicKey::new(decode(&key.n)?, decode(&key.e)?)
+        .map_err(|e| format!("Invalid OIDC signing key: {}", e))?;
+    let signature =
+        Signature::try_from(signature).map_err(|_| "ID token signature is malformed")?;
+    VerifyingKey::<Sha256>::new(public_key)
+        .verify(message, &signature)
+        .map_err(|_| "ID token signature is invalid".to_string())
+}
@@ -0,0 +1,237 @@
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::{Algorithm, Argon2, Params, Version};
+use std::sync::OnceLock;
+
+use rand::RngCore;
+use sqlx::MySqlPool;
+
+use crate::models::PasswordOwner;
+use crate::repository;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    Argon2id,
+    Bcrypt,
+}
+
+// Settings for newly written password hashes. Stored hashes are PHC-style
+// strings (`$argon2id$...` or bcrypt's `$2b$...`), so verification works for
+// either algorithm regardless of the current settings.
+#[derive(Debug, Clone)]
+pub struct PasswordHashConfig {
+    pub algorithm: HashAlgorithm,
+    pub bcrypt_cost: u32,
+    pub argon2_memory_kib: u32,
+    pub argon2_iterations: u32,
+    pub argon2_parallelism: u32,
+}
+
+impl Default for PasswordHashConfig {
+    // OWASP's minimum Argon2id recommendation (19 MiB, 2 passes, 1 lane)
+    fn default() -> Self {
+        Self {
+            algorithm: HashAlgorithm::Argon2id,
+            bcrypt_cost: bcrypt::DEFAULT_COST,
+            argon2_memory_kib: 19_456,
+            argon2_iterations: 2,
+            argon2_parallelism: 1,
+        }
+    }
+}
+
+impl PasswordHashConfig {
+    pub fn from_env() -> Result<Self, String> {
+        fn read(name: &str, default: u32) -> u32 {
+            std::env::var(name)
+                .ok()
+                .and_then(|v| v.parse::<u32>().ok())
+                .filter(|v| *v > 0)
+                .unwrap_or(default)
+        }
+
+        let defaults = Self::default();
+        let algorithm = match std::env::var("PASSWORD_HASH_ALGORITHM")
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase()
+            .as_str()
+        {
+            "" | "argon2id" | "argon2" => HashAlgorithm::Argon2id,
+            "bcrypt" => HashAlgorithm::Bcrypt,
+            other => return Err(format!("Unknown PASSWORD_HASH_ALGORITHM: {}", other)),
+        };
+
+        let config = Self {
+            algorithm,
+            bcrypt_cost: read("BCRYPT_COST", defaults.bcrypt_cost),
+            argon2_memory_kib: read("ARGON2_MEMORY_KIB", defaults.argon2_memory_kib),
+            argon2_iterations: read("ARGON2_ITERATIONS", defaults.argon2_iterations),
+            argon2_parallelism: read("ARGON2_PARALLELISM", defaults.argon2_parallelism),
+        };
+        if !(4..=31).contains(&config.bcrypt_cost) {
+            return Err("BCRYPT_COST must be between 4 and 31".to_string());
+        }
+        config.argon2()?;
+        Ok(config)
+    }
+
+    pub fn algorithm_name(&self) -> &'static str {
+        match self.algorithm {
+            HashAlgorithm::Argon2id => "argon2id",
+            HashAlgorithm::Bcrypt => "bcrypt",
+        }
+    }
+
+    fn argon2(&self) -> Result<Argon2<'static>, String> {
+        let params = Params::new(
+            self.argon2_memory_kib,
+            self.argon2_iterations,
+            self.argon2_parallelism,
+            None,
+        )
+        .map_err(|e| format!("Invalid Argon2 parameters: {}", e))?;
+        Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, params))
+    }
+
+    // Hashing is deliberately slow, so it runs on the blocking thread pool
+    // instead of stalling the async workers
+    pub async fn hash(&self, password: &str) -> Result<String, String> {
+        let config = self.clone();
+        let password = password.to_string();
+        tokio::task::spawn_blocking(move || config.hash_blocking(&password))
+            .await
+            .map_err(|e| e.to_string())?
+    }
+
+    fn hash_blocking(&self, password: &str) -> Result<String, String> {
+        match self.algorithm {
+            HashAlgorithm::Bcrypt => {
+                bcrypt::hash(password, self.bcrypt_cost).map_err(|e| e.to_string())
+            }
+            HashAlgorithm::Argon2id => {
+                let mut salt = [0u8; 16];
+                rand::thread_rng().fill_bytes(&mut salt);
+                let salt = SaltString::encode_b64(&salt).map_err(|e| e.to_string())?;
+                self.argon2()?
+                    .hash_password(password.as_bytes(), &salt)
+                    .map(|hash| hash.to_string())
+                    .map_err(|e| e.to_string())
+            }
+        }
+    }
+
+    // True when the stored hash uses another algorithm or other parameters than
+    // the current settings. Unrecognised hashes are left alone.
+    pub fn needs_rehash(&self, stored: &str) -> bool {
+        match (self.algorithm, parse_stored(stored)) {
+            (HashAlgorithm::Bcrypt, Some(StoredHash::Bcrypt { cost })) => cost != self.bcrypt_cost,
+            (HashAlgorithm::Argon2id, Some(StoredHash::Argon2id { params })) => {
+                params.m_cost() != self.argon2_memory_kib
+                    || params.t_cost() != self.argon2_iterations
+                    || params.p_cost() != self.argon2_parallelism
+            }
+            (_, Some(_)) => true,
+            (_, None) => false,
+        }
+    }
+
+    // Used by logins when no account matches the username: runs a verification
+    // against a hash made with the current settings, so the response takes as
+    // long as for a real account and does not reveal which usernames exist.
+    pub async fn verify_unknown_account(&self, password: &str) {
+        static DUMMY_HASH: OnceLock<String> = OnceLock::new();
+        let config = self.clone();
+        let password = password.to_string();
+        let _ = tokio::task::spawn_blocking(move || {
+            let stored =
+                DUMMY_HASH.get_or_init(|| config.hash_blocking("unknown-account").unwrap_or_default());
+            verify_blocking(&password, stored)
+        })
+        .await;
+    }
+
+    // Called after a successful login: rewrites the stored hash with the
+    // current settings when it is outdated. Failures are logged; the login
+    // itself is not affected.
+    pub async fn upgrade_if_needed(
+        &self,
+        db: &MySqlPool,
+        owner: PasswordOwner,
+        stored: &str,
+        password: &str,
+    ) {
+        if !self.needs_rehash(stored) {
+            return;
+        }
+        let new_hash = match self.hash(password).await {
+            Ok(hash) => hash,
+            Err(e) => {
+                tracing::error!("Failed to rehash password: {}", e);
+                return;
+            }
+        };
+        let result = match owner {
+            PasswordOwner::Admin(id) => repository::update_admin_password(db, id, &new_hash).await,
+            PasswordOwner::User(id) => repository::update_password(db, id, &new_hash).await,
+        };
+        match result {
+            Ok(()) => tracing::info!(
+                "Upgraded password hash for {} {} to {}",
+                owner.account_type(),
+                owner.account_id(),
+                self.algorithm_name()
+            ),
+            Err(e) => tracing::error!("Failed to store upgraded password hash: {:?}", e),
+        }
+    }
+}
+
+enum StoredHash {
+    Bcrypt { cost: u32 },
+    Argon2id { params: Params },
+}
+
+fn parse_stored(stored: &str) -> Option<StoredHash> {
+    if let Some(rest) = ["$2a$", "$2b$", "$2x$", "$2y$"]
+        .iter()
+        .find_map(|prefix| stored.strip_prefix(prefix))
+    {
+        let cost = rest.get(..2)?.parse().ok()?;
+        return Some(StoredHash::Bcrypt { cost });
+    }
+    let hash = PasswordHash::new(stored).ok()?;
+    if hash.algorithm != argon2::ARGON2ID_IDENT {
+        return None;
+    }
+    let params = Params::try_from(&hash).ok()?;
+    Some(StoredHash::Argon2id { params })
+}
+
+// Checks a password against a stored bcrypt or Argon2id hash, on the
+// blocking thread pool
+pub async fn verify(password: &str, stored: &str) -> bool {
+    verify_any(password, vec![stored.to_string()]).await
+}
+
+// True when the password matches any of the stored hashes. All checks share
+// one blocking task, which keeps password history lookups off the async workers.
+pub async fn verify_any(password: &str, stored: Vec<String>) -> bool {
+    let password = password.to_string();
+    tokio::task::spawn_blocking(move || stored.iter().any(|hash| verify_blocking(&password, hash)))
+        .await
+        .unwrap_or(false)
+}
+
+fn verify_blocking(password: &str, stored: &str) -> bool {
+    match parse_stored(stored) {
+        Some(StoredHash::Bcrypt { .. }) => bcrypt::verify(password, stored).unwrap_or(false),
+        Some(StoredHash::Argon2id { .. }) => PasswordHash::new(stored)
+            .map(|hash| {
+                Argon2::default()
+                    .verify_password(password.as_bytes(), &hash)
+                    .is_ok()
+            })
+            .unwrap_or(false),
+        None => false,
+    }
+}
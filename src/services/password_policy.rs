@@ -0,0 +1,277 @@
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use sha1::{Digest, Sha1};
+use sqlx::MySqlPool;
+use validator::{ValidationError, ValidationErrors};
+
+use crate::models::PasswordOwner;
+use crate::repository;
+use crate::services::password_hash;
+
+// bcrypt only looks at the first 72 bytes, so longer passwords are rejected
+// rather than silently truncated (Argon2id has no such limit, but hashes may
+// still be written with bcrypt, see services::password_hash)
+pub const MAX_PASSWORD_BYTES: usize = 72;
+const DEFAULT_BREACHED_FILE: &str = "data/breached-passwords.txt";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CharClass {
+    Lowercase,
+    Uppercase,
+    Digit,
+    Symbol,
+}
+
+impl CharClass {
+    fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "lower" | "lowercase" => Some(CharClass::Lowercase),
+            "upper" | "uppercase" => Some(CharClass::Uppercase),
+            "digit" | "number" => Some(CharClass::Digit),
+            "symbol" | "special" => Some(CharClass::Symbol),
+            _ => None,
+        }
+    }
+
+    fn matches(&self, c: char) -> bool {
+        match self {
+            CharClass::Lowercase => c.is_lowercase(),
+            CharClass::Uppercase => c.is_uppercase(),
+            CharClass::Digit => c.is_ascii_digit(),
+            CharClass::Symbol => !c.is_alphanumeric() && !c.is_whitespace(),
+        }
+    }
+
+    fn message(&self) -> &'static str {
+        match self {
+            CharClass::Lowercase => "Password must contain a lowercase letter",
+            CharClass::Uppercase => "Password must contain an uppercase letter",
+            CharClass::Digit => "Password must contain a digit",
+            CharClass::Symbol => "Password must contain a symbol",
+        }
+    }
+}
+
+// SHA-1 hashes of known-breached passwords, bucketed by their first five hex
+// digits like the Pwned Passwords range API. A lookup only touches the bucket
+// for its own prefix, so the same interface works for a remote range source.
+#[derive(Debug, Default)]
+pub struct BreachedPasswords {
+    ranges: HashMap<String, HashSet<String>>,
+}
+
+impl BreachedPasswords {
+    // One uppercase or lowercase SHA-1 hex digest per line, optionally followed
+    // by `:count` (the Pwned Passwords download format). `#` starts a comment.
+    pub fn parse(contents: &str) -> Self {
+        let mut ranges: HashMap<String, HashSet<String>> = HashMap::new();
+        for line in contents.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let hash = line.split(':').next().unwrap_or_default().to_ascii_uppercase();
+            if hash.len() != 40 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+                continue;
+            }
+            let (prefix, suffix) = hash.split_at(5);
+            ranges
+                .entry(prefix.to_string())
+                .or_default()
+                .insert(suffix.to_string());
+        }
+        Self { ranges }
+    }
+
+    pub fn load(path: &str) -> std::io::Result<Self> {
+        std::fs::read_to_string(path).map(|contents| Self::parse(&contents))
+    }
+
+    pub fn len(&self) -> usize {
+        self.ranges.values().map(HashSet::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    // Hash suffixes known for a five-digit prefix
+    fn range(&self, prefix: &str) -> Option<&HashSet<String>> {
+        self.ranges.get(prefix)
+    }
+
+    pub fn contains(&self, password: &str) -> bool {
+        let hash: String = Sha1::digest(password.as_bytes())
+            .iter()
+            .map(|b| format!("{:02X}", b))
+            .collect();
+        let (prefix, suffix) = hash.split_at(5);
+        self.range(prefix).is_some_and(|suffixes| suffixes.contains(suffix))
+    }
+}
+
+// Rules for every password an admin or user sets. Login forms do not use it,
+// so tightening the policy never locks anyone out.
+#[derive(Debug, Clone)]
+pub struct PasswordPolicy {
+    pub min_length: usize,
+    pub required_classes: Vec<CharClass>,
+    // How many previous passwords (including the current one) may not be reused; 0 disables
+    pub history_size: u32,
+    breached: Option<Arc<BreachedPasswords>>,
+}
+
+impl PasswordPolicy {
+    pub fn from_env() -> Result<Self, String> {
+        let min_length = std::env::var("PASSWORD_MIN_LENGTH")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .filter(|v| (1..=MAX_PASSWORD_BYTES).contains(v))
+            .unwrap_or(8);
+
+        let required_classes = match std::env::var("PASSWORD_REQUIRE_CLASSES") {
+            Ok(value) => value
+                .split(',')
+                .filter(|name| !name.trim().is_empty())
+                .map(|name| {
+                    CharClass::parse(name)
+                        .ok_or_else(|| format!("Unknown PASSWORD_REQUIRE_CLASSES entry: {}", name))
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+            Err(_) => vec![CharClass::Lowercase, CharClass::Uppercase, CharClass::Digit],
+        };
+
+        let history_size = std::env::var("PASSWORD_HISTORY")
+            .ok()
+            .and_then(|v| v.parse::<u32>().ok())
+            .unwrap_or(5);
+
+        // Unset uses the bundled list if present; set but empty disables the check
+        let breached = match std::env::var("PASSWORD_BREACHED_FILE") {
+            Ok(path) if path.trim().is_empty() => None,
+            Ok(path) => Some(
+                BreachedPasswords::load(path.trim())
+                    .map_err(|e| format!("Cannot read PASSWORD_BREACHED_FILE {}: {}", path, e))?,
+            ),
+            Err(_) => match BreachedPasswords::load(DEFAULT_BREACHED_FILE) {
+                Ok(list) => Some(list),
+                Err(e) => {
+                    tracing::warn!("Breached-password list {} not loaded: {}", DEFAULT_BREACHED_FILE, e);
+                    None
+                }
+            },
+        };
+
+        Ok(Self {
+            min_length,
+            required_classes,
+            history_size,
+            breached: breached.map(Arc::new),
+        })
+    }
+
+    pub fn breached_count(&self) -> usize {
+        self.breached.as_ref().map_or(0, |list| list.len())
+    }
+
+    // Length, character class and breached-list violations, in display order
+    pub fn violations(&self, password: &str) -> Vec<String> {
+        let mut messages = Vec::new();
+        if password.chars().count() < self.min_length {
+            messages.push(format!(
+                "Password must be at least {} characters",
+                self.min_length
+            ));
+        }
+        if password.len() > MAX_PASSWORD_BYTES {
+            messages.push(format!(
+                "Password must be at most {} bytes",
+                MAX_PASSWORD_BYTES
+            ));
+        }
+        for class in &self.required_classes {
+            if !password.chars().any(|c| class.matches(c)) {
+                messages.push(class.message().to_string());
+            }
+        }
+        if self.breached.as_ref().is_some_and(|list| list.contains(password)) {
+            messages.push(
+                "This password appears in a list of breached passwords; choose another".to_string(),
+            );
+        }
+        messages
+    }
+
+    // Field-level errors for a new password. `owner` is None for accounts that
+    // do not exist yet; otherwise the account's recent passwords are checked too.
+    pub async fn validate(
+        &self,
+        db: &MySqlPool,
+        field: &'static str,
+        password: &str,
+        owner: Option<PasswordOwner>,
+    ) -> Result<(), ValidationErrors> {
+        let mut messages = self.violations(password);
+        if let Some(owner) = owner
+            && messages.is_empty()
+            && self.is_reused(db, owner, password).await
+        {
+            messages.push(format!(
+                "Password must differ from your last {} passwords",
+                self.history_size
+            ));
+        }
+
+        if messages.is_empty() {
+            return Ok(());
+        }
+        let mut errors = ValidationErrors::new();
+        for message in messages {
+            errors.add(field, ValidationError::new("password_policy").with_message(Cow::Owned(message)));
+        }
+        Err(errors)
+    }
+
+    // A failed history lookup is logged and treated as "not reused"
+    async fn is_reused(&self, db: &MySqlPool, owner: PasswordOwner, password: &str) -> bool {
+        if self.history_size == 0 {
+            return false;
+        }
+        match repository::find_password_history(db, owner, self.history_size).await {
+            Ok(hashes) => password_hash::verify_any(password, hashes).await,
+            Err(e) => {
+                tracing::error!("Failed to load password history: {:?}", e);
+                false
+            }
+        }
+    }
+
+    // Records a newly set password hash. Best-effort: the password change has
+    // already been committed.
+    pub async fn remember(&self, db: &MySqlPool, owner: PasswordOwner, password_hash: &str) {
+        if self.history_size == 0 {
+            return;
+        }
+        if let Err(e) =
+            repository::insert_password_history(db, owner, password_hash, self.history_size).await
+        {
+            tracing::error!("Failed to record password history: {:?}", e);
+        }
+    }
+}
+
+// Messages recorded for one field, for rendering under that input
+pub fn field_messages(errors: &ValidationErrors, field: &str) -> Vec<String> {
+    errors
+        .field_errors()
+        .get(field)
+        .map(|errors| {
+            errors
+                .iter()
+                .map(|e| e.message.as_deref().unwrap_or("Invalid value").to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
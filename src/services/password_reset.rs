@@ -0,0 +1,22 @@
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+
+// Reset links are valid for this long and can be used once
+pub const RESET_TOKEN_TTL_MINUTES: i64 = 30;
+
+// Returns (token for the link, SHA-256 hex stored in the database)
+pub fn generate_token() -> (String, String) {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    let token = URL_SAFE_NO_PAD.encode(bytes);
+    let hash = hash_token(&token);
+    (token, hash)
+}
+
+pub fn hash_token(token: &str) -> String {
+    Sha256::digest(token.trim().as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
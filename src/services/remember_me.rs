@@ -0,0 +1,168 @@
+use axum_extra::extract::cookie::{Cookie, SameSite};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use rand::RngCore;
+use sqlx::MySqlPool;
+use subtle::ConstantTimeEq;
+use time::{Duration, OffsetDateTime};
+
+use crate::models::AdminRememberToken;
+use crate::repository;
+use crate::services::password_reset::hash_token;
+
+pub const COOKIE_NAME: &str = "admin_remember";
+// A request that raced the rotation may still present the previous validator
+// for this long without being treated as theft
+const ROTATION_GRACE_SECONDS: i64 = 60;
+
+// Opt-in persistent admin logins. The cookie is `{selector}:{validator}`: the
+// selector finds the row, the validator is checked against its stored hash
+// and replaced each time the token restores a session.
+#[derive(Debug, Clone)]
+pub struct RememberMeConfig {
+    pub lifetime_days: i64,
+    cookie_path: String,
+}
+
+// Result of looking up a cookie
+pub enum TokenCheck {
+    // Current validator: restore the session and rotate
+    Valid(AdminRememberToken, String),
+    // Validator replaced moments ago by a concurrent request: restore only
+    Superseded(AdminRememberToken),
+    // Old validator replayed after rotation; someone else has a copy
+    Stolen(AdminRememberToken),
+    Invalid,
+}
+
+fn random_part(len: usize) -> String {
+    let mut bytes = vec![0u8; len];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+fn hashes_match(a: &str, b: &str) -> bool {
+    a.as_bytes().ct_eq(b.as_bytes()).into()
+}
+
+fn parse_cookie(value: &str) -> Option<(&str, &str)> {
+    let (selector, validator) = value.split_once(':')?;
+    (selector.len() == 24 && !validator.is_empty()).then_some((selector, validator))
+}
+
+impl RememberMeConfig {
+    // Reads REMEMBER_ME_DAYS (default 30, 0 hides the checkbox). The cookie is
+    // only sent to the admin routes under `base_path`.
+    pub fn from_env(base_path: &str) -> Self {
+        let lifetime_days = std::env::var("REMEMBER_ME_DAYS")
+            .ok()
+            .and_then(|v| v.parse::<i64>().ok())
+            .filter(|v| *v >= 0)
+            .unwrap_or(30);
+        let cookie_path = if base_path.is_empty() { "/".to_string() } else { base_path.to_string() };
+        Self { lifetime_days, cookie_path }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.lifetime_days > 0
+    }
+
+    fn cookie(&self, value: String, max_age: Duration) -> Cookie<'static> {
+        Cookie::build((COOKIE_NAME, value))
+            .path(self.cookie_path.clone())
+            .http_only(true)
+            .secure(true)
+            .same_site(SameSite::Lax)
+            .max_age(max_age)
+            .build()
+    }
+
+    // Expires the cookie in the browser
+    pub fn removal_cookie(&self) -> Cookie<'static> {
+        self.cookie(String::new(), Duration::ZERO)
+    }
+
+    // New token for an admin who ticked "remember me"; `session_ref` is the
+    // admin session index entry the login created
+    pub async fn issue(
+        &self,
+        pool: &MySqlPool,
+        admin_id: i32,
+        session_ref: &str,
+    ) -> Result<Cookie<'static>, sqlx::Error> {
+        let _ = repository::delete_expired_remember_tokens(pool).await;
+        let selector = random_part(18);
+        let validator = random_part(32);
+        repository::create_remember_token(
+            pool,
+            admin_id,
+            &selector,
+            &hash_token(&validator),
+            session_ref,
+            self.lifetime_days,
+        )
+        .await?;
+        Ok(self.cookie(format!("{}:{}", selector, validator), Duration::days(self.lifetime_days)))
+    }
+
+    pub async fn check(&self, pool: &MySqlPool, cookie_value: &str) -> Result<TokenCheck, sqlx::Error> {
+        let Some((selector, validator)) = parse_cookie(cookie_value) else {
+            return Ok(TokenCheck::Invalid);
+        };
+        let Some(token) = repository::find_remember_token(pool, selector).await? else {
+            return Ok(TokenCheck::Invalid);
+        };
+
+        let presented = hash_token(validator);
+        if hashes_match(&presented, &token.validator_hash) {
+            return Ok(TokenCheck::Valid(token, presented));
+        }
+        let recently_rotated = token.rotated_at.is_some_and(|rotated_at| {
+            OffsetDateTime::now_utc() - rotated_at < Duration::seconds(ROTATION_GRACE_SECONDS)
+        });
+        let is_previous = token
+            .previous_validator_hash
+            .as_deref()
+            .is_some_and(|previous| hashes_match(&presented, previous));
+        if is_previous && recently_rotated {
+            Ok(TokenCheck::Superseded(token))
+        } else {
+            // The selector is right but the validator is not current: the
+            // cookie was copied before a rotation and used after it
+            Ok(TokenCheck::Stolen(token))
+        }
+    }
+
+    // Replaces the validator of a token that just restored `session_ref`.
+    // Returns None if a concurrent request rotated it first.
+    pub async fn rotate(
+        &self,
+        pool: &MySqlPool,
+        token: &AdminRememberToken,
+        current_hash: &str,
+        session_ref: &str,
+    ) -> Result<Option<Cookie<'static>>, sqlx::Error> {
+        let validator = random_part(32);
+        let rotated = repository::rotate_remember_token(
+            pool,
+            token.id,
+            current_hash,
+            &hash_token(&validator),
+            session_ref,
+        )
+        .await?;
+        if !rotated {
+            return Ok(None);
+        }
+        let remaining = token.expires_at - OffsetDateTime::now_utc();
+        Ok(Some(self.cookie(format!("{}:{}", token.selector, validator), remaining)))
+    }
+
+    // Logout: the token in this browser's cookie stops working
+    pub async fn forget(&self, pool: &MySqlPool, cookie_value: &str) {
+        if let Some((selector, _)) = parse_cookie(cookie_value)
+            && let Err(e) = repository::delete_remember_token(pool, selector).await
+        {
+            tracing::error!("Failed to delete remember-me token: {:?}", e);
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use std::time::Duration;
+
+use sqlx::MySqlPool;
+
+use crate::repository;
+
+// How often the auto-purge task looks for expired trash
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+// Retention of soft-deleted users, countries and states (TRASH_RETENTION_DAYS,
+// default 30). 0 keeps them until they are purged by hand on the Trash page.
+#[derive(Debug, Clone, Copy)]
+pub struct TrashConfig {
+    pub retention_days: Option<u32>,
+}
+
+impl TrashConfig {
+    pub fn from_env() -> Result<Self, String> {
+        let retention_days = match std::env::var("TRASH_RETENTION_DAYS") {
+            Ok(raw) if !raw.trim().is_empty() => raw
+                .trim()
+                .parse::<u32>()
+                .map_err(|e| format!("TRASH_RETENTION_DAYS must be a number of days: {}", e))?,
+            _ => 30,
+        };
+        Ok(Self { retention_days: Some(retention_days).filter(|days| *days > 0) })
+    }
+}
+
+// Purges expired trash now and then every PURGE_INTERVAL, for the lifetime
+// of the process. Does nothing when auto-purge is off.
+pub fn spawn_auto_purge(pool: MySqlPool, config: TrashConfig) {
+    let Some(days) = config.retention_days else {
+        return;
+    };
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(PURGE_INTERVAL);
+        loop {
+            interval.tick().await;
+            match repository::purge_trash_older_than(&pool, days).await {
+                Ok(purged) if purged.total() > 0 => tracing::info!(
+                    "Purged trash older than {} days: {} users, {} states, {} countries",
+                    days,
+                    purged.users,
+                    purged.states,
+                    purged.countries
+                ),
+                Ok(_) => {}
+                Err(e) => tracing::error!("Failed to purge trash: {:?}", e),
+            }
+        }
+    });
+}
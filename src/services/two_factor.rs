@@ -0,0 +1,139 @@
+use aes_gcm::{
+    Aes256Gcm, Key, Nonce,
+    aead::{Aead, KeyInit, OsRng, rand_core::RngCore},
+};
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use qrcode::{QrCode, render::svg};
+use rand::{Rng, distributions::Alphanumeric};
+use sha2::{Digest, Sha256};
+use totp_rs::{Algorithm, Secret, TOTP};
+
+const NONCE_LEN: usize = 12;
+pub const RECOVERY_CODE_COUNT: usize = 10;
+
+// TOTP settings shared by enrollment and the second login step.
+// Secrets are stored AES-256-GCM encrypted as base64(nonce || ciphertext).
+#[derive(Clone)]
+pub struct TwoFactorConfig {
+    key: [u8; 32],
+    pub issuer: String,
+}
+
+impl TwoFactorConfig {
+    // Reads TOTP_ENCRYPTION_KEY (base64, 32 bytes) and TOTP_ISSUER.
+    // Returns None when no key is configured, which disables enrollment.
+    pub fn from_env() -> Option<Self> {
+        let raw = std::env::var("TOTP_ENCRYPTION_KEY").ok()?;
+        let decoded = match BASE64.decode(raw.trim()) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                tracing::error!("TOTP_ENCRYPTION_KEY is not valid base64: {:?}", e);
+                return None;
+            }
+        };
+        let key: [u8; 32] = match decoded.try_into() {
+            Ok(key) => key,
+            Err(_) => {
+                tracing::error!("TOTP_ENCRYPTION_KEY must decode to exactly 32 bytes");
+                return None;
+            }
+        };
+        let issuer = std::env::var("TOTP_ISSUER")
+            .unwrap_or_else(|_| "Admin Console".to_string())
+            .replace(':', "");
+
+        Some(Self { key, issuer })
+    }
+
+    pub fn encrypt_secret(&self, secret: &[u8]) -> Result<String, String> {
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&self.key));
+        let mut nonce_bytes = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce_bytes);
+        let ciphertext = cipher
+            .encrypt(Nonce::from_slice(&nonce_bytes), secret)
+            .map_err(|_| "TOTP secret encryption failed".to_string())?;
+
+        let mut payload = nonce_bytes.to_vec();
+        payload.extend_from_slice(&ciphertext);
+        Ok(BASE64.encode(payload))
+    }
+
+    pub fn decrypt_secret(&self, encoded: &str) -> Result<Vec<u8>, String> {
+        let payload = BASE64
+            .decode(encoded)
+            .map_err(|_| "Stored TOTP secret is not valid base64".to_string())?;
+        if payload.len() <= NONCE_LEN {
+            return Err("Stored TOTP secret is truncated".to_string());
+        }
+        let (nonce, ciphertext) = payload.split_at(NONCE_LEN);
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&self.key));
+        cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| "TOTP secret decryption failed".to_string())
+    }
+
+    pub fn totp(&self, secret: Vec<u8>, account_name: &str) -> Result<TOTP, String> {
+        TOTP::new(
+            Algorithm::SHA1,
+            6,
+            1,
+            30,
+            secret,
+            Some(self.issuer.clone()),
+            account_name.replace(':', ""),
+        )
+        .map_err(|e| format!("Invalid TOTP parameters: {:?}", e))
+    }
+}
+
+pub fn generate_secret() -> Vec<u8> {
+    Secret::generate_secret()
+        .to_bytes()
+        .expect("freshly generated TOTP secret is raw bytes")
+}
+
+// Checks a 6-digit code against the current time step (with one step of skew).
+pub fn verify_code(totp: &TOTP, code: &str) -> bool {
+    let code = code.trim().replace(' ', "");
+    if code.len() != 6 || !code.chars().all(|c| c.is_ascii_digit()) {
+        return false;
+    }
+    totp.check_current(&code).unwrap_or(false)
+}
+
+pub fn qr_code_svg(totp: &TOTP) -> Result<String, String> {
+    let code = QrCode::new(totp.get_url().as_bytes())
+        .map_err(|e| format!("Failed to build QR code: {:?}", e))?;
+    Ok(code
+        .render::<svg::Color>()
+        .min_dimensions(200, 200)
+        .build())
+}
+
+// Recovery codes look like `abcde-fghij`; only their SHA-256 hash is stored.
+pub fn generate_recovery_codes() -> Vec<String> {
+    let mut rng = rand::thread_rng();
+    (0..RECOVERY_CODE_COUNT)
+        .map(|_| {
+            let raw: String = (&mut rng)
+                .sample_iter(&Alphanumeric)
+                .take(10)
+                .map(|b| char::from(b).to_ascii_lowercase())
+                .collect();
+            format!("{}-{}", &raw[..5], &raw[5..])
+        })
+        .collect()
+}
+
+pub fn hash_recovery_code(code: &str) -> String {
+    let normalized: String = code
+        .trim()
+        .chars()
+        .filter(|c| *c != '-' && !c.is_whitespace())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    Sha256::digest(normalized.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
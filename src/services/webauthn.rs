@@ -0,0 +1,647 @@
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use ciborium::Value as Cbor;
+use p256::ecdsa::{Signature as P256Signature, VerifyingKey as P256VerifyingKey};
+use p256::EncodedPoint;
+use rand::RngCore;
+use rsa::pkcs1v15::{Signature as RsaSignature, VerifyingKey as RsaVerifyingKey};
+use rsa::signature::Verifier;
+use rsa::{BigUint, RsaPublicKey};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+use time::OffsetDateTime;
+
+// How long the browser has to answer a registration or login challenge
+pub const CEREMONY_TTL_SECONDS: i64 = 300;
+
+// COSE algorithm identifiers we can verify
+const COSE_ES256: i64 = -7;
+const COSE_RS256: i64 = -257;
+
+// Authenticator data flags
+const FLAG_USER_PRESENT: u8 = 0x01;
+const FLAG_USER_VERIFIED: u8 = 0x04;
+const FLAG_ATTESTED_DATA: u8 = 0x40;
+
+// Relying party settings for admin passkeys. Passkeys are bound to the RP ID,
+// so changing WEBAUTHN_RP_ID makes existing passkeys unusable.
+#[derive(Debug, Clone)]
+pub struct WebAuthnConfig {
+    pub rp_id: String,
+    pub rp_name: String,
+    // Exact origin the browser reports, e.g. https://admin.example.com
+    pub origin: String,
+}
+
+impl WebAuthnConfig {
+    // Reads WEBAUTHN_RP_ID, WEBAUTHN_RP_NAME and WEBAUTHN_ORIGIN; the RP ID and
+    // origin default to the host and origin of `public_url`
+    pub fn from_env(public_url: &str) -> Result<Self, String> {
+        let origin = std::env::var("WEBAUTHN_ORIGIN")
+            .ok()
+            .map(|v| v.trim().trim_end_matches('/').to_string())
+            .filter(|v| !v.is_empty())
+            .unwrap_or_else(|| public_url.trim_end_matches('/').to_string());
+        let url = reqwest::Url::parse(&origin)
+            .map_err(|e| format!("WEBAUTHN_ORIGIN is not a valid URL: {}", e))?;
+        let host = url
+            .host_str()
+            .ok_or("WEBAUTHN_ORIGIN has no host")?
+            .to_string();
+        let origin = url.origin().ascii_serialization();
+
+        let rp_id = std::env::var("WEBAUTHN_RP_ID")
+            .ok()
+            .map(|v| v.trim().to_lowercase())
+            .filter(|v| !v.is_empty())
+            .unwrap_or(host.clone());
+        // The RP ID must be the origin's host or a registrable suffix of it
+        if host != rp_id && !host.ends_with(&format!(".{}", rp_id)) {
+            return Err(format!("WEBAUTHN_RP_ID {} does not match origin {}", rp_id, origin));
+        }
+
+        let rp_name = std::env::var("WEBAUTHN_RP_NAME")
+            .ok()
+            .filter(|v| !v.trim().is_empty())
+            .unwrap_or_else(|| "Admin Console".to_string());
+
+        Ok(Self { rp_id, rp_name, origin })
+    }
+}
+
+// Challenge of a registration or login in progress, kept in the session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingCeremony {
+    pub challenge: String,
+    // Set for registration (the admin the new passkey will belong to) and for
+    // re-authentication (the signed-in admin who must answer)
+    pub admin_id: Option<i32>,
+    pub started_at: i64,
+}
+
+impl PendingCeremony {
+    fn new(admin_id: Option<i32>) -> Self {
+        let mut challenge = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut challenge);
+        Self {
+            challenge: URL_SAFE_NO_PAD.encode(challenge),
+            admin_id,
+            started_at: OffsetDateTime::now_utc().unix_timestamp(),
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        OffsetDateTime::now_utc().unix_timestamp() - self.started_at > CEREMONY_TTL_SECONDS
+    }
+}
+
+// Public key credential produced by a successful registration
+#[derive(Debug, Clone)]
+pub struct NewCredential {
+    pub credential_id: Vec<u8>,
+    // COSE_Key as sent by the authenticator
+    pub public_key: Vec<u8>,
+    pub algorithm: i64,
+    pub sign_count: u32,
+}
+
+#[derive(Deserialize)]
+struct ClientData {
+    #[serde(rename = "type")]
+    kind: String,
+    challenge: String,
+    origin: String,
+}
+
+// Admin ids are the WebAuthn user handle, so a discoverable passkey tells us
+// whose it is
+pub fn user_handle(admin_id: i32) -> String {
+    URL_SAFE_NO_PAD.encode(admin_id.to_string())
+}
+
+pub fn admin_id_from_user_handle(handle: &[u8]) -> Option<i32> {
+    std::str::from_utf8(handle).ok()?.parse().ok()
+}
+
+// Authenticators without a counter always report 0. Otherwise the counter must
+// grow; a value that does not suggests a cloned authenticator.
+pub fn sign_count_ok(stored: u32, received: u32) -> bool {
+    (stored == 0 && received == 0) || received > stored
+}
+
+pub fn decode_b64url(value: &str) -> Result<Vec<u8>, String> {
+    URL_SAFE_NO_PAD
+        .decode(value.trim().trim_end_matches('='))
+        .map_err(|_| "Malformed base64url value".to_string())
+}
+
+impl WebAuthnConfig {
+    // Options for navigator.credentials.create(); `exclude` keeps an
+    // authenticator from registering twice
+    pub fn creation_options(
+        &self,
+        admin_id: i32,
+        username: &str,
+        exclude: &[Vec<u8>],
+    ) -> (Value, PendingCeremony) {
+        let pending = PendingCeremony::new(Some(admin_id));
+        let exclude: Vec<Value> = exclude
+            .iter()
+            .map(|id| json!({ "type": "public-key", "id": URL_SAFE_NO_PAD.encode(id) }))
+            .collect();
+        let options = json!({
+            "challenge": pending.challenge,
+            "rp": { "id": self.rp_id, "name": self.rp_name },
+            "user": { "id": user_handle(admin_id), "name": username, "displayName": username },
+            "pubKeyCredParams": [
+                { "type": "public-key", "alg": COSE_ES256 },
+                { "type": "public-key", "alg": COSE_RS256 },
+            ],
+            "timeout": CEREMONY_TTL_SECONDS * 1000,
+            "attestation": "none",
+            "excludeCredentials": exclude,
+            "authenticatorSelection": {
+                "residentKey": "required",
+                "requireResidentKey": true,
+                "userVerification": "required",
+            },
+        });
+        (options, pending)
+    }
+
+    // Options for navigator.credentials.get(); no allow list, the browser
+    // offers the passkeys it has for this RP
+    pub fn request_options(&self) -> (Value, PendingCeremony) {
+        let pending = PendingCeremony::new(None);
+        let options = json!({
+            "challenge": pending.challenge,
+            "rpId": self.rp_id,
+            "timeout": CEREMONY_TTL_SECONDS * 1000,
+            "userVerification": "required",
+        });
+        (options, pending)
+    }
+
+    // Options for confirming a signed-in admin's identity (sudo mode); only
+    // their own passkeys are allowed
+    pub fn reauth_options(&self, admin_id: i32, allow: &[Vec<u8>]) -> (Value, PendingCeremony) {
+        let pending = PendingCeremony::new(Some(admin_id));
+        let allow: Vec<Value> = allow
+            .iter()
+            .map(|id| json!({ "type": "public-key", "id": URL_SAFE_NO_PAD.encode(id) }))
+            .collect();
+        let options = json!({
+            "challenge": pending.challenge,
+            "rpId": self.rp_id,
+            "timeout": CEREMONY_TTL_SECONDS * 1000,
+            "userVerification": "required",
+            "allowCredentials": allow,
+        });
+        (options, pending)
+    }
+
+    fn check_client_data(
+        &self,
+        client_data_json: &[u8],
+        expected_type: &str,
+        pending: &PendingCeremony,
+    ) -> Result<(), String> {
+        let client_data: ClientData =
+            serde_json::from_slice(client_data_json).map_err(|_| "Malformed client data")?;
+        if client_data.kind != expected_type {
+            return Err(format!("Unexpected client data type {}", client_data.kind));
+        }
+        let challenge_matches: bool = client_data
+            .challenge
+            .trim_end_matches('=')
+            .as_bytes()
+            .ct_eq(pending.challenge.as_bytes())
+            .into();
+        if !challenge_matches {
+            return Err("Challenge mismatch".to_string());
+        }
+        if client_data.origin != self.origin {
+            return Err(format!("Unexpected origin {}", client_data.origin));
+        }
+        Ok(())
+    }
+
+    // Checks the RP ID hash and flags; returns the flags and sign count
+    fn check_authenticator_data(&self, auth_data: &[u8]) -> Result<(u8, u32), String> {
+        if auth_data.len() < 37 {
+            return Err("Authenticator data is too short".to_string());
+        }
+        let rp_id_hash = Sha256::digest(self.rp_id.as_bytes());
+        if auth_data[..32] != rp_id_hash[..] {
+            return Err("RP ID hash mismatch".to_string());
+        }
+        let flags = auth_data[32];
+        if flags & FLAG_USER_PRESENT == 0 {
+            return Err("User presence flag not set".to_string());
+        }
+        if flags & FLAG_USER_VERIFIED == 0 {
+            return Err("User verification flag not set".to_string());
+        }
+        let sign_count = u32::from_be_bytes([auth_data[33], auth_data[34], auth_data[35], auth_data[36]]);
+        Ok((flags, sign_count))
+    }
+
+    // Verifies a navigator.credentials.create() response. Attestation
+    // statements are not checked ("none" is requested), so any authenticator
+    // is accepted.
+    pub fn verify_registration(
+        &self,
+        pending: &PendingCeremony,
+        client_data_json: &[u8],
+        attestation_object: &[u8],
+    ) -> Result<NewCredential, String> {
+        self.check_client_data(client_data_json, "webauthn.create", pending)?;
+
+        let attestation: Cbor = ciborium::from_reader(attestation_object)
+            .map_err(|_| "Malformed attestation object")?;
+        let auth_data = cbor_map_get(&attestation, &Cbor::Text("authData".to_string()))
+            .and_then(Cbor::as_bytes)
+            .ok_or("Attestation object has no authenticator data")?;
+
+        let (flags, sign_count) = self.check_authenticator_data(auth_data)?;
+        if flags & FLAG_ATTESTED_DATA == 0 {
+            return Err("No attested credential data".to_string());
+        }
+        // aaguid (16 bytes) follows the sign count, then the id length and id
+        let rest = &auth_data[37..];
+        if rest.len() < 18 {
+            return Err("Attested credential data is too short".to_string());
+        }
+        let id_len = u16::from_be_bytes([rest[16], rest[17]]) as usize;
+        let rest = &rest[18..];
+        if id_len == 0 || id_len > 1023 || rest.len() < id_len {
+            return Err("Invalid credential id".to_string());
+        }
+        let credential_id = rest[..id_len].to_vec();
+
+        let mut key_bytes = &rest[id_len..];
+        let before = key_bytes.len();
+        let cose_key: Cbor =
+            ciborium::from_reader(&mut key_bytes).map_err(|_| "Malformed credential public key")?;
+        let public_key = rest[id_len..id_len + before - key_bytes.len()].to_vec();
+
+        let algorithm = cose_int(&cose_key, 3).ok_or("Credential public key has no algorithm")?;
+        // Parse now so unusable keys are refused at registration
+        PublicKey::from_cose(&cose_key, algorithm)?;
+
+        Ok(NewCredential { credential_id, public_key, algorithm, sign_count })
+    }
+
+    // Verifies a navigator.credentials.get() response against a stored key;
+    // returns the authenticator's new sign count
+    pub fn verify_assertion(
+        &self,
+        pending: &PendingCeremony,
+        public_key: &[u8],
+        algorithm: i64,
+        client_data_json: &[u8],
+        authenticator_data: &[u8],
+        signature: &[u8],
+    ) -> Result<u32, String> {
+        self.check_client_data(client_data_json, "webauthn.get", pending)?;
+        let (_, sign_count) = self.check_authenticator_data(authenticator_data)?;
+
+        let cose_key: Cbor =
+            ciborium::from_reader(public_key).map_err(|_| "Stored public key is malformed")?;
+        let key = PublicKey::from_cose(&cose_key, algorithm)?;
+
+        let mut signed = authenticator_data.to_vec();
+        signed.extend_from_slice(&Sha256::digest(client_data_json));
+        key.verify(&signed, signature)?;
+        Ok(sign_count)
+    }
+}
+
+enum PublicKey {
+    Es256(P256VerifyingKey),
+    Rs256(RsaPublicKey),
+}
+
+impl PublicKey {
+    fn from_cose(key: &Cbor, algorithm: i64) -> Result<Self, String> {
+        let bytes = |label: i64| {
+            cbor_map_get(key, &Cbor::Integer(label.into()))
+                .and_then(Cbor::as_bytes)
+                .ok_or_else(|| format!("Credential public key is missing parameter {}", label))
+        };
+        match (algorithm, cose_int(key, 1)) {
+            // kty 2 = EC2, crv 1 = P-256
+            (COSE_ES256, Some(2)) => {
+                if cose_int(key, -1) != Some(1) {
+                    return Err("Unsupported elliptic curve".to_string());
+                }
+                let (x, y) = (bytes(-2)?, bytes(-3)?);
+                if x.len() != 32 || y.len() != 32 {
+                    return Err("Invalid P-256 coordinates".to_string());
+                }
+                let point = EncodedPoint::from_affine_coordinates(x[..].into(), y[..].into(), false);
+                P256VerifyingKey::from_encoded_point(&point)
+                    .map(PublicKey::Es256)
+                    .map_err(|_| "Invalid P-256 public key".to_string())
+            }
+            // kty 3 = RSA
+            (COSE_RS256, Some(3)) => {
+                let n = BigUint::from_bytes_be(bytes(-1)?);
+                let e = BigUint::from_bytes_be(bytes(-2)?);
+                RsaPublicKey::new(n, e)
+                    .map(PublicKey::Rs256)
+                    .map_err(|e| format!("Invalid RSA public key: {}", e))
+            }
+            _ => Err(format!("Unsupported credential algorithm {}", algorithm)),
+        }
+    }
+
+    fn verify(&self, message: &[u8], signature: &[u8]) -> Result<(), String> {
+        match self {
+            PublicKey::Es256(key) => {
+                let signature =
+                    P256Signature::from_der(signature).map_err(|_| "Malformed signature")?;
+                key.verify(message, &signature)
+            }
+            PublicKey::Rs256(key) => {
+                let signature =
+                    RsaSignature::try_from(signature).map_err(|_| "Malformed signature")?;
+                RsaVerifyingKey::<Sha256>::new(key.clone()).verify(message, &signature)
+            }
+        }
+        .map_err(|_| "Signature is invalid".to_string())
+    }
+}
+
+fn cbor_map_get<'a>(map: &'a Cbor, key: &Cbor) -> Option<&'a Cbor> {
+    map.as_map()?
+        .iter()
+        .find(|(k, _)| k == key)
+        .map(|(_, v)| v)
+}
+
+fn cose_int(key: &Cbor, label: i64) -> Option<i64> {
+    cbor_map_get(key, &Cbor::Integer(label.into()))
+        .and_then(Cbor::as_integer)
+        .and_then(|v| i64::try_from(v).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use p256::ecdsa::{SigningKey, signature::Signer};
+
+    use super::*;
+
+    const CHALLENGE: &str = "c2VydmVyLWlzc3VlZC1jaGFsbGVuZ2U";
+    const CREDENTIAL_ID: &[u8] = b"credential-1";
+
+    fn config() -> WebAuthnConfig {
+        WebAuthnConfig {
+            rp_id: "admin.example.com".to_string(),
+            rp_name: "Admin Console".to_string(),
+            origin: "https://admin.example.com".to_string(),
+        }
+    }
+
+    fn pending() -> PendingCeremony {
+        PendingCeremony { challenge: CHALLENGE.to_string(), admin_id: Some(7), started_at: 0 }
+    }
+
+    // Fixed key, so signatures (RFC 6979) are the same on every run
+    fn signing_key() -> SigningKey {
+        SigningKey::from_bytes(&[0x11; 32].into()).unwrap()
+    }
+
+    fn cose_key(key: &SigningKey) -> Vec<u8> {
+        let point = key.verifying_key().to_encoded_point(false);
+        let int = |v: i64| Cbor::Integer(v.into());
+        let map = Cbor::Map(vec![
+            (int(1), int(2)),
+            (int(3), int(COSE_ES256)),
+            (int(-1), int(1)),
+            (int(-2), Cbor::Bytes(point.x().unwrap().to_vec())),
+            (int(-3), Cbor::Bytes(point.y().unwrap().to_vec())),
+        ]);
+        let mut bytes = Vec::new();
+        ciborium::into_writer(&map, &mut bytes).unwrap();
+        bytes
+    }
+
+    fn client_data(kind: &str, challenge: &str, origin: &str) -> Vec<u8> {
+        json!({ "type": kind, "challenge": challenge, "origin": origin }).to_string().into_bytes()
+    }
+
+    fn auth_data(rp_id: &str, flags: u8, sign_count: u32) -> Vec<u8> {
+        let mut data = Sha256::digest(rp_id.as_bytes()).to_vec();
+        data.push(flags);
+        data.extend_from_slice(&sign_count.to_be_bytes());
+        data
+    }
+
+    fn attestation_object(auth_data: Vec<u8>) -> Vec<u8> {
+        let map = Cbor::Map(vec![
+            (Cbor::Text("fmt".to_string()), Cbor::Text("none".to_string())),
+            (Cbor::Text("attStmt".to_string()), Cbor::Map(Vec::new())),
+            (Cbor::Text("authData".to_string()), Cbor::Bytes(auth_data)),
+        ]);
+        let mut bytes = Vec::new();
+        ciborium::into_writer(&map, &mut bytes).unwrap();
+        bytes
+    }
+
+    fn registration_auth_data(flags: u8, public_key: &[u8]) -> Vec<u8> {
+        let mut data = auth_data("admin.example.com", flags, 0);
+        data.extend_from_slice(&[0; 16]);
+        data.extend_from_slice(&(CREDENTIAL_ID.len() as u16).to_be_bytes());
+        data.extend_from_slice(CREDENTIAL_ID);
+        data.extend_from_slice(public_key);
+        data
+    }
+
+    const REGISTRATION_FLAGS: u8 = FLAG_USER_PRESENT | FLAG_USER_VERIFIED | FLAG_ATTESTED_DATA;
+    const ASSERTION_FLAGS: u8 = FLAG_USER_PRESENT | FLAG_USER_VERIFIED;
+
+    fn sign(key: &SigningKey, auth_data: &[u8], client_data_json: &[u8]) -> Vec<u8> {
+        let mut signed = auth_data.to_vec();
+        signed.extend_from_slice(&Sha256::digest(client_data_json));
+        let signature: P256Signature = key.sign(&signed);
+        signature.to_der().as_bytes().to_vec()
+    }
+
+    fn register(client_data_json: &[u8], flags: u8) -> Result<NewCredential, String> {
+        let public_key = cose_key(&signing_key());
+        let attestation = attestation_object(registration_auth_data(flags, &public_key));
+        config().verify_registration(&pending(), client_data_json, &attestation)
+    }
+
+    #[test]
+    fn registration_accepts_valid_response() {
+        let client_data = client_data("webauthn.create", CHALLENGE, "https://admin.example.com");
+        let credential = register(&client_data, REGISTRATION_FLAGS).unwrap();
+        assert_eq!(credential.credential_id, CREDENTIAL_ID);
+        assert_eq!(credential.public_key, cose_key(&signing_key()));
+        assert_eq!(credential.algorithm, COSE_ES256);
+        assert_eq!(credential.sign_count, 0);
+    }
+
+    #[test]
+    fn registration_rejects_wrong_origin_challenge_and_type() {
+        let wrong_origin = client_data("webauthn.create", CHALLENGE, "https://evil.example.com");
+        assert!(register(&wrong_origin, REGISTRATION_FLAGS).is_err());
+
+        let wrong_challenge = client_data("webauthn.create", "b3RoZXI", "https://admin.example.com");
+        assert_eq!(
+            register(&wrong_challenge, REGISTRATION_FLAGS).unwrap_err(),
+            "Challenge mismatch"
+        );
+
+        let wrong_type = client_data("webauthn.get", CHALLENGE, "https://admin.example.com");
+        assert!(register(&wrong_type, REGISTRATION_FLAGS).is_err());
+
+        assert!(register(b"not json", REGISTRATION_FLAGS).is_err());
+    }
+
+    #[test]
+    fn registration_rejects_missing_flags_and_bad_data() {
+        let client_data = client_data("webauthn.create", CHALLENGE, "https://admin.example.com");
+        assert!(register(&client_data, FLAG_USER_PRESENT | FLAG_ATTESTED_DATA).is_err());
+        assert!(register(&client_data, FLAG_USER_VERIFIED | FLAG_ATTESTED_DATA).is_err());
+        assert!(register(&client_data, ASSERTION_FLAGS).is_err());
+
+        let pending = pending();
+        assert!(config().verify_registration(&pending, &client_data, b"\xff").is_err());
+
+        // RP ID hash of another site
+        let mut data = auth_data("example.org", REGISTRATION_FLAGS, 0);
+        data.extend_from_slice(&registration_auth_data(REGISTRATION_FLAGS, &cose_key(&signing_key()))[37..]);
+        let attestation = attestation_object(data);
+        let result = config().verify_registration(&pending, &client_data, &attestation);
+        assert_eq!(result.unwrap_err(), "RP ID hash mismatch");
+
+        // Credential id longer than the data that follows
+        let mut data = auth_data("admin.example.com", REGISTRATION_FLAGS, 0);
+        data.extend_from_slice(&[0; 16]);
+        data.extend_from_slice(&500u16.to_be_bytes());
+        data.extend_from_slice(CREDENTIAL_ID);
+        let attestation = attestation_object(data);
+        assert!(config().verify_registration(&pending, &client_data, &attestation).is_err());
+    }
+
+    #[test]
+    fn registration_rejects_unsupported_keys() {
+        let client_data = client_data("webauthn.create", CHALLENGE, "https://admin.example.com");
+        let int = |v: i64| Cbor::Integer(v.into());
+        // EC2 key claiming curve P-384
+        let point = signing_key().verifying_key().to_encoded_point(false);
+        let key = Cbor::Map(vec![
+            (int(1), int(2)),
+            (int(3), int(COSE_ES256)),
+            (int(-1), int(2)),
+            (int(-2), Cbor::Bytes(point.x().unwrap().to_vec())),
+            (int(-3), Cbor::Bytes(point.y().unwrap().to_vec())),
+        ]);
+        let mut public_key = Vec::new();
+        ciborium::into_writer(&key, &mut public_key).unwrap();
+        let attestation = attestation_object(registration_auth_data(REGISTRATION_FLAGS, &public_key));
+        let result = config().verify_registration(&pending(), &client_data, &attestation);
+        assert_eq!(result.unwrap_err(), "Unsupported elliptic curve");
+
+        // EdDSA
+        let key = Cbor::Map(vec![(int(1), int(1)), (int(3), int(-8))]);
+        let mut public_key = Vec::new();
+        ciborium::into_writer(&key, &mut public_key).unwrap();
+        let attestation = attestation_object(registration_auth_data(REGISTRATION_FLAGS, &public_key));
+        assert!(config().verify_registration(&pending(), &client_data, &attestation).is_err());
+    }
+
+    fn assert_with(client_data_json: &[u8], auth_data: &[u8], signature: &[u8]) -> Result<u32, String> {
+        let public_key = cose_key(&signing_key());
+        config().verify_assertion(&pending(), &public_key, COSE_ES256, client_data_json, auth_data, signature)
+    }
+
+    #[test]
+    fn assertion_accepts_valid_signature() {
+        let client_data = client_data("webauthn.get", CHALLENGE, "https://admin.example.com");
+        let auth_data = auth_data("admin.example.com", ASSERTION_FLAGS, 42);
+        let signature = sign(&signing_key(), &auth_data, &client_data);
+        assert_eq!(assert_with(&client_data, &auth_data, &signature), Ok(42));
+    }
+
+    #[test]
+    fn assertion_rejects_bad_signature() {
+        let client_data = client_data("webauthn.get", CHALLENGE, "https://admin.example.com");
+        let auth_data = auth_data("admin.example.com", ASSERTION_FLAGS, 42);
+
+        // Signed by another key
+        let other = SigningKey::from_bytes(&[0x22; 32].into()).unwrap();
+        let signature = sign(&other, &auth_data, &client_data);
+        assert_eq!(assert_with(&client_data, &auth_data, &signature).unwrap_err(), "Signature is invalid");
+
+        // Sign count changed after signing
+        let signature = sign(&signing_key(), &auth_data, &client_data);
+        let tampered = auth_data_with_count(&auth_data, 43);
+        assert!(assert_with(&client_data, &tampered, &signature).is_err());
+
+        assert_eq!(assert_with(&client_data, &auth_data, b"junk").unwrap_err(), "Malformed signature");
+    }
+
+    fn auth_data_with_count(auth_data: &[u8], sign_count: u32) -> Vec<u8> {
+        let mut data = auth_data.to_vec();
+        data[33..37].copy_from_slice(&sign_count.to_be_bytes());
+        data
+    }
+
+    #[test]
+    fn assertion_rejects_wrong_origin_and_challenge() {
+        let auth_data = auth_data("admin.example.com", ASSERTION_FLAGS, 42);
+        for client_data in [
+            client_data("webauthn.get", CHALLENGE, "https://evil.example.com"),
+            client_data("webauthn.get", "b3RoZXI", "https://admin.example.com"),
+            client_data("webauthn.create", CHALLENGE, "https://admin.example.com"),
+        ] {
+            // Validly signed, but for another origin, challenge or ceremony
+            let signature = sign(&signing_key(), &auth_data, &client_data);
+            assert!(assert_with(&client_data, &auth_data, &signature).is_err());
+        }
+    }
+
+    #[test]
+    fn assertion_rejects_missing_user_verification() {
+        let client_data = client_data("webauthn.get", CHALLENGE, "https://admin.example.com");
+        let auth_data = auth_data("admin.example.com", FLAG_USER_PRESENT, 42);
+        let signature = sign(&signing_key(), &auth_data, &client_data);
+        assert_eq!(
+            assert_with(&client_data, &auth_data, &signature).unwrap_err(),
+            "User verification flag not set"
+        );
+    }
+
+    #[test]
+    fn sign_count_must_grow_unless_unsupported() {
+        assert!(sign_count_ok(0, 0));
+        assert!(sign_count_ok(0, 1));
+        assert!(sign_count_ok(41, 42));
+        assert!(!sign_count_ok(42, 42));
+        assert!(!sign_count_ok(42, 41));
+        assert!(!sign_count_ok(42, 0));
+    }
+
+    #[test]
+    fn user_handle_round_trips() {
+        let handle = decode_b64url(&user_handle(7)).unwrap();
+        assert_eq!(admin_id_from_user_handle(&handle), Some(7));
+        assert_eq!(admin_id_from_user_handle(b"admin"), None);
+    }
+
+    #[test]
+    fn reauth_options_allow_only_the_admins_passkeys() {
+        let (options, pending) = config().reauth_options(7, &[CREDENTIAL_ID.to_vec()]);
+        assert_eq!(pending.admin_id, Some(7));
+        assert_eq!(options["challenge"], pending.challenge);
+        assert_eq!(options["userVerification"], "required");
+        let allowed = options["allowCredentials"].as_array().unwrap();
+        assert_eq!(allowed.len(), 1);
+        assert_eq!(decode_b64url(allowed[0]["id"].as_str().unwrap()).unwrap(), CREDENTIAL_ID);
+    }
+}
@@ -1,9 +1,58 @@
 use tower_sessions_redis_store::fred::prelude::RedisPool;
 use sqlx::MySqlPool;
 
+use crate::controllers::request_info::TrustedProxies;
+use crate::middleware::security_headers::SecurityHeadersConfig;
+use crate::services::access_policy::AccessPolicyConfig;
+use crate::services::email_verification::EmailVerificationConfig;
+use crate::services::export_links::ExportLinkConfig;
+use crate::services::login_history::GeoIp;
+use crate::services::login_throttle::LoginThrottleConfig;
+use crate::services::mailer::Mailer;
+use crate::services::oidc::OidcClient;
+use crate::services::password_hash::PasswordHashConfig;
+use crate::services::password_policy::PasswordPolicy;
+use crate::services::remember_me::RememberMeConfig;
+use crate::services::trash::TrashConfig;
+use crate::services::two_factor::TwoFactorConfig;
+use crate::services::webauthn::WebAuthnConfig;
+
 #[derive(Clone)]
 pub struct AppState {
     pub db: MySqlPool,
     pub redis: RedisPool,
     pub base_path: String,
+    pub two_factor: Option<TwoFactorConfig>,
+    pub login_throttle: LoginThrottleConfig,
+    // Admin single sign-on; None when OIDC_ISSUER is not set
+    pub oidc: Option<OidcClient>,
+    // Persistent admin logins ("remember me")
+    pub remember_me: RememberMeConfig,
+    // Relying party for admin passkeys
+    pub webauthn: WebAuthnConfig,
+    pub mailer: Mailer,
+    // Signing key for email verification links
+    pub email_verification: EmailVerificationConfig,
+    // Signing key for shareable PDF export links
+    pub export_links: ExportLinkConfig,
+    pub password_policy: PasswordPolicy,
+    // Algorithm and parameters for newly stored password hashes
+    pub password_hash: PasswordHashConfig,
+    // Absolute origin used in emailed links, e.g. https://admin.example.com
+    pub public_url: String,
+    // Inactivity timeout of the session layer, also used for the admin session index
+    pub session_timeout_seconds: i64,
+    // Sudo mode: how long a sign-in or re-authentication covers the routes
+    // behind middleware::reauth
+    pub reauth_window_seconds: i64,
+    // CSP and other response headers set by middleware::security_headers
+    pub security_headers: SecurityHeadersConfig,
+    // Proxies allowed to set X-Forwarded-For (middleware::client_ip)
+    pub trusted_proxies: TrustedProxies,
+    // Global admin IP allowlist (middleware::admin_access)
+    pub access_policy: AccessPolicyConfig,
+    // Country lookups for the admin login history; None without GEOIP_DATABASE
+    pub geoip: Option<GeoIp>,
+    // Auto-purge of soft-deleted rows (services::trash)
+    pub trash: TrashConfig,
 }
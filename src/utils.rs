@@ -1,5 +1,6 @@
 use serde::Serializer;
 use time::OffsetDateTime;
+use time::macros::format_description;
 
 // Custom serializer for OffsetDateTime
 pub fn serialize_datetime<S>(dt: &OffsetDateTime, serializer: S) -> Result<S::Ok, S::Error>
@@ -28,7 +29,8 @@ where
     }
 }
 
-// Password verification utility
-pub async fn verify_password_hash(password_hash: &str, password: &str) -> bool {
-    bcrypt::verify(password, password_hash).unwrap_or(false)
+// Same format as the serializers above, for templates
+pub fn format_datetime(dt: &OffsetDateTime) -> String {
+    dt.format(format_description!("[day]-[month]-[year] [hour]:[minute]:[second]"))
+        .unwrap_or_else(|_| "Invalid Date".to_string())
 }
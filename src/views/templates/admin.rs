@@ -1,5 +1,12 @@
 use askama::Template;
 
+use crate::controllers::auth_controller::AdminUser;
+use crate::models::{
+    AdminListRow, AdminLoginEvent, AdminOption, ApiToken, AuditLogEntry, Passkey, RoleOption,
+    TrashSection, UserStatus, UserStatusChange,
+};
+use crate::services::admin_sessions::AdminSessionRecord;
+
 use super::{AdminStateRow, CountryOption, StateOption, User};
 
 // Admin templates
@@ -7,14 +14,60 @@ use super::{AdminStateRow, CountryOption, StateOption, User};
 #[template(path = "login_admin.html")]
 pub struct AdminLoginTemplate {
     pub error: Option<String>,
+    pub lockout: Option<String>,
+    // Button text for single sign-on; None when OIDC is not configured
+    pub sso_label: Option<String>,
+    // Lifetime of a remember-me login; 0 hides the checkbox
+    pub remember_me_days: i64,
+    pub csrf_token: String,
+    pub base_path: String,
+}
+
+#[derive(Template)]
+#[template(path = "login_admin_2fa.html")]
+pub struct AdminLoginTwoFactorTemplate {
+    pub error: Option<String>,
+    pub username: String,
+    pub csrf_token: String,
+    pub base_path: String,
+}
+
+#[derive(Template)]
+#[template(path = "forgot_password_admin.html")]
+pub struct AdminForgotPasswordTemplate {
+    pub error: Option<String>,
+    pub sent: bool,
     pub csrf_token: String,
     pub base_path: String,
 }
 
+#[derive(Template)]
+#[template(path = "reset_password_admin.html")]
+pub struct AdminResetPasswordTemplate {
+    pub error: Option<String>,
+    // None when the link is missing, expired or already used
+    pub token: Option<String>,
+    pub completed: bool,
+    // Password policy violations shown under the password field
+    pub password_errors: Vec<String>,
+    pub csrf_token: String,
+    pub base_path: String,
+}
+
+#[derive(Template)]
+#[template(path = "email/admin_password_reset.txt")]
+pub struct AdminPasswordResetEmail {
+    pub username: String,
+    pub reset_url: String,
+    pub ttl_minutes: i64,
+}
+
 #[derive(Template)]
 #[template(path = "admin/dashboard.html")]
 pub struct AdminDashboardTemplate {
-    pub current_admin: Option<String>,
+    pub current_admin: Option<AdminUser>,
+    // The signed-in admin's own sign-in attempts, newest first
+    pub recent_logins: Vec<AdminLoginEvent>,
     pub base_path: String,
 }
 
@@ -23,7 +76,7 @@ pub struct AdminDashboardTemplate {
 pub struct AdminErrorTemplate {
     pub error_code: u16,
     pub error_message: String,
-    pub current_admin: Option<String>,
+    pub current_admin: Option<AdminUser>,
     pub base_path: String,
 }
 
@@ -31,7 +84,11 @@ pub struct AdminErrorTemplate {
 #[template(path = "admin/users/list.html")]
 pub struct AdminUsersListTemplate {
     pub page_title: String,
-    pub current_admin: Option<String>,
+    pub current_admin: Option<AdminUser>,
+    // For the "Share link" request (X-CSRF-Token)
+    pub csrf_token: String,
+    // Lifetime of a shared export link
+    pub export_link_minutes: i64,
     pub base_path: String,
 }
 
@@ -39,7 +96,13 @@ pub struct AdminUsersListTemplate {
 #[template(path = "admin/users/detail.html")]
 pub struct AdminUserDetailTemplate {
     pub user: User,
-    pub current_admin: Option<String>,
+    // Statuses the user can be moved to from the current one
+    pub status_transitions: Vec<UserStatus>,
+    // Newest first
+    pub status_history: Vec<UserStatusChange>,
+    pub error: Option<String>,
+    pub success: Option<String>,
+    pub current_admin: Option<AdminUser>,
     pub csrf_token: String,
     pub base_path: String,
 }
@@ -51,7 +114,8 @@ pub struct AdminCreateUserTemplate {
     pub success: Option<String>,
     pub username: Option<String>,
     pub email: Option<String>,
-    pub current_admin: Option<String>,
+    pub current_admin: Option<AdminUser>,
+    pub password_errors: Vec<String>,
     pub csrf_token: String,
     pub countries: Vec<CountryOption>,
     pub states: Vec<StateOption>,
@@ -69,7 +133,8 @@ pub struct AdminEditUserTemplate {
     pub user_id: i32,
     pub username: String,
     pub email: String,
-    pub current_admin: Option<String>,
+    pub current_admin: Option<AdminUser>,
+    pub password_errors: Vec<String>,
     pub csrf_token: String,
     pub countries: Vec<CountryOption>,
     pub states: Vec<StateOption>,
@@ -83,7 +148,7 @@ pub struct AdminEditUserTemplate {
 #[template(path = "admin/geo/countries_list.html")]
 pub struct AdminCountriesListTemplate {
     pub page_title: String,
-    pub current_admin: Option<String>,
+    pub current_admin: Option<AdminUser>,
     pub csrf_token: String,
     pub countries: Vec<CountryOption>,
     pub base_path: String,
@@ -99,7 +164,7 @@ pub struct AdminCountryFormTemplate {
     pub name: Option<String>,
     pub error: Option<String>,
     pub success: Option<String>,
-    pub current_admin: Option<String>,
+    pub current_admin: Option<AdminUser>,
     pub csrf_token: String,
     pub base_path: String,
 }
@@ -108,7 +173,7 @@ pub struct AdminCountryFormTemplate {
 #[template(path = "admin/geo/states_list.html")]
 pub struct AdminStatesListTemplate {
     pub page_title: String,
-    pub current_admin: Option<String>,
+    pub current_admin: Option<AdminUser>,
     pub csrf_token: String,
     pub states: Vec<AdminStateRow>,
     pub base_path: String,
@@ -126,7 +191,185 @@ pub struct AdminStateFormTemplate {
     pub selected_country_id: i32,
     pub error: Option<String>,
     pub success: Option<String>,
-    pub current_admin: Option<String>,
+    pub current_admin: Option<AdminUser>,
+    pub csrf_token: String,
+    pub base_path: String,
+}
+
+#[derive(Template)]
+#[template(path = "admin/security/index.html")]
+pub struct AdminSecurityTemplate {
+    pub current_admin: Option<AdminUser>,
+    pub csrf_token: String,
+    pub two_factor_available: bool,
+    pub two_factor_enabled: bool,
+    pub recovery_codes_remaining: i64,
+    pub error: Option<String>,
+    pub success: Option<String>,
+    pub base_path: String,
+}
+
+#[derive(Template)]
+#[template(path = "admin/security/totp_setup.html")]
+pub struct AdminTotpSetupTemplate {
+    pub current_admin: Option<AdminUser>,
+    pub csrf_token: String,
+    pub secret_base32: String,
+    pub qr_svg: String,
+    pub error: Option<String>,
+    pub base_path: String,
+}
+
+#[derive(Template)]
+#[template(path = "admin/security/recovery_codes.html")]
+pub struct AdminRecoveryCodesTemplate {
+    pub current_admin: Option<AdminUser>,
+    pub codes: Vec<String>,
+    pub base_path: String,
+}
+
+#[derive(Template)]
+#[template(path = "admin/security/sessions.html")]
+pub struct AdminSessionsTemplate {
+    pub current_admin: Option<AdminUser>,
+    pub csrf_token: String,
+    pub sessions: Vec<AdminSessionRecord>,
+    pub current_ref: String,
+    pub error: Option<String>,
+    pub success: Option<String>,
+    pub base_path: String,
+}
+
+#[derive(Template)]
+#[template(path = "admin/security/api_tokens.html")]
+pub struct AdminApiTokensTemplate {
+    pub current_admin: Option<AdminUser>,
+    pub csrf_token: String,
+    pub tokens: Vec<ApiToken>,
+    pub available_scopes: Vec<String>,
+    pub expiry_options: Vec<i64>,
+    pub default_expiry_days: i64,
+    // Plaintext of a token just created; only ever shown in this response
+    pub new_token: Option<String>,
+    pub error: Option<String>,
+    pub success: Option<String>,
+    pub base_path: String,
+}
+
+#[derive(Template)]
+#[template(path = "admin/security/passkeys.html")]
+pub struct AdminPasskeysTemplate {
+    pub current_admin: Option<AdminUser>,
+    pub csrf_token: String,
+    pub passkeys: Vec<Passkey>,
+    pub error: Option<String>,
+    pub success: Option<String>,
+    pub base_path: String,
+}
+
+#[derive(Template)]
+#[template(path = "admin/audit/list.html")]
+pub struct AdminAuditLogTemplate {
+    pub current_admin: Option<AdminUser>,
+    pub entries: Vec<AuditLogEntry>,
+    pub admins: Vec<AdminOption>,
+    pub entity_types: Vec<String>,
+    pub selected_admin_id: i32,
+    pub selected_entity_type: String,
+    pub entity_id: String,
+    pub from: String,
+    pub to: String,
+    // Current filters as a query string, for pagination and CSV links
+    pub filter_query: String,
+    pub total: i64,
+    pub page: i64,
+    pub has_previous: bool,
+    pub has_next: bool,
+    pub base_path: String,
+}
+
+#[derive(Template)]
+#[template(path = "admin/admins/list.html")]
+pub struct AdminAdminsListTemplate {
+    pub current_admin: Option<AdminUser>,
+    // Signed-in admin, who cannot disable or delete their own account here
+    pub current_admin_id: i32,
+    pub csrf_token: String,
+    pub admins: Vec<AdminListRow>,
+    pub error: Option<String>,
+    pub success: Option<String>,
+    pub base_path: String,
+}
+
+// Sudo-mode confirmation: asks for the password (or a second-factor code),
+// a passkey or single sign-on and re-posts the original form fields to `action`
+#[derive(Template)]
+#[template(path = "admin/reauth.html")]
+pub struct AdminReauthTemplate {
+    pub action: String,
+    pub fields: Vec<(String, String)>,
+    pub two_factor_enabled: bool,
+    pub passkeys_enabled: bool,
+    // Button label when the admin may confirm through single sign-on
+    pub sso_label: Option<String>,
+    // Back from single sign-on: only the form remains to be re-posted
+    pub confirmed: bool,
+    pub window_minutes: i64,
+    pub error: Option<String>,
+    pub current_admin: Option<AdminUser>,
+    pub csrf_token: String,
+    pub base_path: String,
+}
+
+// The signed-in admin's own account details, email and password forms
+#[derive(Template)]
+#[template(path = "admin/profile.html")]
+pub struct AdminProfileTemplate {
+    pub username: String,
+    pub email: String,
+    pub roles: Vec<String>,
+    pub two_factor_enabled: bool,
+    pub created_at: String,
+    pub error: Option<String>,
+    pub success: Option<String>,
+    pub password_error: Option<String>,
+    pub password_errors: Vec<String>,
+    pub password_success: Option<String>,
+    pub current_admin: Option<AdminUser>,
+    pub csrf_token: String,
+    pub base_path: String,
+}
+
+// Create (admin_id None) and edit form for admin accounts
+#[derive(Template)]
+#[template(path = "admin/admins/form.html")]
+pub struct AdminAdminFormTemplate {
+    pub form_title: String,
+    pub form_action: String,
+    pub submit_label: String,
+    pub admin_id: Option<i32>,
+    pub username: Option<String>,
+    pub email: Option<String>,
+    pub selected_role: String,
+    pub roles: Vec<RoleOption>,
+    pub password_errors: Vec<String>,
+    pub error: Option<String>,
+    pub success: Option<String>,
+    pub current_admin: Option<AdminUser>,
+    pub csrf_token: String,
+    pub base_path: String,
+}
+
+#[derive(Template)]
+#[template(path = "admin/trash.html")]
+pub struct AdminTrashTemplate {
+    // Only the kinds the admin may delete
+    pub sections: Vec<TrashSection>,
+    // None when TRASH_RETENTION_DAYS is 0
+    pub retention_days: Option<u32>,
+    pub error: Option<String>,
+    pub success: Option<String>,
+    pub current_admin: Option<AdminUser>,
     pub csrf_token: String,
     pub base_path: String,
 }
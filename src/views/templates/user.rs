@@ -1,6 +1,6 @@
 use askama::Template;
 
-use super::User;
+use super::{CountryOption, StateOption, User};
 
 // Index page template
 #[derive(Template)]
@@ -10,6 +10,7 @@ pub struct IndexTemplate {
     pub message: String,
     pub user: Option<String>,
     pub flash_success: Option<String>,
+    pub impersonation: Option<ImpersonationBanner>,
     pub base_path: String,
 }
 
@@ -19,5 +20,78 @@ pub struct IndexTemplate {
 pub struct ErrorTemplate {
     pub error_code: u16,
     pub error_message: String,
+    pub impersonation: Option<ImpersonationBanner>,
     pub base_path: String,
 }
+
+// Result of following an email verification link
+#[derive(Template)]
+#[template(path = "verify_email.html")]
+pub struct VerifyEmailTemplate {
+    pub verified: bool,
+    pub message: String,
+    pub impersonation: Option<ImpersonationBanner>,
+    pub base_path: String,
+}
+
+#[derive(Template)]
+#[template(path = "email/user_email_verification.txt")]
+pub struct UserEmailVerificationEmail {
+    pub username: String,
+    pub email: String,
+    pub verify_url: String,
+    pub ttl_hours: i64,
+}
+
+// Public account templates
+#[derive(Template)]
+#[template(path = "login.html")]
+pub struct UserLoginTemplate {
+    pub error: Option<String>,
+    pub lockout: Option<String>,
+    pub username: Option<String>,
+    pub csrf_token: String,
+    pub base_path: String,
+}
+
+#[derive(Template)]
+#[template(path = "register.html")]
+pub struct UserRegisterTemplate {
+    pub error: Option<String>,
+    pub username: Option<String>,
+    pub email: Option<String>,
+    // Password policy violations shown under the password field
+    pub password_errors: Vec<String>,
+    pub csrf_token: String,
+    pub base_path: String,
+}
+
+#[derive(Template)]
+#[template(path = "profile.html")]
+pub struct UserProfileTemplate {
+    pub current_user: String,
+    pub email: String,
+    pub address: Option<String>,
+    pub countries: Vec<CountryOption>,
+    pub states: Vec<StateOption>,
+    pub selected_country_id: i32,
+    pub selected_state_id: i32,
+    pub error: Option<String>,
+    pub success: Option<String>,
+    pub password_error: Option<String>,
+    pub password_success: Option<String>,
+    // Password policy violations shown under the password field
+    pub password_errors: Vec<String>,
+    pub impersonation: Option<ImpersonationBanner>,
+    pub csrf_token: String,
+    pub base_path: String,
+}
+
+// Shown at the top of public pages while an admin is signed in as a user
+// (templates/partials/impersonation_banner.html)
+pub struct ImpersonationBanner {
+    pub admin_username: String,
+    pub username: String,
+    pub started_at: String,
+    pub csrf_token: String,
+}
@@ -3,6 +3,7 @@ $(document).ready(function () {
     const countrySelect = $('#country_id');
     const stateSelect = $('#state_id');
     const selectedStateId = stateSelect.data('selected'); // Store the original selected state
+    const statesUrl = stateSelect.data('states-url') || basePath + '/geo/states';
 
     async function loadStates(countryId) {
         stateSelect.prop('disabled', true);
@@ -15,7 +16,7 @@ $(document).ready(function () {
         }
 
         try {
-            const resp = await fetch(basePath + '/geo/states?country_id=' + encodeURIComponent(countryId));
+            const resp = await fetch(statesUrl + '?country_id=' + encodeURIComponent(countryId));
             if (!resp.ok) {
                 throw new Error('Failed to load states');
             }
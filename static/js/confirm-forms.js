@@ -0,0 +1,9 @@
+// Asks for confirmation before submitting forms marked with data-confirm.
+// Replaces inline onsubmit/onclick handlers, which the CSP does not allow.
+document.addEventListener('submit', function (event) {
+    const form = event.target;
+    const message = form.getAttribute && form.getAttribute('data-confirm');
+    if (message && !window.confirm(message)) {
+        event.preventDefault();
+    }
+});
@@ -0,0 +1,168 @@
+// Passkey registration (security/passkeys page), passkey login (admin login
+// page) and passkey confirmation (sudo-mode page). The server sends WebAuthn
+// options with binary fields as base64url; responses are posted back the
+// same way with the CSRF header.
+(function () {
+    function toBytes(value) {
+        const base64 = value.replace(/-/g, '+').replace(/_/g, '/');
+        const padded = base64 + '='.repeat((4 - (base64.length % 4)) % 4);
+        return Uint8Array.from(atob(padded), function (c) { return c.charCodeAt(0); });
+    }
+
+    function toBase64Url(buffer) {
+        const bytes = new Uint8Array(buffer);
+        let binary = '';
+        bytes.forEach(function (b) { binary += String.fromCharCode(b); });
+        return btoa(binary).replace(/\+/g, '-').replace(/\//g, '_').replace(/=+$/, '');
+    }
+
+    function showError(message) {
+        const alert = document.getElementById('passkeyError');
+        if (alert) {
+            alert.textContent = message;
+            alert.classList.remove('d-none');
+        }
+    }
+
+    async function postJson(url, csrfToken, body) {
+        const response = await fetch(url, {
+            method: 'POST',
+            credentials: 'same-origin',
+            headers: { 'Content-Type': 'application/json', 'X-CSRF-Token': csrfToken },
+            body: JSON.stringify(body || {}),
+        });
+        const data = await response.json().catch(function () { return {}; });
+        if (!response.ok) {
+            throw new Error(data.error || 'Request failed. Please reload the page and try again.');
+        }
+        return data;
+    }
+
+    // A cancelled browser prompt is not worth an error message
+    function isCancelled(error) {
+        return error && (error.name === 'NotAllowedError' || error.name === 'AbortError');
+    }
+
+    async function register(form) {
+        const basePath = form.dataset.basePath;
+        const csrfToken = form.querySelector('input[name="csrf_token"]').value;
+        const name = form.querySelector('input[name="name"]').value.trim();
+
+        const options = await postJson(basePath + '/security/passkeys/options', csrfToken);
+        options.challenge = toBytes(options.challenge);
+        options.user.id = toBytes(options.user.id);
+        options.excludeCredentials = (options.excludeCredentials || []).map(function (c) {
+            return { type: c.type, id: toBytes(c.id) };
+        });
+
+        const credential = await navigator.credentials.create({ publicKey: options });
+        await postJson(basePath + '/security/passkeys/register', csrfToken, {
+            name: name,
+            clientDataJson: toBase64Url(credential.response.clientDataJSON),
+            attestationObject: toBase64Url(credential.response.attestationObject),
+        });
+        window.location.reload();
+    }
+
+    async function login(button) {
+        const basePath = button.dataset.basePath;
+        const csrfToken = button.dataset.csrfToken;
+        const rememberMe = document.getElementById('remember_me');
+
+        const options = await postJson(basePath + '/login/passkey/options', csrfToken);
+        options.challenge = toBytes(options.challenge);
+
+        const credential = await navigator.credentials.get({ publicKey: options });
+        const response = credential.response;
+        const result = await postJson(basePath + '/login/passkey', csrfToken, {
+            rawId: toBase64Url(credential.rawId),
+            clientDataJson: toBase64Url(response.clientDataJSON),
+            authenticatorData: toBase64Url(response.authenticatorData),
+            signature: toBase64Url(response.signature),
+            userHandle: response.userHandle ? toBase64Url(response.userHandle) : null,
+            rememberMe: !!(rememberMe && rememberMe.checked),
+        });
+        window.location.assign(result.redirect);
+    }
+
+    async function reauth(button) {
+        const basePath = button.dataset.basePath;
+        const csrfToken = button.dataset.csrfToken;
+
+        const options = await postJson(basePath + '/reauth/passkey/options', csrfToken);
+        options.challenge = toBytes(options.challenge);
+        options.allowCredentials = (options.allowCredentials || []).map(function (c) {
+            return { type: c.type, id: toBytes(c.id) };
+        });
+
+        const credential = await navigator.credentials.get({ publicKey: options });
+        const response = credential.response;
+        await postJson(basePath + '/reauth/passkey', csrfToken, {
+            rawId: toBase64Url(credential.rawId),
+            clientDataJson: toBase64Url(response.clientDataJSON),
+            authenticatorData: toBase64Url(response.authenticatorData),
+            signature: toBase64Url(response.signature),
+            userHandle: response.userHandle ? toBase64Url(response.userHandle) : null,
+        });
+
+        // Confirmed: re-post the original form without a password or code
+        const form = document.getElementById('reauthForm');
+        form.querySelectorAll('input[name="reauth_password"], input[name="reauth_code"]').forEach(function (input) {
+            input.disabled = true;
+        });
+        form.submit();
+    }
+
+    function bindButton(button, action) {
+        button.addEventListener('click', function () {
+            button.disabled = true;
+            action(button)
+                .catch(function (error) {
+                    if (!isCancelled(error)) {
+                        showError(error.message);
+                    }
+                })
+                .finally(function () {
+                    button.disabled = false;
+                });
+        });
+    }
+
+    document.addEventListener('DOMContentLoaded', function () {
+        const supported = !!window.PublicKeyCredential;
+        const form = document.getElementById('passkeyRegisterForm');
+        const button = document.getElementById('passkeyLoginButton');
+        const reauthButton = document.getElementById('reauthPasskeyButton');
+
+        if (form) {
+            if (!supported) {
+                showError('This browser does not support passkeys.');
+                form.querySelector('button[type="submit"]').disabled = true;
+            }
+            form.addEventListener('submit', function (event) {
+                event.preventDefault();
+                register(form).catch(function (error) {
+                    if (!isCancelled(error)) {
+                        showError(error.message);
+                    }
+                });
+            });
+        }
+
+        if (button) {
+            if (!supported) {
+                button.closest('.d-grid').classList.add('d-none');
+            } else {
+                bindButton(button, login);
+            }
+        }
+
+        if (reauthButton) {
+            if (!supported) {
+                reauthButton.closest('.d-grid').classList.add('d-none');
+            } else {
+                bindButton(reauthButton, reauth);
+            }
+        }
+    });
+})();
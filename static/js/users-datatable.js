@@ -1,6 +1,7 @@
 // Initialize DataTable for users list
 $(document).ready(function () {
     const basePath = window.BASE_PATH || '/admin';
+    const canEdit = $('#usersTable').data('can-edit') === true;
 
     const table = $('#usersTable').DataTable({
         // Enable server-side processing
@@ -11,6 +12,10 @@ $(document).ready(function () {
         ajax: {
             url: '/api/v1/users/datatable',
             type: 'GET',
+            data: function (d) {
+                d.verified = $('#verifiedFilter').val();
+                d.status = $('#statusFilter').val();
+            },
             error: function (xhr, error, thrown) {
                 console.error('DataTables AJAX error:', error);
                 alert('Failed to load user data. Please try again.');
@@ -27,17 +32,17 @@ $(document).ready(function () {
             {
                 data: 'username',
                 title: 'Username',
-                width: '25%'
+                width: '20%'
             },
             {
                 data: 'email',
                 title: 'Email',
-                width: '30%'
+                width: '20%'
             },
             {
                 data: 'created_at',
                 title: 'Created At',
-                width: '25%',
+                width: '20%',
                 render: function (data, type, row) {
                     if (type === 'display' || type === 'filter') {
                         // Data is already formatted as 'dd-mm-yyyy hh:mm:ss' from backend
@@ -46,6 +51,42 @@ $(document).ready(function () {
                     return data;
                 }
             },
+            {
+                data: 'email_verified_at',
+                title: 'Email',
+                orderable: false,
+                searchable: false,
+                width: '10%',
+                render: function (data, type, row) {
+                    if (type !== 'display') {
+                        return data ? 'verified' : 'unverified';
+                    }
+                    return data
+                        ? '<span class="badge bg-success">Verified</span>'
+                        : '<span class="badge bg-warning text-dark">Unverified</span>';
+                }
+            },
+            {
+                data: 'status',
+                title: 'Status',
+                orderable: false,
+                searchable: false,
+                width: '10%',
+                render: function (data, type, row) {
+                    if (type !== 'display') {
+                        return data;
+                    }
+                    const badges = {
+                        pending: 'bg-secondary',
+                        active: 'bg-success',
+                        suspended: 'bg-warning text-dark',
+                        locked: 'bg-info text-dark',
+                        closed: 'bg-dark'
+                    };
+                    const label = data ? data.charAt(0).toUpperCase() + data.slice(1) : 'N/A';
+                    return '<span class="badge ' + (badges[data] || 'bg-secondary') + '">' + label + '</span>';
+                }
+            },
             {
                 data: 'id',
                 title: 'Actions',
@@ -55,7 +96,7 @@ $(document).ready(function () {
                 render: function (data, type, row) {
                     return (
                         '<a href="' + basePath + '/users/' + data + '" class="btn btn-sm btn-gradient me-1">View</a>' +
-                        '<a href="' + basePath + '/users/' + data + '/edit" class="btn btn-sm btn-outline-secondary">Edit</a>'
+                        (canEdit ? '<a href="' + basePath + '/users/' + data + '/edit" class="btn btn-sm btn-outline-secondary">Edit</a>' : '')
                     );
                 }
             }
@@ -91,7 +132,12 @@ $(document).ready(function () {
         responsive: true
     });
 
-    $('#exportPdfBtn').on('click', function () {
+    $('#verifiedFilter, #statusFilter').on('change', function () {
+        table.ajax.reload();
+    });
+
+    // Current search, filter and order, as the export expects them
+    function exportParams() {
         const search = table.search();
         const order = table.order();
         const columnMap = ['id', 'username', 'email', 'created_at'];
@@ -102,9 +148,38 @@ $(document).ready(function () {
         if (search) {
             params.set('search', search);
         }
+        const verified = $('#verifiedFilter').val();
+        if (verified) {
+            params.set('verified', verified);
+        }
+        const status = $('#statusFilter').val();
+        if (status) {
+            params.set('status', status);
+        }
         params.set('order_column', orderColumn);
         params.set('order_direction', orderDirection);
+        return params;
+    }
+
+    $('#exportPdfBtn').on('click', function () {
+        window.open(basePath + '/users/print?' + exportParams().toString(), '_blank');
+    });
 
-        window.open(basePath + '/users/print?' + params.toString(), '_blank');
+    $('#shareExportBtn').on('click', function () {
+        $.ajax({
+            url: basePath + '/users/print/link',
+            method: 'POST',
+            data: exportParams().toString(),
+            contentType: 'application/x-www-form-urlencoded',
+            headers: { 'X-CSRF-Token': $(this).data('csrf-token') },
+            dataType: 'json'
+        }).done(function (link) {
+            $('#shareExportExpires').text(link.expires_at);
+            $('#shareExportUrl').val(link.url);
+            $('#shareExportResult').removeClass('d-none');
+            $('#shareExportUrl').trigger('select');
+        }).fail(function () {
+            window.alert('The export link could not be created.');
+        });
     });
 });